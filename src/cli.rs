@@ -0,0 +1,1185 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::prefix::is_docker_line;
+use crate::ingestion::text::is_logfmt_line;
+use crate::ingestion::{
+    LinePrefix, SocketAddress, SortOnLoad, UntimedPosition, parse_json_line, parse_prefixed_line,
+};
+use crate::ui::age::AgeReference;
+use crate::ui::badges::BadgeRule;
+use crate::ui::display::{TimePrecision, validate_time_format};
+use jiff::tz::TimeZone;
+use rootcause::prelude::{Report, ResultExt};
+
+/// Command-line options
+#[derive(Debug, Clone, Default)]
+pub struct CliArgs {
+    pub log_file: String,
+    /// `--prefix`, or the profile's; `None` reads bare JSON lines
+    pub line_prefix: Option<LinePrefix>,
+    pub hidden_fields: Vec<String>,
+    /// Write ingested logs to this Parquet file and exit instead of starting the TUI
+    pub to_parquet: Option<String>,
+    /// DuckDB file holding the logs instead of memory, reused by later runs
+    pub db_file: Option<String>,
+    /// Drop the log table in `db_file` before loading, for logs of another shape
+    pub rebuild_db: bool,
+    /// WHERE clause applied at startup, so the list opens filtered
+    pub filter: Option<String>,
+    /// WHERE clauses checked without the TUI; exit non-zero if any log matches one
+    pub fail_if: Vec<String>,
+    /// DuckDB extensions installed and loaded at startup, for use in filters
+    pub extensions: Vec<String>,
+    /// Build a full-text index over the message column
+    pub fts: bool,
+    /// Matching logs printed by `--fail-if`
+    pub show_matches: usize,
+    /// Display time zone; `None` means UTC
+    pub time_zone: Option<TimeZone>,
+    /// Custom `strftime` pattern for displayed timestamps
+    pub time_format: Option<String>,
+    /// Sub-second digits shown by the default time formats
+    pub time_precision: TimePrecision,
+    /// Time the age tags and age filters measure from
+    pub age_reference: AgeReference,
+    /// Fields appended to list rows in wide mode
+    pub columns: Vec<String>,
+    /// Start in wide mode
+    pub wide: bool,
+    /// Show the logger name in the list
+    pub show_logger: bool,
+    /// Show the source location (Bunyan `src`) in the list
+    pub show_source: bool,
+    /// Format profile given with `--profile`; `None` detects one from the file
+    pub profile: Option<Profile>,
+    /// Fields holding the trace id; empty uses the defaults
+    pub trace_fields: Vec<String>,
+    /// Fields holding an error object; empty uses the defaults
+    pub error_fields: Vec<String>,
+    /// Rows kept visible above and below the selection
+    pub scrolloff: usize,
+    /// Order applied to JSON lines input before ingestion
+    pub sort_on_load: SortOnLoad,
+    /// Where logs without a timestamp go when sorting by time
+    pub untimed: UntimedPosition,
+    /// Field ordering logs with equal timestamps when sorting by time
+    pub sort_tiebreak: Option<String>,
+    /// Keep reading lines appended to the file
+    pub follow: bool,
+    /// Unix socket (`--socket`) or TCP address (`--tcp`) streaming JSON lines, read instead of a file
+    pub stream: Option<SocketAddress>,
+    /// Followed lines inserted per batch; `None` uses the default
+    pub follow_batch: Option<usize>,
+    /// Rows committed per transaction when loading; `None` uses the default
+    pub insert_batch: Option<usize>,
+    /// Longest a followed line waits before insertion, in ms; `None` uses the default
+    pub follow_flush_ms: Option<u64>,
+    /// Seconds without input before the event loop stops ticking, 0 for never;
+    /// `None` uses the default
+    pub idle_after_secs: Option<u64>,
+    /// Follow every file in the log directory, including newly created ones
+    pub watch_dir: bool,
+    /// Longest line read, in bytes; `None` uses the default
+    pub max_line_bytes: Option<usize>,
+    /// Most logs kept in memory; older ones are read back from the database
+    pub memory_limit: Option<usize>,
+    /// Most matches a filter loads at first; more are loaded on request
+    pub result_limit: Option<usize>,
+    /// Fields rendered as colored badges in the list
+    pub badges: Vec<BadgeRule>,
+    /// Record each log's source line number as the `_line` field
+    pub line_numbers: bool,
+    /// Show lines that fail to parse as placeholder rows instead of skipping them
+    pub show_unparsed: bool,
+    /// Fields replaced with `"[REDACTED]"` on export
+    pub redact: Vec<String>,
+    /// Detect the schema from every log instead of a sample
+    pub two_pass: bool,
+    /// Store "true"/"false" strings as booleans and "null"/"" as null
+    pub coerce_strings: bool,
+    /// Store a numeric `level` column derived from `level`, `lvl` or `severity`
+    pub normalize_levels: bool,
+    /// Color non-standard level numbers as the nearest level below
+    pub lenient_levels: bool,
+    /// Dotted path to a nested level, e.g. `severity.number`
+    pub level_field: Option<String>,
+    /// Fields joined into the list's message; empty uses `message`/`msg`
+    pub message_fields: Vec<String>,
+    /// Separator between the message fields; `None` uses the default
+    pub message_separator: Option<String>,
+    /// Show the file name and active filter in the terminal window title
+    pub set_title: bool,
+    /// Draw the TUI without colors, as with `NO_COLOR`
+    pub no_color: bool,
+    /// Leave fields with one value in every sampled log out of the list's field count
+    pub hide_constant: bool,
+    /// Field the list groups logs by
+    pub group_by: Option<String>,
+}
+
+impl CliArgs {
+    /// Parse options from the process arguments (excluding the program name)
+    pub fn parse_from<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = CliArgs::default();
+        let mut log_file = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--prefix" => {
+                    parsed.line_prefix = Some(
+                        next_value(&mut args, &arg)?
+                            .parse::<LinePrefix>()
+                            .attach("Invalid value for --prefix")?,
+                    );
+                }
+                "--hide" => {
+                    parsed
+                        .hidden_fields
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--redact" => {
+                    parsed
+                        .redact
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--group-by" => {
+                    parsed.group_by = Some(next_value(&mut args, &arg)?);
+                }
+                "--to-parquet" => {
+                    parsed.to_parquet = Some(next_value(&mut args, &arg)?);
+                }
+                "--db-file" => {
+                    parsed.db_file = Some(next_value(&mut args, &arg)?);
+                }
+                "--rebuild-db" => {
+                    parsed.rebuild_db = true;
+                }
+                "--filter" => {
+                    parsed.filter = Some(next_value(&mut args, &arg)?);
+                }
+                "--fail-if" => {
+                    parsed.fail_if.push(next_value(&mut args, &arg)?);
+                }
+                "--extension" => {
+                    parsed.extensions.push(next_value(&mut args, &arg)?);
+                }
+                "--fts" => {
+                    parsed.fts = true;
+                }
+                "--show-matches" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.show_matches = parse_number(&value, &arg, "logs")?;
+                }
+                "--timezone" => {
+                    parsed.time_zone = Some(parse_time_zone(&next_value(&mut args, &arg)?)?);
+                }
+                "--time-format" => {
+                    let format = next_value(&mut args, &arg)?;
+                    validate_time_format(&format).map_err(|e| {
+                        Report::new(LogViewerError::InvalidArgument(format!(
+                            "Invalid --time-format '{}': {}",
+                            format, e
+                        )))
+                    })?;
+                    parsed.time_format = Some(format);
+                }
+                "--time-precision" => {
+                    parsed.time_precision = next_value(&mut args, &arg)?
+                        .parse::<TimePrecision>()
+                        .attach("Invalid value for --time-precision")?;
+                }
+                "--age-reference" => {
+                    parsed.age_reference = next_value(&mut args, &arg)?
+                        .parse::<AgeReference>()
+                        .attach("Invalid value for --age-reference")?;
+                }
+                "--columns" => {
+                    parsed
+                        .columns
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--trace-fields" => {
+                    parsed
+                        .trace_fields
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--error-fields" => {
+                    parsed
+                        .error_fields
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--wide" => {
+                    parsed.wide = true;
+                }
+                "--show-logger" => {
+                    parsed.show_logger = true;
+                }
+                "--show-source" => {
+                    parsed.show_source = true;
+                }
+                "--profile" | "--preset" => {
+                    let profile = next_value(&mut args, &arg)?
+                        .parse::<Profile>()
+                        .attach_with(|| format!("Invalid value for {}", arg))?;
+                    profile.apply(&mut parsed);
+                    parsed.profile = Some(profile);
+                }
+                "--line-numbers" => {
+                    parsed.line_numbers = true;
+                }
+                "--show-unparsed" => {
+                    parsed.show_unparsed = true;
+                }
+                "--two-pass" => {
+                    parsed.two_pass = true;
+                }
+                "--coerce-strings" => {
+                    parsed.coerce_strings = true;
+                }
+                "--normalize-levels" => {
+                    parsed.normalize_levels = true;
+                }
+                "--lenient-levels" => {
+                    parsed.lenient_levels = true;
+                }
+                "--level-field" => {
+                    parsed.level_field = Some(next_value(&mut args, &arg)?);
+                }
+                "--message-fields" => {
+                    parsed
+                        .message_fields
+                        .extend(split_list(&next_value(&mut args, &arg)?));
+                }
+                "--message-separator" => {
+                    parsed.message_separator = Some(next_value(&mut args, &arg)?);
+                }
+                "--set-title" => {
+                    parsed.set_title = true;
+                }
+                "--no-color" => {
+                    parsed.no_color = true;
+                }
+                "--hide-constant" => {
+                    parsed.hide_constant = true;
+                }
+                "--scrolloff" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.scrolloff = parse_number(&value, &arg, "rows")?;
+                }
+                "--follow" | "-f" => {
+                    parsed.follow = true;
+                }
+                "--watch-dir" => {
+                    parsed.watch_dir = true;
+                }
+                "--socket" | "--tcp" => {
+                    if parsed.stream.is_some() {
+                        return Err(Report::new(LogViewerError::InvalidArgument(
+                            "Pass only one --socket or --tcp".to_string(),
+                        )));
+                    }
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.stream = Some(if arg == "--tcp" {
+                        SocketAddress::tcp(&value)?
+                    } else {
+                        SocketAddress::unix(value)?
+                    });
+                }
+                "--follow-batch" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.follow_batch = Some(parse_positive(&value, &arg, "lines")?);
+                }
+                "--insert-batch" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.insert_batch = Some(parse_positive(&value, &arg, "rows")?);
+                }
+                "--max-line-bytes" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.max_line_bytes = Some(parse_positive(&value, &arg, "bytes")?);
+                }
+                "--memory-limit" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.memory_limit = Some(parse_positive(&value, &arg, "logs")?);
+                }
+                "--result-limit" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.result_limit = Some(parse_positive(&value, &arg, "logs")?);
+                }
+                "--follow-flush-ms" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.follow_flush_ms = Some(parse_number(&value, &arg, "milliseconds")?);
+                }
+                "--idle-after" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.idle_after_secs = Some(parse_number(&value, &arg, "seconds")?);
+                }
+                "--badge" => {
+                    parsed
+                        .badges
+                        .push(next_value(&mut args, &arg)?.parse::<BadgeRule>()?);
+                }
+                "--sort-on-load" => {
+                    parsed.sort_on_load = next_value(&mut args, &arg)?
+                        .parse::<SortOnLoad>()
+                        .attach("Invalid value for --sort-on-load")?;
+                }
+                "--untimed" => {
+                    parsed.untimed = next_value(&mut args, &arg)?
+                        .parse::<UntimedPosition>()
+                        .attach("Invalid value for --untimed")?;
+                }
+                "--sort-tiebreak" => {
+                    parsed.sort_tiebreak = Some(next_value(&mut args, &arg)?);
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                        "Unknown option '{}'",
+                        flag
+                    ))));
+                }
+                _ => {
+                    if log_file.is_some() {
+                        return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                            "Unexpected argument '{}'",
+                            arg
+                        ))));
+                    }
+                    log_file = Some(arg);
+                }
+            }
+        }
+
+        if parsed.rebuild_db && parsed.db_file.is_none() {
+            return Err(Report::new(LogViewerError::InvalidArgument(
+                "--rebuild-db needs --db-file".to_string(),
+            )));
+        }
+
+        // The socket path or TCP address stands in for the log file (title, profile detection)
+        parsed.log_file = match (log_file, &parsed.stream) {
+            (Some(_), Some(_)) => {
+                return Err(Report::new(LogViewerError::InvalidArgument(
+                    "--socket and --tcp replace the log file path; pass only one".to_string(),
+                )));
+            }
+            (None, Some(stream)) => stream.to_string(),
+            (log_file, None) => log_file.ok_or_else(|| {
+                Report::new(LogViewerError::InvalidArgument(
+                    "Missing log file path".to_string(),
+                ))
+            })?,
+        };
+
+        Ok(parsed)
+    }
+
+    /// Read lines with `line_prefix` unless `--prefix` already chose one,
+    /// `--prefix none` included
+    fn set_default_prefix(&mut self, line_prefix: LinePrefix) {
+        self.line_prefix.get_or_insert(line_prefix);
+    }
+}
+
+/// Lines sampled by `Profile::detect`, within `reader::SNIFF_BYTES`
+pub const PROFILE_DETECT_LINES: usize = 100;
+
+/// journald's cursor, monotonic clock and machine ids, hidden by the journald profile
+const JOURNALD_HIDDEN_FIELDS: [&str; 6] = [
+    "__CURSOR",
+    "__MONOTONIC_TIMESTAMP",
+    "__SEQNUM",
+    "__SEQNUM_ID",
+    "_BOOT_ID",
+    "_MACHINE_ID",
+];
+
+/// Bundles of options for a logging ecosystem's output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Pino's JSON lines, the native format: nothing to change
+    Pino,
+    /// Node's Bunyan: Pino's level numbers, plus a `v` format version and an optional `src`
+    Bunyan,
+    /// Docker's json-file driver wrapping each line (`--prefix docker`)
+    Docker,
+    /// `journalctl -o json`: syslog priorities, microsecond times and `MESSAGE`
+    Journald,
+    /// logfmt `key=value` lines (Go's logrus, slog, go-kit)
+    Logfmt,
+    /// Unstructured text lines, each one a message
+    Plain,
+}
+
+impl Profile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Pino => "pino",
+            Profile::Bunyan => "bunyan",
+            Profile::Docker => "docker",
+            Profile::Journald => "journald",
+            Profile::Logfmt => "logfmt",
+            Profile::Plain => "plain",
+        }
+    }
+
+    /// Turn on the profile's options; options given explicitly still apply
+    pub fn apply(&self, args: &mut CliArgs) {
+        match self {
+            Profile::Pino => {}
+            Profile::Bunyan => {
+                args.hidden_fields.push("v".to_string());
+                args.show_source = true;
+            }
+            Profile::Docker => args.set_default_prefix(LinePrefix::Docker),
+            Profile::Journald => {
+                args.set_default_prefix(LinePrefix::Journald);
+                args.hidden_fields
+                    .extend(JOURNALD_HIDDEN_FIELDS.iter().map(|field| field.to_string()));
+            }
+            Profile::Logfmt => args.set_default_prefix(LinePrefix::Logfmt),
+            Profile::Plain => args.set_default_prefix(LinePrefix::Plain),
+        }
+    }
+
+    /// Guess the profile from the first lines of a file: Docker's wrapper, then
+    /// journald's and Bunyan's fields in JSON lines. When most lines aren't JSON (or
+    /// CRI), the file is logfmt if most read as `key=value` pairs, else plain text,
+    /// so a banner or stack trace ahead of JSON lines doesn't make it text.
+    /// Anything else falls back to Pino
+    pub fn detect(lines: &[String]) -> Self {
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .take(PROFILE_DETECT_LINES)
+            .collect();
+        if lines.iter().any(|line| is_docker_line(line)) {
+            return Profile::Docker;
+        }
+        let logs: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parse_json_line(line).ok())
+            .collect();
+        let cri_lines = lines
+            .iter()
+            .filter(|line| parse_prefixed_line(line, LinePrefix::Cri).is_ok())
+            .count();
+        if (logs.len() + cri_lines) * 2 < lines.len() {
+            let logfmt_lines = lines.iter().filter(|line| is_logfmt_line(line)).count();
+            return if logfmt_lines * 2 > lines.len() {
+                Profile::Logfmt
+            } else {
+                Profile::Plain
+            };
+        }
+        let is_journald = logs.iter().any(|log| {
+            log.get_field("__REALTIME_TIMESTAMP").is_some()
+                || (log.get_field("MESSAGE").is_some() && log.get_field("PRIORITY").is_some())
+        });
+        let is_bunyan = logs.iter().any(|log| {
+            log.get_field("v").is_some_and(|v| v.is_number()) && log.get_field("hostname").is_some()
+        });
+        if is_journald {
+            Profile::Journald
+        } else if is_bunyan {
+            Profile::Bunyan
+        } else {
+            Profile::Pino
+        }
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pino" => Ok(Profile::Pino),
+            "bunyan" => Ok(Profile::Bunyan),
+            "docker" => Ok(Profile::Docker),
+            "journald" => Ok(Profile::Journald),
+            "logfmt" => Ok(Profile::Logfmt),
+            "plain" => Ok(Profile::Plain),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown profile '{}' (expected pino, bunyan, journald, docker, logfmt or plain)",
+                s
+            )))),
+        }
+    }
+}
+
+/// Take the value following a flag
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().ok_or_else(|| {
+        Report::new(LogViewerError::InvalidArgument(format!(
+            "Option '{}' requires a value",
+            flag
+        )))
+    })
+}
+
+/// Parse the `value` of `flag` as a number of `unit`, zero included
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str, unit: &str) -> Result<T> {
+    value.parse::<T>().map_err(|_| {
+        Report::new(LogViewerError::InvalidArgument(format!(
+            "Invalid {} '{}': expected a number of {}",
+            flag, value, unit
+        )))
+    })
+}
+
+/// Parse the `value` of `flag` as a number of `unit` that must be above zero
+fn parse_positive<T: std::str::FromStr + PartialOrd + Default>(
+    value: &str,
+    flag: &str,
+    unit: &str,
+) -> Result<T> {
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|n| *n > T::default())
+        .ok_or_else(|| {
+            Report::new(LogViewerError::InvalidArgument(format!(
+                "Invalid {} '{}': expected a positive number of {}",
+                flag, value, unit
+            )))
+        })
+}
+
+/// Resolve an IANA time zone name, `UTC`, or `local` (the system zone)
+fn parse_time_zone(name: &str) -> Result<TimeZone> {
+    match name {
+        "local" => Ok(TimeZone::system()),
+        "UTC" | "utc" => Ok(TimeZone::UTC),
+        _ => TimeZone::get(name).map_err(|e| {
+            Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown time zone '{}': {}",
+                name, e
+            )))
+        }),
+    }
+}
+
+/// Split a comma-separated option value, dropping empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Usage text printed on invalid arguments
+pub fn usage(program: &str) -> String {
+    format!(
+        "Usage: {} [OPTIONS] <log-file-path>
+
+A directory loads every file in it (including rotated .N and .N.gz files) as one timeline.
+
+Options:
+  -f, --follow                     Keep reading new lines, reopening the file on rotation
+  --watch-dir                      Follow a directory, picking up newly created files
+  --socket <path>                  Read JSON lines from a Unix socket instead of a file, reconnecting if it closes
+  --tcp <host:port>                Read JSON lines from a TCP connection, reconnecting if it drops
+  --follow-batch <lines>           Insert followed lines in batches of this size (default: 1000)
+  --follow-flush-ms <ms>           Insert a partial batch after this delay (default: 200)
+  --idle-after <seconds>           Stop polling for input after this long idle (default: 5, 0: never)
+  --insert-batch <rows>            Commit loaded logs every this many rows (default: 50000)
+  --memory-limit <logs>            Keep at most this many logs in memory, paging older ones from the DB
+                                   (needed for paging: without it every log stays in memory)
+  --result-limit <logs>            Load at most this many filter matches at first (M loads more)
+  --max-line-bytes <bytes>         Skip lines longer than this (default: 16777216)
+  --prefix <none|auto|docker|cri>  Strip a container runtime prefix before parsing
+  --hide <field,...>               Hide fields from the detail panel and field count
+  --hide-constant                  Drop fields constant across the schema sample from the field count
+  --to-parquet <path>              Write ingested logs to a Parquet file and exit
+  --db-file <path>                 Store logs in a DuckDB file, adding to the table already in it
+  --rebuild-db                     Replace the log table in --db-file, for logs of another shape
+  --filter <where-clause>          Open with this filter applied (e.g. \"level >= 50\")
+  --fail-if <where-clause>         Exit with status 1 if any log matches (repeatable, ORed)
+  --show-matches <count>           Print the first matching logs with --fail-if
+  --extension <name>               INSTALL and LOAD a DuckDB extension for filters (repeatable)
+  --fts                            Full-text index the message column for match_bm25 filters
+  --redact <field,...>             Replace these fields (dotted paths allowed) on NDJSON/CSV export
+  --timezone <zone>                Display times in an IANA zone or `local` (default: UTC)
+  --time-format <strftime>         Timestamp format (default: %H:%M:%S)
+  --time-precision <s|ms|us|ns>    Fractional seconds in the default formats (default: s)
+  --age-reference <now|newest>     Measure age tags and filters from now or the newest log (default: now)
+  --columns <field,...>            Fields shown as key=value in wide mode
+  --wide                           Start in wide mode
+  --group-by <field>               Group the list by a field (e.g. reqId), Enter expands a group
+  --show-logger                    Show the logger name (name/logger field) in the list
+  --show-source                    Show the source location (Bunyan src field) in the list
+  --trace-fields <field,...>       Fields holding the trace id (default: trace_id,traceId)
+  --error-fields <field,...>       Fields holding an error object (default: err,error,exception)
+  --profile <name>                 Options for a log format: pino, bunyan, journald, docker, logfmt
+                                   or plain (default: detected from the first lines)
+  --line-numbers                   Show and store each log's source line number (_line)
+  --show-unparsed                  Show lines that fail to parse as red placeholder rows
+  --set-title                      Show the file name and filter in the terminal window title
+  --no-color                       Draw without colors (also with NO_COLOR set or TERM=dumb)
+  --lenient-levels                 Show custom level numbers as the nearest level below (35 = INFO)
+  --level-field <path>             Read the level from a nested field (e.g. OTel severity.number)
+  --message-fields <field,...>     Join these fields as the list message (e.g. context,msg,detail)
+  --message-separator <text>       Text between the message fields (default: \" | \")
+  --badge <field[=value:color,..]> Show a field as a colored badge (repeatable)
+  --scrolloff <rows>               Rows kept visible above/below the selection (default: 0)
+  --two-pass                       Detect column types from every log, not the first 100
+  --coerce-strings                 Store \"true\"/\"false\" strings as booleans, \"null\"/\"\" as null
+  --normalize-levels               Store level_num from level, lvl or severity (\"warn\" = 40)
+  --sort-on-load <none|time>       Sort JSON lines input before loading (default: none)
+  --untimed <start|end>            Where logs without a timestamp go when sorting (default: end)
+  --sort-tiebreak <field>          Order logs with equal timestamps by this field when sorting",
+        program
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs> {
+        CliArgs::parse_from(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_file_only() {
+        let args = parse(&["app.log"]).unwrap();
+        assert_eq!(args.log_file, "app.log");
+        assert_eq!(args.line_prefix, None);
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        let args = parse(&["--prefix", "cri", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::Cri));
+
+        assert!(parse(&["--prefix", "bogus", "app.log"]).is_err());
+        assert!(parse(&["app.log", "--prefix"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_hidden_fields() {
+        let args = parse(&["--hide", "pid, hostname,,", "--hide", "v", "app.log"]).unwrap();
+        assert_eq!(args.hidden_fields, vec!["pid", "hostname", "v"]);
+    }
+
+    #[test]
+    fn test_parse_preset() {
+        let args = parse(&["--preset", "bunyan", "--hide", "pid", "app.log"]).unwrap();
+        assert_eq!(args.hidden_fields, vec!["v", "pid"]);
+        assert!(args.show_source);
+        assert!(parse(&["--preset", "winston", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(parse(&["app.log"]).unwrap().profile, None);
+        let args = parse(&["--profile", "docker", "app.log"]).unwrap();
+        assert_eq!(args.profile, Some(Profile::Docker));
+        assert_eq!(args.line_prefix, Some(LinePrefix::Docker));
+        // An explicit prefix wins, even `none`
+        let args = parse(&["--prefix", "auto", "--profile", "docker", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::Auto));
+        let args = parse(&["--prefix", "none", "--profile", "logfmt", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::None));
+
+        let args = parse(&["--profile", "journald", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::Journald));
+        assert!(args.hidden_fields.iter().any(|field| field == "__CURSOR"));
+        let args = parse(&["--profile", "logfmt", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::Logfmt));
+        let args = parse(&["--profile", "plain", "app.log"]).unwrap();
+        assert_eq!(args.line_prefix, Some(LinePrefix::Plain));
+        assert!(parse(&["--profile", "winston", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_detect_profile() {
+        let lines =
+            |lines: &[&str]| -> Vec<String> { lines.iter().map(|l| l.to_string()).collect() };
+        assert_eq!(
+            Profile::detect(&lines(&[
+                "",
+                r#"{"name":"myapp","hostname":"banana.local","pid":40161,"level":30,"msg":"hi","time":"2013-01-04T18:46:23.851Z","v":0}"#,
+            ])),
+            Profile::Bunyan
+        );
+        assert_eq!(
+            Profile::detect(&lines(&[
+                r#"{"log":"{\"level\":30,\"msg\":\"hi\"}\n","stream":"stdout","time":"2024-01-15T10:30:00.123456789Z"}"#,
+            ])),
+            Profile::Docker
+        );
+        assert_eq!(
+            Profile::detect(&lines(&[
+                r#"{"level":30,"time":1700000000000,"pid":1,"hostname":"a","msg":"hi"}"#,
+            ])),
+            Profile::Pino
+        );
+        assert_eq!(
+            Profile::detect(&lines(&[
+                r#"{"__REALTIME_TIMESTAMP":"1705314600123456","PRIORITY":"6","MESSAGE":"hi"}"#,
+            ])),
+            Profile::Journald
+        );
+        assert_eq!(
+            Profile::detect(&lines(&[
+                "time=2024-01-15T10:30:00Z level=info msg=started",
+                r#"time=2024-01-15T10:30:01Z level=warn msg="slow query" took=1.5"#,
+            ])),
+            Profile::Logfmt
+        );
+        assert_eq!(
+            Profile::detect(&lines(&["not json", "level=info msg=hi"])),
+            Profile::Plain
+        );
+        // CRI-wrapped JSON is still JSON lines
+        assert_eq!(
+            Profile::detect(&lines(&[r#"2023-01-01T00:00:00Z stdout F {"msg":"a"}"#])),
+            Profile::Pino
+        );
+        // So is JSON after a banner and a stack trace, as long as most lines are JSON
+        let mut mixed = vec![
+            "Starting server v1.2.3",
+            "Error: boom",
+            "    at main (index.js:1:1)",
+        ];
+        mixed.extend([r#"{"level":30,"msg":"hi"}"#; 4]);
+        assert_eq!(Profile::detect(&lines(&mixed)), Profile::Pino);
+        assert_eq!(
+            Profile::detect(&lines(&[
+                "level=info msg=a",
+                "level=info msg=b",
+                "panic: runtime error",
+            ])),
+            Profile::Logfmt
+        );
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(parse(&["app.log"]).unwrap().filter, None);
+        let args = parse(&["--filter", "level >= 50", "app.log"]).unwrap();
+        assert_eq!(args.filter.as_deref(), Some("level >= 50"));
+        assert!(parse(&["app.log", "--filter"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_extensions() {
+        let args = parse(&["app.log"]).unwrap();
+        assert!(args.extensions.is_empty());
+        assert!(!args.fts);
+
+        let args = parse(&[
+            "--extension",
+            "json",
+            "--extension",
+            "spatial",
+            "--fts",
+            "app.log",
+        ])
+        .unwrap();
+        assert_eq!(args.extensions, ["json", "spatial"]);
+        assert!(args.fts);
+        assert!(parse(&["app.log", "--extension"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_fail_if() {
+        let args = parse(&[
+            "app.log",
+            "--fail-if",
+            "level >= 50",
+            "--fail-if",
+            "status = 500",
+            "--show-matches",
+            "3",
+        ])
+        .unwrap();
+        assert_eq!(args.fail_if, vec!["level >= 50", "status = 500"]);
+        assert_eq!(args.show_matches, 3);
+        assert_eq!(parse(&["app.log"]).unwrap().show_matches, 0);
+        assert!(parse(&["app.log", "--show-matches", "some"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_to_parquet() {
+        let args = parse(&["app.log", "--to-parquet", "out.parquet"]).unwrap();
+        assert_eq!(args.to_parquet.as_deref(), Some("out.parquet"));
+    }
+
+    #[test]
+    fn test_parse_db_file() {
+        let args = parse(&["app.log"]).unwrap();
+        assert!(args.db_file.is_none());
+        assert!(!args.rebuild_db);
+
+        let args = parse(&["--db-file", "logs.duckdb", "--rebuild-db", "app.log"]).unwrap();
+        assert_eq!(args.db_file.as_deref(), Some("logs.duckdb"));
+        assert!(args.rebuild_db);
+        assert!(parse(&["--rebuild-db", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        let args = parse(&["app.log"]).unwrap();
+        assert!(args.time_zone.is_none());
+
+        assert!(
+            parse(&["--timezone", "UTC", "app.log"])
+                .unwrap()
+                .time_zone
+                .is_some()
+        );
+        assert!(parse(&["--timezone", "local", "app.log"]).is_ok());
+        assert!(parse(&["--timezone", "Not/AZone", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_time_format() {
+        let args = parse(&["--time-format", "%Y-%m-%d %H:%M:%S", "app.log"]).unwrap();
+        assert_eq!(args.time_format.as_deref(), Some("%Y-%m-%d %H:%M:%S"));
+
+        assert!(parse(&["--time-format", "%H:%", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_time_precision() {
+        let args = parse(&["app.log"]).unwrap();
+        assert_eq!(args.time_precision, TimePrecision::Seconds);
+
+        let args = parse(&["--time-precision", "us", "app.log"]).unwrap();
+        assert_eq!(args.time_precision, TimePrecision::Micros);
+        assert!(parse(&["--time-precision", "minutes", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_age_reference() {
+        let args = parse(&["app.log"]).unwrap();
+        assert_eq!(args.age_reference, AgeReference::Now);
+
+        let args = parse(&["--age-reference", "newest", "app.log"]).unwrap();
+        assert_eq!(args.age_reference, AgeReference::Newest);
+        assert!(parse(&["--age-reference", "oldest", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trace_fields() {
+        let args = parse(&["--trace-fields", "dd.trace_id, traceId", "app.log"]).unwrap();
+        assert_eq!(args.trace_fields, vec!["dd.trace_id", "traceId"]);
+        assert!(parse(&["app.log"]).unwrap().trace_fields.is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_fields() {
+        let args = parse(&["--error-fields", "failure,cause", "app.log"]).unwrap();
+        assert_eq!(args.error_fields, vec!["failure", "cause"]);
+        assert!(parse(&["app.log"]).unwrap().error_fields.is_empty());
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let args = parse(&["--columns", "hostname,pid", "--wide", "app.log"]).unwrap();
+        assert_eq!(args.columns, vec!["hostname", "pid"]);
+        assert!(args.wide);
+    }
+
+    #[test]
+    fn test_parse_scrolloff() {
+        assert_eq!(parse(&["app.log"]).unwrap().scrolloff, 0);
+        assert_eq!(
+            parse(&["--scrolloff", "5", "app.log"]).unwrap().scrolloff,
+            5
+        );
+        assert!(parse(&["--scrolloff", "-1", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sort_on_load() {
+        let args = parse(&["app.log"]).unwrap();
+        assert_eq!(args.sort_on_load, SortOnLoad::None);
+        assert_eq!(args.untimed, UntimedPosition::End);
+
+        let args = parse(&["--sort-on-load", "time", "--untimed", "start", "app.log"]).unwrap();
+        assert_eq!(args.sort_on_load, SortOnLoad::Time);
+        assert_eq!(args.untimed, UntimedPosition::Start);
+
+        assert!(parse(&["--sort-on-load", "level", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sort_tiebreak() {
+        assert_eq!(parse(&["app.log"]).unwrap().sort_tiebreak, None);
+        let args = parse(&[
+            "--sort-on-load",
+            "time",
+            "--sort-tiebreak",
+            "seq",
+            "app.log",
+        ])
+        .unwrap();
+        assert_eq!(args.sort_tiebreak.as_deref(), Some("seq"));
+        assert!(parse(&["app.log", "--sort-tiebreak"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_redact() {
+        let args = parse(&["--redact", "email, req.headers.authorization", "app.log"]).unwrap();
+        assert_eq!(args.redact, vec!["email", "req.headers.authorization"]);
+    }
+
+    #[test]
+    fn test_parse_two_pass() {
+        assert!(!parse(&["app.log"]).unwrap().two_pass);
+        assert!(parse(&["--two-pass", "app.log"]).unwrap().two_pass);
+    }
+
+    #[test]
+    fn test_parse_coerce_strings() {
+        assert!(!parse(&["app.log"]).unwrap().coerce_strings);
+        assert!(
+            parse(&["--coerce-strings", "app.log"])
+                .unwrap()
+                .coerce_strings
+        );
+    }
+
+    #[test]
+    fn test_parse_normalize_levels() {
+        assert!(!parse(&["app.log"]).unwrap().normalize_levels);
+        assert!(
+            parse(&["--normalize-levels", "app.log"])
+                .unwrap()
+                .normalize_levels
+        );
+    }
+
+    #[test]
+    fn test_parse_lenient_levels() {
+        assert!(!parse(&["app.log"]).unwrap().lenient_levels);
+        assert!(
+            parse(&["--lenient-levels", "app.log"])
+                .unwrap()
+                .lenient_levels
+        );
+    }
+
+    #[test]
+    fn test_parse_level_field() {
+        assert_eq!(parse(&["app.log"]).unwrap().level_field, None);
+        let args = parse(&["--level-field", "severity.number", "app.log"]).unwrap();
+        assert_eq!(args.level_field.as_deref(), Some("severity.number"));
+        assert!(parse(&["app.log", "--level-field"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_message_fields() {
+        let args = parse(&["app.log"]).unwrap();
+        assert!(args.message_fields.is_empty());
+        assert_eq!(args.message_separator, None);
+
+        let args = parse(&[
+            "--message-fields",
+            "context, msg,detail",
+            "--message-separator",
+            " - ",
+            "app.log",
+        ])
+        .unwrap();
+        assert_eq!(args.message_fields, vec!["context", "msg", "detail"]);
+        assert_eq!(args.message_separator.as_deref(), Some(" - "));
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        assert_eq!(parse(&["app.log"]).unwrap().group_by, None);
+        assert_eq!(
+            parse(&["--group-by", "reqId", "app.log"]).unwrap().group_by,
+            Some("reqId".to_string())
+        );
+        assert!(parse(&["app.log", "--group-by"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_hide_constant() {
+        assert!(!parse(&["app.log"]).unwrap().hide_constant);
+        assert!(
+            parse(&["--hide-constant", "app.log"])
+                .unwrap()
+                .hide_constant
+        );
+    }
+
+    #[test]
+    fn test_parse_no_color() {
+        assert!(!parse(&["app.log"]).unwrap().no_color);
+        assert!(parse(&["--no-color", "app.log"]).unwrap().no_color);
+    }
+
+    #[test]
+    fn test_parse_set_title() {
+        assert!(!parse(&["app.log"]).unwrap().set_title);
+        assert!(parse(&["--set-title", "app.log"]).unwrap().set_title);
+    }
+
+    #[test]
+    fn test_parse_line_numbers() {
+        assert!(!parse(&["app.log"]).unwrap().line_numbers);
+        assert!(parse(&["--line-numbers", "app.log"]).unwrap().line_numbers);
+    }
+
+    #[test]
+    fn test_parse_show_unparsed() {
+        assert!(!parse(&["app.log"]).unwrap().show_unparsed);
+        assert!(
+            parse(&["--show-unparsed", "app.log"])
+                .unwrap()
+                .show_unparsed
+        );
+    }
+
+    #[test]
+    fn test_parse_watch_dir() {
+        assert!(!parse(&["logs"]).unwrap().watch_dir);
+        assert!(parse(&["--watch-dir", "logs"]).unwrap().watch_dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_socket() {
+        assert_eq!(parse(&["app.log"]).unwrap().stream, None);
+        let args = parse(&["--socket", "/run/app.sock"]).unwrap();
+        assert_eq!(
+            args.stream,
+            Some(SocketAddress::Unix("/run/app.sock".into()))
+        );
+        assert_eq!(args.log_file, "/run/app.sock");
+        assert!(parse(&["--socket", "/run/app.sock", "app.log"]).is_err());
+        assert!(parse(&["--socket"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tcp() {
+        let args = parse(&["--tcp", "logs.internal:5170"]).unwrap();
+        assert_eq!(
+            args.stream,
+            Some(SocketAddress::Tcp("logs.internal:5170".to_string()))
+        );
+        assert_eq!(args.log_file, "logs.internal:5170");
+        assert!(parse(&["--tcp", "logs.internal"]).is_err());
+        assert!(parse(&["--tcp", "a:1", "--socket", "/run/app.sock"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_follow() {
+        assert!(!parse(&["app.log"]).unwrap().follow);
+        assert!(parse(&["-f", "app.log"]).unwrap().follow);
+        assert!(parse(&["app.log", "--follow"]).unwrap().follow);
+        assert_eq!(parse(&["app.log"]).unwrap().follow_batch, None);
+
+        let args = parse(&[
+            "-f",
+            "--follow-batch",
+            "50",
+            "--follow-flush-ms",
+            "20",
+            "app.log",
+        ])
+        .unwrap();
+        assert_eq!(args.follow_batch, Some(50));
+        assert_eq!(args.follow_flush_ms, Some(20));
+        assert!(parse(&["--follow-batch", "0", "app.log"]).is_err());
+        assert!(parse(&["--follow-flush-ms", "soon", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_insert_batch() {
+        assert_eq!(parse(&["app.log"]).unwrap().insert_batch, None);
+        let args = parse(&["--insert-batch", "1000", "app.log"]).unwrap();
+        assert_eq!(args.insert_batch, Some(1000));
+        assert!(parse(&["--insert-batch", "0", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_idle_after() {
+        assert_eq!(parse(&["app.log"]).unwrap().idle_after_secs, None);
+        let args = parse(&["--idle-after", "30", "app.log"]).unwrap();
+        assert_eq!(args.idle_after_secs, Some(30));
+        assert_eq!(
+            parse(&["--idle-after", "0", "app.log"])
+                .unwrap()
+                .idle_after_secs,
+            Some(0)
+        );
+        assert!(parse(&["--idle-after", "-1", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_number_helpers() {
+        assert_eq!(
+            parse_positive::<usize>("5", "--insert-batch", "rows").unwrap(),
+            5
+        );
+        assert!(parse_positive::<usize>("0", "--insert-batch", "rows").is_err());
+        assert!(parse_positive::<usize>("-5", "--insert-batch", "rows").is_err());
+        assert_eq!(
+            parse_number::<u64>("0", "--idle-after", "seconds").unwrap(),
+            0
+        );
+        assert!(parse_number::<u64>("soon", "--idle-after", "seconds").is_err());
+    }
+
+    #[test]
+    fn test_parse_max_line_bytes() {
+        assert_eq!(parse(&["app.log"]).unwrap().max_line_bytes, None);
+        let args = parse(&["--max-line-bytes", "1048576", "app.log"]).unwrap();
+        assert_eq!(args.max_line_bytes, Some(1_048_576));
+        assert!(parse(&["--max-line-bytes", "0", "app.log"]).is_err());
+        assert!(parse(&["--max-line-bytes", "1MB", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_memory_limit() {
+        assert_eq!(parse(&["app.log"]).unwrap().memory_limit, None);
+        let args = parse(&["-f", "--memory-limit", "100000", "app.log"]).unwrap();
+        assert_eq!(args.memory_limit, Some(100_000));
+        assert!(parse(&["--memory-limit", "0", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_result_limit() {
+        assert_eq!(parse(&["app.log"]).unwrap().result_limit, None);
+        let args = parse(&["--result-limit", "10000", "app.log"]).unwrap();
+        assert_eq!(args.result_limit, Some(10_000));
+        assert!(parse(&["--result-limit", "none", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_badges() {
+        let args = parse(&[
+            "--badge",
+            "statusCode",
+            "--badge",
+            "env=prod:red,dev:green",
+            "app.log",
+        ])
+        .unwrap();
+        assert_eq!(args.badges.len(), 2);
+        assert_eq!(args.badges[0].field, "statusCode");
+        assert_eq!(args.badges[1].field, "env");
+
+        assert!(parse(&["--badge", "env=prod", "app.log"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--nope", "app.log"]).is_err());
+        assert!(parse(&["a.log", "b.log"]).is_err());
+    }
+}
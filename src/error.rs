@@ -15,6 +15,15 @@ pub enum LogViewerError {
     #[error("Invalid log format: {0}")]
     InvalidLogFormat(String),
 
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Existing table doesn't match the logs: {0}")]
+    SchemaMismatch(String),
+
+    #[error("File watch error: {0}")]
+    Watch(#[from] notify::Error),
+
     #[error("Timestamp conversion error: {0}")]
     TimestampError(String),
 
@@ -0,0 +1,106 @@
+use crate::ingestion::JsonLog;
+use serde_json::{Map, Value};
+
+/// Format the `req` object of an HTTP request log (pino-http style) as a curl command
+///
+/// Reads `req.method`, `req.url`, `req.headers` and `req.body`. Relative URLs are
+/// made absolute with the `host` header. Returns `None` when the log has no `req`
+/// object or it lacks a URL.
+pub fn format_curl(log: &JsonLog) -> Option<String> {
+    let req = request_object(log.get_field("req")?)?;
+
+    let url = req.get("url").and_then(|v| v.as_str())?;
+    let method = req
+        .get("method")
+        .and_then(|v| v.as_str())
+        .unwrap_or("GET")
+        .to_uppercase();
+    let headers = req.get("headers").and_then(|v| v.as_object());
+
+    let full_url = if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        match headers.and_then(|h| h.get("host")).and_then(|v| v.as_str()) {
+            Some(host) => format!("http://{}{}", host, url),
+            None => url.to_string(),
+        }
+    };
+
+    let mut parts = vec![format!("curl -X {} {}", method, shell_quote(&full_url))];
+
+    if let Some(headers) = headers {
+        for (name, value) in headers {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            parts.push(format!(
+                "-H {}",
+                shell_quote(&format!("{}: {}", name, value))
+            ));
+        }
+    }
+
+    match req.get("body") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(body)) => parts.push(format!("--data-raw {}", shell_quote(body))),
+        Some(body) => parts.push(format!("--data-raw {}", shell_quote(&body.to_string()))),
+    }
+
+    Some(parts.join(" \\\n  "))
+}
+
+/// The `req` field as an object; logs read back from the database store it as JSON text
+fn request_object(value: &Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Some(map.clone()),
+        Value::String(s) => serde_json::from_str(s).ok(),
+        _ => None,
+    }
+}
+
+/// Quote a string for a POSIX shell
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+
+    #[test]
+    fn test_format_curl() {
+        let log = parse_json_line(
+            r#"{"level":30,"msg":"request completed","req":{"method":"post","url":"/api/users?x=1","headers":{"host":"example.com","content-type":"application/json"},"body":{"name":"o'neil"}}}"#,
+        )
+        .unwrap();
+
+        let curl = format_curl(&log).unwrap();
+        assert!(curl.starts_with("curl -X POST 'http://example.com/api/users?x=1'"));
+        assert!(curl.contains("-H 'host: example.com'"));
+        assert!(curl.contains("-H 'content-type: application/json'"));
+        assert!(curl.contains(r#"--data-raw '{"name":"o'\''neil"}'"#));
+    }
+
+    #[test]
+    fn test_format_curl_from_json_text() {
+        // Logs queried back from DuckDB carry nested objects as JSON strings
+        let log = parse_json_line(r#"{"msg":"req","req":"{\"url\":\"https://api.test/health\"}"}"#)
+            .unwrap();
+
+        assert_eq!(
+            format_curl(&log).as_deref(),
+            Some("curl -X GET 'https://api.test/health'")
+        );
+    }
+
+    #[test]
+    fn test_format_curl_missing_fields() {
+        let no_req = parse_json_line(r#"{"msg":"hello"}"#).unwrap();
+        assert_eq!(format_curl(&no_req), None);
+
+        let no_url = parse_json_line(r#"{"msg":"hello","req":{"method":"GET"}}"#).unwrap();
+        assert_eq!(format_curl(&no_url), None);
+    }
+}
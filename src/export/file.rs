@@ -0,0 +1,143 @@
+use crate::error::{LogViewerError, Result};
+use crate::export::redact::redact_log;
+use crate::ingestion::JsonLog;
+use rootcause::prelude::ResultExt;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Text formats logs can be exported to from the viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Ndjson,
+    /// Header row with the union of all field names, one row per log
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Write `logs` to `path`, replacing the `redact` fields with `"[REDACTED]"`
+/// Returns the number of logs written
+pub fn export_logs(
+    logs: &[JsonLog],
+    path: &str,
+    format: ExportFormat,
+    redact: &[String],
+) -> Result<usize> {
+    let file = File::create(path)
+        .map_err(LogViewerError::from)
+        .attach_with(|| format!("Failed to create export file {}", path))?;
+    let mut writer = BufWriter::new(file);
+
+    let redacted: Vec<JsonLog> = logs.iter().map(|log| redact_log(log, redact)).collect();
+    let written = match format {
+        ExportFormat::Ndjson => write_ndjson(&redacted, &mut writer),
+        ExportFormat::Csv => write_csv(&redacted, &mut writer),
+    };
+    written
+        .and_then(|()| writer.flush())
+        .map_err(LogViewerError::from)
+        .attach_with(|| format!("Failed to write export file {}", path))?;
+
+    Ok(logs.len())
+}
+
+fn write_ndjson(logs: &[JsonLog], writer: &mut impl Write) -> std::io::Result<()> {
+    for log in logs {
+        serde_json::to_writer(&mut *writer, &log.fields)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn write_csv(logs: &[JsonLog], writer: &mut impl Write) -> std::io::Result<()> {
+    let columns: BTreeSet<&str> = logs
+        .iter()
+        .flat_map(|log| log.fields.keys().map(String::as_str))
+        .collect();
+
+    let header: Vec<String> = columns.iter().map(|name| csv_field(name)).collect();
+    writeln!(writer, "{}", header.join(","))?;
+
+    for log in logs {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|name| match log.get_field(name) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => csv_field(s),
+                Some(other) => csv_field(&other.to_string()),
+            })
+            .collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+
+    fn sample_logs() -> Vec<JsonLog> {
+        vec![
+            parse_json_line(r#"{"msg":"a, b","level":30,"email":"x@y.z"}"#).unwrap(),
+            parse_json_line(r#"{"msg":"say \"hi\"","ctx":{"id":1}}"#).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut out = Vec::new();
+        write_csv(&sample_logs(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ctx,email,level,msg\n\
+             ,x@y.z,30,\"a, b\"\n\
+             \"{\"\"id\"\":1}\",,,\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_redacts_fields() {
+        let path =
+            std::env::temp_dir().join(format!("log-viewer-export-{}.ndjson", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let written = export_logs(
+            &sample_logs(),
+            path,
+            ExportFormat::Ndjson,
+            &["email".to_string()],
+        )
+        .unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(written, 2);
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["email"], "[REDACTED]");
+        assert_eq!(lines[1]["ctx"]["id"], 1);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod curl;
+pub mod file;
+pub mod redact;
+pub mod table;
+
+pub use curl::format_curl;
+pub use file::{ExportFormat, export_logs};
+pub use table::format_table;
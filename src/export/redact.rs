@@ -0,0 +1,98 @@
+use crate::ingestion::JsonLog;
+use serde_json::Value;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Copy of `log` with the listed fields replaced by `"[REDACTED]"`
+///
+/// A field is a top-level key or a dotted path into nested objects
+/// (`req.headers.authorization`). Objects stored as JSON text, as in logs read
+/// back from the database, are searched too. Missing fields are ignored.
+pub fn redact_log(log: &JsonLog, fields: &[String]) -> JsonLog {
+    let mut redacted = log.clone();
+    for field in fields {
+        if let Some(value) = redacted.fields.get_mut(field.as_str()) {
+            *value = Value::String(REDACTED.to_string());
+            continue;
+        }
+
+        let nested = field
+            .split_once('.')
+            .and_then(|(head, rest)| Some((redacted.fields.get_mut(head)?, rest)));
+        if let Some((value, rest)) = nested {
+            redact_path(value, rest);
+        }
+    }
+    redacted
+}
+
+/// Redact the dotted `path` inside `value`
+fn redact_path(value: &mut Value, path: &str) {
+    match value {
+        Value::Object(map) => {
+            if let Some(target) = map.get_mut(path) {
+                *target = Value::String(REDACTED.to_string());
+                return;
+            }
+            let child = path
+                .split_once('.')
+                .and_then(|(head, rest)| Some((map.get_mut(head)?, rest)));
+            if let Some((child, rest)) = child {
+                redact_path(child, rest);
+            }
+        }
+        Value::String(text) => {
+            let Ok(mut parsed @ Value::Object(_)) = serde_json::from_str::<Value>(text) else {
+                return;
+            };
+            redact_path(&mut parsed, path);
+            *text = parsed.to_string();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+    use serde_json::json;
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_redact_top_level_fields() {
+        let log = parse_json_line(r#"{"msg":"login","email":"a@b.c","ip":"10.0.0.1"}"#).unwrap();
+        let redacted = redact_log(&log, &fields(&["email", "ip", "missing"]));
+
+        assert_eq!(redacted.get_field("email"), Some(&json!(REDACTED)));
+        assert_eq!(redacted.get_field("ip"), Some(&json!(REDACTED)));
+        assert_eq!(redacted.get_message(), Some("login"));
+        assert!(redacted.get_field("missing").is_none());
+        // The original is untouched
+        assert_eq!(log.get_field("email"), Some(&json!("a@b.c")));
+    }
+
+    #[test]
+    fn test_redact_nested_fields() {
+        let log = parse_json_line(
+            r#"{"req":{"url":"/","headers":{"authorization":"Bearer x","host":"h"}}}"#,
+        )
+        .unwrap();
+        let redacted = redact_log(&log, &fields(&["req.headers.authorization"]));
+        assert_eq!(
+            redacted.get_field("req"),
+            Some(&json!({"url": "/", "headers": {"authorization": REDACTED, "host": "h"}}))
+        );
+
+        // Objects read back from the database are JSON text
+        let log = parse_json_line(r#"{"user":"{\"token\":\"t\",\"id\":1}"}"#).unwrap();
+        let redacted = redact_log(&log, &fields(&["user.token"]));
+        let user: Value =
+            serde_json::from_str(redacted.get_field("user").unwrap().as_str().unwrap()).unwrap();
+        assert_eq!(user, json!({"token": REDACTED, "id": 1}));
+    }
+}
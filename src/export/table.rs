@@ -0,0 +1,117 @@
+use crate::export::redact::redact_log;
+use crate::ingestion::JsonLog;
+use crate::ui::DisplayOptions;
+use serde_json::Value;
+
+/// Longest cell in a text table, in characters; longer values end in `…`
+pub const MAX_CELL_CHARS: usize = 60;
+
+/// Format logs as an aligned plain-text table for pasting into tickets
+///
+/// Columns are time (in the list's format), level, message and the wide mode
+/// columns (`--columns`). The `redact` fields are replaced like on export.
+pub fn format_table(logs: &[JsonLog], display: &DisplayOptions, redact: &[String]) -> String {
+    let mut header = vec![
+        "time".to_string(),
+        "level".to_string(),
+        "message".to_string(),
+    ];
+    header.extend(display.visible_columns.iter().cloned());
+
+    let mut rows = vec![header];
+    for log in logs {
+        let log = redact_log(log, redact);
+        let level = display.log_level(&log);
+        let mut row = vec![
+            log.timestamp()
+                .map(|timestamp| display.format_timestamp(timestamp, display.list_time_format()))
+                .unwrap_or_default(),
+            level
+                .map(|level| level.as_str().to_string())
+                .unwrap_or_default(),
+            display.log_message(&log).unwrap_or_default().to_string(),
+        ];
+        row.extend(
+            display
+                .visible_columns
+                .iter()
+                .map(|field| match log.get_field(field) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                }),
+        );
+        rows.push(row.iter().map(|cell| truncate_cell(cell)).collect());
+    }
+
+    let mut widths = vec![0; rows[0].len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut lines: Vec<String> = rows.iter().map(|row| format_row(row, &widths)).collect();
+    let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    lines.insert(1, rule.join("  "));
+    lines.join("\n") + "\n"
+}
+
+/// Pad each cell to its column width, two spaces apart, without trailing spaces
+fn format_row(row: &[String], widths: &[usize]) -> String {
+    let cells: Vec<String> = row
+        .iter()
+        .zip(widths)
+        .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+        .collect();
+    cells.join("  ").trim_end().to_string()
+}
+
+/// Keep a cell on one line and at most `MAX_CELL_CHARS` characters
+fn truncate_cell(value: &str) -> String {
+    let value = value.replace(['\n', '\r', '\t'], " ");
+    if value.chars().count() > MAX_CELL_CHARS {
+        let truncated: String = value.chars().take(MAX_CELL_CHARS - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+
+    #[test]
+    fn test_format_table() {
+        let logs = vec![
+            parse_json_line(
+                r#"{"level":30,"time":1700000000000,"msg":"request done","status":200,"email":"x@y.z"}"#,
+            )
+            .unwrap(),
+            parse_json_line(r#"{"level":50,"time":1700000001000,"msg":"disk\nfull"}"#).unwrap(),
+        ];
+        let display = DisplayOptions {
+            visible_columns: vec!["status".to_string(), "email".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_table(&logs, &display, &["email".to_string()]),
+            "time      level  message       status  email\n\
+             --------  -----  ------------  ------  ----------\n\
+             22:13:20  INFO   request done  200     [REDACTED]\n\
+             22:13:21  ERROR  disk full\n"
+        );
+    }
+
+    #[test]
+    fn test_truncate_cell() {
+        let long = "x".repeat(100);
+        let cell = truncate_cell(&long);
+        assert_eq!(cell.chars().count(), MAX_CELL_CHARS);
+        assert!(cell.ends_with('…'));
+        assert_eq!(truncate_cell("a\tb"), "a b");
+    }
+}
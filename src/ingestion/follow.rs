@@ -0,0 +1,449 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::JsonLog;
+use crate::ingestion::prefix::{CriPartials, LinePrefix, parse_prefixed_line_with_duplicates};
+use crate::ingestion::reader::DEFAULT_MAX_LINE_BYTES;
+use rootcause::prelude::ResultExt;
+use std::fs::{File, Metadata};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Followed lines inserted per batch unless `--follow-batch` says otherwise
+pub const DEFAULT_FOLLOW_BATCH: usize = 1000;
+
+/// Longest a followed line waits for its batch unless `--follow-flush-ms` says otherwise
+pub const DEFAULT_FOLLOW_FLUSH: Duration = Duration::from_millis(200);
+
+/// Reads lines appended to a log file, like `tail -F`
+///
+/// Survives rotation: when the path points at a new file (different inode) the
+/// rest of the old file is drained and the new one is read from the start.
+/// A file truncated in place (copytruncate) is also read again from the start.
+pub struct FileFollower {
+    path: PathBuf,
+    reader: BufReader<File>,
+    identity: Option<(u64, u64)>,
+    /// Byte offset of the next unread byte in the open file
+    position: u64,
+    /// Trailing bytes of a line whose newline has not been written yet
+    pending: Vec<u8>,
+    /// Lines longer than this (newline excluded) are reported and skipped
+    max_line_bytes: usize,
+    /// Whether the rest of the current line is being skipped for its length
+    skipping: bool,
+    line_prefix: LinePrefix,
+    /// CRI `P` chunks waiting for their `F` line
+    partials: CriPartials,
+    /// Line number of the last complete line, when recording line numbers
+    line_number: Option<usize>,
+}
+
+impl FileFollower {
+    /// Follow `path` starting at byte `offset` (where the initial load stopped)
+    pub fn new(path: impl AsRef<Path>, offset: u64) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to open {} for following", path.display()))?;
+        let identity = file.metadata().ok().as_ref().and_then(file_identity);
+        file.seek(SeekFrom::Start(offset))
+            .map_err(LogViewerError::from)
+            .attach("Failed to seek to the end of the loaded logs")?;
+
+        Ok(Self {
+            path,
+            reader: BufReader::new(file),
+            identity,
+            position: offset,
+            pending: Vec::new(),
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            skipping: false,
+            line_prefix: LinePrefix::None,
+            partials: CriPartials::default(),
+            line_number: None,
+        })
+    }
+
+    /// Skip lines longer than `max_line_bytes` instead of buffering them
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self.partials = CriPartials::new(max_line_bytes);
+        self
+    }
+
+    /// Strip a container runtime prefix (Docker/CRI) before parsing each line
+    pub fn with_line_prefix(mut self, line_prefix: LinePrefix) -> Self {
+        self.line_prefix = line_prefix;
+        self
+    }
+
+    /// Record each log's source line number, continuing after `lines_read`
+    pub fn with_line_numbers(mut self, lines_read: usize) -> Self {
+        self.line_number = Some(lines_read);
+        self
+    }
+
+    /// Parse the complete lines written since the last poll
+    /// Lines that fail to parse are skipped, as in the initial load
+    pub fn poll(&mut self) -> Result<Vec<JsonLog>> {
+        let mut logs = self.read_available()?;
+
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            // Rotated away and not recreated yet; keep the old handle and retry later
+            return Ok(logs);
+        };
+
+        if self.identity.is_some() && file_identity(&metadata) != self.identity {
+            // Path now points at a new file: the old one was drained above
+            let Ok(file) = File::open(&self.path) else {
+                return Ok(logs);
+            };
+            tracing::info!("{} was rotated, reopening", self.path.display());
+            self.identity = file.metadata().ok().as_ref().and_then(file_identity);
+            self.reader = BufReader::new(file);
+            self.restart();
+            logs.extend(self.read_available()?);
+        } else if metadata.len() < self.position {
+            tracing::info!(
+                "{} was truncated, reading from the start",
+                self.path.display()
+            );
+            self.reader
+                .seek(SeekFrom::Start(0))
+                .map_err(LogViewerError::from)
+                .attach("Failed to rewind truncated log file")?;
+            self.restart();
+            logs.extend(self.read_available()?);
+        }
+
+        Ok(logs)
+    }
+
+    fn restart(&mut self) {
+        self.position = 0;
+        self.pending.clear();
+        self.skipping = false;
+        self.partials.clear();
+        if let Some(line_number) = self.line_number.as_mut() {
+            *line_number = 0;
+        }
+    }
+
+    /// Read up to EOF, keeping an unterminated last line for the next poll
+    /// Like `LogFileReader`, a line past `max_line_bytes` stops being buffered and
+    /// is skipped up to its newline
+    fn read_available(&mut self) -> Result<Vec<JsonLog>> {
+        let mut logs = Vec::new();
+
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(LogViewerError::from(e))
+                        .attach_with(|| format!("Failed to read {}", self.path.display()));
+                }
+            };
+            if available.is_empty() {
+                break;
+            }
+            let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(end) => (&available[..=end], true),
+                None => (available, false),
+            };
+            let len = chunk.len();
+            if !self.skipping {
+                if self.pending.len() + len - usize::from(done) > self.max_line_bytes {
+                    tracing::warn!(
+                        "Followed line in {} is longer than {} bytes, skipped",
+                        self.path.display(),
+                        self.max_line_bytes
+                    );
+                    self.skipping = true;
+                    self.pending = Vec::new();
+                } else {
+                    self.pending.extend_from_slice(chunk);
+                }
+            }
+            self.reader.consume(len);
+            self.position += len as u64;
+            if !done {
+                continue;
+            }
+
+            if let Some(line_number) = self.line_number.as_mut() {
+                *line_number += 1;
+            }
+            if std::mem::take(&mut self.skipping) {
+                continue;
+            }
+            let line_bytes = std::mem::take(&mut self.pending);
+            let line = String::from_utf8_lossy(&line_bytes);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let Some(line) = self.partials.join(&line, self.line_prefix) else {
+                continue;
+            };
+            if let Some(mut log) = parse_followed_line(&line, self.line_prefix) {
+                if let Some(line_number) = self.line_number {
+                    log.set_line_number(line_number);
+                }
+                logs.push(log);
+            }
+        }
+
+        Ok(logs)
+    }
+}
+
+/// Parse a line read after the initial load, skipping it (with a debug log) if invalid
+pub(crate) fn parse_followed_line(line: &str, line_prefix: LinePrefix) -> Option<JsonLog> {
+    match parse_prefixed_line_with_duplicates(line, line_prefix) {
+        Ok((log, duplicates)) => {
+            if !duplicates.is_empty() {
+                tracing::warn!(
+                    "Followed line has duplicate keys {}, later values kept as <key>_2, <key>_3, ...",
+                    duplicates.join(", ")
+                );
+            }
+            Some(log)
+        }
+        Err(e) => {
+            tracing::debug!("Skipping unparsable followed line: {:?}", e);
+            None
+        }
+    }
+}
+
+/// Collects followed logs so they are inserted in batches instead of per poll
+///
+/// A batch is due once it holds `max_lines` logs or its oldest log has waited
+/// `max_delay`, whichever comes first.
+pub struct FollowBuffer {
+    pending: Vec<JsonLog>,
+    max_lines: usize,
+    max_delay: Duration,
+    /// When the oldest pending log arrived
+    since: Option<Instant>,
+}
+
+impl FollowBuffer {
+    pub fn new(max_lines: usize, max_delay: Duration) -> Self {
+        Self {
+            pending: Vec::new(),
+            max_lines: max_lines.max(1),
+            max_delay,
+            since: None,
+        }
+    }
+
+    /// Queue logs read at `now`
+    pub fn push(&mut self, logs: Vec<JsonLog>, now: Instant) {
+        if logs.is_empty() {
+            return;
+        }
+        if self.pending.is_empty() {
+            self.since = Some(now);
+        }
+        self.pending.extend(logs);
+    }
+
+    /// Whether the pending logs should be inserted at `now`
+    pub fn is_due(&self, now: Instant) -> bool {
+        match self.since {
+            Some(since) => {
+                self.pending.len() >= self.max_lines
+                    || now.saturating_duration_since(since) >= self.max_delay
+            }
+            None => false,
+        }
+    }
+
+    /// Take every pending log, e.g. to flush on quit
+    pub fn take(&mut self) -> Vec<JsonLog> {
+        self.since = None;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl Default for FollowBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_FOLLOW_BATCH, DEFAULT_FOLLOW_FLUSH)
+    }
+}
+
+/// Device and inode of a file, used to notice the path being replaced
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Without inodes only truncation can be detected
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "log-viewer-follow-{}-{}.log",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn append(path: &Path, content: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    fn messages(logs: &[JsonLog]) -> Vec<&str> {
+        logs.iter().filter_map(|log| log.get_message()).collect()
+    }
+
+    #[test]
+    fn test_follow_appended_lines() {
+        let path = temp_path("append");
+        let initial = "{\"msg\":\"old\"}\n";
+        std::fs::write(&path, initial).unwrap();
+
+        let mut follower = FileFollower::new(&path, initial.len() as u64).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        // A partial line is held back until its newline arrives
+        append(&path, "{\"msg\":\"one\"}\n{\"msg\":");
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["one"]);
+        append(&path, "\"two\"}\n");
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["two"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_joins_cri_partials() {
+        let path = temp_path("cri-partials");
+        std::fs::write(&path, "").unwrap();
+
+        let mut follower = FileFollower::new(&path, 0)
+            .unwrap()
+            .with_line_prefix(LinePrefix::Cri);
+        append(&path, "2023-01-01T00:00:00Z stdout P {\"msg\":\"lo\n");
+        assert!(follower.poll().unwrap().is_empty());
+        append(&path, "2023-01-01T00:00:00Z stdout F ng\"}\n");
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["long"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_skips_long_lines() {
+        let path = temp_path("long-line");
+        std::fs::write(&path, "").unwrap();
+
+        let mut follower = FileFollower::new(&path, 0)
+            .unwrap()
+            .with_max_line_bytes(16)
+            .with_line_numbers(0);
+        // Written in two polls, so the skip carries over to the newline
+        append(&path, "{\"msg\":\"this line is too long");
+        assert!(follower.poll().unwrap().is_empty());
+        assert!(follower.pending.is_empty());
+        append(&path, " to keep\"}\n{\"msg\":\"short\"}\n");
+        let logs = follower.poll().unwrap();
+        assert_eq!(messages(&logs), vec!["short"]);
+        assert_eq!(logs[0].line_number(), Some(2));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_truncation() {
+        let path = temp_path("truncate");
+        std::fs::write(&path, "{\"msg\":\"before truncate\"}\n").unwrap();
+
+        let mut follower = FileFollower::new(&path, 0).unwrap();
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["before truncate"]);
+
+        std::fs::write(&path, "{\"msg\":\"new\"}\n").unwrap();
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["new"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_line_numbers() {
+        let path = temp_path("line-numbers");
+        let initial = "{\"msg\":\"a\"}\n\n";
+        std::fs::write(&path, initial).unwrap();
+
+        let mut follower = FileFollower::new(&path, initial.len() as u64)
+            .unwrap()
+            .with_line_numbers(2);
+        append(&path, "{\"msg\":\"b\"}\nnot json\n{\"msg\":\"c\"}\n");
+        let lines: Vec<Option<u64>> = follower
+            .poll()
+            .unwrap()
+            .iter()
+            .map(JsonLog::line_number)
+            .collect();
+        assert_eq!(lines, vec![Some(3), Some(5)]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_buffer_flushes_by_size_or_age() {
+        let log = || crate::ingestion::parse_json_line("{\"msg\":\"x\"}").unwrap();
+        let start = Instant::now();
+        let mut buffer = FollowBuffer::new(3, Duration::from_millis(100));
+        assert!(!buffer.is_due(start));
+
+        buffer.push(vec![log(), log()], start);
+        assert!(!buffer.is_due(start + Duration::from_millis(50)));
+        assert!(buffer.is_due(start + Duration::from_millis(100)));
+
+        buffer.push(vec![log()], start);
+        assert!(buffer.is_due(start));
+        assert_eq!(buffer.take().len(), 3);
+        assert!(!buffer.is_due(start + Duration::from_secs(1)));
+
+        // The delay counts from the oldest pending log
+        buffer.push(vec![log()], start + Duration::from_millis(500));
+        assert!(!buffer.is_due(start + Duration::from_millis(550)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_rotation() {
+        let path = temp_path("rotate");
+        let rotated = temp_path("rotate-1");
+        std::fs::write(&path, "").unwrap();
+
+        let mut follower = FileFollower::new(&path, 0).unwrap();
+        append(&path, "{\"msg\":\"a\"}\n");
+        std::fs::rename(&path, &rotated).unwrap();
+        // Written to the old file after the rename, before the writer reopened
+        append(&rotated, "{\"msg\":\"b\"}\n");
+        append(&path, "{\"msg\":\"c\"}\n");
+
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["a", "b", "c"]);
+
+        append(&path, "{\"msg\":\"d\"}\n");
+        assert_eq!(messages(&follower.poll().unwrap()), vec!["d"]);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated).unwrap();
+    }
+}
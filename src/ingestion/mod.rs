@@ -1,7 +1,20 @@
+pub mod follow;
 pub mod models;
 pub mod parser;
+pub mod prefix;
 pub mod reader;
+pub mod rotation;
+pub mod socket;
+pub mod sort;
+pub mod text;
+pub mod watch;
 
+pub use follow::{DEFAULT_FOLLOW_BATCH, DEFAULT_FOLLOW_FLUSH, FileFollower, FollowBuffer};
 pub use models::{JsonLog, LogLevel};
 pub use parser::parse_json_line;
+pub use prefix::{CriPartials, LinePrefix, parse_prefixed_line};
 pub use reader::LogFileReader;
+pub use rotation::{merge_by_time, rotated_log_files, tag_source};
+pub use socket::{SocketAddress, SocketFollower};
+pub use sort::{SortOnLoad, UntimedPosition, sort_logs};
+pub use watch::DirectoryWatcher;
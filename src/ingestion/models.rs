@@ -1,5 +1,5 @@
+use crate::storage::schema::normalize_field_name;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum LogLevel {
@@ -24,6 +24,57 @@ impl LogLevel {
         }
     }
 
+    /// Nearest standard level at or below `level`, for custom levels like Pino's 35
+    /// Exact values map as in `from_u64`; below 20 is Trace, 60 and above Fatal
+    pub fn from_u64_lenient(level: u64) -> Self {
+        match level {
+            ..20 => LogLevel::Trace,
+            20..30 => LogLevel::Debug,
+            30..40 => LogLevel::Info,
+            40..50 => LogLevel::Warn,
+            50..60 => LogLevel::Error,
+            _ => LogLevel::Fatal,
+        }
+    }
+
+    /// Level for a name such as `"warn"` or `"WARNING"` (case-insensitive), as
+    /// written by loggers that use string levels or a `severity` field
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" | "information" | "notice" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "err" => Some(LogLevel::Error),
+            "fatal" | "critical" | "crit" | "panic" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Level for a syslog priority (0 emerg ... 7 debug), as in journald's `PRIORITY`
+    pub fn from_syslog(priority: u64) -> Self {
+        match priority {
+            0..=2 => LogLevel::Fatal,
+            3 => LogLevel::Error,
+            4 => LogLevel::Warn,
+            5 | 6 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+
+    /// Level for an OpenTelemetry severity number (1-4 trace ... 21-24 fatal)
+    pub fn from_otel_severity(number: u64) -> Option<Self> {
+        match number {
+            1..=4 => Some(LogLevel::Trace),
+            5..=8 => Some(LogLevel::Debug),
+            9..=12 => Some(LogLevel::Info),
+            13..=16 => Some(LogLevel::Warn),
+            17..=20 => Some(LogLevel::Error),
+            21..=24 => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             LogLevel::Trace => "TRACE",
@@ -46,21 +97,106 @@ impl std::fmt::Display for LogLevel {
     }
 }
 
+/// Field names holding the level, numeric or a name, in lookup order
+pub const LEVEL_FIELDS: [&str; 3] = ["level", "lvl", "severity"];
+
+/// Field names holding the logger name (Pino/Bunyan `name`), in lookup order
+pub const LOGGER_NAME_FIELDS: [&str; 2] = ["name", "logger"];
+
+/// Default field names holding the trace id (OpenTelemetry `traceId`), in lookup order
+pub const TRACE_ID_FIELDS: [&str; 2] = ["trace_id", "traceId"];
+
+/// Field names holding the span id, in lookup order
+pub const SPAN_ID_FIELDS: [&str; 2] = ["span_id", "spanId"];
+
+/// Default field names holding a logged error object, in lookup order
+pub const ERROR_FIELDS: [&str; 3] = ["err", "error", "exception"];
+
+/// Field holding the 1-based source line number with `--line-numbers`
+pub const LINE_NUMBER_FIELD: &str = "_line";
+
+/// Field holding the content of a line that failed to parse, in placeholder logs
+pub const UNPARSED_FIELD: &str = "_unparsed";
+
+/// Field tagging each log with the file it came from when loading a directory
+pub const SOURCE_FIELD: &str = "_source";
+
+/// Time, level and message fields (original and normalized names), shown in every list row
+pub const STANDARD_FIELDS: [&str; 6] = ["time", "timestamp", "level", "lvl", "message", "msg"];
+
+/// Error logged as an object, e.g. Pino's `err: {type, message, stack}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorObject {
+    /// Field the object was found in
+    pub field: String,
+    /// `type` (Pino) or `name` (Bunyan, `Error.name`)
+    pub error_type: Option<String>,
+    pub message: Option<String>,
+    pub stack: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonLog {
     #[serde(flatten)]
-    pub fields: HashMap<String, serde_json::Value>,
+    /// Top-level fields in source order, so exports keep the original layout
+    pub fields: IndexMap<String, serde_json::Value>,
 }
 
 impl JsonLog {
-    pub fn new(fields: HashMap<String, serde_json::Value>) -> Self {
-        Self { fields }
+    pub fn new(fields: impl IntoIterator<Item = (String, serde_json::Value)>) -> Self {
+        Self {
+            fields: fields.into_iter().collect(),
+        }
+    }
+
+    /// Placeholder for a line that failed to parse (`--show-unparsed`)
+    /// Holds a preview of the line (or why it was skipped) and its line number
+    pub fn unparsed(line_number: usize, content: String) -> Self {
+        let mut log = Self::new([(
+            UNPARSED_FIELD.to_string(),
+            serde_json::Value::String(content),
+        )]);
+        log.set_line_number(line_number);
+        log
+    }
+
+    /// Whether this is a placeholder for an unparsable line
+    pub fn is_unparsed(&self) -> bool {
+        self.fields.contains_key(UNPARSED_FIELD)
     }
 
     pub fn get_field(&self, key: &str) -> Option<&serde_json::Value> {
         self.fields.get(key)
     }
 
+    /// Record the source line number in `LINE_NUMBER_FIELD`
+    pub fn set_line_number(&mut self, line_number: usize) {
+        self.fields.insert(
+            LINE_NUMBER_FIELD.to_string(),
+            serde_json::Value::from(line_number),
+        );
+    }
+
+    /// Source line number, if recorded with `--line-numbers`
+    pub fn line_number(&self) -> Option<u64> {
+        self.fields.get(LINE_NUMBER_FIELD).and_then(|v| v.as_u64())
+    }
+
+    /// Names of the fields other than time, level, message, the line number and
+    /// the source file, sorted
+    pub fn extra_field_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .fields
+            .keys()
+            .map(String::as_str)
+            .filter(|key| {
+                !STANDARD_FIELDS.contains(key) && *key != LINE_NUMBER_FIELD && *key != SOURCE_FIELD
+            })
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
     pub fn get_timestamp_ms(&self) -> Option<i64> {
         // Check normalized field name first, then original
         self.fields
@@ -77,6 +213,22 @@ impl JsonLog {
             .and_then(|v| v.as_str())
     }
 
+    /// Values of `fields` (dotted paths allowed) joined with `separator`, for
+    /// `--message-fields`; missing, null and empty fields are skipped.
+    /// Names match through `normalize_field_name`, so `msg` finds a stored `message`
+    pub fn get_message_from(&self, fields: &[String], separator: &str) -> Option<String> {
+        let parts: Vec<String> = fields
+            .iter()
+            .filter_map(|field| match self.get_normalized_path(field)? {
+                serde_json::Value::String(s) => Some(s),
+                serde_json::Value::Null => None,
+                other => Some(other.to_string()),
+            })
+            .filter(|part| !part.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(separator))
+    }
+
     pub fn get_level_raw(&self) -> Option<u64> {
         // Check normalized field name first, then original
         self.fields
@@ -85,13 +237,222 @@ impl JsonLog {
             .and_then(|v| v.as_u64())
     }
 
+    /// Level at a dotted `path` (`severity.number`, `log.level`) for `--level-field`:
+    /// a number, or a level name mapped with `LogLevel::from_name`. OpenTelemetry
+    /// severity numbers (`severity.number`, `severity_number`, `severityNumber`)
+    /// are mapped with `LogLevel::from_otel_severity`
+    pub fn get_level_raw_at(&self, path: &str) -> Option<u64> {
+        let value = self.get_path(path)?;
+        if is_otel_severity_path(path) {
+            return value
+                .as_u64()
+                .and_then(LogLevel::from_otel_severity)
+                .map(|level| level.as_u64());
+        }
+        value.as_u64().or_else(|| {
+            value
+                .as_str()
+                .and_then(LogLevel::from_name)
+                .map(|level| level.as_u64())
+        })
+    }
+
     pub fn get_level(&self) -> Option<LogLevel> {
         let level = self.get_level_raw()?;
         LogLevel::from_u64(level)
     }
 
+    /// Value at a dotted `path`: a top-level key of that name, or a walk through
+    /// nested objects (`severity.number`). Objects stored as JSON text, as in logs
+    /// read back from the database, are parsed on the way
+    pub fn get_path(&self, path: &str) -> Option<serde_json::Value> {
+        if let Some(value) = self.fields.get(path) {
+            return Some(value.clone());
+        }
+        let (head, rest) = path.split_once('.')?;
+        value_at_path(self.fields.get(head)?, rest)
+    }
+
+    /// Like `get_path`, falling back to the top-level key with the same normalized
+    /// name, as rows read back from the database use `message` and `time`
+    fn get_normalized_path(&self, path: &str) -> Option<serde_json::Value> {
+        self.get_path(path).or_else(|| {
+            let name = normalize_field_name(path);
+            self.fields
+                .iter()
+                .find(|(key, _)| normalize_field_name(key) == name)
+                .map(|(_, value)| value.clone())
+        })
+    }
+
+    /// Numeric level from the first of `LEVEL_FIELDS` holding a number or a level
+    /// name (`LogLevel::from_name`), for `--normalize-levels`
+    pub fn canonical_level(&self) -> Option<u64> {
+        LEVEL_FIELDS.iter().find_map(|field| {
+            let value = self.fields.get(*field)?;
+            value.as_u64().or_else(|| {
+                value
+                    .as_str()
+                    .and_then(LogLevel::from_name)
+                    .map(|level| level.as_u64())
+            })
+        })
+    }
+
+    /// Level mapped with `LogLevel::from_u64_lenient`, so custom numbers still get a level
+    pub fn get_level_lenient(&self) -> Option<LogLevel> {
+        self.get_level_raw().map(LogLevel::from_u64_lenient)
+    }
+
+    pub fn get_logger_name(&self) -> Option<&str> {
+        self.get_logger_name_entry().map(|(_, name)| name)
+    }
+
+    /// Field name and value of the logger name, for building filters
+    pub fn get_logger_name_entry(&self) -> Option<(&'static str, &str)> {
+        LOGGER_NAME_FIELDS.iter().find_map(|field| {
+            self.fields
+                .get(*field)
+                .and_then(|v| v.as_str())
+                .map(|name| (*field, name))
+        })
+    }
+
+    /// Field name and value of the trace id, looked up in `fields` order
+    /// Fields may be dotted paths (`dd.trace_id`), resolved with `get_path`
+    pub fn get_trace_id<'a>(&self, fields: &'a [String]) -> Option<(&'a str, String)> {
+        fields.iter().find_map(|field| match self.get_path(field)? {
+            serde_json::Value::String(id) => Some((field.as_str(), id)),
+            _ => None,
+        })
+    }
+
+    /// Span id (`span_id`/`spanId`)
+    pub fn get_span_id(&self) -> Option<&str> {
+        SPAN_ID_FIELDS
+            .iter()
+            .find_map(|field| self.fields.get(*field).and_then(|v| v.as_str()))
+    }
+
+    /// Source location from Bunyan's `src` field (`{"file", "line", "func"}`) as `file:line`
+    /// The file is shortened to its name; `src` read back from the database is JSON text
+    pub fn get_source_location(&self) -> Option<String> {
+        let src = match self.fields.get("src")? {
+            serde_json::Value::String(text) => serde_json::from_str(text).ok()?,
+            other => other.clone(),
+        };
+        let file = src.get("file")?.as_str()?;
+        let file = file.rsplit(['/', '\\']).next().unwrap_or(file);
+        match src.get("line").and_then(|line| line.as_u64()) {
+            Some(line) => Some(format!("{}:{}", file, line)),
+            None => Some(file.to_string()),
+        }
+    }
+
+    /// Error object from the first of `fields` shaped like one, looked up in order
+    /// The object needs a string `message` or `stack`; anything else, like a plain
+    /// `"error": "timeout"` string, is left to the generic rendering. Objects read
+    /// back from the database are JSON text.
+    pub fn get_error_object(&self, fields: &[String]) -> Option<ErrorObject> {
+        fields.iter().find_map(|field| {
+            let value = match self.fields.get(field)? {
+                serde_json::Value::String(text) => serde_json::from_str(text).ok()?,
+                other => other.clone(),
+            };
+            let text = |key: &str| value.get(key)?.as_str().map(str::to_string);
+            let error = ErrorObject {
+                field: field.clone(),
+                error_type: text("type").or_else(|| text("name")),
+                message: text("message"),
+                stack: text("stack"),
+            };
+            (error.message.is_some() || error.stack.is_some()).then_some(error)
+        })
+    }
+
+    /// Timestamp of the log, keeping whatever sub-second precision the source has
+    ///
+    /// Integers are epoch milliseconds, or micro/nanoseconds when too large to be
+    /// milliseconds; floats are milliseconds with a fractional part; strings are
+    /// RFC 3339.
     pub fn timestamp(&self) -> Option<jiff::Timestamp> {
-        let ms = self.get_timestamp_ms()?;
-        jiff::Timestamp::from_millisecond(ms).ok()
+        let value = self
+            .fields
+            .get("time")
+            .or_else(|| self.fields.get("timestamp"))?;
+        match value {
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(v) => timestamp_from_integer(v),
+                None => timestamp_from_float_ms(n.as_f64()?),
+            },
+            serde_json::Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Numeric time too far out for `timestamp()` to represent, like a bogus
+    /// `time: 1e30`; callers show the raw number with a marker instead of no time
+    pub fn out_of_range_timestamp(&self) -> Option<&serde_json::Number> {
+        let value = self
+            .fields
+            .get("time")
+            .or_else(|| self.fields.get("timestamp"))?;
+        match value {
+            serde_json::Value::Number(n) if self.timestamp().is_none() => Some(n),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `path` names an OpenTelemetry severity number, however it is cased or separated
+fn is_otel_severity_path(path: &str) -> bool {
+    path.replace(['.', '_'], "")
+        .eq_ignore_ascii_case("severitynumber")
+}
+
+/// Value at a dotted `path` inside `value`, like `JsonLog::get_path`
+fn value_at_path(value: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(target) = map.get(path) {
+                return Some(target.clone());
+            }
+            let (head, rest) = path.split_once('.')?;
+            value_at_path(map.get(head)?, rest)
+        }
+        serde_json::Value::String(text) => {
+            let parsed: serde_json::Value = serde_json::from_str(text).ok()?;
+            match parsed {
+                serde_json::Value::Object(_) => value_at_path(&parsed, path),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Integer epoch timestamps at or above these are micro/nanoseconds (year 5138+ in ms)
+/// `sql::epoch_ms_expr` scales stored times with the same thresholds
+pub const MICROSECOND_THRESHOLD: i64 = 100_000_000_000_000;
+pub const NANOSECOND_THRESHOLD: i64 = 100_000_000_000_000_000;
+
+fn timestamp_from_integer(value: i64) -> Option<jiff::Timestamp> {
+    let magnitude = value.unsigned_abs();
+    if magnitude >= NANOSECOND_THRESHOLD as u64 {
+        jiff::Timestamp::from_nanosecond(value as i128).ok()
+    } else if magnitude >= MICROSECOND_THRESHOLD as u64 {
+        jiff::Timestamp::from_microsecond(value).ok()
+    } else {
+        jiff::Timestamp::from_millisecond(value).ok()
+    }
+}
+
+fn timestamp_from_float_ms(ms: f64) -> Option<jiff::Timestamp> {
+    if !ms.is_finite() {
+        return None;
     }
+    // Scale the fraction on its own so whole milliseconds stay exact
+    let whole = ms.floor();
+    let nanos = whole as i128 * 1_000_000 + ((ms - whole) * 1_000_000.0).round() as i128;
+    jiff::Timestamp::from_nanosecond(nanos).ok()
 }
@@ -1,8 +1,18 @@
 use crate::error::{LogViewerError, Result};
 use crate::ingestion::models::JsonLog;
 use rootcause::prelude::{Report, ResultExt};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::Value;
 
 pub fn parse_json_line(line: &str) -> Result<JsonLog> {
+    parse_json_line_with_duplicates(line).map(|(log, _)| log)
+}
+
+/// Parse a JSON line, also returning the top-level keys that appeared more than once
+///
+/// Repeated keys are kept in order of appearance as `key`, `key_2`, `key_3`, ...
+/// instead of silently keeping only the last value.
+pub fn parse_json_line_with_duplicates(line: &str) -> Result<(JsonLog, Vec<String>)> {
     let trimmed = line.trim();
 
     if trimmed.is_empty() {
@@ -11,18 +21,70 @@ pub fn parse_json_line(line: &str) -> Result<JsonLog> {
         )));
     }
 
-    let fields: std::collections::HashMap<String, serde_json::Value> =
-        serde_json::from_str(trimmed)
-            .map_err(|e| LogViewerError::from(e))
-            .attach("Failed to parse JSON line")?;
+    let object: DedupedObject = serde_json::from_str(trimmed)
+        .map_err(|e| LogViewerError::from(e))
+        .attach("Failed to parse JSON line")?;
 
-    if fields.is_empty() {
+    if object.fields.is_empty() {
         return Err(Report::new(LogViewerError::InvalidLogFormat(
             "Empty JSON object".to_string(),
         )));
     }
 
-    Ok(JsonLog::new(fields))
+    Ok((JsonLog::new(object.fields), object.duplicates))
+}
+
+/// Top-level JSON object that renames repeated keys instead of overwriting them
+struct DedupedObject {
+    fields: IndexMap<String, Value>,
+    duplicates: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for DedupedObject {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(DedupedObjectVisitor)
+    }
+}
+
+struct DedupedObjectVisitor;
+
+impl<'de> Visitor<'de> for DedupedObjectVisitor {
+    type Value = DedupedObject;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = IndexMap::new();
+        let mut duplicates = Vec::new();
+
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            if !fields.contains_key(&key) {
+                fields.insert(key, value);
+                continue;
+            }
+
+            let mut suffix = 2;
+            let mut renamed = format!("{}_{}", key, suffix);
+            while fields.contains_key(&renamed) {
+                suffix += 1;
+                renamed = format!("{}_{}", key, suffix);
+            }
+            fields.insert(renamed, value);
+            if !duplicates.contains(&key) {
+                duplicates.push(key);
+            }
+        }
+
+        Ok(DedupedObject { fields, duplicates })
+    }
 }
 
 #[cfg(test)]
@@ -55,6 +117,245 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_logger_name() {
+        let log = parse_json_line(r#"{"level":30,"name":"api","msg":"hi"}"#).unwrap();
+        assert_eq!(log.get_logger_name(), Some("api"));
+        assert_eq!(log.get_logger_name_entry(), Some(("name", "api")));
+
+        let log = parse_json_line(r#"{"level":30,"logger":"db.pool","msg":"hi"}"#).unwrap();
+        assert_eq!(log.get_logger_name_entry(), Some(("logger", "db.pool")));
+
+        let log = parse_json_line(r#"{"level":30,"name":42,"msg":"hi"}"#).unwrap();
+        assert_eq!(log.get_logger_name(), None);
+    }
+
+    #[test]
+    fn test_error_object() {
+        use crate::ingestion::models::{ERROR_FIELDS, ErrorObject};
+
+        let fields: Vec<String> = ERROR_FIELDS.iter().map(|f| f.to_string()).collect();
+        let log = parse_json_line(
+            r#"{"level":50,"msg":"failed","err":{"type":"TypeError","message":"x is undefined","stack":"TypeError: x is undefined\n    at f (app.js:1:2)"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            log.get_error_object(&fields),
+            Some(ErrorObject {
+                field: "err".to_string(),
+                error_type: Some("TypeError".to_string()),
+                message: Some("x is undefined".to_string()),
+                stack: Some("TypeError: x is undefined\n    at f (app.js:1:2)".to_string()),
+            })
+        );
+
+        // Bunyan's `name`, and JSON text as read back from the database
+        let log =
+            parse_json_line(r#"{"level":50,"error":"{\"name\":\"Error\",\"message\":\"boom\"}"}"#)
+                .unwrap();
+        let error = log.get_error_object(&fields).unwrap();
+        assert_eq!(error.field, "error");
+        assert_eq!(error.error_type.as_deref(), Some("Error"));
+        assert_eq!(error.stack, None);
+
+        // Other shapes are not error objects
+        for line in [
+            r#"{"level":50,"error":"timeout"}"#,
+            r#"{"level":50,"err":{"code":42}}"#,
+            r#"{"level":50,"exception":["a"]}"#,
+        ] {
+            let log = parse_json_line(line).unwrap();
+            assert_eq!(log.get_error_object(&fields), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_trace_id() {
+        use crate::ingestion::models::TRACE_ID_FIELDS;
+
+        let fields: Vec<String> = TRACE_ID_FIELDS.iter().map(|f| f.to_string()).collect();
+        let log = parse_json_line(
+            r#"{"level":30,"traceId":"4bf92f3577b34da6a3ce929d0e0e4736","spanId":"00f067aa0ba902b7"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            log.get_trace_id(&fields),
+            Some(("traceId", "4bf92f3577b34da6a3ce929d0e0e4736".to_string()))
+        );
+        assert_eq!(log.get_span_id(), Some("00f067aa0ba902b7"));
+
+        // Only the configured fields are read
+        let log = parse_json_line(r#"{"level":30,"x_trace":"123","msg":"hi"}"#).unwrap();
+        assert_eq!(log.get_trace_id(&fields), None);
+        let custom = vec!["x_trace".to_string()];
+        assert_eq!(
+            log.get_trace_id(&custom),
+            Some(("x_trace", "123".to_string()))
+        );
+
+        // Dotted paths reach nested ids
+        let log = parse_json_line(r#"{"level":30,"dd":{"trace_id":"456"}}"#).unwrap();
+        let custom = vec!["dd.trace_id".to_string()];
+        assert_eq!(
+            log.get_trace_id(&custom),
+            Some(("dd.trace_id", "456".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_timestamp_precision() {
+        let nanos = |line: &str| {
+            parse_json_line(line)
+                .unwrap()
+                .timestamp()
+                .map(|ts| ts.as_nanosecond())
+        };
+
+        assert_eq!(
+            nanos(r#"{"time":1531171074631}"#),
+            Some(1_531_171_074_631_000_000)
+        );
+        assert_eq!(
+            nanos(r#"{"time":1531171074631042}"#),
+            Some(1_531_171_074_631_042_000)
+        );
+        assert_eq!(
+            nanos(r#"{"time":1531171074631042517}"#),
+            Some(1_531_171_074_631_042_517)
+        );
+        assert_eq!(
+            nanos(r#"{"time":1531171074631.25}"#),
+            Some(1_531_171_074_631_250_000)
+        );
+        assert_eq!(
+            nanos(r#"{"timestamp":"2018-07-09T21:17:54.631042517Z"}"#),
+            Some(1_531_171_074_631_042_517)
+        );
+        assert_eq!(nanos(r#"{"time":"yesterday"}"#), None);
+    }
+
+    #[test]
+    fn test_get_path() {
+        let log = parse_json_line(
+            r#"{"severity":{"number":17,"text":"INFO"},"log.level":"warn","log":{"level":"info"}}"#,
+        )
+        .unwrap();
+        assert_eq!(log.get_path("severity.number"), Some(serde_json::json!(17)));
+        // A top-level key spelled with a dot wins over the nested path
+        assert_eq!(log.get_path("log.level"), Some(serde_json::json!("warn")));
+        assert_eq!(log.get_path("severity.missing"), None);
+        assert_eq!(log.get_path("severity.text.more"), None);
+        // OpenTelemetry severity 17 is ERROR
+        assert_eq!(log.get_level_raw_at("severity.number"), Some(50));
+        assert_eq!(log.get_level_raw_at("log.level"), Some(40));
+    }
+
+    #[test]
+    fn test_otel_severity_level() {
+        let level = |line: &str, path: &str| parse_json_line(line).unwrap().get_level_raw_at(path);
+
+        assert_eq!(
+            level(r#"{"severity_number":1}"#, "severity_number"),
+            Some(10)
+        );
+        assert_eq!(level(r#"{"severityNumber":9}"#, "severityNumber"), Some(30));
+        assert_eq!(
+            level(r#"{"SeverityNumber":14}"#, "SeverityNumber"),
+            Some(40)
+        );
+        assert_eq!(
+            level(r#"{"severity":{"number":24}}"#, "severity.number"),
+            Some(60)
+        );
+        // Outside 1-24 isn't a severity number
+        assert_eq!(level(r#"{"severity_number":50}"#, "severity_number"), None);
+        // Other fields keep the Pino scale
+        assert_eq!(level(r#"{"lvl":{"n":17}}"#, "lvl.n"), Some(17));
+    }
+
+    #[test]
+    fn test_get_message_from() {
+        let log = parse_json_line(
+            r#"{"context":{"job":"sync"},"msg":"failed","detail":"","code":7,"extra":null}"#,
+        )
+        .unwrap();
+        let fields = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            log.get_message_from(&fields(&["context.job", "msg", "code"]), " | "),
+            Some("sync | failed | 7".to_string())
+        );
+        // Missing, empty and null fields leave no stray separators
+        assert_eq!(
+            log.get_message_from(&fields(&["missing", "msg", "detail", "extra"]), " | "),
+            Some("failed".to_string())
+        );
+        assert_eq!(
+            log.get_message_from(&fields(&["detail", "missing"]), " | "),
+            None
+        );
+
+        // Rows read back from the database hold `message`, not `msg`
+        let stored = parse_json_line(r#"{"context":"db","message":"failed"}"#).unwrap();
+        assert_eq!(
+            stored.get_message_from(&fields(&["context", "msg"]), " | "),
+            Some("db | failed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_timestamp() {
+        // Past i64, so read as float milliseconds far beyond year 9999
+        let log = parse_json_line(r#"{"time":99999999999999999999,"msg":"bogus"}"#).unwrap();
+        assert_eq!(log.timestamp(), None);
+        assert_eq!(
+            log.out_of_range_timestamp().map(|n| n.to_string()),
+            Some("1e20".to_string())
+        );
+
+        let log = parse_json_line(r#"{"time":1e300}"#).unwrap();
+        assert!(log.out_of_range_timestamp().is_some());
+
+        // In range, or not a number at all: nothing to flag
+        let log = parse_json_line(r#"{"time":1531171074631}"#).unwrap();
+        assert_eq!(log.out_of_range_timestamp(), None);
+        let log = parse_json_line(r#"{"time":"yesterday"}"#).unwrap();
+        assert_eq!(log.out_of_range_timestamp(), None);
+    }
+
+    #[test]
+    fn test_timestamp_offsets() {
+        let timestamp = |line: &str| parse_json_line(line).unwrap().timestamp();
+
+        let utc = timestamp(r#"{"time":"2018-07-09T18:37:54Z"}"#);
+        assert!(utc.is_some());
+        assert_eq!(timestamp(r#"{"time":"2018-07-09T20:37:54+02:00"}"#), utc);
+        assert_eq!(timestamp(r#"{"time":"2018-07-09T13:37:54-05:00"}"#), utc);
+        assert_eq!(timestamp(r#"{"time":"2018-07-09T18:37:54+00:00"}"#), utc);
+    }
+
+    #[test]
+    fn test_parse_duplicate_keys() {
+        let (log, duplicates) = parse_json_line_with_duplicates(
+            r#"{"msg":"a","err":"first","err":"second","err":"third"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(duplicates, vec!["err"]);
+        assert_eq!(log.get_field("err").and_then(|v| v.as_str()), Some("first"));
+        assert_eq!(
+            log.get_field("err_2").and_then(|v| v.as_str()),
+            Some("second")
+        );
+        assert_eq!(
+            log.get_field("err_3").and_then(|v| v.as_str()),
+            Some("third")
+        );
+
+        let (_, duplicates) = parse_json_line_with_duplicates(r#"{"msg":"a"}"#).unwrap();
+        assert!(duplicates.is_empty());
+    }
+
     #[test]
     fn test_parse_empty_line() {
         let line = "";
@@ -82,4 +383,50 @@ mod tests {
         assert_eq!(LogLevel::Info.as_u64(), 30);
         assert_eq!(LogLevel::Error.as_str(), "ERROR");
     }
+
+    #[test]
+    fn test_log_level_lenient() {
+        use crate::ingestion::LogLevel;
+
+        assert_eq!(LogLevel::from_u64(35), None);
+        assert_eq!(LogLevel::from_u64_lenient(35), LogLevel::Info);
+        assert_eq!(LogLevel::from_u64_lenient(49), LogLevel::Warn);
+        assert_eq!(LogLevel::from_u64_lenient(5), LogLevel::Trace);
+        assert_eq!(LogLevel::from_u64_lenient(100), LogLevel::Fatal);
+        // Exact values keep their level
+        for level in [10, 20, 30, 40, 50, 60] {
+            assert_eq!(
+                Some(LogLevel::from_u64_lenient(level)),
+                LogLevel::from_u64(level)
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_level() {
+        use crate::ingestion::LogLevel;
+
+        assert_eq!(LogLevel::from_name("WARNING"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::from_name("verbose"), None);
+
+        let level = |line: &str| parse_json_line(line).unwrap().canonical_level();
+        assert_eq!(level(r#"{"level":35}"#), Some(35));
+        assert_eq!(level(r#"{"lvl":"error"}"#), Some(50));
+        assert_eq!(level(r#"{"severity":"Warn","msg":"x"}"#), Some(40));
+        // A numeric level wins over a severity name
+        assert_eq!(level(r#"{"level":20,"severity":"error"}"#), Some(20));
+        assert_eq!(level(r#"{"severity":"verbose"}"#), None);
+        assert_eq!(level(r#"{"msg":"x"}"#), None);
+    }
+
+    #[test]
+    fn test_parse_keeps_field_order() {
+        let log = parse_json_line(r#"{"zone":"eu","msg":"hi","level":30,"a":1}"#).unwrap();
+        let names: Vec<&str> = log.fields.keys().map(String::as_str).collect();
+        assert_eq!(names, ["zone", "msg", "level", "a"]);
+        assert_eq!(
+            serde_json::to_string(&log.fields).unwrap(),
+            r#"{"zone":"eu","msg":"hi","level":30,"a":1}"#
+        );
+    }
 }
@@ -0,0 +1,464 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::{JsonLog, LogLevel};
+use crate::ingestion::parser::parse_json_line_with_duplicates;
+use crate::ingestion::reader::DEFAULT_MAX_LINE_BYTES;
+use crate::ingestion::text::{parse_logfmt_line, parse_plain_line};
+use rootcause::prelude::{Report, ResultExt};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Container log line formats that wrap the JSON payload, plus the non-JSON line
+/// formats chosen with `--profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinePrefix {
+    /// Lines are bare JSON objects
+    #[default]
+    None,
+    /// Detect Docker or CRI wrapping per line, falling back to bare JSON
+    Auto,
+    /// Docker json-file driver: `{"log":"<payload>\n","stream":"stdout","time":"<rfc3339>"}`
+    Docker,
+    /// CRI (containerd, CRI-O, Kubernetes): `<rfc3339> <stream> <P|F> <payload>`
+    Cri,
+    /// journalctl `-o json`: `MESSAGE`, syslog `PRIORITY` and `__REALTIME_TIMESTAMP` (µs)
+    Journald,
+    /// logfmt `key=value` pairs (`parse_logfmt_line`)
+    Logfmt,
+    /// Plain text, each line a message (`parse_plain_line`)
+    Plain,
+}
+
+impl LinePrefix {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinePrefix::None => "none",
+            LinePrefix::Auto => "auto",
+            LinePrefix::Docker => "docker",
+            LinePrefix::Cri => "cri",
+            LinePrefix::Journald => "journald",
+            LinePrefix::Logfmt => "logfmt",
+            LinePrefix::Plain => "plain",
+        }
+    }
+}
+
+impl std::str::FromStr for LinePrefix {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(LinePrefix::None),
+            "auto" => Ok(LinePrefix::Auto),
+            "docker" => Ok(LinePrefix::Docker),
+            "cri" | "k8s" => Ok(LinePrefix::Cri),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown line prefix '{}' (expected none, auto, docker or cri)",
+                s
+            )))),
+        }
+    }
+}
+
+/// Parse a line that may carry a container runtime prefix
+///
+/// The prefix is stripped and the remaining payload is handed to `parse_json_line`.
+/// If the inner JSON has no `time`/`timestamp` field, the outer timestamp is merged
+/// in as `time` (milliseconds since epoch).
+pub fn parse_prefixed_line(line: &str, prefix: LinePrefix) -> Result<JsonLog> {
+    parse_prefixed_line_with_duplicates(line, prefix).map(|(log, _)| log)
+}
+
+/// Like `parse_prefixed_line`, also returning the payload's repeated keys
+/// (see `parse_json_line_with_duplicates`)
+pub fn parse_prefixed_line_with_duplicates(
+    line: &str,
+    prefix: LinePrefix,
+) -> Result<(JsonLog, Vec<String>)> {
+    let trimmed = line.trim();
+
+    match prefix {
+        LinePrefix::None => parse_json_line_with_duplicates(trimmed),
+        LinePrefix::Docker => parse_docker_line(trimmed),
+        LinePrefix::Cri => parse_cri_line(trimmed),
+        LinePrefix::Journald => parse_journald_line(trimmed),
+        LinePrefix::Logfmt => parse_logfmt_line(trimmed),
+        LinePrefix::Plain => parse_plain_line(trimmed),
+        LinePrefix::Auto => {
+            if is_docker_line(trimmed) {
+                parse_docker_line(trimmed)
+            } else if trimmed.starts_with('{') {
+                parse_json_line_with_duplicates(trimmed)
+            } else {
+                parse_cri_line(trimmed)
+            }
+        }
+    }
+}
+
+/// Cheap check for the Docker json-file wrapper without fully parsing the line
+pub fn is_docker_line(line: &str) -> bool {
+    line.starts_with('{') && line.contains("\"log\":") && line.contains("\"stream\":")
+}
+
+fn parse_docker_line(line: &str) -> Result<(JsonLog, Vec<String>)> {
+    let outer: serde_json::Map<String, Value> = serde_json::from_str(line)
+        .map_err(LogViewerError::from)
+        .attach("Failed to parse Docker json-file wrapper")?;
+
+    let payload = outer.get("log").and_then(|v| v.as_str()).ok_or_else(|| {
+        Report::new(LogViewerError::InvalidLogFormat(
+            "Docker log line has no string 'log' field".to_string(),
+        ))
+    })?;
+
+    let (mut log, duplicates) =
+        parse_json_line_with_duplicates(payload).attach("Failed to parse Docker log payload")?;
+
+    if let Some(time) = outer.get("time").and_then(|v| v.as_str()) {
+        merge_outer_timestamp(&mut log, time);
+    }
+
+    Ok((log, duplicates))
+}
+
+/// Map journald's fields to the ones Pino uses: `MESSAGE` to `msg`, the syslog
+/// `PRIORITY` to a level and `__REALTIME_TIMESTAMP` to `time` in milliseconds
+/// journald writes the numbers as strings; values that don't parse are left alone
+fn parse_journald_line(line: &str) -> Result<(JsonLog, Vec<String>)> {
+    let (log, duplicates) =
+        parse_json_line_with_duplicates(line).attach("Failed to parse journald entry")?;
+
+    let number = |value: &Value| {
+        value
+            .as_u64()
+            .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+    };
+    let mut standard = Vec::new();
+    let mut rest = Vec::with_capacity(log.fields.len());
+    for (key, value) in log.fields {
+        let converted = match key.as_str() {
+            "__REALTIME_TIMESTAMP" => {
+                number(&value).map(|micros| ("time", Value::from(micros / 1000)))
+            }
+            "PRIORITY" => number(&value).map(|priority| {
+                (
+                    "level",
+                    Value::from(LogLevel::from_syslog(priority).as_u64()),
+                )
+            }),
+            "MESSAGE" => Some(("msg", value.clone())),
+            _ => None,
+        };
+        match converted {
+            Some((name, converted)) => standard.push((name.to_string(), converted)),
+            None => rest.push((key, value)),
+        }
+    }
+    standard.extend(rest);
+
+    Ok((JsonLog::new(standard), duplicates))
+}
+
+/// Split a CRI line into its time, stream, tag and payload
+fn split_cri_line(line: &str) -> Option<(&str, &str, &str, &str)> {
+    let mut parts = line.splitn(4, ' ');
+    Some((parts.next()?, parts.next()?, parts.next()?, parts.next()?))
+}
+
+fn parse_cri_line(line: &str) -> Result<(JsonLog, Vec<String>)> {
+    let Some((time, stream, _tag, payload)) = split_cri_line(line) else {
+        return Err(Report::new(LogViewerError::InvalidLogFormat(
+            "Line does not match CRI format '<time> <stream> <P|F> <payload>'".to_string(),
+        )));
+    };
+
+    if stream != "stdout" && stream != "stderr" {
+        return Err(Report::new(LogViewerError::InvalidLogFormat(format!(
+            "Unknown CRI stream '{}'",
+            stream
+        ))));
+    }
+
+    let (mut log, duplicates) =
+        parse_json_line_with_duplicates(payload).attach("Failed to parse CRI log payload")?;
+    merge_outer_timestamp(&mut log, time);
+
+    Ok((log, duplicates))
+}
+
+/// Joins CRI partial (`P`) lines with the full (`F`) line that ends them
+///
+/// CRI runtimes split long lines into `P` chunks followed by a final `F` chunk;
+/// only the concatenation of their payloads is a complete log line. Chunks are
+/// kept per stream, since stdout and stderr can interleave. A line whose chunks
+/// add up to more than `max_bytes` is dropped instead of buffered without end.
+#[derive(Debug)]
+pub struct CriPartials {
+    /// Time of the first chunk and the payload gathered so far, by stream
+    pending: HashMap<String, (String, String)>,
+    /// Streams whose line outgrew `max_bytes`; their chunks are dropped until the `F` line
+    skipping: HashSet<String>,
+    max_bytes: usize,
+}
+
+impl Default for CriPartials {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LINE_BYTES)
+    }
+}
+
+impl CriPartials {
+    /// Join lines of up to `max_bytes` of payload (see `--max-line-bytes`)
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            pending: HashMap::new(),
+            skipping: HashSet::new(),
+            max_bytes,
+        }
+    }
+
+    /// Feed one line read with `prefix`
+    /// Returns the line to parse, with any buffered `P` chunks joined into it,
+    /// or `None` while the line is a `P` chunk held back for the rest.
+    /// After a line outgrows `max_bytes`, only its `F` chunk is returned, which
+    /// fails to parse like any other broken line
+    pub fn join<'a>(&mut self, line: &'a str, prefix: LinePrefix) -> Option<Cow<'a, str>> {
+        let trimmed = line.trim_start().trim_end_matches(['\r', '\n']);
+        let is_cri = match prefix {
+            LinePrefix::Cri => true,
+            LinePrefix::Auto => !is_docker_line(trimmed) && !trimmed.starts_with('{'),
+            _ => false,
+        };
+        // Other text that happens to have a `P` as its third word is not held back
+        let Some((time, stream, tag, payload)) = split_cri_line(trimmed)
+            .filter(|(time, ..)| is_cri && time.parse::<jiff::Timestamp>().is_ok())
+        else {
+            return Some(Cow::Borrowed(line));
+        };
+
+        if tag == "P" {
+            if self.skipping.contains(stream) {
+                return None;
+            }
+            let (_, joined) = self
+                .pending
+                .entry(stream.to_string())
+                .or_insert_with(|| (time.to_string(), String::new()));
+            if joined.len() + payload.len() > self.max_bytes {
+                tracing::warn!(
+                    "CRI {} line split into chunks is longer than {} bytes, skipped",
+                    stream,
+                    self.max_bytes
+                );
+                self.pending.remove(stream);
+                self.skipping.insert(stream.to_string());
+            } else {
+                joined.push_str(payload);
+            }
+            return None;
+        }
+        if self.skipping.remove(stream) {
+            return Some(Cow::Borrowed(line));
+        }
+
+        match self.pending.remove(stream) {
+            Some((first_time, mut joined)) => {
+                joined.push_str(payload);
+                Some(Cow::Owned(format!(
+                    "{} {} F {}",
+                    first_time, stream, joined
+                )))
+            }
+            None => Some(Cow::Borrowed(line)),
+        }
+    }
+
+    /// Whether `P` chunks are waiting for their `F` line
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Take the chunks still waiting, each stream's joined into one `F` line, for
+    /// when no `F` line will come (the end of a file nobody follows)
+    pub fn take_pending(&mut self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .pending
+            .drain()
+            .map(|(stream, (time, joined))| format!("{} {} F {}", time, stream, joined))
+            .collect();
+        lines.sort();
+        self.skipping.clear();
+        lines
+    }
+
+    /// Drop buffered chunks, e.g. when the source starts over
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.skipping.clear();
+    }
+}
+
+/// Add the runtime timestamp as `time` unless the payload already has one
+fn merge_outer_timestamp(log: &mut JsonLog, time: &str) {
+    if log.fields.contains_key("time") || log.fields.contains_key("timestamp") {
+        return;
+    }
+
+    match time.parse::<jiff::Timestamp>() {
+        Ok(ts) => {
+            log.fields
+                .insert("time".to_string(), Value::from(ts.as_millisecond()));
+        }
+        Err(e) => {
+            tracing::warn!("Could not parse container timestamp '{}': {}", time, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_docker_line() {
+        let line = r#"{"log":"{\"level\":30,\"msg\":\"hello\"}\n","stream":"stdout","time":"2023-01-01T00:00:00.123456789Z"}"#;
+        let log = parse_prefixed_line(line, LinePrefix::Docker).unwrap();
+
+        assert_eq!(log.get_message(), Some("hello"));
+        assert_eq!(log.get_level_raw(), Some(30));
+        assert_eq!(log.get_timestamp_ms(), Some(1672531200123));
+    }
+
+    #[test]
+    fn test_parse_cri_line() {
+        let line = r#"2023-01-01T00:00:00Z stdout F {"level":50,"msg":"boom"}"#;
+        let log = parse_prefixed_line(line, LinePrefix::Cri).unwrap();
+
+        assert_eq!(log.get_message(), Some("boom"));
+        assert_eq!(log.get_level_raw(), Some(50));
+        assert_eq!(log.get_timestamp_ms(), Some(1672531200000));
+    }
+
+    #[test]
+    fn test_inner_timestamp_wins() {
+        let line = r#"2023-01-01T00:00:00Z stderr F {"level":30,"time":1531171074631,"msg":"hi"}"#;
+        let log = parse_prefixed_line(line, LinePrefix::Cri).unwrap();
+
+        assert_eq!(log.get_timestamp_ms(), Some(1531171074631));
+    }
+
+    #[test]
+    fn test_auto_detect() {
+        let docker =
+            r#"{"log":"{\"msg\":\"a\"}\n","stream":"stderr","time":"2023-01-01T00:00:00Z"}"#;
+        let cri = r#"2023-01-01T00:00:00Z stdout P {"msg":"b"}"#;
+        let plain = r#"{"level":30,"msg":"c"}"#;
+
+        assert_eq!(
+            parse_prefixed_line(docker, LinePrefix::Auto)
+                .unwrap()
+                .get_message(),
+            Some("a")
+        );
+        assert_eq!(
+            parse_prefixed_line(cri, LinePrefix::Auto)
+                .unwrap()
+                .get_message(),
+            Some("b")
+        );
+        assert_eq!(
+            parse_prefixed_line(plain, LinePrefix::Auto)
+                .unwrap()
+                .get_message(),
+            Some("c")
+        );
+    }
+
+    #[test]
+    fn test_join_cri_partials() {
+        let mut partials = CriPartials::default();
+        let lines = [
+            r#"2023-01-01T00:00:00Z stdout P {"level":30,"#,
+            r#"2023-01-01T00:00:01Z stderr F {"msg":"other"}"#,
+            r#"2023-01-01T00:00:02Z stdout P "msg":"split "#,
+            r#"2023-01-01T00:00:03Z stdout F line"}"#,
+        ];
+
+        let joined: Vec<String> = lines
+            .iter()
+            .filter_map(|line| partials.join(line, LinePrefix::Cri))
+            .map(Cow::into_owned)
+            .collect();
+        assert!(!partials.has_pending());
+        assert_eq!(joined.len(), 2);
+        assert_eq!(
+            parse_prefixed_line(&joined[0], LinePrefix::Cri)
+                .unwrap()
+                .get_message(),
+            Some("other")
+        );
+        let log = parse_prefixed_line(&joined[1], LinePrefix::Cri).unwrap();
+        assert_eq!(log.get_message(), Some("split line"));
+        assert_eq!(log.get_level_raw(), Some(30));
+        // The time of the first chunk is kept
+        assert_eq!(log.get_timestamp_ms(), Some(1672531200000));
+
+        // Other formats pass through untouched
+        let plain = r#"{"msg":"P"}"#;
+        assert_eq!(
+            partials.join(plain, LinePrefix::Auto).as_deref(),
+            Some(plain)
+        );
+        // So does text without a CRI timestamp in front
+        let text = "foo bar P baz";
+        assert_eq!(partials.join(text, LinePrefix::Auto).as_deref(), Some(text));
+        assert_eq!(partials.join(text, LinePrefix::Cri).as_deref(), Some(text));
+    }
+
+    #[test]
+    fn test_join_cri_partials_limit() {
+        let mut partials = CriPartials::new(8);
+        let join = |partials: &mut CriPartials, line: &'static str| {
+            partials.join(line, LinePrefix::Cri).map(Cow::into_owned)
+        };
+
+        assert!(join(&mut partials, "2023-01-01T00:00:00Z stdout P 12345").is_none());
+        assert!(join(&mut partials, "2023-01-01T00:00:00Z stdout P 6789").is_none());
+        // Nothing more is buffered for the oversized line
+        assert!(!partials.has_pending());
+        assert!(join(&mut partials, "2023-01-01T00:00:00Z stdout P 0").is_none());
+        assert!(!partials.has_pending());
+        // Its F chunk comes back alone, and the next line is joined again
+        assert_eq!(
+            join(&mut partials, "2023-01-01T00:00:00Z stdout F end").as_deref(),
+            Some("2023-01-01T00:00:00Z stdout F end")
+        );
+        assert!(join(&mut partials, r#"2023-01-01T00:00:01Z stdout P {"a":"#).is_none());
+        assert_eq!(
+            join(&mut partials, "2023-01-01T00:00:01Z stdout F 1}").as_deref(),
+            Some(r#"2023-01-01T00:00:01Z stdout F {"a":1}"#)
+        );
+    }
+
+    #[test]
+    fn test_parse_journald_line() {
+        let line = r#"{"__CURSOR":"s=1","__REALTIME_TIMESTAMP":"1705314600123456","PRIORITY":"3","_HOSTNAME":"web-1","SYSLOG_IDENTIFIER":"nginx","MESSAGE":"upstream timed out"}"#;
+        let log = parse_prefixed_line(line, LinePrefix::Journald).unwrap();
+
+        assert_eq!(log.get_message(), Some("upstream timed out"));
+        assert_eq!(log.get_level(), Some(LogLevel::Error));
+        assert_eq!(log.get_timestamp_ms(), Some(1705314600123));
+        assert_eq!(
+            log.get_field("SYSLOG_IDENTIFIER").and_then(|v| v.as_str()),
+            Some("nginx")
+        );
+        assert!(log.get_field("PRIORITY").is_none());
+    }
+
+    #[test]
+    fn test_invalid_cri_line() {
+        assert!(parse_prefixed_line("not a log line", LinePrefix::Cri).is_err());
+        assert!(parse_prefixed_line("2023-01-01T00:00:00Z weird F {}", LinePrefix::Cri).is_err());
+    }
+}
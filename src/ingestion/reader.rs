@@ -1,37 +1,144 @@
 use crate::error::{LogViewerError, Result};
 use crate::ingestion::models::JsonLog;
-use crate::ingestion::parser::parse_json_line;
+use crate::ingestion::prefix::{CriPartials, LinePrefix, parse_prefixed_line_with_duplicates};
+use flate2::read::MultiGzDecoder;
 use rootcause::prelude::{Report, ResultExt};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
+/// Longest line read by default (16 MiB); longer lines are skipped
+pub const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
 pub struct LogFileReader {
-    reader: BufReader<File>,
+    reader: BufReader<Box<dyn Read>>,
     line_number: usize,
+    /// Bytes consumed so far, where a follower should pick up
+    /// (decompressed bytes for gzip files)
+    position: u64,
+    line_prefix: LinePrefix,
+    /// CRI `P` chunks waiting for their `F` line
+    partials: CriPartials,
+    /// Bytes and lines of the buffered `P` chunks, left for a follower to read again
+    partial_bytes: u64,
+    partial_lines: usize,
+    /// Lines longer than this (newline excluded) are reported and skipped
+    max_line_bytes: usize,
+    /// Return placeholder logs for lines that fail to parse instead of errors
+    unparsed_placeholders: bool,
 }
 
 impl LogFileReader {
+    /// Open a log file, decompressing it on the fly if it ends in `.gz`
     pub fn new(path: impl AsRef<Path>) -> Result<Self> {
-        let file = File::open(path.as_ref())
-            .map_err(|e| LogViewerError::from(e))
-            .attach("Failed to open log file")?;
         Ok(Self {
-            reader: BufReader::new(file),
+            reader: BufReader::new(open_source(path.as_ref())?),
             line_number: 0,
+            position: 0,
+            line_prefix: LinePrefix::None,
+            partials: CriPartials::default(),
+            partial_bytes: 0,
+            partial_lines: 0,
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            unparsed_placeholders: false,
         })
     }
 
+    /// Strip a container runtime prefix (Docker/CRI) before parsing each line
+    pub fn with_line_prefix(mut self, line_prefix: LinePrefix) -> Self {
+        self.line_prefix = line_prefix;
+        self
+    }
+
+    /// Skip lines longer than `max_line_bytes` instead of reading them into memory
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self.partials = CriPartials::new(max_line_bytes);
+        self
+    }
+
+    /// Turn lines that fail to parse into `JsonLog::unparsed` placeholders
+    pub fn with_unparsed_placeholders(mut self, unparsed_placeholders: bool) -> Self {
+        self.unparsed_placeholders = unparsed_placeholders;
+        self
+    }
+
+    /// The error for a line, or a placeholder showing `content` in its place
+    fn failed_line(&self, content: String, error: Report<LogViewerError>) -> Result<JsonLog> {
+        if self.unparsed_placeholders {
+            Ok(JsonLog::unparsed(self.line_number, content))
+        } else {
+            Err(error)
+        }
+    }
+
     pub fn read_logs(&mut self) -> Vec<(usize, Result<JsonLog>)> {
         let mut logs = Vec::new();
 
         loop {
-            let mut line = String::new();
-            match self.reader.read_line(&mut line) {
-                Ok(0) => break, // EOF
-                Ok(_) => {
+            let mut bytes = Vec::new();
+            match self.read_capped_line(&mut bytes) {
+                Ok((0, _)) => break, // EOF
+                Ok((read, too_long)) => {
                     self.line_number += 1;
-                    let parse_result = parse_json_line(&line);
+                    self.position += read as u64;
+
+                    if too_long {
+                        tracing::warn!(
+                            "Line {}: longer than {} bytes, skipped",
+                            self.line_number,
+                            self.max_line_bytes
+                        );
+                        let message = format!(
+                            "line is longer than {} bytes (see --max-line-bytes)",
+                            self.max_line_bytes
+                        );
+                        let error = Report::new(LogViewerError::InvalidLogFormat(message.clone()));
+                        logs.push((self.line_number, self.failed_line(message, error)));
+                        continue;
+                    }
+                    let line = match String::from_utf8(bytes) {
+                        Ok(line) => line,
+                        Err(e) => {
+                            let content = preview_line(&String::from_utf8_lossy(e.as_bytes()));
+                            let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+                            let error = Report::new(LogViewerError::from(e));
+                            logs.push((self.line_number, self.failed_line(content, error)));
+                            continue;
+                        }
+                    };
+
+                    // Blank lines are not log entries
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    // CRI `P` chunks are parsed once their `F` line completes them
+                    let Some(line) = self.partials.join(&line, self.line_prefix) else {
+                        self.partial_bytes += read as u64;
+                        self.partial_lines += 1;
+                        continue;
+                    };
+                    self.partial_bytes = 0;
+                    self.partial_lines = 0;
+
+                    let parse_result =
+                        parse_prefixed_line_with_duplicates(&line, self.line_prefix)
+                            .map(|(log, duplicates)| {
+                                if !duplicates.is_empty() {
+                                    tracing::warn!(
+                                        "Line {}: duplicate keys {}, later values kept as <key>_2, <key>_3, ...",
+                                        self.line_number,
+                                        duplicates.join(", ")
+                                    );
+                                }
+                                log
+                            })
+                            .attach_with(|| format!("Line content: {}", preview_line(&line)));
+                    let parse_result = match parse_result {
+                        Ok(log) => Ok(log),
+                        Err(error) => self.failed_line(preview_line(&line), error),
+                    };
                     logs.push((self.line_number, parse_result));
                 }
                 Err(e) => {
@@ -43,7 +150,363 @@ impl LogFileReader {
         logs
     }
 
+    /// Parse the CRI `P` chunks left at the end as if their `F` line had followed,
+    /// for when no follower will read the rest of them
+    /// `position()` and `current_line_number()` then include them
+    pub fn finish_partials(&mut self) -> Vec<(usize, Result<JsonLog>)> {
+        self.partial_bytes = 0;
+        self.partial_lines = 0;
+        let lines = self.partials.take_pending();
+        lines
+            .into_iter()
+            .map(|line| {
+                tracing::warn!(
+                    "Line {}: CRI partial line has no final chunk, loaded as it is",
+                    self.line_number
+                );
+                let parse_result = parse_prefixed_line_with_duplicates(&line, self.line_prefix)
+                    .map(|(log, _)| log)
+                    .attach_with(|| format!("Line content: {}", preview_line(&line)));
+                let parse_result = match parse_result {
+                    Ok(log) => Ok(log),
+                    Err(error) => self.failed_line(preview_line(&line), error),
+                };
+                (self.line_number, parse_result)
+            })
+            .collect()
+    }
+
+    /// Read up to and including the next newline into `buf`, like `read_until`,
+    /// but stop buffering once the line exceeds `max_line_bytes`
+    /// Returns the bytes consumed and whether the line was too long (`buf` is then empty)
+    fn read_capped_line(&mut self, buf: &mut Vec<u8>) -> std::io::Result<(usize, bool)> {
+        let mut consumed = 0;
+        let mut too_long = false;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                break;
+            }
+            let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(end) => (&available[..=end], true),
+                None => (available, false),
+            };
+            let len = chunk.len();
+            if !too_long {
+                if buf.len() + len - usize::from(done) > self.max_line_bytes {
+                    too_long = true;
+                    buf.clear();
+                } else {
+                    buf.extend_from_slice(chunk);
+                }
+            }
+            self.reader.consume(len);
+            consumed += len;
+            if done {
+                break;
+            }
+        }
+        Ok((consumed, too_long))
+    }
+
+    /// Number of the last line read, not counting trailing CRI `P` chunks
     pub fn current_line_number(&self) -> usize {
-        self.line_number
+        self.line_number - self.partial_lines
+    }
+
+    /// Byte offset just past the last line read
+    /// Trailing CRI `P` chunks are excluded so a follower reads them with their `F` line
+    pub fn position(&self) -> u64 {
+        self.position - self.partial_bytes
+    }
+}
+
+/// Open a file, decompressing it on the fly if it ends in `.gz`
+fn open_source(path: &Path) -> Result<Box<dyn Read>> {
+    let file = File::open(path)
+        .map_err(LogViewerError::from)
+        .attach("Failed to open log file")?;
+    Ok(if is_gzip_path(path) {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    })
+}
+
+/// Bytes `read_first_lines` reads at most (decompressed, for gzip files)
+pub const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// Up to `count` lines from the start of a file, for sniffing its format
+/// Reads at most `SNIFF_BYTES`; a line cut off by that budget is left out.
+/// Invalid UTF-8 is replaced
+pub fn read_first_lines(path: impl AsRef<Path>, count: usize) -> Result<Vec<String>> {
+    let mut reader = BufReader::new(open_source(path.as_ref())?.take(SNIFF_BYTES));
+    let mut lines = Vec::new();
+    let mut bytes = Vec::new();
+    let mut total = 0;
+    while lines.len() < count {
+        bytes.clear();
+        let read = reader
+            .read_until(b'\n', &mut bytes)
+            .map_err(LogViewerError::from)
+            .attach("Failed to read log file")?;
+        if read == 0 {
+            break;
+        }
+        total += read as u64;
+        if total == SNIFF_BYTES && bytes.last() != Some(&b'\n') {
+            break;
+        }
+        lines.push(String::from_utf8_lossy(&bytes).into_owned());
+    }
+    Ok(lines)
+}
+
+/// Whether a file is gzip-compressed, judged by its `.gz` extension
+pub fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Shorten a line for inclusion in error messages
+fn preview_line(line: &str) -> String {
+    const MAX_PREVIEW_CHARS: usize = 120;
+
+    let line = line.trim_end();
+    if line.chars().count() > MAX_PREVIEW_CHARS {
+        let truncated: String = line.chars().take(MAX_PREVIEW_CHARS).collect();
+        format!("{}...", truncated)
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::models::UNPARSED_FIELD;
+    use std::io::Write;
+
+    fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "log-viewer-reader-{}-{}.log",
+            name,
+            std::process::id()
+        ));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_logs_skips_blank_lines() {
+        let path = write_temp_file(
+            "blank",
+            "{\"level\":30,\"msg\":\"a\"}\n\n   \n{\"level\":40,\"msg\":\"b\"}\n",
+        );
+
+        let logs = LogFileReader::new(&path).unwrap().read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        let line_numbers: Vec<usize> = logs.iter().map(|(n, _)| *n).collect();
+        assert_eq!(line_numbers, vec![1, 4]);
+        assert!(logs.iter().all(|(_, r)| r.is_ok()));
+    }
+
+    #[test]
+    fn test_read_logs_reports_invalid_lines() {
+        let path = write_temp_file("invalid", "plain text line\n{\"msg\":\"ok\"}\n");
+
+        let logs = LogFileReader::new(&path).unwrap().read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].0, 1);
+        assert!(logs[0].1.is_err());
+        assert!(logs[1].1.is_ok());
+    }
+
+    #[test]
+    fn test_read_logs_unparsed_placeholders() {
+        let long_line = format!("{{\"msg\":\"{}\"}}", "x".repeat(100));
+        let contents = format!("plain text line\n{{\"msg\":\"ok\"}}\n{}\n", long_line);
+        let path = write_temp_file("placeholders", &contents);
+
+        let logs = LogFileReader::new(&path)
+            .unwrap()
+            .with_max_line_bytes(64)
+            .with_unparsed_placeholders(true)
+            .read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        let logs: Vec<JsonLog> = logs.into_iter().map(|(_, r)| r.unwrap()).collect();
+        assert!(logs[0].is_unparsed());
+        assert_eq!(logs[0].line_number(), Some(1));
+        assert_eq!(
+            logs[0].get_field(UNPARSED_FIELD).and_then(|v| v.as_str()),
+            Some("plain text line")
+        );
+        assert!(!logs[1].is_unparsed());
+        assert_eq!(logs[2].line_number(), Some(3));
+        assert!(
+            logs[2]
+                .get_field(UNPARSED_FIELD)
+                .and_then(|v| v.as_str())
+                .unwrap()
+                .contains("longer than 64 bytes")
+        );
+    }
+
+    #[test]
+    fn test_read_logs_skips_long_lines() {
+        let long_line = format!("{{\"msg\":\"{}\"}}", "x".repeat(100));
+        let contents = format!("{{\"msg\":\"a\"}}\n{}\n{{\"msg\":\"b\"}}\n", long_line);
+        let path = write_temp_file("long", &contents);
+
+        let mut reader = LogFileReader::new(&path).unwrap().with_max_line_bytes(64);
+        let logs = reader.read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(logs.len(), 3);
+        assert!(logs[0].1.is_ok());
+        assert_eq!(logs[1].0, 2);
+        let error = format!("{:?}", logs[1].1.as_ref().unwrap_err());
+        assert!(error.contains("longer than 64 bytes"));
+        assert_eq!(logs[2].1.as_ref().unwrap().get_message(), Some("b"));
+        // The skipped line still counts towards the follow position
+        assert_eq!(reader.position(), contents.len() as u64);
+    }
+
+    #[test]
+    fn test_read_logs_line_at_the_limit() {
+        let line = "{\"msg\":\"a\"}";
+        let path = write_temp_file("limit", &format!("{}\n{}", line, line));
+
+        let logs = LogFileReader::new(&path)
+            .unwrap()
+            .with_max_line_bytes(line.len())
+            .read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(logs.iter().all(|(_, r)| r.is_ok()));
+    }
+
+    #[test]
+    fn test_position_tracks_bytes_read() {
+        let contents = "{\"msg\":\"a\"}\n\n{\"msg\":\"b\"}\n";
+        let path = write_temp_file("position", contents);
+
+        let mut reader = LogFileReader::new(&path).unwrap();
+        reader.read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.position(), contents.len() as u64);
+    }
+
+    #[test]
+    fn test_read_logs_joins_cri_partials() {
+        let complete = "2023-01-01T00:00:00Z stdout P {\"msg\":\"long \n\
+                        2023-01-01T00:00:00Z stdout F line\"}\n";
+        let trailing = "2023-01-01T00:00:01Z stdout P {\"msg\":\n";
+        let path = write_temp_file("cri-partials", &format!("{}{}", complete, trailing));
+
+        let mut reader = LogFileReader::new(&path)
+            .unwrap()
+            .with_line_prefix(LinePrefix::Cri);
+        let logs = reader.read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, 2);
+        assert_eq!(logs[0].1.as_ref().unwrap().get_message(), Some("long line"));
+        // A follower picks up at the unfinished chunk
+        assert_eq!(reader.position(), complete.len() as u64);
+        assert_eq!(reader.current_line_number(), 2);
+    }
+
+    #[test]
+    fn test_finish_partials_at_end_of_file() {
+        let contents = "2023-01-01T00:00:00Z stdout P {\"msg\":\"cut\"}\n\
+                        2023-01-01T00:00:01Z stderr P {\"msg\":\n";
+        let path = write_temp_file("cri-finish", contents);
+
+        let mut reader = LogFileReader::new(&path)
+            .unwrap()
+            .with_line_prefix(LinePrefix::Cri)
+            .with_unparsed_placeholders(true);
+        assert!(reader.read_logs().is_empty());
+        let logs = reader.finish_partials();
+        std::fs::remove_file(&path).unwrap();
+
+        // A complete payload still parses; a broken one becomes a placeholder
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].1.as_ref().unwrap().get_message(), Some("cut"));
+        assert!(logs[1].1.as_ref().unwrap().is_unparsed());
+        assert_eq!(reader.position(), contents.len() as u64);
+        assert_eq!(reader.current_line_number(), 2);
+    }
+
+    #[test]
+    fn test_read_gzip_logs() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let path = std::env::temp_dir().join(format!(
+            "log-viewer-reader-gzip-{}.log.gz",
+            std::process::id()
+        ));
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder
+            .write_all(b"{\"msg\":\"a\"}\n{\"msg\":\"b\"}\n")
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let logs = LogFileReader::new(&path).unwrap().read_logs();
+        std::fs::remove_file(&path).unwrap();
+
+        let messages: Vec<String> = logs
+            .into_iter()
+            .map(|(_, r)| r.unwrap().get_message().unwrap().to_string())
+            .collect();
+        assert_eq!(messages, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_read_first_lines() {
+        let path = write_temp_file(
+            "first",
+            "{\"msg\":\"a\"}\n\n{\"msg\":\"b\"}\n{\"msg\":\"c\"}\n",
+        );
+
+        let lines = read_first_lines(&path, 3).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines, vec!["{\"msg\":\"a\"}\n", "\n", "{\"msg\":\"b\"}\n"]);
+    }
+
+    #[test]
+    fn test_read_first_lines_stops_at_byte_budget() {
+        let line = format!("{{\"msg\":\"{}\"}}\n", "x".repeat(1000));
+        let path = write_temp_file("first-budget", &line.repeat(100));
+
+        let lines = read_first_lines(&path, 100).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Only whole lines within the budget
+        assert_eq!(lines.len(), SNIFF_BYTES as usize / line.len());
+        assert!(lines.iter().all(|l| *l == line));
+    }
+
+    #[test]
+    fn test_preview_line_truncates() {
+        let long_line = "x".repeat(200);
+        let preview = preview_line(&long_line);
+        assert_eq!(preview.len(), 123);
+        assert!(preview.ends_with("..."));
+        assert_eq!(preview_line("short\n"), "short");
     }
 }
@@ -0,0 +1,221 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::{JsonLog, SOURCE_FIELD};
+use rootcause::prelude::ResultExt;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+
+/// Tag `log` with the file it came from in `SOURCE_FIELD`, apart from any
+/// `source` field the log has itself
+pub fn tag_source(log: &mut JsonLog, file_name: &str) {
+    log.fields.insert(
+        SOURCE_FIELD.to_string(),
+        serde_json::Value::String(file_name.to_string()),
+    );
+}
+
+/// Merge the logs of several files into one timeline by `JsonLog::timestamp()`
+///
+/// Each file keeps its own order; a log without a time stays after the log
+/// before it in its file. Logs with equal times go in file order.
+pub fn merge_by_time(files: Vec<Vec<JsonLog>>) -> Vec<JsonLog> {
+    let total = files.iter().map(Vec::len).sum();
+    let mut merged = Vec::with_capacity(total);
+    let mut files: Vec<_> = files
+        .into_iter()
+        .map(|logs| logs.into_iter().peekable())
+        .collect();
+
+    // Heads of each file keyed by their time, or the time of the log before them
+    let mut heads = BinaryHeap::new();
+    for (index, logs) in files.iter_mut().enumerate() {
+        if let Some(log) = logs.peek() {
+            heads.push(Reverse((log.timestamp(), index)));
+        }
+    }
+    while let Some(Reverse((time, index))) = heads.pop() {
+        let logs = &mut files[index];
+        merged.extend(logs.next());
+        if let Some(log) = logs.peek() {
+            heads.push(Reverse((log.timestamp().or(time), index)));
+        }
+    }
+    merged
+}
+
+/// Log files in `dir`, ordered so each rotation set reads oldest to newest
+///
+/// logrotate names rotated files `app.log.1`, `app.log.2.gz`, ... with higher
+/// numbers being older, so `app.log.2.gz`, `app.log.1`, `app.log` is returned.
+/// With `dateext` (`app.log-20240101`) the dates order them instead.
+/// Compressed and uncompressed rotations mix freely. Separate sets (`app.log`,
+/// `worker.log`) follow each other in name order. Hidden files are skipped.
+pub fn rotated_log_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(LogViewerError::from)
+        .attach_with(|| format!("Failed to read directory {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to read directory {}", dir.display()))?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') || !path.is_file() {
+            continue;
+        }
+        let (base, rotation) = rotation_key(name);
+        files.push((base.to_string(), rotation, path));
+    }
+
+    // Base name ascending, then oldest rotation first
+    files.sort_by(|(a_base, a_rot, _), (b_base, b_rot, _)| {
+        a_base.cmp(b_base).then(a_rot.cmp(b_rot))
+    });
+
+    Ok(files.into_iter().map(|(_, _, path)| path).collect())
+}
+
+/// Place of a file in its rotation set, ordered oldest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Rotation {
+    /// `app.log.2`; higher numbers are older
+    Numbered(Reverse<u32>),
+    /// `app.log-20240101` or `app.log.2024-01-01`, the date's digits as a number
+    Dated(u64),
+    /// The file a logger writes to
+    Live,
+}
+
+/// Split a file name into its rotation set and place in it:
+/// `app.log.2.gz` → (`app.log`, 2nd rotation), `app.log-20240101` → (`app.log`, dated)
+pub(super) fn rotation_key(name: &str) -> (&str, Rotation) {
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    let suffix_start = name
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '-')
+        .len();
+    let suffix = name[suffix_start..].trim_start_matches('-');
+    let head = &name[..name.len() - suffix.len()];
+
+    // At least a full YYYYMMDD date, the separators left out
+    let digits: String = suffix.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() >= 8
+        && let Some(base) = head
+            .strip_suffix(['.', '-'])
+            .filter(|base| !base.is_empty())
+        && let Ok(date) = digits.parse()
+    {
+        return (base, Rotation::Dated(date));
+    }
+    match (head.strip_suffix('.'), suffix.parse::<u32>()) {
+        (Some(base), Ok(number)) if !base.is_empty() => (base, Rotation::Numbered(Reverse(number))),
+        _ => (name, Rotation::Live),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_key() {
+        let numbered = |n| Rotation::Numbered(Reverse(n));
+        assert_eq!(rotation_key("app.log"), ("app.log", Rotation::Live));
+        assert_eq!(rotation_key("app.log.1"), ("app.log", numbered(1)));
+        assert_eq!(rotation_key("app.log.12.gz"), ("app.log", numbered(12)));
+        assert_eq!(rotation_key("app.log.gz"), ("app.log", Rotation::Live));
+        assert_eq!(rotation_key("app"), ("app", Rotation::Live));
+        assert_eq!(
+            rotation_key("app.log-20240101.gz"),
+            ("app.log", Rotation::Dated(20240101))
+        );
+        assert_eq!(
+            rotation_key("app.log.2024-01-31"),
+            ("app.log", Rotation::Dated(20240131))
+        );
+        assert_eq!(
+            rotation_key("app-2024-01-01.log"),
+            ("app-2024-01-01.log", Rotation::Live)
+        );
+    }
+
+    #[test]
+    fn test_merge_by_time() {
+        let parse = |line: &str| crate::ingestion::parse_json_line(line).unwrap();
+        let app = vec![
+            parse(r#"{"time":1000,"msg":"app 1"}"#),
+            parse(r#"{"msg":"app untimed"}"#),
+            parse(r#"{"time":3000,"msg":"app 3"}"#),
+        ];
+        let worker = vec![
+            parse(r#"{"time":1000,"msg":"worker 1"}"#),
+            parse(r#"{"time":2000,"msg":"worker 2"}"#),
+        ];
+
+        let merged = merge_by_time(vec![app, worker]);
+        let messages: Vec<_> = merged
+            .iter()
+            .map(|log| log.get_message().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["app 1", "app untimed", "worker 1", "worker 2", "app 3"]
+        );
+    }
+
+    #[test]
+    fn test_tag_source_keeps_logged_source() {
+        let mut log = crate::ingestion::parse_json_line(r#"{"source":"db"}"#).unwrap();
+        tag_source(&mut log, "app.log");
+        assert_eq!(log.get_field("source"), Some(&serde_json::json!("db")));
+        assert_eq!(
+            log.get_field(SOURCE_FIELD),
+            Some(&serde_json::json!("app.log"))
+        );
+    }
+
+    #[test]
+    fn test_rotated_log_files_order() {
+        let dir = std::env::temp_dir().join(format!("log-viewer-rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "app.log",
+            "app.log.1",
+            "app.log.2.gz",
+            "app.log.10.gz",
+            "worker.log",
+            "worker.log.1.gz",
+            "web.log",
+            "web.log-20240102",
+            "web.log-20231231.gz",
+            ".hidden",
+        ] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let names: Vec<String> = rotated_log_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            names,
+            vec![
+                "app.log.10.gz",
+                "app.log.2.gz",
+                "app.log.1",
+                "app.log",
+                "web.log-20231231.gz",
+                "web.log-20240102",
+                "web.log",
+                "worker.log.1.gz",
+                "worker.log",
+            ]
+        );
+    }
+}
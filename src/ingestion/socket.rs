@@ -0,0 +1,528 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::follow::parse_followed_line;
+use crate::ingestion::models::JsonLog;
+use crate::ingestion::prefix::{CriPartials, LinePrefix};
+use crate::ingestion::reader::DEFAULT_MAX_LINE_BYTES;
+use rootcause::prelude::{Report, ResultExt};
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+use std::time::{Duration, Instant};
+
+/// Time between reconnection attempts after the socket closes
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest a TCP connection attempt may take per resolved address
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Bytes read from the socket per `read` call
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Most bytes read per `poll`, so a writer that never pauses can't keep the
+/// event loop from drawing; the rest waits in the socket for the next poll
+const MAX_READ_PER_POLL: usize = 16 * READ_CHUNK;
+
+/// Where `SocketFollower` reads from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketAddress {
+    /// Unix domain socket path (`--socket`)
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// `host:port` (`--tcp`)
+    Tcp(String),
+}
+
+impl SocketAddress {
+    /// Unix domain socket at `path`; an error where those don't exist
+    pub fn unix(path: String) -> Result<Self> {
+        #[cfg(unix)]
+        {
+            Ok(SocketAddress::Unix(PathBuf::from(path)))
+        }
+        #[cfg(not(unix))]
+        {
+            Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Can't read {}: Unix domain sockets aren't supported on this platform",
+                path
+            ))))
+        }
+    }
+
+    /// TCP address written as `host:port`
+    pub fn tcp(address: &str) -> Result<Self> {
+        let valid = address
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if !valid {
+            return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Invalid TCP address '{}': expected host:port",
+                address
+            ))));
+        }
+        Ok(SocketAddress::Tcp(address.to_string()))
+    }
+
+    fn connect(&self) -> std::io::Result<Connection> {
+        match self {
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_nonblocking(true)?;
+                Ok(Connection::Unix(stream))
+            }
+            SocketAddress::Tcp(address) => {
+                let mut last_error = None;
+                for addr in address.to_socket_addrs()? {
+                    match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                        Ok(stream) => {
+                            stream.set_nonblocking(true)?;
+                            return Ok(Connection::Tcp(stream));
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| {
+                    std::io::Error::new(ErrorKind::NotFound, "address resolved to nothing")
+                }))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SocketAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => write!(f, "{}", path.display()),
+            SocketAddress::Tcp(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+/// An open, non-blocking stream
+enum Connection {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.read(buf),
+            Connection::Tcp(stream) => stream.read(buf),
+        }
+    }
+}
+
+/// Reads newline-delimited JSON streamed over a Unix domain socket (`--socket`)
+/// or a TCP connection (`--tcp`)
+///
+/// Reads never block: `poll` returns whatever complete lines have arrived.
+/// When the writer closes the connection, it is retried every `RECONNECT_INTERVAL`
+/// on a background thread, so name resolution and connect timeouts never stall `poll`.
+pub struct SocketFollower {
+    address: SocketAddress,
+    stream: Option<Connection>,
+    /// Trailing bytes of a line whose newline has not arrived yet
+    pending: Vec<u8>,
+    /// Lines longer than this (newline excluded) are reported and skipped
+    max_line_bytes: usize,
+    /// Whether the rest of the current line is being skipped for its length
+    skipping: bool,
+    line_prefix: LinePrefix,
+    /// CRI `P` chunks waiting for their `F` line
+    partials: CriPartials,
+    /// When the connection was last lost or retried
+    last_attempt: Instant,
+    /// Result of the reconnection attempt running in the background, if any
+    connecting: Option<Receiver<std::io::Result<Connection>>>,
+}
+
+impl SocketFollower {
+    /// Connect to `address`; failing to connect at startup is an error
+    pub fn connect(address: SocketAddress) -> Result<Self> {
+        let stream = address
+            .connect()
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to connect to {}", address))?;
+
+        Ok(Self {
+            address,
+            stream: Some(stream),
+            pending: Vec::new(),
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            skipping: false,
+            line_prefix: LinePrefix::None,
+            partials: CriPartials::default(),
+            last_attempt: Instant::now(),
+            connecting: None,
+        })
+    }
+
+    /// Strip a container runtime prefix (Docker/CRI) before parsing each line
+    pub fn with_line_prefix(mut self, line_prefix: LinePrefix) -> Self {
+        self.line_prefix = line_prefix;
+        self
+    }
+
+    /// Skip lines longer than `max_line_bytes` instead of buffering them
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self.partials = CriPartials::new(max_line_bytes);
+        self
+    }
+
+    /// Whether the socket is connected; `false` while waiting to reconnect
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Parse the complete lines received since the last poll
+    /// Lines that fail to parse are skipped, as in the initial load
+    pub fn poll(&mut self) -> Result<Vec<JsonLog>> {
+        if self.stream.is_none() {
+            self.reconnect();
+        }
+        self.read_available();
+        Ok(self.take_lines())
+    }
+
+    /// Block until the first logs arrive, then keep reading for `settle` so the
+    /// schema is detected from more than one log
+    pub fn wait_for_logs(&mut self, settle: Duration) -> Result<Vec<JsonLog>> {
+        let mut logs = Vec::new();
+        let mut first_at = None;
+        loop {
+            logs.extend(self.poll()?);
+            if !logs.is_empty() {
+                let first = *first_at.get_or_insert_with(Instant::now);
+                if first.elapsed() >= settle {
+                    return Ok(logs);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Pick up the result of a background connection attempt, or start one once
+    /// `RECONNECT_INTERVAL` has passed
+    fn reconnect(&mut self) {
+        if let Some(connecting) = &self.connecting {
+            let result = match connecting.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => Err(std::io::Error::other(
+                    "connection attempt ended without a result",
+                )),
+            };
+            self.connecting = None;
+            self.last_attempt = Instant::now();
+            match result {
+                Ok(stream) => {
+                    tracing::info!("Reconnected to {}", self.address);
+                    self.stream = Some(stream);
+                }
+                Err(e) => tracing::debug!("Reconnecting to {} failed: {}", self.address, e),
+            }
+            return;
+        }
+
+        if self.last_attempt.elapsed() < RECONNECT_INTERVAL {
+            return;
+        }
+        self.last_attempt = Instant::now();
+        let (tx, rx) = channel();
+        let address = self.address.clone();
+        std::thread::spawn(move || {
+            // The follower may be gone by the time the attempt finishes
+            let _ = tx.send(address.connect());
+        });
+        self.connecting = Some(rx);
+    }
+
+    /// Read what is available, up to `MAX_READ_PER_POLL`, without blocking,
+    /// dropping the connection on EOF or error
+    fn read_available(&mut self) {
+        let Some(mut stream) = self.stream.take() else {
+            return;
+        };
+        let mut chunk = vec![0; READ_CHUNK];
+        let mut total = 0;
+        let lost = loop {
+            if total >= MAX_READ_PER_POLL {
+                break None;
+            }
+            match stream.read(&mut chunk) {
+                Ok(0) => break Some("closed by the writer".to_string()),
+                Ok(read) => {
+                    total += read;
+                    self.buffer(&chunk[..read]);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break None,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => break Some(e.to_string()),
+            }
+        };
+
+        let Some(reason) = lost else {
+            self.stream = Some(stream);
+            return;
+        };
+        tracing::warn!("Lost connection to {}: {}", self.address, reason);
+        self.last_attempt = Instant::now();
+        // A line cut off by the disconnect is never completed
+        self.pending.truncate(self.line_start());
+        self.skipping = false;
+        self.partials.clear();
+    }
+
+    /// Append received bytes to `pending`, skipping the rest of any line that grows
+    /// past `max_line_bytes` up to its newline, like `LogFileReader`
+    fn buffer(&mut self, mut bytes: &[u8]) {
+        let mut line_start = self.line_start();
+        while !bytes.is_empty() {
+            let (chunk, done) = match bytes.iter().position(|&b| b == b'\n') {
+                Some(end) => (&bytes[..=end], true),
+                None => (bytes, false),
+            };
+            bytes = &bytes[chunk.len()..];
+            if self.skipping {
+                self.skipping = !done;
+                continue;
+            }
+            if self.pending.len() - line_start + chunk.len() - usize::from(done)
+                > self.max_line_bytes
+            {
+                tracing::warn!(
+                    "Line from {} is longer than {} bytes, skipped",
+                    self.address,
+                    self.max_line_bytes
+                );
+                self.pending.truncate(line_start);
+                self.skipping = !done;
+                continue;
+            }
+            self.pending.extend_from_slice(chunk);
+            if done {
+                line_start = self.pending.len();
+            }
+        }
+    }
+
+    /// Offset in `pending` where its unterminated last line starts
+    fn line_start(&self) -> usize {
+        self.pending
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Parse the complete lines in `pending`, keeping an unterminated last line
+    fn take_lines(&mut self) -> Vec<JsonLog> {
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let rest = self.pending.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.pending, rest);
+
+        String::from_utf8_lossy(&complete)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| self.partials.join(line, self.line_prefix))
+            .filter_map(|line| parse_followed_line(&line, self.line_prefix))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    #[cfg(unix)]
+    use std::os::unix::net::UnixListener;
+
+    #[cfg(unix)]
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "log-viewer-socket-{}-{}.sock",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn messages(logs: &[JsonLog]) -> Vec<&str> {
+        logs.iter().filter_map(|log| log.get_message()).collect()
+    }
+
+    /// Poll until something arrives, since the writer runs concurrently
+    fn poll_some(follower: &mut SocketFollower) -> Vec<JsonLog> {
+        for _ in 0..100 {
+            let logs = follower.poll().unwrap();
+            if !logs.is_empty() {
+                return logs;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Vec::new()
+    }
+
+    #[test]
+    fn test_socket_address_tcp() {
+        assert_eq!(
+            SocketAddress::tcp("localhost:5170").unwrap(),
+            SocketAddress::Tcp("localhost:5170".to_string())
+        );
+        assert!(SocketAddress::tcp("[::1]:5170").is_ok());
+        assert!(SocketAddress::tcp("localhost").is_err());
+        assert!(SocketAddress::tcp(":5170").is_err());
+        assert!(SocketAddress::tcp("localhost:http").is_err());
+    }
+
+    #[test]
+    fn test_tcp_reads_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = SocketAddress::tcp(&listener.local_addr().unwrap().to_string()).unwrap();
+
+        let mut follower = SocketFollower::connect(address).unwrap();
+        let (mut writer, _) = listener.accept().unwrap();
+        writer.write_all(b"{\"msg\":\"over tcp\"}\n").unwrap();
+        assert_eq!(messages(&poll_some(&mut follower)), vec!["over tcp"]);
+
+        // With the listener gone the reconnection attempts fail too
+        drop(writer);
+        drop(listener);
+        poll_some(&mut follower);
+        assert!(!follower.is_connected());
+    }
+
+    #[test]
+    fn test_tcp_skips_long_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = SocketAddress::tcp(&listener.local_addr().unwrap().to_string()).unwrap();
+
+        let mut follower = SocketFollower::connect(address)
+            .unwrap()
+            .with_max_line_bytes(16);
+        let (mut writer, _) = listener.accept().unwrap();
+        writer
+            .write_all(b"{\"msg\":\"this line is too long")
+            .unwrap();
+        for _ in 0..10 {
+            follower.poll().unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // Nothing of the long line is kept while waiting for its newline
+        assert!(follower.pending.is_empty());
+        writer
+            .write_all(b" to keep\"}\n{\"msg\":\"short\"}\n")
+            .unwrap();
+        assert_eq!(messages(&poll_some(&mut follower)), vec!["short"]);
+    }
+
+    #[test]
+    fn test_poll_reads_a_bounded_amount() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = SocketAddress::tcp(&listener.local_addr().unwrap().to_string()).unwrap();
+        let mut follower = SocketFollower::connect(address).unwrap();
+        let (mut writer, _) = listener.accept().unwrap();
+
+        let line = format!("{{\"msg\":\"{}\"}}\n", "x".repeat(1000));
+        let lines = 3 * MAX_READ_PER_POLL / line.len();
+        let writing = std::thread::spawn(move || {
+            for _ in 0..lines {
+                writer.write_all(line.as_bytes()).unwrap();
+            }
+        });
+
+        // Each poll hands back at most a poll's worth of lines, however fast they come
+        let mut received = 0;
+        while received < lines {
+            let logs = follower.poll().unwrap();
+            assert!(logs.len() <= MAX_READ_PER_POLL / 1000 + 1);
+            received += logs.len();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        writing.join().unwrap();
+        assert_eq!(received, lines);
+    }
+
+    #[test]
+    fn test_reconnect_does_not_block_poll() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = SocketAddress::tcp(&listener.local_addr().unwrap().to_string()).unwrap();
+        let mut follower = SocketFollower::connect(address).unwrap();
+        drop(listener.accept().unwrap());
+        drop(listener);
+        poll_some(&mut follower);
+        assert!(!follower.is_connected());
+
+        // A blackholed address takes the whole connect timeout to fail
+        follower.connecting = None;
+        follower.address = SocketAddress::Tcp("10.255.255.1:9".to_string());
+        follower.last_attempt -= RECONNECT_INTERVAL;
+        let start = Instant::now();
+        follower.poll().unwrap();
+        follower.poll().unwrap();
+        assert!(start.elapsed() < CONNECT_TIMEOUT / 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_reads_lines() {
+        let path = socket_path("lines");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let mut follower = SocketFollower::connect(SocketAddress::Unix(path.clone())).unwrap();
+        let (mut writer, _) = listener.accept().unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        // A partial line is held back until its newline arrives
+        writer
+            .write_all(b"{\"msg\":\"one\"}\nnot json\n{\"msg\":")
+            .unwrap();
+        assert_eq!(messages(&poll_some(&mut follower)), vec!["one"]);
+        writer.write_all(b"\"two\"}\n").unwrap();
+        assert_eq!(messages(&poll_some(&mut follower)), vec!["two"]);
+        assert!(follower.is_connected());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_reconnects() {
+        let path = socket_path("reconnect");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let mut follower = SocketFollower::connect(SocketAddress::Unix(path.clone())).unwrap();
+        let (writer, _) = listener.accept().unwrap();
+        drop(writer);
+        assert!(follower.poll().unwrap().is_empty());
+        assert!(!follower.is_connected());
+
+        // Retried in the background once `RECONNECT_INTERVAL` has passed
+        follower.last_attempt -= RECONNECT_INTERVAL;
+        for _ in 0..100 {
+            follower.poll().unwrap();
+            if follower.is_connected() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(follower.is_connected());
+        let (mut writer, _) = listener.accept().unwrap();
+        writer.write_all(b"{\"msg\":\"back\"}\n").unwrap();
+        assert_eq!(messages(&poll_some(&mut follower)), vec!["back"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
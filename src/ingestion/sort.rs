@@ -0,0 +1,233 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::JsonLog;
+use rootcause::prelude::Report;
+use std::cmp::Ordering;
+
+/// Order applied to parsed logs before they are inserted into the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOnLoad {
+    /// Keep file order
+    #[default]
+    None,
+    /// Sort chronologically by `JsonLog::timestamp()`
+    Time,
+}
+
+impl std::str::FromStr for SortOnLoad {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(SortOnLoad::None),
+            "time" => Ok(SortOnLoad::Time),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown sort order '{}' (expected none or time)",
+                s
+            )))),
+        }
+    }
+}
+
+/// Where logs without a timestamp end up when sorting by time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UntimedPosition {
+    Start,
+    #[default]
+    End,
+}
+
+impl std::str::FromStr for UntimedPosition {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "start" => Ok(UntimedPosition::Start),
+            "end" => Ok(UntimedPosition::End),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown position '{}' (expected start or end)",
+                s
+            )))),
+        }
+    }
+}
+
+/// Sort logs in place
+///
+/// Logs with equal timestamps are ordered by the `tiebreak` field if given
+/// (`--sort-tiebreak`, e.g. a sequence number). The sort is stable: remaining
+/// ties, and logs without a timestamp, keep their file order.
+pub fn sort_logs(
+    logs: &mut [JsonLog],
+    order: SortOnLoad,
+    untimed: UntimedPosition,
+    tiebreak: Option<&str>,
+) {
+    match order {
+        SortOnLoad::None => {}
+        // Timestamps are parsed once per log rather than once per comparison
+        SortOnLoad::Time => logs.sort_by_cached_key(|log| {
+            let (untimed_last, timestamp) = time_key(log, untimed);
+            let tiebreak = tiebreak.map_or(Tiebreak::Other, |field| Tiebreak::of(log, field));
+            (untimed_last, timestamp, tiebreak)
+        }),
+    }
+}
+
+/// Sort key putting untimed logs at the chosen end
+fn time_key(log: &JsonLog, untimed: UntimedPosition) -> (bool, Option<jiff::Timestamp>) {
+    let timestamp = log.timestamp();
+    let untimed_last = match untimed {
+        UntimedPosition::Start => timestamp.is_some(),
+        UntimedPosition::End => timestamp.is_none(),
+    };
+    (untimed_last, timestamp)
+}
+
+/// Value of the tiebreak field: numbers by value before strings, logs without
+/// the field (or with another type) last
+enum Tiebreak {
+    Number(f64),
+    Text(String),
+    Other,
+}
+
+impl Tiebreak {
+    fn of(log: &JsonLog, field: &str) -> Self {
+        use serde_json::Value;
+        match log.get_field(field) {
+            Some(Value::Number(n)) => Tiebreak::Number(n.as_f64().unwrap_or(0.0)),
+            Some(Value::String(s)) => Tiebreak::Text(s.clone()),
+            _ => Tiebreak::Other,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Tiebreak::Number(_) => 0,
+            Tiebreak::Text(_) => 1,
+            Tiebreak::Other => 2,
+        }
+    }
+}
+
+impl Ord for Tiebreak {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Tiebreak::Number(x), Tiebreak::Number(y)) => x.total_cmp(y),
+            (Tiebreak::Text(x), Tiebreak::Text(y)) => x.cmp(y),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+}
+
+impl PartialOrd for Tiebreak {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Tiebreak {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Tiebreak {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+
+    fn logs() -> Vec<JsonLog> {
+        [
+            r#"{"time":3000,"msg":"c"}"#,
+            r#"{"msg":"untimed 1"}"#,
+            r#"{"time":1000,"msg":"a"}"#,
+            r#"{"time":2000,"msg":"b1"}"#,
+            r#"{"msg":"untimed 2"}"#,
+            r#"{"time":2000,"msg":"b2"}"#,
+        ]
+        .iter()
+        .map(|line| parse_json_line(line).unwrap())
+        .collect()
+    }
+
+    fn messages(logs: &[JsonLog]) -> Vec<&str> {
+        logs.iter().map(|log| log.get_message().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_time_untimed_end() {
+        let mut logs = logs();
+        sort_logs(&mut logs, SortOnLoad::Time, UntimedPosition::End, None);
+        assert_eq!(
+            messages(&logs),
+            vec!["a", "b1", "b2", "c", "untimed 1", "untimed 2"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_time_untimed_start() {
+        let mut logs = logs();
+        sort_logs(&mut logs, SortOnLoad::Time, UntimedPosition::Start, None);
+        assert_eq!(
+            messages(&logs),
+            vec!["untimed 1", "untimed 2", "a", "b1", "b2", "c"]
+        );
+    }
+
+    #[test]
+    fn test_sort_none_keeps_file_order() {
+        let mut logs = logs();
+        sort_logs(&mut logs, SortOnLoad::None, UntimedPosition::End, None);
+        assert_eq!(messages(&logs)[0], "c");
+    }
+
+    #[test]
+    fn test_sort_tiebreak() {
+        let mut logs: Vec<JsonLog> = [
+            r#"{"time":2000,"seq":3,"msg":"b3"}"#,
+            r#"{"time":2000,"msg":"b, no seq"}"#,
+            r#"{"time":2000,"seq":1,"msg":"b1"}"#,
+            r#"{"time":1000,"seq":9,"msg":"a"}"#,
+            r#"{"time":2000,"seq":2,"msg":"b2"}"#,
+            r#"{"time":2000,"seq":"x","msg":"b, text seq"}"#,
+        ]
+        .iter()
+        .map(|line| parse_json_line(line).unwrap())
+        .collect();
+
+        // Without a tiebreaker the equal timestamps stay in file order
+        let mut untied = logs.clone();
+        sort_logs(&mut untied, SortOnLoad::Time, UntimedPosition::End, None);
+        assert_eq!(
+            messages(&untied),
+            vec!["a", "b3", "b, no seq", "b1", "b2", "b, text seq"]
+        );
+
+        // Numbers first, then strings, then logs without the field; the
+        // timestamp still comes first
+        sort_logs(
+            &mut logs,
+            SortOnLoad::Time,
+            UntimedPosition::End,
+            Some("seq"),
+        );
+        assert_eq!(
+            messages(&logs),
+            vec!["a", "b1", "b2", "b3", "b, text seq", "b, no seq"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sort_options() {
+        assert_eq!("time".parse::<SortOnLoad>().unwrap(), SortOnLoad::Time);
+        assert!("level".parse::<SortOnLoad>().is_err());
+        assert_eq!(
+            "start".parse::<UntimedPosition>().unwrap(),
+            UntimedPosition::Start
+        );
+        assert!("middle".parse::<UntimedPosition>().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::{JsonLog, LogLevel};
+use rootcause::prelude::Report;
+use serde_json::Value;
+
+/// Parse a logfmt line (`level=info msg="hello world" took=12`)
+///
+/// Unquoted values that look like numbers or booleans are typed, a key without
+/// `=` is `true`, and a level name in `level`/`lvl` or an RFC 3339 `time`/`timestamp`
+/// becomes the numeric level or milliseconds used for JSON logs.
+/// Repeated keys are kept as `key_2`, `key_3`, ... like in `parse_json_line_with_duplicates`.
+pub fn parse_logfmt_line(line: &str) -> Result<(JsonLog, Vec<String>)> {
+    let pairs = logfmt_pairs(line.trim())
+        .filter(|pairs| pairs.iter().any(|(_, value)| value.is_some()))
+        .ok_or_else(|| {
+            Report::new(LogViewerError::InvalidLogFormat(
+                "Line is not logfmt key=value pairs".to_string(),
+            ))
+        })?;
+
+    let mut fields: Vec<(String, Value)> = Vec::with_capacity(pairs.len());
+    let mut duplicates = Vec::new();
+    for (key, value) in pairs {
+        let value = match value {
+            Some(value) => standard_field_value(&key, value),
+            None => Value::Bool(true),
+        };
+        let key = if fields.iter().any(|(name, _)| *name == key) {
+            if !duplicates.contains(&key) {
+                duplicates.push(key.clone());
+            }
+            let mut suffix = 2;
+            while fields
+                .iter()
+                .any(|(name, _)| *name == format!("{}_{}", key, suffix))
+            {
+                suffix += 1;
+            }
+            format!("{}_{}", key, suffix)
+        } else {
+            key
+        };
+        fields.push((key, value));
+    }
+
+    Ok((JsonLog::new(fields), duplicates))
+}
+
+/// Whether a line reads as logfmt rather than text that happens to contain an `=`:
+/// it splits into pairs, most of them `key=value`
+pub fn is_logfmt_line(line: &str) -> bool {
+    logfmt_pairs(line.trim()).is_some_and(|pairs| {
+        let with_value = pairs.iter().filter(|(_, value)| value.is_some()).count();
+        with_value > 0 && with_value * 2 > pairs.len()
+    })
+}
+
+/// Parse a line of plain text as its message
+///
+/// A leading RFC 3339 timestamp and a level word (`ERROR`, `[warn]`, `INFO:`) are
+/// split off into `time` and `level`, so the list can show and filter them.
+pub fn parse_plain_line(line: &str) -> Result<(JsonLog, Vec<String>)> {
+    let mut rest = line.trim();
+    if rest.is_empty() {
+        return Err(Report::new(LogViewerError::InvalidLogFormat(
+            "Empty line".to_string(),
+        )));
+    }
+
+    let mut fields = Vec::new();
+    if let Some((word, after)) = rest.split_once(char::is_whitespace)
+        && let Some(ms) = timestamp_ms(word)
+    {
+        fields.push(("time".to_string(), Value::from(ms)));
+        rest = after.trim_start();
+    }
+    if let Some((word, after)) = rest.split_once(char::is_whitespace)
+        && let Some(level) = LogLevel::from_name(
+            word.trim_matches(|c: char| matches!(c, '[' | ']' | '<' | '>' | ':')),
+        )
+    {
+        fields.push(("level".to_string(), Value::from(level.as_u64())));
+        rest = after.trim_start();
+    }
+    fields.push(("msg".to_string(), Value::String(rest.to_string())));
+
+    Ok((JsonLog::new(fields), Vec::new()))
+}
+
+/// Keys and values of a logfmt line; `None` for a key-less `=value` or an unterminated quote
+/// Quoted values are always strings; unquoted ones are typed by `unquoted_value`
+fn logfmt_pairs(line: &str) -> Option<Vec<(String, Option<Value>)>> {
+    let mut pairs = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Some(pairs);
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if key.is_empty() || key.contains('"') {
+            return None;
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            pairs.push((key, None));
+            continue;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        c => value.push(c),
+                    },
+                    c => value.push(c),
+                }
+            }
+            pairs.push((key, Some(Value::String(value))));
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+            pairs.push((key, Some(unquoted_value(value))));
+        }
+    }
+}
+
+/// Number or boolean for an unquoted logfmt value that spells one, else the text
+fn unquoted_value(value: String) -> Value {
+    if let Ok(n) = value.parse::<i64>() {
+        return Value::from(n);
+    }
+    if let Some(n) = value
+        .parse::<f64>()
+        .ok()
+        .filter(|f| f.is_finite())
+        .and_then(serde_json::Number::from_f64)
+    {
+        return Value::Number(n);
+    }
+    match value.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(value),
+    }
+}
+
+/// Numeric level for a level name and milliseconds for an RFC 3339 time, so text
+/// logs sort, color and filter like JSON ones; other values are kept as they are
+fn standard_field_value(key: &str, value: Value) -> Value {
+    let converted = match (key, &value) {
+        ("level" | "lvl", Value::String(name)) => {
+            LogLevel::from_name(name).map(|level| Value::from(level.as_u64()))
+        }
+        ("time" | "timestamp", Value::String(time)) => timestamp_ms(time).map(Value::from),
+        _ => None,
+    };
+    converted.unwrap_or(value)
+}
+
+/// Milliseconds since the epoch for an RFC 3339 timestamp
+fn timestamp_ms(text: &str) -> Option<i64> {
+    text.parse::<jiff::Timestamp>()
+        .ok()
+        .map(|ts| ts.as_millisecond())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let (log, duplicates) = parse_logfmt_line(
+            r#"time=2024-01-15T10:30:00Z level=warn msg="disk \"/\" almost full" pct=93.5 retry=3 ok=false cached tag=a tag=b"#,
+        )
+        .unwrap();
+
+        assert_eq!(log.get_timestamp_ms(), Some(1705314600000));
+        assert_eq!(log.get_level(), Some(LogLevel::Warn));
+        assert_eq!(log.get_message(), Some("disk \"/\" almost full"));
+        assert_eq!(log.get_field("pct"), Some(&Value::from(93.5)));
+        assert_eq!(log.get_field("retry"), Some(&Value::from(3)));
+        assert_eq!(log.get_field("ok"), Some(&Value::Bool(false)));
+        assert_eq!(log.get_field("cached"), Some(&Value::Bool(true)));
+        assert_eq!(log.get_field("tag_2"), Some(&Value::from("b")));
+        assert_eq!(duplicates, vec!["tag"]);
+    }
+
+    #[test]
+    fn test_invalid_logfmt_line() {
+        assert!(parse_logfmt_line("just some words").is_err());
+        assert!(parse_logfmt_line(r#"msg="unterminated"#).is_err());
+        assert!(parse_logfmt_line("=value").is_err());
+    }
+
+    #[test]
+    fn test_is_logfmt_line() {
+        assert!(is_logfmt_line("level=info msg=started port=8080"));
+        assert!(!is_logfmt_line("Connection failed for user id=5"));
+        assert!(!is_logfmt_line("plain text"));
+    }
+
+    #[test]
+    fn test_parse_plain_line() {
+        let (log, _) =
+            parse_plain_line("2024-01-15T10:30:00Z [ERROR] connection refused\n").unwrap();
+        assert_eq!(log.get_timestamp_ms(), Some(1705314600000));
+        assert_eq!(log.get_level(), Some(LogLevel::Error));
+        assert_eq!(log.get_message(), Some("connection refused"));
+
+        let (log, _) = parse_plain_line("server started on port 8080").unwrap();
+        assert_eq!(log.get_level(), None);
+        assert_eq!(log.get_message(), Some("server started on port 8080"));
+        assert!(parse_plain_line("   ").is_err());
+    }
+}
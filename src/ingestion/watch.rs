@@ -0,0 +1,220 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::follow::FileFollower;
+use crate::ingestion::models::JsonLog;
+use crate::ingestion::prefix::LinePrefix;
+use crate::ingestion::reader::{DEFAULT_MAX_LINE_BYTES, is_gzip_path};
+use crate::ingestion::rotation::{Rotation, rotated_log_files, rotation_key, tag_source};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rootcause::prelude::ResultExt;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, channel};
+
+/// Follows every live log file in a directory, including files created later
+///
+/// Live files are the ones a logger writes to: not hidden, not compressed and
+/// not a rotation (`app.log.1`, `app.log-20240101`), so rotated copies are never
+/// read twice. New files are read from the start, which covers lines written
+/// between creation and the first poll. Logs are tagged with their file name
+/// in `_source`.
+pub struct DirectoryWatcher {
+    dir: PathBuf,
+    /// Kept alive so events keep arriving
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    /// Followed files with their `source` tag
+    followers: Vec<(String, FileFollower)>,
+    /// File names already read; names rather than paths since some backends
+    /// report absolute paths for a relative `dir`
+    known: HashSet<OsString>,
+    /// Whether files created before the watch started have been picked up
+    scanned: bool,
+    line_prefix: LinePrefix,
+    line_numbers: bool,
+    max_line_bytes: usize,
+}
+
+impl DirectoryWatcher {
+    /// Start watching `dir`; call `follow_loaded` for the files already read
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let (tx, events) = channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(LogViewerError::from)
+            .attach("Failed to create directory watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to watch {}", dir.display()))?;
+
+        Ok(Self {
+            dir,
+            _watcher: watcher,
+            events,
+            followers: Vec::new(),
+            known: HashSet::new(),
+            scanned: false,
+            line_prefix: LinePrefix::None,
+            line_numbers: false,
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+        })
+    }
+
+    /// Strip a container runtime prefix (Docker/CRI) before parsing each line
+    pub fn with_line_prefix(mut self, line_prefix: LinePrefix) -> Self {
+        self.line_prefix = line_prefix;
+        self
+    }
+
+    /// Record each log's source line number
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Skip lines longer than `max_line_bytes` in every followed file
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self
+    }
+
+    /// Keep following a file loaded up front, from where loading stopped
+    /// Rotated and compressed files are only marked as seen
+    pub fn follow_loaded(&mut self, path: &Path, offset: u64, lines_read: usize) -> Result<()> {
+        let Some(name) = path.file_name() else {
+            return Ok(());
+        };
+        if self.known.insert(name.to_os_string()) && is_live_log_file(path) {
+            self.follow(path, offset, lines_read)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the lines written since the last poll, from new and existing files
+    pub fn poll(&mut self) -> Result<Vec<JsonLog>> {
+        if !self.scanned {
+            // Files created between loading and the watch starting sent no event
+            self.scanned = true;
+            for path in rotated_log_files(&self.dir)? {
+                self.add_new_file(path);
+            }
+        }
+
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Remove(_) | EventKind::Access(_)) {
+                        continue;
+                    }
+                    for path in event.paths {
+                        self.add_new_file(path);
+                    }
+                }
+                Err(e) => tracing::warn!("Directory watch error: {}", e),
+            }
+        }
+
+        let mut logs = Vec::new();
+        for (source, follower) in &mut self.followers {
+            match follower.poll() {
+                Ok(new_logs) => logs.extend(new_logs.into_iter().map(|mut log| {
+                    tag_source(&mut log, source);
+                    log
+                })),
+                Err(e) => tracing::warn!("Failed to read {}: {:?}", source, e),
+            }
+        }
+
+        Ok(logs)
+    }
+
+    /// Follow a file seen for the first time from its start
+    fn add_new_file(&mut self, path: PathBuf) {
+        let Some(name) = path.file_name().map(|n| n.to_os_string()) else {
+            return;
+        };
+        if self.known.contains(&name) || !path.is_file() || !is_live_log_file(&path) {
+            return;
+        }
+        // A file removed again before it could be opened is retried on its next event
+        match self.follow(&path, 0, 0) {
+            Ok(()) => {
+                tracing::info!("Watching new file {}", path.display());
+                self.known.insert(name);
+            }
+            Err(e) => tracing::debug!("Could not open {}: {:?}", path.display(), e),
+        }
+    }
+
+    fn follow(&mut self, path: &Path, offset: u64, lines_read: usize) -> Result<()> {
+        let mut follower = FileFollower::new(path, offset)?
+            .with_line_prefix(self.line_prefix)
+            .with_max_line_bytes(self.max_line_bytes);
+        if self.line_numbers {
+            follower = follower.with_line_numbers(lines_read);
+        }
+        let source = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.followers.push((source, follower));
+        Ok(())
+    }
+}
+
+/// Whether a logger may still append to `path`: not hidden, compressed or rotated
+pub fn is_live_log_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    !name.starts_with('.') && !is_gzip_path(path) && rotation_key(name).1 == Rotation::Live
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::models::SOURCE_FIELD;
+    use serde_json::json;
+    use std::io::Write;
+
+    #[test]
+    fn test_is_live_log_file() {
+        assert!(is_live_log_file(Path::new("logs/app.log")));
+        assert!(is_live_log_file(Path::new("logs/app-2024010112.log")));
+        assert!(!is_live_log_file(Path::new("logs/app.log.1")));
+        assert!(!is_live_log_file(Path::new("logs/app.log-20240101")));
+        assert!(!is_live_log_file(Path::new("logs/app.log.gz")));
+        assert!(!is_live_log_file(Path::new("logs/.app.log.swp")));
+    }
+
+    #[test]
+    fn test_watcher_reads_new_files() {
+        let dir = std::env::temp_dir().join(format!("log-viewer-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("old.log"), "{\"msg\":\"loaded\"}\n").unwrap();
+        std::fs::write(dir.join("old.log.1"), "{\"msg\":\"rotated\"}\n").unwrap();
+
+        let mut watcher = DirectoryWatcher::new(&dir).unwrap();
+        watcher.follow_loaded(&dir.join("old.log"), 17, 1).unwrap();
+
+        // Created and written before the first poll, last line still incomplete
+        let new_log = dir.join("new.log");
+        std::fs::write(&new_log, "{\"msg\":\"a\"}\n{\"msg\":").unwrap();
+        let logs = watcher.poll().unwrap();
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&new_log)
+            .unwrap();
+        file.write_all(b"\"b\"}\n").unwrap();
+        let later = watcher.poll().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].get_message(), Some("a"));
+        assert_eq!(logs[0].get_field(SOURCE_FIELD), Some(&json!("new.log")));
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].get_message(), Some("b"));
+    }
+}
@@ -1,16 +1,27 @@
+pub mod cli;
 pub mod error;
+pub mod export;
 pub mod ingestion;
 pub mod storage;
 pub mod ui;
 
+#[cfg(test)]
+mod tui_tests;
+
+use cli::CliArgs;
 use error::Result;
-use ingestion::LogFileReader;
+use ingestion::{
+    DEFAULT_FOLLOW_BATCH, DEFAULT_FOLLOW_FLUSH, DirectoryWatcher, FileFollower, FollowBuffer,
+    LinePrefix, LogFileReader, SocketAddress, SocketFollower, SortOnLoad,
+};
 use ratatui::layout::{Constraint, Layout};
-use rootcause::prelude::ResultExt;
-use storage::LogDatabase;
+use rootcause::prelude::{Report, ResultExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use storage::{DEFAULT_INSERT_BATCH, DEFAULT_SCHEMA_SAMPLE, ExternalFormat, LogDatabase};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::prelude::*;
-use ui::{App, cleanup_terminal, handle_events, setup_terminal};
+use ui::{App, DisplayOptions, cleanup_terminal, handle_events, setup_terminal};
 
 fn main() -> Result<()> {
     let _ = tui_logger::init_logger(tui_logger::LevelFilter::Debug);
@@ -24,41 +35,175 @@ fn main() -> Result<()> {
     tracing::info!("Starting log-viewer application");
 
     // Parse command-line arguments
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <log-file-path>", args[0]);
-        std::process::exit(1);
-    }
+    let program = std::env::args()
+        .next()
+        .unwrap_or_else(|| "log-viewer".to_string());
+    let mut args = match CliArgs::parse_from(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", cli::usage(&program));
+            std::process::exit(1);
+        }
+    };
 
-    let log_file = &args[1];
+    detect_profile(&mut args);
+    let log_file = &args.log_file;
     tracing::info!("Loading log file: {}", log_file);
 
-    // Load and parse logs
-    let logs = load_logs(log_file)?;
+    let (db, logs, follower) = if let Some(address) = args.stream.clone() {
+        // `--socket`/`--tcp`: `log_file` only names the address
+        load_stream(address, &args)?
+    } else {
+        match ExternalFormat::from_path(log_file) {
+            // Parquet/CSV: let DuckDB load the table, then read the rows back
+            Some(format) => {
+                if args.follow || args.watch_dir {
+                    eprintln!("--follow and --watch-dir only work with JSON lines files");
+                    std::process::exit(1);
+                }
+                if args.sort_on_load != SortOnLoad::None {
+                    tracing::warn!(
+                        "--sort-on-load only applies to JSON lines input; keeping file order"
+                    );
+                }
+                if args.line_numbers {
+                    tracing::warn!("--line-numbers only applies to JSON lines input");
+                }
+                if args.coerce_strings {
+                    tracing::warn!("--coerce-strings only applies to JSON lines input");
+                }
+                if args.normalize_levels {
+                    tracing::warn!("--normalize-levels only applies to JSON lines input");
+                }
+                if args.two_pass {
+                    tracing::warn!(
+                        "--two-pass only applies to JSON lines input; DuckDB reads the types"
+                    );
+                }
+                if args.db_file.is_some() {
+                    tracing::warn!("--db-file only applies to JSON lines input");
+                }
+                let (db, logs) = load_external(log_file, format)?;
+                (db, logs, None)
+            }
+            None => load_json(log_file, &args)?,
+        }
+    };
 
-    if logs.is_empty() {
-        eprintln!("No logs to display. Exiting.");
-        std::process::exit(1);
+    // Extensions are best-effort: offline, a failed install only loses its functions
+    load_extensions(&db, &args);
+
+    // Headless export: write Parquet and skip the TUI
+    if let Some(parquet_path) = &args.to_parquet {
+        let exported = db
+            .export_parquet(parquet_path)
+            .attach("Failed to export logs to Parquet")?;
+        println!("Wrote {} logs to {}", exported, parquet_path);
+        return Ok(());
     }
 
-    // Create database and insert logs
-    let mut db = LogDatabase::new_in_memory().attach("Failed to create database")?;
-    db.create_table_from_logs(&logs, 100)
-        .attach("Failed to create table from logs")?;
-    db.insert_logs(&logs)
-        .attach("Failed to insert logs into database")?;
+    // Headless check for CI: fail if any log matches
+    if !args.fail_if.is_empty() {
+        let matched = check_fail_if(&db, &args.fail_if, args.show_matches)?;
+        std::process::exit(if matched > 0 { 1 } else { 0 });
+    }
 
     // Setup terminal
     let mut terminal = setup_terminal()?;
+    if args.set_title {
+        ui::save_title()?;
+    }
 
     // Create app state
-    let mut app = App::new(db, logs).attach("Failed to initialize app")?;
+    let mut display = DisplayOptions {
+        hidden_fields: args.hidden_fields.iter().cloned().collect(),
+        time_format: args.time_format.clone(),
+        time_precision: args.time_precision,
+        age_reference: args.age_reference,
+        visible_columns: args.columns.clone(),
+        wide_mode: args.wide,
+        show_logger: args.show_logger,
+        show_source: args.show_source,
+        // Logs from a directory are tagged with their file
+        show_source_file: Path::new(log_file).is_dir(),
+        scrolloff: args.scrolloff,
+        badges: args.badges.clone(),
+        line_numbers: args.line_numbers,
+        lenient_levels: args.lenient_levels,
+        level_field: args.level_field.clone(),
+        message_fields: args.message_fields.clone(),
+        group_by: args.group_by.clone(),
+        grouped: args.group_by.is_some(),
+        monochrome: args.no_color || ui::color::color_disabled_by_env(),
+        ..Default::default()
+    };
+    if let Some(separator) = &args.message_separator {
+        display.message_separator = separator.clone();
+    }
+    if !args.trace_fields.is_empty() {
+        display.trace_fields = args.trace_fields.clone();
+    }
+    if !args.error_fields.is_empty() {
+        display.error_fields = args.error_fields.clone();
+    }
+    if let Some(time_zone) = &args.time_zone {
+        display.time_zone = time_zone.clone();
+    }
+    if args.normalize_levels {
+        // Only rows read back from the database have it, so it would show on filtered rows alone
+        display
+            .hidden_fields
+            .insert(storage::LEVEL_NUM_FIELD.to_string());
+    }
+    if args.hide_constant {
+        tracing::info!("Hiding constant fields: {:?}", db.constant_fields());
+        display.constant_fields = db.constant_fields().iter().cloned().collect();
+    }
+    let mut app = App::new(db, logs, display).attach("Failed to initialize app")?;
+    let size = terminal.size().map_err(error::LogViewerError::from)?;
+    app.resize_volume(size.width);
+    app.following = follower.is_some();
+    app.redacted_fields = args.redact.clone();
+    if let Some(limit) = args.memory_limit {
+        app.set_memory_limit(limit);
+    }
+    app.result_limit = args.result_limit;
+    // Open already filtered; an invalid clause opens unfiltered, left in the input to fix
+    if let Some(filter) = &args.filter {
+        if let Err(e) = app.apply_preset_filter(filter) {
+            tracing::warn!("Ignoring invalid --filter '{}': {:?}", filter, e);
+            app.set_status(format!("Invalid --filter, showing all logs: {}", filter));
+        }
+    }
+    let buffer = FollowBuffer::new(
+        args.follow_batch.unwrap_or(DEFAULT_FOLLOW_BATCH),
+        args.follow_flush_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_FOLLOW_FLUSH),
+    );
 
     // Main event loop
-    let result = run_app(&mut terminal, &mut app);
+    let title_source = args.set_title.then(|| window_title_source(log_file));
+    let idle_after = match args.idle_after_secs {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(ui::DEFAULT_IDLE_AFTER),
+    };
+    let result = run_app(
+        &mut terminal,
+        &mut app,
+        follower,
+        buffer,
+        title_source.as_deref(),
+        idle_after,
+    );
 
     // Cleanup terminal
     cleanup_terminal()?;
+    if args.set_title {
+        ui::restore_title()?;
+    }
 
     // Handle any errors that occurred during the app run
     result?;
@@ -66,25 +211,469 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn load_logs(log_file: &str) -> Result<Vec<ingestion::JsonLog>> {
-    let mut reader = LogFileReader::new(log_file)
-        .attach_with(|| format!("Failed to open log file: {}", log_file))?;
+/// Without `--profile`, apply the profile detected from the first lines of a text file
+/// Directories and Parquet/CSV files keep the defaults
+fn detect_profile(args: &mut CliArgs) {
+    let path = Path::new(&args.log_file);
+    if args.profile.is_some()
+        || !path.is_file()
+        || ExternalFormat::from_path(&args.log_file).is_some()
+    {
+        return;
+    }
+    // An unreadable file is reported when it is loaded
+    let Ok(lines) = ingestion::reader::read_first_lines(path, cli::PROFILE_DETECT_LINES) else {
+        return;
+    };
+    let profile = cli::Profile::detect(&lines);
+    tracing::info!("Detected the {} profile", profile.as_str());
+    profile.apply(args);
+    args.profile = Some(profile);
+}
+
+/// Where `run_app` picks up logs written after loading
+enum LiveSource {
+    /// `--follow`
+    File(FileFollower),
+    /// `--watch-dir`
+    Directory(DirectoryWatcher),
+    /// `--socket` or `--tcp`
+    Socket(SocketFollower),
+}
+
+impl LiveSource {
+    fn poll(&mut self) -> Result<Vec<ingestion::JsonLog>> {
+        match self {
+            LiveSource::File(follower) => follower.poll(),
+            LiveSource::Directory(watcher) => watcher.poll(),
+            LiveSource::Socket(follower) => follower.poll(),
+        }
+    }
+
+    /// Whether new logs can arrive; only a socket can lose its connection
+    fn is_connected(&self) -> bool {
+        match self {
+            LiveSource::Socket(follower) => follower.is_connected(),
+            LiveSource::File(_) | LiveSource::Directory(_) => true,
+        }
+    }
+}
+
+/// Parse a JSON lines file and ingest it into a new database
+/// With `--follow` or `--watch-dir`, also returns a live source positioned where loading stopped
+fn load_json(
+    log_file: &str,
+    args: &CliArgs,
+) -> Result<(LogDatabase, Vec<ingestion::JsonLog>, Option<LiveSource>)> {
+    let is_dir = Path::new(log_file).is_dir();
+    if args.follow && (is_dir || ingestion::reader::is_gzip_path(Path::new(log_file))) {
+        eprintln!(
+            "--follow only works with a single uncompressed file; use --watch-dir for a directory"
+        );
+        std::process::exit(1);
+    }
+    if args.watch_dir && !is_dir {
+        eprintln!("--watch-dir needs a directory; use --follow for a single file");
+        std::process::exit(1);
+    }
+
+    // Load and parse logs
+    let line_prefix = args.line_prefix.unwrap_or_default();
+    let max_line_bytes = args
+        .max_line_bytes
+        .unwrap_or(ingestion::reader::DEFAULT_MAX_LINE_BYTES);
+    // Paged views are read back from the database, which has no placeholders
+    let show_unparsed = args.show_unparsed && args.memory_limit.is_none();
+    if args.show_unparsed && !show_unparsed {
+        tracing::warn!("--show-unparsed is ignored with --memory-limit");
+    }
+    let loaded = if is_dir {
+        load_directory(
+            log_file,
+            line_prefix,
+            args.line_numbers,
+            max_line_bytes,
+            show_unparsed,
+            args.watch_dir,
+        )?
+    } else {
+        load_logs(
+            log_file,
+            line_prefix,
+            args.line_numbers,
+            max_line_bytes,
+            show_unparsed,
+            args.follow,
+        )?
+    };
+
+    if loaded.logs.iter().all(|log| log.is_unparsed()) {
+        report_no_logs(log_file, &loaded);
+        std::process::exit(1);
+    }
+
+    // Sort before inserting so row ids (and filtered results) follow the same order
+    let mut logs = loaded.logs;
+    if args.sort_tiebreak.is_some() && args.sort_on_load == SortOnLoad::None {
+        tracing::warn!("--sort-tiebreak only applies with --sort-on-load time");
+    }
+    ingestion::sort_logs(
+        &mut logs,
+        args.sort_on_load,
+        args.untimed,
+        args.sort_tiebreak.as_deref(),
+    );
+
+    let (db, logs) = create_database(logs, args)?;
+
+    let follower = if args.follow {
+        let mut follower = FileFollower::new(log_file, loaded.end_offset)?
+            .with_line_prefix(line_prefix)
+            .with_max_line_bytes(max_line_bytes);
+        if args.line_numbers {
+            follower = follower.with_line_numbers(loaded.end_line);
+        }
+        Some(LiveSource::File(follower))
+    } else if args.watch_dir {
+        let mut watcher = DirectoryWatcher::new(log_file)?
+            .with_line_prefix(line_prefix)
+            .with_line_numbers(args.line_numbers)
+            .with_max_line_bytes(max_line_bytes);
+        for (path, offset, lines_read) in &loaded.file_ends {
+            watcher.follow_loaded(path, *offset, *lines_read)?;
+        }
+        Some(LiveSource::Directory(watcher))
+    } else {
+        None
+    };
+
+    Ok((db, logs, follower))
+}
+
+/// Load `--extension`s and build the `--fts` index, warning about failures
+fn load_extensions(db: &LogDatabase, args: &CliArgs) {
+    for name in &args.extensions {
+        if let Err(e) = db.load_extension(name) {
+            tracing::warn!("Skipping extension '{}': {:?}", name, e);
+        }
+    }
+    if args.fts {
+        match db.create_fts_index() {
+            Ok(Some(column)) => tracing::info!(
+                "Search '{}' with fts_main_{}.match_bm25(_row_id, 'term') IS NOT NULL",
+                column,
+                db.table_name()
+            ),
+            Ok(None) => tracing::warn!("--fts needs a message field; no index built"),
+            Err(e) => tracing::warn!("Skipping --fts: {:?}", e),
+        }
+    }
+}
+
+/// Open `--db-file`, dropping its log table with `--rebuild-db`, or an in-memory database
+fn open_database(args: &CliArgs) -> Result<LogDatabase> {
+    let Some(path) = &args.db_file else {
+        return LogDatabase::new_in_memory().attach("Failed to create database");
+    };
+    let mut db = LogDatabase::new_with_file(path)
+        .attach_with(|| format!("Failed to open --db-file {}", path))?;
+    if args.rebuild_db {
+        db.drop_table()
+            .attach("Failed to drop the log table for --rebuild-db")?;
+    }
+    Ok(db)
+}
+
+/// Create the database, typing its columns from `logs`, and insert them
+/// A `--db-file` table kept from an earlier run has older rows; all of them
+/// are returned then, so the list shows what filters search
+fn create_database(
+    logs: Vec<ingestion::JsonLog>,
+    args: &CliArgs,
+) -> Result<(LogDatabase, Vec<ingestion::JsonLog>)> {
+    let mut db = open_database(args)?
+        .with_string_coercion(args.coerce_strings)
+        .with_level_normalization(args.normalize_levels)
+        .with_insert_batch(args.insert_batch.unwrap_or(DEFAULT_INSERT_BATCH));
+    // `--two-pass` types columns from every log instead of a sample, so a field
+    // that is usually an integer but sometimes a string becomes TEXT up front
+    let sample_size = if args.two_pass {
+        logs.len()
+    } else {
+        DEFAULT_SCHEMA_SAMPLE
+    };
+    db.create_table_from_logs(&logs, sample_size)
+        .attach("Failed to create table from logs")?;
+    db.insert_logs(&logs)
+        .attach("Failed to insert logs into database")?;
+    warn_out_of_range_timestamps(&logs);
+
+    let inserted = logs.iter().filter(|log| !log.is_unparsed()).count();
+    let stored = db.count_logs().attach("Failed to count stored logs")?;
+    if stored > inserted {
+        tracing::info!(
+            "Added {} logs to the {} already stored",
+            inserted,
+            stored - inserted
+        );
+        let logs = db.query_logs(None).attach("Failed to read stored logs")?;
+        return Ok((db, logs));
+    }
+    Ok((db, logs))
+}
+
+/// One warning for all loaded logs whose time is out of range (shown raw in the list)
+fn warn_out_of_range_timestamps(logs: &[ingestion::JsonLog]) {
+    let count = logs
+        .iter()
+        .filter(|log| log.out_of_range_timestamp().is_some())
+        .count();
+    if count > 0 {
+        tracing::warn!(
+            "{} logs have out-of-range timestamps, showing them raw",
+            count
+        );
+    }
+}
+
+/// Connect to `--socket` or `--tcp` and wait for the logs the schema is typed from
+/// Later lines are read by the returned live source, which reconnects if the connection drops
+fn load_stream(
+    address: SocketAddress,
+    args: &CliArgs,
+) -> Result<(LogDatabase, Vec<ingestion::JsonLog>, Option<LiveSource>)> {
+    let max_line_bytes = args
+        .max_line_bytes
+        .unwrap_or(ingestion::reader::DEFAULT_MAX_LINE_BYTES);
+    let mut follower = SocketFollower::connect(address)?
+        .with_line_prefix(args.line_prefix.unwrap_or_default())
+        .with_max_line_bytes(max_line_bytes);
+    eprintln!("Waiting for logs on {}...", args.log_file);
+    let logs = follower.wait_for_logs(DEFAULT_FOLLOW_FLUSH)?;
+    let (db, logs) = create_database(logs, args)?;
+    Ok((db, logs, Some(LiveSource::Socket(follower))))
+}
+
+/// Load a Parquet/CSV file straight into DuckDB, skipping JSON parsing
+fn load_external(
+    log_file: &str,
+    format: ExternalFormat,
+) -> Result<(LogDatabase, Vec<ingestion::JsonLog>)> {
+    let mut db = LogDatabase::new_in_memory().attach("Failed to create database")?;
+    let count = db
+        .create_table_from_file(log_file, format)
+        .attach_with(|| format!("Failed to load {}", log_file))?;
+
+    if count == 0 {
+        eprintln!("File '{}' contains no rows. Nothing to display.", log_file);
+        std::process::exit(1);
+    }
+
+    let logs = db.query_logs(None).attach("Failed to read loaded rows")?;
+
+    Ok((db, logs))
+}
+
+/// Parsed logs plus enough detail to explain an empty result
+struct LoadedLogs {
+    logs: Vec<ingestion::JsonLog>,
+    /// Number of non-blank lines read
+    line_count: usize,
+    /// Location (e.g. `line 3`) and error of the first line that failed to parse
+    first_error: Option<(String, Report<error::LogViewerError>)>,
+    /// Byte offset where reading stopped, for `--follow`
+    end_offset: u64,
+    /// Line number of the last line read (blank lines included), for `--follow`
+    end_line: usize,
+    /// Path, end offset and end line of each file read from a directory, for `--watch-dir`
+    file_ends: Vec<(PathBuf, u64, usize)>,
+}
 
-    let log_results = reader.read_logs();
+/// With `line_numbers`, each log records its source line in `_line`
+/// Lines over `max_line_bytes` are skipped like unparsable ones; with
+/// `show_unparsed` both become placeholder logs (`JsonLog::unparsed`)
+/// CRI `P` chunks left at the end wait for the follower when `following`,
+/// otherwise they are loaded as they are
+fn load_logs(
+    log_file: &str,
+    line_prefix: LinePrefix,
+    line_numbers: bool,
+    max_line_bytes: usize,
+    show_unparsed: bool,
+    following: bool,
+) -> Result<LoadedLogs> {
+    let mut reader = LogFileReader::new(log_file)
+        .attach_with(|| format!("Failed to open log file: {}", log_file))?
+        .with_line_prefix(line_prefix)
+        .with_max_line_bytes(max_line_bytes)
+        .with_unparsed_placeholders(show_unparsed);
+
+    let mut log_results = reader.read_logs();
+    if !following {
+        log_results.extend(reader.finish_partials());
+    }
+    let line_count = log_results.len();
     let mut parsed_logs = Vec::new();
+    let mut first_error = None;
+
+    for (line_num, result) in log_results {
+        match result {
+            Ok(mut log) => {
+                if line_numbers {
+                    log.set_line_number(line_num);
+                }
+                parsed_logs.push(log);
+            }
+            Err(e) => {
+                // Silently skip parse errors in TUI mode, but keep the first one
+                // to explain an all-invalid file
+                if first_error.is_none() {
+                    first_error = Some((format!("line {}", line_num), e));
+                }
+            }
+        }
+    }
+
+    Ok(LoadedLogs {
+        logs: parsed_logs,
+        line_count,
+        first_error,
+        end_offset: reader.position(),
+        end_line: reader.current_line_number(),
+        file_ends: Vec::new(),
+    })
+}
 
-    for (_line_num, result) in log_results {
-        if let Ok(log) = result {
-            parsed_logs.push(log);
+/// Load every log file in a directory as one timeline, merged by time
+/// (`merge_by_time`) with each file in order, oldest rotation first
+/// Each log is tagged with its file name in `_source`
+/// With `watching`, live files are loaded like `load_logs` with `following`
+fn load_directory(
+    dir: &str,
+    line_prefix: LinePrefix,
+    line_numbers: bool,
+    max_line_bytes: usize,
+    show_unparsed: bool,
+    watching: bool,
+) -> Result<LoadedLogs> {
+    let files = ingestion::rotated_log_files(Path::new(dir))?;
+    let mut file_logs = Vec::new();
+    let mut combined = LoadedLogs {
+        logs: Vec::new(),
+        line_count: 0,
+        first_error: None,
+        end_offset: 0,
+        end_line: 0,
+        file_ends: Vec::new(),
+    };
+
+    for path in files {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let loaded = match load_logs(
+            &path.to_string_lossy(),
+            line_prefix,
+            line_numbers,
+            max_line_bytes,
+            show_unparsed,
+            watching && ingestion::watch::is_live_log_file(&path),
+        ) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {:?}", name, e);
+                continue;
+            }
+        };
+        tracing::info!("Loaded {} logs from {}", loaded.logs.len(), name);
+
+        combined.line_count += loaded.line_count;
+        combined
+            .file_ends
+            .push((path, loaded.end_offset, loaded.end_line));
+        if combined.first_error.is_none() {
+            combined.first_error = loaded
+                .first_error
+                .map(|(location, e)| (format!("{} {}", name, location), e));
         }
-        // Silently skip parse errors in TUI mode
+        let mut logs = loaded.logs;
+        for log in &mut logs {
+            ingestion::tag_source(log, &name);
+        }
+        file_logs.push(logs);
     }
 
-    Ok(parsed_logs)
+    // Interleave separate sets (app.log, worker.log) into one timeline
+    combined.logs = ingestion::merge_by_time(file_logs);
+    Ok(combined)
 }
 
-fn run_app(terminal: &mut ui::terminal::Tui, app: &mut App) -> Result<()> {
+/// Count the logs matching any of the `--fail-if` clauses and print the result
+/// Up to `show` matching logs are printed as JSON lines after the count
+fn check_fail_if(db: &LogDatabase, clauses: &[String], show: usize) -> Result<usize> {
+    let clause = storage::sql::any_clause(clauses);
+    let matched = db
+        .count_matching(Some(&clause))
+        .attach("Invalid --fail-if clause")?;
+    if matched == 0 {
+        println!("No logs match {}", clause);
+        return Ok(0);
+    }
+
+    println!("{} logs match {}", matched, clause);
+    if show > 0 {
+        // Rows have every column; leave out the ones this log doesn't have
+        for log in db.query_page(Some(&clause), show, 0)? {
+            let fields = log.fields.into_iter().filter(|(_, value)| !value.is_null());
+            println!("{}", serde_json::Value::Object(fields.collect()));
+        }
+    }
+    Ok(matched)
+}
+
+/// Explain why there is nothing to display
+fn report_no_logs(log_file: &str, loaded: &LoadedLogs) {
+    if loaded.line_count == 0 {
+        eprintln!("Log file '{}' is empty. Nothing to display.", log_file);
+        return;
+    }
+
+    eprintln!(
+        "Read {} lines from '{}' but none could be parsed as JSON logs.",
+        loaded.line_count, log_file
+    );
+
+    if let Some((location, error)) = &loaded.first_error {
+        eprintln!("\nFirst failure at {}:\n{:?}", location, error);
+    }
+
+    eprintln!(
+        "\nThe file may not be in JSON lines format. \
+         If lines carry a container runtime prefix, try `--prefix auto`."
+    );
+}
+
+fn run_app(
+    terminal: &mut ui::terminal::Tui,
+    app: &mut App,
+    mut follower: Option<LiveSource>,
+    mut buffer: FollowBuffer,
+    title_source: Option<&str>,
+    idle_after: Option<Duration>,
+) -> Result<()> {
+    let mut window_title = None;
+    let mut idle = ui::IdleTimer::new(idle_after, Instant::now());
     loop {
+        // Keep the window title in step with the active filter (`--set-title`)
+        if let Some(source) = title_source {
+            let title = create_window_title(source, app);
+            if window_title.as_ref() != Some(&title) {
+                ui::set_title(&title)?;
+                window_title = Some(title);
+            }
+        }
+
         // Draw UI
         terminal
             .draw(|frame| {
@@ -93,41 +682,54 @@ fn run_app(terminal: &mut ui::terminal::Tui, app: &mut App) -> Result<()> {
             .map_err(error::LogViewerError::from)
             .attach("Failed to draw UI")?;
 
-        // Get the height of the log list area for pagination
-        let area = terminal.size().map_err(error::LogViewerError::from)?;
-        let page_height = calculate_log_list_height(area.height, app.show_detail_panel);
+        // Page by the list rows the frame just drawn showed
+        let page_height = app.page_height;
 
-        // Handle events
-        handle_events(app, page_height)?;
+        // Handle events; without logs to follow, an idle loop sleeps until the next one
+        handle_events(app, page_height, &mut idle, follower.is_some())?;
 
-        // Check if we should quit
+        // Check if we should quit, inserting any logs still buffered
         if app.should_quit {
+            if let Err(e) = app.append_logs(buffer.take()) {
+                tracing::warn!("Failed to store followed logs on quit: {:?}", e);
+            }
             break;
         }
+
+        // Pick up lines appended since the last tick, inserting them in batches
+        if let Some(follower) = follower.as_mut() {
+            match follower.poll() {
+                Ok(new_logs) => buffer.push(new_logs, Instant::now()),
+                Err(e) => app.set_status(format!("Follow error: {}", e)),
+            }
+            app.disconnected = !follower.is_connected();
+            if buffer.is_due(Instant::now())
+                && let Err(e) = app.append_logs(buffer.take())
+            {
+                app.set_status(format!("Follow error: {}", e));
+            }
+        }
     }
 
     Ok(())
 }
 
-fn calculate_log_list_height(total_height: u16, show_detail: bool) -> usize {
-    if show_detail {
-        // Split screen: top half for logs, bottom half for details
-        ((total_height / 2).saturating_sub(4)) as usize
-    } else {
-        // Full screen for logs
-        (total_height.saturating_sub(6)) as usize
-    }
-}
-
-fn render_ui(frame: &mut ratatui::Frame, app: &App) {
-    use ui::components::{filter_panel, help_menu};
+fn render_ui(frame: &mut ratatui::Frame, app: &mut App) {
+    use ui::components::{
+        command_palette, filter_panel, help_menu, log_detail, numeric_filter, schema_summary,
+    };
 
     let area = frame.area();
 
     // If filter panel is shown, render it as an overlay
     if app.show_filter_panel {
-        // Render main content first (dimmed)
+        // Render main content first, dimmed; filters applied with the panel
+        // kept open show their results here
         render_main_content(frame, app, area);
+        frame.buffer_mut().set_style(
+            area,
+            ratatui::style::Style::default().add_modifier(ratatui::style::Modifier::DIM),
+        );
 
         // Render filter panel as centered overlay
         let popup_width = 80;
@@ -146,6 +748,8 @@ fn render_ui(frame: &mut ratatui::Frame, app: &App) {
             &app.field_schema,
             &app.filter_input,
             app.filter_error.as_deref(),
+            app.filter_editor,
+            (app.focus == ui::Focus::FilterPresets).then_some(app.filter_field),
             popup_area,
             frame.buffer_mut(),
         );
@@ -154,14 +758,80 @@ fn render_ui(frame: &mut ratatui::Frame, app: &App) {
         render_main_content(frame, app, area);
     }
 
+    // Command palette overlays the current view
+    if app.show_command_palette {
+        command_palette::render_command_palette(
+            &app.palette_input,
+            &app.palette_matches(),
+            app.palette_selected,
+            area,
+            frame.buffer_mut(),
+        );
+    }
+
+    // Numeric filter prompt overlays the current view
+    if let Some(prompt) = &app.numeric_filter {
+        numeric_filter::render_numeric_filter(prompt, area, frame.buffer_mut());
+    }
+
+    // Detail modal covers the whole view
+    if app.show_detail_modal {
+        if let Some(log) = app.detail_log() {
+            // Clamp so scrolling back up starts moving immediately after G
+            let max_scroll = log_detail::max_detail_modal_scroll(
+                log,
+                app.detail_previous_log(),
+                &app.display,
+                area,
+            );
+            app.detail_modal_scroll = app.detail_modal_scroll.min(max_scroll);
+        }
+        // While typing a search, follow the first match instead
+        let scroll = (app.focus != ui::Focus::DetailSearch).then_some(app.detail_modal_scroll);
+        log_detail::render_detail_modal(
+            app.detail_log(),
+            app.detail_previous_log(),
+            log_detail::LogDetailState::new(app.selected_index, app.current_logs().len())
+                .with_search(Some(app.detail_search.clone()))
+                .with_scroll(scroll)
+                .with_pinned(app.pinned_log.is_some()),
+            &app.display,
+            &app.field_schema,
+            area,
+            frame.buffer_mut(),
+        );
+    }
+
+    // Schema summary overlays the current view
+    if let Some(fields) = &app.schema_summary {
+        // Clamp so scrolling back up starts moving immediately after G
+        app.schema_summary_scroll = app
+            .schema_summary_scroll
+            .min(schema_summary::max_schema_summary_scroll(fields.len(), area));
+        schema_summary::render_schema_summary(
+            fields,
+            app.all_logs.len(),
+            app.schema_summary_scroll,
+            area,
+            frame.buffer_mut(),
+        );
+    }
+
     // Help menu has highest priority - render on top of everything
     if app.show_help {
-        help_menu::render_help_menu(area, frame.buffer_mut());
+        // Clamp so scrolling back up starts moving immediately after G
+        app.help_scroll = app.help_scroll.min(help_menu::max_help_scroll(area));
+        help_menu::render_help_menu(app.help_scroll, area, frame.buffer_mut());
+    }
+
+    // Without color support, the finished frame loses its colors in one place
+    if app.display.monochrome {
+        ui::color::strip_colors(frame.buffer_mut());
     }
 }
 
-fn render_main_content(frame: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
-    use ui::components::{debug_logs, log_detail, log_list};
+fn render_main_content(frame: &mut ratatui::Frame, app: &mut App, area: ratatui::layout::Rect) {
+    use ui::components::{debug_logs, facets, log_detail, log_list, volume};
 
     // If debug logs are shown, split the screen
     let (main_area, debug_area) = if app.show_debug_logs {
@@ -172,56 +842,171 @@ fn render_main_content(frame: &mut ratatui::Frame, app: &App, area: ratatui::lay
         (area, None)
     };
 
+    // The volume sparkline takes the bottom row, when the terminal is wide enough
+    let (main_area, volume_area) =
+        if app.show_volume && main_area.width >= volume::MIN_VOLUME_WIDTH {
+            let chunks =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(main_area);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (main_area, None)
+        };
+
+    // If facets are shown, reserve a sidebar on the right
+    let (main_area, facets_area) = if app.show_facets {
+        let chunks =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(40)]).split(main_area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (main_area, None)
+    };
+
     // Create layout based on whether detail panel is shown
     if app.show_detail_panel {
-        // Split view: logs on top, detail on bottom
-        let chunks = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(main_area);
+        // Split view: logs on top, detail on bottom (resized with `+`/`-`)
+        let chunks = Layout::vertical([
+            Constraint::Percentage(app.list_percent),
+            Constraint::Percentage(100 - app.list_percent),
+        ])
+        .split(main_area);
 
         // Render log list
+        update_scroll_offset(app, chunks[0]);
         let logs = app.current_logs();
         let title = create_log_list_title(app);
         log_list::render_log_list(
             logs,
-            app.selected_index,
+            log_list::LogListState::new(app.selected_index, app.scroll_offset)
+                .with_rows(app.list_rows()),
             title,
+            list_status(app).as_deref(),
+            &app.display,
             chunks[0],
             frame.buffer_mut(),
         );
 
-        // Render log detail
-        let selected_log = app.selected_log();
+        // Render log detail, of the pinned log if there is one
         let total_logs = logs.len();
+        let selected_field = (app.focus == ui::Focus::Detail).then_some(app.detail_field);
         log_detail::render_log_detail(
-            selected_log,
-            app.selected_index,
-            total_logs,
+            app.detail_log(),
+            app.detail_previous_log(),
+            log_detail::LogDetailState::new(app.selected_index, total_logs)
+                .with_selected_field(selected_field)
+                .with_search(Some(app.detail_search.clone()))
+                .with_pinned(app.pinned_log.is_some()),
+            &app.display,
+            &app.field_schema,
             chunks[1],
             frame.buffer_mut(),
         );
     } else {
         // Full screen log list
+        update_scroll_offset(app, main_area);
         let logs = app.current_logs();
         let title = create_log_list_title(app);
         log_list::render_log_list(
             logs,
-            app.selected_index,
+            log_list::LogListState::new(app.selected_index, app.scroll_offset)
+                .with_rows(app.list_rows()),
             title,
+            list_status(app).as_deref(),
+            &app.display,
             main_area,
             frame.buffer_mut(),
         );
     }
 
+    // Render the volume sparkline, queried by the app when the view or the width changes
+    if let Some(volume_area) = volume_area {
+        volume::render_volume(&app.volume, volume_area, frame.buffer_mut());
+    }
+
+    // Render facets sidebar if enabled, querying again if the view changed
+    if let Some(facets_area) = facets_area {
+        app.refresh_facets();
+        facets::render_facets(
+            app.facet_field(),
+            &app.facets,
+            app.focus == ui::Focus::Facets,
+            facets_area,
+            frame.buffer_mut(),
+        );
+    }
+
     // Render debug logs if enabled
     if let Some(debug_area) = debug_area {
         debug_logs::render_debug_logs(debug_area, frame.buffer_mut());
     }
 }
 
+/// Keep the list offset from the previous frame, scrolling only as needed for `scrolloff`
+fn update_scroll_offset(app: &mut App, list_area: ratatui::layout::Rect) {
+    use ui::components::log_list;
+
+    // Recorded for the next key press, so paging matches what is on screen
+    app.page_height = log_list::visible_rows(list_area);
+    app.scroll_offset = log_list::compute_scroll_offset(
+        app.scroll_offset,
+        app.selected_index,
+        app.row_count(),
+        app.page_height,
+        app.display.scrolloff,
+    );
+}
+
+/// Name shown in the window title: the file or directory name without its path
+fn window_title_source(log_file: &str) -> String {
+    Path::new(log_file)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| log_file.to_string())
+}
+
+fn create_window_title(source: &str, app: &App) -> String {
+    match &app.active_filter {
+        Some(filter) => format!("log-viewer: {} [{}]", source, filter),
+        None => format!("log-viewer: {}", source),
+    }
+}
+
+/// Text for the list's bottom border: the message quick filter prompt while
+/// it is open, otherwise the status message
+fn list_status(app: &App) -> Option<String> {
+    if app.focus == ui::Focus::MessageSearch {
+        return Some(format!("Message contains: {}_", app.message_search_input));
+    }
+    app.status_message.clone()
+}
+
 fn create_log_list_title(app: &App) -> String {
-    let total = app.current_logs().len();
+    let total = app.total_log_count();
+    let mut flags = String::new();
+    let in_memory = app.current_logs().len();
+    if app.is_result_limited() {
+        flags.push_str(&format!(" [showing {}, limited: M loads more]", in_memory));
+    } else if in_memory < total {
+        flags.push_str(&format!(" [{} in memory]", in_memory));
+    }
+    if app.display.newest_first {
+        flags.push_str(" [newest first]");
+    }
+    if app.disconnected {
+        flags.push_str(" [disconnected, retrying]");
+    } else if app.following {
+        flags.push_str(" [following]");
+    }
+    if let Some(term) = &app.message_search {
+        return format!(
+            "Log Viewer - {} logs{} (Message contains: {})",
+            total, flags, term
+        );
+    }
     match &app.active_filter {
-        Some(filter) => format!("Log Viewer - {} logs (Filtered: {})", total, filter),
-        None => format!("Log Viewer - {} logs", total),
+        Some(filter) => format!(
+            "Log Viewer - {} logs{} (Filtered: {})",
+            total, flags, filter
+        ),
+        None => format!("Log Viewer - {} logs{}", total, flags),
     }
 }
@@ -1,14 +1,72 @@
 use crate::error::{LogViewerError, Result};
 use crate::ingestion::JsonLog;
-use crate::storage::schema::{SchemaBuilder, normalize_field_name};
+use crate::storage::schema::{
+    FieldType, ROW_ID_COLUMN, SchemaBuilder, coerce_value, normalize_field_name,
+};
+use crate::storage::sql::{quote_identifier, quote_literal};
+use duckdb::types::Value as DuckValue;
 use duckdb::{Connection, params_from_iter};
 use rootcause::prelude::*;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Tabular file formats DuckDB can load directly, bypassing JSON parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalFormat {
+    Parquet,
+    Csv,
+}
+
+impl ExternalFormat {
+    /// Detect the format from a file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "parquet" => Some(ExternalFormat::Parquet),
+            "csv" | "tsv" => Some(ExternalFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// DuckDB table function reading this format
+    fn reader_function(&self) -> &'static str {
+        match self {
+            ExternalFormat::Parquet => "read_parquet",
+            ExternalFormat::Csv => "read_csv_auto",
+        }
+    }
+}
+
+/// Table used unless `with_table_name` picks another
+pub const DEFAULT_TABLE_NAME: &str = "logs";
+
+/// Logs sampled for schema detection unless `--two-pass` analyzes them all
+pub const DEFAULT_SCHEMA_SAMPLE: usize = 100;
+
+/// Rows `insert_logs` inserts per transaction unless `with_insert_batch` sets another size
+pub const DEFAULT_INSERT_BATCH: usize = 50_000;
+
+/// INTEGER column holding `JsonLog::canonical_level()` with `with_level_normalization`
+pub const LEVEL_NUM_FIELD: &str = "level_num";
 
 pub struct LogDatabase {
     conn: Connection,
     table_name: String,
     field_names: Vec<String>,
+    /// Store boolean-like and null-like strings as their underlying type
+    coerce_strings: bool,
+    /// Store `JsonLog::canonical_level()` in an INTEGER `LEVEL_NUM_FIELD` column
+    normalize_levels: bool,
+    /// Fields with one value throughout the schema sample
+    constant_fields: Vec<String>,
+    /// TEXT columns holding JSON arrays, reported as `FieldType::Array`
+    array_fields: Vec<String>,
+    /// Rows committed per transaction by `insert_logs`
+    insert_batch: usize,
 }
 
 impl LogDatabase {
@@ -20,37 +78,171 @@ impl LogDatabase {
 
         Ok(Self {
             conn,
-            table_name: "logs".to_string(),
+            table_name: DEFAULT_TABLE_NAME.to_string(),
             field_names: Vec::new(),
+            coerce_strings: false,
+            normalize_levels: false,
+            constant_fields: Vec::new(),
+            array_fields: Vec::new(),
+            insert_batch: DEFAULT_INSERT_BATCH,
         })
     }
 
     /// Create a new file-based database
+    /// If the file already has a log table, its columns are picked up so logs can
+    /// be inserted without creating it again
     pub fn new_with_file(path: &str) -> Result<Self> {
         let conn = Connection::open(path)
             .map_err(LogViewerError::from)
             .attach_with(|| format!("Failed to open DuckDB database at {}", path))?;
 
-        Ok(Self {
+        let mut db = Self {
             conn,
-            table_name: "logs".to_string(),
+            table_name: DEFAULT_TABLE_NAME.to_string(),
             field_names: Vec::new(),
-        })
+            coerce_strings: false,
+            normalize_levels: false,
+            constant_fields: Vec::new(),
+            array_fields: Vec::new(),
+            insert_batch: DEFAULT_INSERT_BATCH,
+        };
+        db.load_existing_columns()?;
+        Ok(db)
+    }
+
+    /// Use `name` for the log table and its `seq_<name>_id` sequence, e.g. one
+    /// table per source in a file-based database. Call before creating the table.
+    /// Only letters, digits and `_` are allowed, since the name is spliced into SQL
+    pub fn with_table_name(mut self, name: &str) -> Result<Self> {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Invalid table name '{}': use letters, digits and '_', not starting with a digit",
+                name
+            ))));
+        }
+
+        self.table_name = name.to_string();
+        self.load_existing_columns()?;
+        Ok(self)
+    }
+
+    /// Whether the log table exists, e.g. in a reopened database file
+    pub fn table_exists(&self) -> Result<bool> {
+        let count: i64 = self
+            .conn
+            .query_row(
+                "SELECT count(*) FROM information_schema.tables WHERE table_name = ?",
+                [&self.table_name],
+                |row| row.get(0),
+            )
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to look up table '{}'", self.table_name))?;
+        Ok(count > 0)
+    }
+
+    /// Take `field_names` from an existing table, so inserts match its columns
+    fn load_existing_columns(&mut self) -> Result<()> {
+        if !self.table_exists()? {
+            self.field_names.clear();
+            return Ok(());
+        }
+        self.field_names = self
+            .column_names()?
+            .into_iter()
+            .filter(|name| name != ROW_ID_COLUMN)
+            .collect();
+        tracing::info!(
+            "Found existing table '{}' with {} fields",
+            self.table_name,
+            self.field_names.len()
+        );
+        Ok(())
+    }
+
+    /// Drop the log table and its id sequence, so `create_table_from_logs` can
+    /// rebuild it for logs of a different shape
+    pub fn drop_table(&mut self) -> Result<()> {
+        let sql = format!(
+            "DROP TABLE IF EXISTS {table}; DROP SEQUENCE IF EXISTS seq_{table}_id;",
+            table = self.table_name
+        );
+        self.conn
+            .execute_batch(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to drop table with SQL: {}", sql))?;
+        self.field_names.clear();
+        Ok(())
+    }
+
+    /// Treat `"true"`/`"false"` as booleans and `"null"`/`""` as null, both when
+    /// detecting the schema and when inserting (see `coerce_value`)
+    pub fn with_string_coercion(mut self, coerce_strings: bool) -> Self {
+        self.coerce_strings = coerce_strings;
+        self
+    }
+
+    /// Add a `level_num` column filled from `level`, `lvl` or `severity` (numbers or
+    /// level names, see `JsonLog::canonical_level`) so `level_num >= 40` works across
+    /// sources. The original fields, `level` included, are stored as parsed
+    pub fn with_level_normalization(mut self, normalize_levels: bool) -> Self {
+        self.normalize_levels = normalize_levels;
+        self
+    }
+
+    /// Commit `insert_logs` every `rows` rows instead of every `DEFAULT_INSERT_BATCH`,
+    /// bounding the size of each transaction on large files
+    pub fn with_insert_batch(mut self, rows: usize) -> Self {
+        self.insert_batch = rows.max(1);
+        self
     }
 
     /// Create table with auto-generated schema from sample logs
     /// Samples the first `sample_size` logs to detect field types
     pub fn create_table_from_logs(&mut self, logs: &[JsonLog], sample_size: usize) -> Result<()> {
-        let sample_logs = if logs.len() > sample_size {
-            &logs[..sample_size]
-        } else {
-            logs
-        };
-
-        tracing::info!("Analyzing {} sample logs to detect schema", sample_logs.len());
-
-        let mut schema_builder = SchemaBuilder::new();
-        schema_builder.analyze_logs(sample_logs);
+        // Placeholders for unparsable lines have no place in the typed table
+        let sample_logs = logs
+            .iter()
+            .filter(|log| !log.is_unparsed())
+            .take(sample_size);
+
+        let mut schema_builder = SchemaBuilder::new().with_string_coercion(self.coerce_strings);
+        let mut sampled = 0;
+        for log in sample_logs {
+            schema_builder.analyze_log(log);
+            sampled += 1;
+        }
+        if self.normalize_levels {
+            schema_builder.set_field_type(LEVEL_NUM_FIELD, FieldType::Integer);
+        }
+        tracing::info!("Analyzed {} sample logs to detect schema", sampled);
+        self.constant_fields = schema_builder.constant_fields();
+        let array_fields = schema_builder.array_fields();
+
+        // A reopened database file keeps its table if the new logs fit in it
+        if self.table_exists()? {
+            let existing: HashMap<String, FieldType> = self.get_schema()?.into_iter().collect();
+            let mut mismatches = schema_mismatches(&existing, schema_builder.field_types());
+            // Tables from before the row key was renamed order their rows by `id`
+            let columns = self.column_names()?;
+            if !columns.iter().any(|name| name == ROW_ID_COLUMN) {
+                mismatches.insert(0, format!("has no '{}' column", ROW_ID_COLUMN));
+            }
+            if !mismatches.is_empty() {
+                return Err(Report::new(LogViewerError::SchemaMismatch(format!(
+                    "table '{}' {}. Rerun with --rebuild-db to replace it, or use another --db-file",
+                    self.table_name,
+                    mismatches.join(", ")
+                ))));
+            }
+            self.load_existing_columns()?;
+            self.array_fields = array_fields;
+            return Ok(());
+        }
 
         let create_sql = schema_builder.generate_create_table_sql(&self.table_name);
 
@@ -62,6 +254,7 @@ impl LogDatabase {
             .attach_with(|| format!("Failed to create table with SQL: {}", create_sql))?;
 
         self.field_names = schema_builder.field_names();
+        self.array_fields = array_fields;
 
         tracing::info!("Created table '{}' with {} fields: {:?}",
             self.table_name, self.field_names.len(), self.field_names);
@@ -69,6 +262,71 @@ impl LogDatabase {
         Ok(())
     }
 
+    /// Create the table directly from a Parquet or CSV file
+    /// Column names and types come from DuckDB's reader. A `ROW_ID_COLUMN` is added
+    /// in file order, so an `id` column in the file stays an ordinary field.
+    pub fn create_table_from_file(&mut self, path: &str, format: ExternalFormat) -> Result<usize> {
+        let create_sql = format!(
+            "CREATE TABLE {} AS SELECT * FROM {}({})",
+            self.table_name,
+            format.reader_function(),
+            quote_literal(path)
+        );
+
+        tracing::debug!("Creating table with SQL: {}", create_sql);
+
+        self.conn
+            .execute(&create_sql, [])
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to load {:?} file: {}", format, path))?;
+
+        let columns = self.column_names()?;
+
+        let add_id_sql = format!(
+            "ALTER TABLE {table} ADD COLUMN {id} BIGINT; UPDATE {table} SET {id} = rowid + 1;",
+            table = self.table_name,
+            id = ROW_ID_COLUMN
+        );
+        self.conn
+            .execute_batch(&add_id_sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to add row id column with SQL: {}", add_id_sql))?;
+
+        self.field_names = columns;
+
+        let count = self.count_logs()?;
+
+        tracing::info!(
+            "Loaded {} rows into table '{}' with {} fields: {:?}",
+            count,
+            self.table_name,
+            self.field_names.len(),
+            self.field_names
+        );
+
+        Ok(count)
+    }
+
+    /// Column names of the table, in table order
+    fn column_names(&self) -> Result<Vec<String>> {
+        let sql = format!("PRAGMA table_info({})", self.table_name);
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to prepare schema query: {}", sql))?;
+
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(LogViewerError::from)
+            .attach("Failed to query table columns")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(LogViewerError::from)
+            .attach("Failed to read table columns")?;
+
+        Ok(names)
+    }
+
     /// Insert a single log entry
     pub fn insert_log(&self, log: &JsonLog) -> Result<()> {
         if self.field_names.is_empty() {
@@ -88,6 +346,7 @@ impl LogDatabase {
             placeholders.join(", ")
         );
 
+        self.widen_mismatched_columns(&[log])?;
         let params = self.extract_params_from_log(log);
 
         self.conn
@@ -99,6 +358,7 @@ impl LogDatabase {
     }
 
     /// Insert multiple logs in a batch (using a transaction for efficiency)
+    /// Placeholders for unparsable lines (`JsonLog::is_unparsed`) are skipped
     pub fn insert_logs(&mut self, logs: &[JsonLog]) -> Result<usize> {
         if self.field_names.is_empty() {
             return Err(LogViewerError::Database(
@@ -111,25 +371,6 @@ impl LogDatabase {
 
         tracing::info!("Inserting {} logs into database", logs.len());
 
-        // Extract all params before starting transaction to avoid borrow issues
-        let all_params: Vec<_> = logs
-            .iter()
-            .enumerate()
-            .map(|(idx, log)| {
-                let params = self.extract_params_from_log(log);
-                if idx == 0 {
-                    tracing::debug!("First log has {} fields: {:?}", log.fields.len(), log.fields.keys().collect::<Vec<_>>());
-                }
-                params
-            })
-            .collect();
-
-        let tx = self
-            .conn
-            .transaction()
-            .map_err(LogViewerError::from)
-            .attach("Failed to start transaction")?;
-
         let placeholders: Vec<String> = (1..=self.field_names.len())
             .map(|i| format!("?{}", i))
             .collect();
@@ -142,39 +383,128 @@ impl LogDatabase {
 
         tracing::debug!("Insert SQL: {}", insert_sql);
 
+        let parsed: Vec<&JsonLog> = logs.iter().filter(|log| !log.is_unparsed()).collect();
+        if let Some(first) = parsed.first() {
+            tracing::debug!(
+                "First log has {} fields: {:?}",
+                first.fields.len(),
+                first.fields.keys().collect::<Vec<_>>()
+            );
+        }
+
+        self.widen_mismatched_columns(&parsed)?;
+
+        // One transaction per `insert_batch` rows keeps each commit bounded
         let mut inserted = 0;
-        for params in all_params {
-            tx.execute(&insert_sql, params_from_iter(params.iter()))
+        for batch in parsed.chunks(self.insert_batch) {
+            // Extract params before starting the transaction to avoid borrow issues
+            let all_params: Vec<_> = batch
+                .iter()
+                .map(|log| self.extract_params_from_log(log))
+                .collect();
+
+            let tx = self
+                .conn
+                .transaction()
                 .map_err(LogViewerError::from)
-                .attach_with(|| {
-                    format!("Failed to insert log in batch with SQL: {}", insert_sql)
-                })?;
+                .attach("Failed to start transaction")?;
 
-            inserted += 1;
-        }
+            for params in all_params {
+                tx.execute(&insert_sql, params_from_iter(params.iter()))
+                    .map_err(LogViewerError::from)
+                    .attach_with(|| {
+                        format!("Failed to insert log in batch with SQL: {}", insert_sql)
+                    })?;
 
-        tx.commit()
-            .map_err(LogViewerError::from)
-            .attach("Failed to commit transaction")?;
+                inserted += 1;
+            }
+
+            tx.commit()
+                .map_err(LogViewerError::from)
+                .attach("Failed to commit transaction")?;
+            tracing::debug!("Committed {} of {} logs", inserted, parsed.len());
+        }
 
         tracing::info!("Successfully inserted {} logs", inserted);
 
         Ok(inserted)
     }
 
+    /// Change columns to TEXT where `logs` hold values their type can't store,
+    /// so the stored value matches the logged one instead of failing the insert
+    fn widen_mismatched_columns(&self, logs: &[&JsonLog]) -> Result<()> {
+        let column_types: HashMap<String, FieldType> = self.get_schema()?.into_iter().collect();
+        let mut widened: Vec<&String> = Vec::new();
+        for field_name in &self.field_names {
+            let Some(field_type) = column_types.get(field_name) else {
+                continue;
+            };
+            let mismatched = logs.iter().any(|log| {
+                self.field_value(log, field_name)
+                    .is_some_and(|value| !fits_column(&value, field_type))
+            });
+            if mismatched {
+                widened.push(field_name);
+            }
+        }
+        if widened.is_empty() {
+            return Ok(());
+        }
+
+        for field_name in &widened {
+            let sql = format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET DATA TYPE VARCHAR",
+                self.table_name, field_name
+            );
+            self.conn
+                .execute_batch(&sql)
+                .map_err(LogViewerError::from)
+                .attach_with(|| format!("Failed to widen column {}", field_name))?;
+        }
+        let names: Vec<&str> = widened.iter().map(|name| name.as_str()).collect();
+        tracing::warn!(
+            "Some values didn't fit their column type; stored as text: {}",
+            names.join(", ")
+        );
+
+        Ok(())
+    }
+
+    /// Value stored for `field_name`, found with normalization and coerced when enabled
+    fn field_value<'a>(&self, log: &'a JsonLog, field_name: &str) -> Option<Cow<'a, Value>> {
+        log.fields
+            .iter()
+            .find(|(k, _)| normalize_field_name(k) == field_name)
+            .map(|(_, v)| {
+                if self.coerce_strings {
+                    coerce_value(v)
+                } else {
+                    Cow::Borrowed(v)
+                }
+            })
+    }
+
     /// Extract parameter values from a log entry in the order of field_names
     fn extract_params_from_log(&self, log: &JsonLog) -> Vec<Box<dyn duckdb::ToSql>> {
         let mut params: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
 
         for field_name in &self.field_names {
-            // Try to find the field with normalization
-            let value = log
-                .fields
-                .iter()
-                .find(|(k, _)| normalize_field_name(k) == field_name)
-                .map(|(_, v)| v);
+            if self.normalize_levels && field_name == LEVEL_NUM_FIELD {
+                match log
+                    .canonical_level()
+                    .and_then(|level| i64::try_from(level).ok())
+                {
+                    Some(level) => params.push(Box::new(level)),
+                    None => params.push(Box::new(None::<i64>)),
+                }
+                continue;
+            }
 
-            match value {
+            let value = self.field_value(log, field_name);
+
+            // Null and missing fields are stored as NULL and read back as Null;
+            // an empty string stays an empty string
+            match value.as_deref() {
                 Some(Value::Null) | None => params.push(Box::new(None::<String>)),
                 Some(Value::Bool(b)) => params.push(Box::new(*b)),
                 Some(Value::Number(n)) => {
@@ -187,9 +517,9 @@ impl LogDatabase {
                     }
                 }
                 Some(Value::String(s)) => params.push(Box::new(s.clone())),
-                Some(Value::Array(_)) | Some(Value::Object(_)) => {
+                Some(complex @ (Value::Array(_) | Value::Object(_))) => {
                     // Store complex types as JSON strings
-                    params.push(Box::new(value.unwrap().to_string()));
+                    params.push(Box::new(complex.to_string()));
                 }
             }
         }
@@ -212,6 +542,148 @@ impl LogDatabase {
         Ok(count)
     }
 
+    /// Write all logs to a Parquet file using DuckDB's native COPY
+    /// Rows are written in insertion order; the internal `ROW_ID_COLUMN` is left out.
+    /// JSON fields (arrays/objects) are exported as their JSON text.
+    pub fn export_parquet(&self, path: &str) -> Result<usize> {
+        let sql = format!(
+            "COPY (SELECT * EXCLUDE ({id}) FROM {} ORDER BY {id}) TO {} (FORMAT PARQUET)",
+            self.table_name,
+            quote_literal(path),
+            id = ROW_ID_COLUMN
+        );
+
+        tracing::info!("Exporting logs to Parquet file: {}", path);
+        tracing::debug!("Export SQL: {}", sql);
+
+        self.conn
+            .execute(&sql, [])
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to export logs to Parquet file: {}", path))?;
+
+        let exported = self.count_logs()?;
+
+        tracing::info!("Exported {} logs to {}", exported, path);
+
+        Ok(exported)
+    }
+
+    /// `INSTALL` and `LOAD` a DuckDB extension (e.g. `fts`, `spatial`) so its
+    /// functions can be used in filters. Installing downloads the extension once,
+    /// so this fails offline unless it is already in DuckDB's extension directory
+    pub fn load_extension(&self, name: &str) -> Result<()> {
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Invalid extension name '{}': use letters, digits and '_'",
+                name
+            ))));
+        }
+
+        let sql = format!("INSTALL {name}; LOAD {name};");
+        self.conn
+            .execute_batch(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to load DuckDB extension '{}'", name))?;
+        tracing::info!("Loaded DuckDB extension '{}'", name);
+        Ok(())
+    }
+
+    /// Build a full-text index over the `message` column (`msg` is stored as `message`), so
+    /// `fts_main_<table>.match_bm25(_row_id, 'term') IS NOT NULL` finds logs by word,
+    /// stemmed and ranked by DuckDB's `fts` extension
+    /// Returns the indexed column, or `None` if the table has no message column.
+    /// The index is a snapshot: logs inserted later (`--follow`) are not in it
+    pub fn create_fts_index(&self) -> Result<Option<String>> {
+        let Some(column) = self.field_names.iter().find(|name| *name == "message") else {
+            return Ok(None);
+        };
+
+        self.load_extension("fts")?;
+        let sql = format!(
+            "PRAGMA create_fts_index({}, {}, {}, overwrite = 1)",
+            quote_literal(&self.table_name),
+            quote_literal(ROW_ID_COLUMN),
+            quote_literal(column)
+        );
+        self.conn
+            .execute_batch(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to create full-text index with SQL: {}", sql))?;
+        tracing::info!("Created full-text index over '{}'", column);
+        Ok(Some(column.clone()))
+    }
+
+    /// Count logs per time bucket, from the earliest to the latest time
+    /// `time_expr` gives each row's time in epoch milliseconds (see
+    /// `sql::epoch_ms_expr`); rows where it is NULL are left out. Buckets are a
+    /// minute wide unless that makes more than `max_buckets`, then the next of
+    /// `VOLUME_INTERVALS_MS` that fits (a week at most, with the last bucket
+    /// taking the rest)
+    pub fn log_volume(
+        &self,
+        time_expr: &str,
+        max_buckets: usize,
+        where_clause: Option<&str>,
+    ) -> Result<LogVolume> {
+        let times = format!(
+            "SELECT {} AS ms FROM {}{}",
+            time_expr,
+            self.table_name,
+            where_sql(where_clause)
+        );
+
+        let range_sql = format!(
+            "SELECT min(ms), max(ms) FROM ({}) WHERE ms IS NOT NULL",
+            times
+        );
+        let (first, last): (Option<i64>, Option<i64>) = self
+            .conn
+            .query_row(&range_sql, [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to query time range with SQL: {}", range_sql))?;
+        let (Some(first), Some(last)) = (first, last) else {
+            return Ok(LogVolume::default());
+        };
+
+        let max_buckets = max_buckets.max(1) as i64;
+        let span = last - first;
+        let interval_ms = VOLUME_INTERVALS_MS
+            .into_iter()
+            .find(|interval| span / interval < max_buckets)
+            .unwrap_or(VOLUME_INTERVALS_MS[VOLUME_INTERVALS_MS.len() - 1]);
+        let len = (span / interval_ms + 1).min(max_buckets) as usize;
+
+        let sql = format!(
+            "SELECT (ms - {}) // {} AS bucket, COUNT(*) FROM ({}) WHERE ms IS NOT NULL GROUP BY bucket",
+            first, interval_ms, times
+        );
+        tracing::debug!("Executing log volume query: {}", sql);
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to prepare log volume query: {}", sql))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to query log volume with SQL: {}", sql))?;
+
+        let mut counts = vec![0; len];
+        for row in rows {
+            let (bucket, count) = row
+                .map_err(LogViewerError::from)
+                .attach("Failed to read log volume row")?;
+            counts[(bucket.max(0) as usize).min(len - 1)] += count as u64;
+        }
+
+        Ok(LogVolume {
+            interval_ms,
+            counts,
+        })
+    }
+
     /// Get the table name
     pub fn table_name(&self) -> &str {
         &self.table_name
@@ -222,22 +694,121 @@ impl LogDatabase {
         &self.field_names
     }
 
-    /// Query logs with optional WHERE clause
+    /// Fields that had the same value in every log of the schema sample
+    /// (`SchemaBuilder::constant_fields`); empty for tables loaded from a file
+    pub fn constant_fields(&self) -> &[String] {
+        &self.constant_fields
+    }
+
+    /// Query logs with optional WHERE clause, in insertion order
     /// Returns JsonLog instances constructed from database rows
     pub fn query_logs(&self, where_clause: Option<&str>) -> Result<Vec<JsonLog>> {
-        let sql = if let Some(where_clause) = where_clause {
-            format!("SELECT * FROM {} WHERE {}", self.table_name, where_clause)
-        } else {
-            format!("SELECT * FROM {}", self.table_name)
-        };
+        let sql = format!(
+            "SELECT * FROM {}{} ORDER BY {}",
+            self.table_name,
+            where_sql(where_clause),
+            ROW_ID_COLUMN
+        );
+        self.query_logs_sql(&sql)
+    }
 
-        tracing::debug!("Executing query: {}", sql);
+    /// Query one page of logs matching an optional WHERE clause, in insertion order
+    /// Skips the first `offset` matches; used to page logs not held in memory
+    pub fn query_page(
+        &self,
+        where_clause: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<JsonLog>> {
+        let sql = format!(
+            "SELECT * FROM {}{} ORDER BY {} LIMIT {} OFFSET {}",
+            self.table_name,
+            where_sql(where_clause),
+            ROW_ID_COLUMN,
+            limit,
+            offset
+        );
+        self.query_logs_sql(&sql)
+    }
 
+    /// Logs inserted after the row with `after_id` that match a WHERE clause, in insertion order
+    /// Tests only the new rows, so followed logs can join a filtered view cheaply
+    pub fn query_logs_after(&self, where_clause: &str, after_id: i64) -> Result<Vec<JsonLog>> {
+        let sql = format!(
+            "SELECT * FROM {table} WHERE {id} > {} AND ({}) ORDER BY {id}",
+            after_id,
+            where_clause,
+            table = self.table_name,
+            id = ROW_ID_COLUMN
+        );
+        self.query_logs_sql(&sql)
+    }
+
+    /// Ids of the logs matching a WHERE clause, in insertion order
+    /// Row id `n` is the `n`-th log inserted, so callers can map back to the parsed logs
+    pub fn query_ids(&self, where_clause: &str) -> Result<Vec<i64>> {
+        let sql = format!(
+            "SELECT {id} FROM {} WHERE {} ORDER BY {id}",
+            self.table_name,
+            where_clause,
+            id = ROW_ID_COLUMN
+        );
         let mut stmt = self
             .conn
             .prepare(&sql)
             .map_err(LogViewerError::from)
             .attach_with(|| format!("Failed to prepare query: {}", sql))?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to query ids with SQL: {}", sql))?
+            .collect::<std::result::Result<Vec<i64>, _>>()
+            .map_err(LogViewerError::from)
+            .attach("Failed to collect query results")?;
+
+        Ok(ids)
+    }
+
+    /// Highest row id, 0 for an empty table
+    pub fn max_id(&self) -> Result<i64> {
+        let sql = format!(
+            "SELECT COALESCE(MAX({}), 0) FROM {}",
+            ROW_ID_COLUMN, self.table_name
+        );
+        let id: i64 = self
+            .conn
+            .query_row(&sql, [], |row| row.get(0))
+            .map_err(LogViewerError::from)
+            .attach("Failed to read the last log id")?;
+
+        Ok(id)
+    }
+
+    /// Count the logs matching an optional WHERE clause
+    pub fn count_matching(&self, where_clause: Option<&str>) -> Result<usize> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {}{}",
+            self.table_name,
+            where_sql(where_clause)
+        );
+        let count: usize = self
+            .conn
+            .query_row(&sql, [], |row| row.get(0))
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to count logs with SQL: {}", sql))?;
+
+        Ok(count)
+    }
+
+    /// Run a `SELECT *` over the logs table and build a JsonLog per row
+    fn query_logs_sql(&self, sql: &str) -> Result<Vec<JsonLog>> {
+        tracing::debug!("Executing query: {}", sql);
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to prepare query: {}", sql))?;
 
         stmt.execute([])
             .map_err(LogViewerError::from)
@@ -256,39 +827,25 @@ impl LogDatabase {
 
         let rows = stmt
             .query_map([], |row| {
-                let mut fields = std::collections::HashMap::new();
+                let mut fields = Vec::with_capacity(column_names.len());
 
                 for (i, col_name) in column_names.iter().enumerate() {
-                    // Skip the id column
-                    if col_name == "id" {
+                    // Skip the row id column
+                    if col_name == ROW_ID_COLUMN {
                         continue;
                     }
 
-                    // Try to get the value as different types
-                    let value: Value = if let Ok(s) = row.get::<_, String>(i) {
-                        tracing::trace!("Column '{}' [{}]: String = {:?}", col_name, i, s);
-                        Value::String(s)
-                    } else if let Ok(i_val) = row.get::<_, i64>(i) {
-                        tracing::trace!("Column '{}' [{}]: i64 = {}", col_name, i, i_val);
-                        Value::Number(i_val.into())
-                    } else if let Ok(f) = row.get::<_, f64>(i) {
-                        tracing::trace!("Column '{}' [{}]: f64 = {}", col_name, i, f);
-                        serde_json::Number::from_f64(f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null)
-                    } else if let Ok(b) = row.get::<_, bool>(i) {
-                        tracing::trace!("Column '{}' [{}]: bool = {}", col_name, i, b);
-                        Value::Bool(b)
-                    } else {
-                        tracing::warn!("Column '{}' [{}]: Could not parse, using Null", col_name, i);
-                        Value::Null
-                    };
-
-                    fields.insert(col_name.clone(), value);
+                    let value = value_from_row(row, i, col_name);
+                    fields.push((col_name.clone(), value));
                 }
 
-                tracing::trace!("Parsed log with {} fields: {:?}", fields.len(), fields.keys().collect::<Vec<_>>());
-                Ok(JsonLog::new(fields))
+                let log = JsonLog::new(fields);
+                tracing::trace!(
+                    "Parsed log with {} fields: {:?}",
+                    log.fields.len(),
+                    log.fields.keys().collect::<Vec<_>>()
+                );
+                Ok(log)
             })
             .map_err(LogViewerError::from)
             .attach_with(|| format!("Failed to query logs with SQL: {}", sql))?;
@@ -302,10 +859,66 @@ impl LogDatabase {
         Ok(logs)
     }
 
-    /// Get the schema (field names and types) for the UI
-    pub fn get_schema(&self) -> Result<Vec<(String, crate::storage::FieldType)>> {
-        use crate::storage::FieldType;
+    /// Get the most common values of a field with their counts
+    /// Optionally restricted by a WHERE clause. `limit` is capped at `MAX_TOP_VALUES`.
+    pub fn top_values(
+        &self,
+        field: &str,
+        limit: usize,
+        where_clause: Option<&str>,
+    ) -> Result<TopValues> {
+        if !self.field_names.iter().any(|name| name == field) {
+            return Err(Report::new(LogViewerError::Other(format!(
+                "Unknown field '{}'",
+                field
+            ))));
+        }
 
+        let limit = limit.clamp(1, MAX_TOP_VALUES);
+        let column = quote_identifier(field);
+
+        let mut sql = format!("SELECT {}, COUNT(*) FROM {}", column, self.table_name);
+        if let Some(where_clause) = where_clause {
+            sql.push_str(&format!(" WHERE ({})", where_clause));
+        }
+        // Fetch one extra row to know whether there are more values
+        sql.push_str(&format!(
+            " GROUP BY {} ORDER BY COUNT(*) DESC, {} LIMIT {}",
+            column,
+            column,
+            limit + 1
+        ));
+
+        tracing::debug!("Executing top values query: {}", sql);
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to prepare top values query: {}", sql))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let value = value_from_row(row, 0, field);
+                let count: usize = row.get(1)?;
+                Ok((value, count))
+            })
+            .map_err(LogViewerError::from)
+            .attach_with(|| format!("Failed to query top values with SQL: {}", sql))?;
+
+        let mut values: Vec<(Value, usize)> = rows
+            .collect::<std::result::Result<_, _>>()
+            .map_err(LogViewerError::from)
+            .attach("Failed to collect top values")?;
+
+        let has_more = values.len() > limit;
+        values.truncate(limit);
+
+        Ok(TopValues { values, has_more })
+    }
+
+    /// Get the schema (field names and types) for the UI
+    pub fn get_schema(&self) -> Result<Vec<(String, FieldType)>> {
         let sql = format!("PRAGMA table_info({})", self.table_name);
         let mut stmt = self
             .conn
@@ -328,17 +941,16 @@ impl LogDatabase {
                 .map_err(LogViewerError::from)
                 .attach("Failed to read schema row")?;
 
-            // Skip the id column
-            if name == "id" {
+            // Skip the row id column
+            if name == ROW_ID_COLUMN {
                 continue;
             }
 
-            let field_type = match type_str.as_str() {
-                "TEXT" => FieldType::Text,
-                "BIGINT" => FieldType::Integer,
-                "DOUBLE" => FieldType::Float,
-                "BOOLEAN" => FieldType::Boolean,
-                _ => FieldType::Text,
+            // Arrays are stored as JSON text; the column type doesn't tell them apart
+            let field_type = if self.array_fields.contains(&name) {
+                FieldType::Array
+            } else {
+                FieldType::from_sql(&type_str)
             };
 
             schema.push((name, field_type));
@@ -348,9 +960,215 @@ impl LogDatabase {
     }
 }
 
+/// Whether DuckDB can store `value` in a column of `field_type`
+/// Strings are cast on insert, so they only fit numeric and boolean columns when they parse
+fn fits_column(value: &Value, field_type: &FieldType) -> bool {
+    match (field_type, value) {
+        (_, Value::Null) => true,
+        (FieldType::Text | FieldType::Json | FieldType::Array, _) => true,
+        (FieldType::Integer, Value::Number(n)) => {
+            n.as_i64().is_some()
+                || n.as_f64()
+                    .is_some_and(|f| (i64::MIN as f64..i64::MAX as f64).contains(&f))
+        }
+        (FieldType::Float | FieldType::Boolean, Value::Number(_)) => true,
+        (FieldType::Integer | FieldType::Float | FieldType::Boolean, Value::Bool(_)) => true,
+        (FieldType::Integer, Value::String(s)) => s.parse::<i64>().is_ok(),
+        (FieldType::Float, Value::String(s)) => s.parse::<f64>().is_ok(),
+        (FieldType::Boolean, Value::String(s)) => ["true", "false", "t", "f", "1", "0"]
+            .iter()
+            .any(|b| s.eq_ignore_ascii_case(b)),
+        (_, Value::Array(_) | Value::Object(_)) => false,
+    }
+}
+
+/// Ways the detected `new` schema doesn't fit an `existing` table: columns it
+/// lacks, or columns whose type can't hold the new values
+fn schema_mismatches(
+    existing: &HashMap<String, FieldType>,
+    new: &HashMap<String, FieldType>,
+) -> Vec<String> {
+    let mut names: Vec<&String> = new.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            // Compare as stored, so JSON fields match TEXT columns
+            let new_type = FieldType::from_sql(new[name].to_sql());
+            match existing.get(name) {
+                None => Some(format!("has no '{}' column", name)),
+                Some(old_type) if old_type.merge(&new_type) != *old_type => Some(format!(
+                    "has '{}' as {}, but the logs hold {}",
+                    name,
+                    old_type.to_sql(),
+                    new_type.to_sql()
+                )),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+/// Upper bound for `top_values` results, protecting against high-cardinality fields
+pub const MAX_TOP_VALUES: usize = 50;
+
+/// Result of `LogDatabase::top_values`
+#[derive(Debug, Clone, Default)]
+pub struct TopValues {
+    /// Distinct values with their counts, most common first
+    pub values: Vec<(Value, usize)>,
+    /// More distinct values exist beyond the returned ones
+    pub has_more: bool,
+}
+
+/// Bucket widths `log_volume` picks from, in milliseconds: 1m, 5m, 15m, 1h, 6h, 1d, 1w
+const VOLUME_INTERVALS_MS: [i64; 7] = [
+    60_000,
+    5 * 60_000,
+    15 * 60_000,
+    60 * 60_000,
+    6 * 60 * 60_000,
+    24 * 60 * 60_000,
+    7 * 24 * 60 * 60_000,
+];
+
+/// Result of `LogDatabase::log_volume`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogVolume {
+    /// Width of each bucket in milliseconds
+    pub interval_ms: i64,
+    /// Logs per bucket, oldest first; empty when no log has a time
+    pub counts: Vec<u64>,
+}
+
+/// ` WHERE <clause>` for an optional filter, empty without one
+fn where_sql(where_clause: Option<&str>) -> String {
+    where_clause
+        .map(|clause| format!(" WHERE {}", clause))
+        .unwrap_or_default()
+}
+
+/// Read a column of a result row as a JSON value
+fn value_from_row(row: &duckdb::Row, i: usize, col_name: &str) -> Value {
+    match row.get::<_, DuckValue>(i) {
+        Ok(value) => {
+            tracing::trace!("Column '{}' [{}]: {:?}", col_name, i, value);
+            json_from_duckdb(value)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Column '{}' [{}]: Could not read ({}), using Null",
+                col_name,
+                i,
+                e
+            );
+            Value::Null
+        }
+    }
+}
+
+/// Convert a DuckDB value to JSON
+/// Timestamps, dates, times and intervals become RFC 3339 / ISO 8601 strings;
+/// lists, structs and maps become JSON arrays and objects
+fn json_from_duckdb(value: DuckValue) -> Value {
+    match value {
+        DuckValue::Null => Value::Null,
+        DuckValue::Boolean(b) => Value::Bool(b),
+        DuckValue::TinyInt(n) => Value::from(n),
+        DuckValue::SmallInt(n) => Value::from(n),
+        DuckValue::Int(n) => Value::from(n),
+        DuckValue::BigInt(n) => Value::from(n),
+        DuckValue::UTinyInt(n) => Value::from(n),
+        DuckValue::USmallInt(n) => Value::from(n),
+        DuckValue::UInt(n) => Value::from(n),
+        DuckValue::UBigInt(n) => Value::from(n),
+        DuckValue::HugeInt(n) => i64::try_from(n)
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::String(n.to_string())),
+        DuckValue::Float(f) => float_value(f as f64),
+        DuckValue::Double(f) => float_value(f),
+        DuckValue::Decimal(d) => {
+            let text = d.to_string();
+            text.parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or(Value::String(text))
+        }
+        DuckValue::Text(s) | DuckValue::Enum(s) => Value::String(s),
+        DuckValue::Timestamp(unit, v) => {
+            let micros = unit.to_micros(v);
+            jiff::Timestamp::from_microsecond(micros)
+                .map(|ts| Value::String(ts.to_string()))
+                .unwrap_or_else(|_| Value::from(micros))
+        }
+        DuckValue::Date32(days) => jiff::Span::new()
+            .try_days(days)
+            .and_then(|span| jiff::civil::date(1970, 1, 1).checked_add(span))
+            .map(|date| Value::String(date.to_string()))
+            .unwrap_or_else(|_| Value::from(days)),
+        DuckValue::Time64(unit, v) => {
+            let micros = unit.to_micros(v);
+            jiff::civil::Time::midnight()
+                .checked_add(jiff::SignedDuration::from_micros(micros))
+                .map(|time| Value::String(time.to_string()))
+                .unwrap_or_else(|_| Value::from(micros))
+        }
+        DuckValue::Interval {
+            months,
+            days,
+            nanos,
+        } => Value::String(interval_string(months, days, nanos)),
+        DuckValue::Blob(bytes) => Value::String(String::from_utf8_lossy(&bytes).into_owned()),
+        DuckValue::List(items) | DuckValue::Array(items) => {
+            Value::Array(items.into_iter().map(json_from_duckdb).collect())
+        }
+        DuckValue::Struct(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), json_from_duckdb(value.clone())))
+                .collect(),
+        ),
+        // JSON keys are strings, so other key types are written as their JSON text
+        DuckValue::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match json_from_duckdb(key.clone()) {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, json_from_duckdb(value.clone()))
+                })
+                .collect(),
+        ),
+        DuckValue::Union(value) => json_from_duckdb(*value),
+    }
+}
+
+/// ISO 8601 duration for a DuckDB interval, e.g. `P1M2DT3S`
+fn interval_string(months: i32, days: i32, nanos: i64) -> String {
+    jiff::Span::new()
+        .try_months(months)
+        .and_then(|span| span.try_days(days))
+        .and_then(|span| span.try_nanoseconds(nanos))
+        // jiff writes the unit designators in lowercase
+        .map(|span| span.to_string().to_uppercase())
+        .unwrap_or_else(|_| format!("{} months {} days {} ns", months, days, nanos))
+}
+
+/// JSON number for a float, or Null for NaN/infinity
+fn float_value(f: f64) -> Value {
+    serde_json::Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::FieldType;
+    use crate::storage::sql::array_contains_clause;
     use serde_json::json;
     use std::collections::HashMap;
 
@@ -378,6 +1196,269 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn test_load_extension_checks_name() {
+        let db = LogDatabase::new_in_memory().unwrap();
+        assert!(db.load_extension("fts; DROP TABLE logs").is_err());
+        assert!(db.load_extension("").is_err());
+
+        // No message column: nothing to index, and nothing is installed
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        let log = crate::ingestion::parse_json_line(r#"{"level":30}"#).unwrap();
+        db.create_table_from_logs(&[log], 100).unwrap();
+        assert_eq!(db.create_fts_index().unwrap(), None);
+    }
+
+    #[test]
+    fn test_custom_table_name() {
+        let mut db = LogDatabase::new_in_memory()
+            .unwrap()
+            .with_table_name("api_logs")
+            .unwrap();
+
+        let log = crate::ingestion::parse_json_line(r#"{"msg":"hi","level":30}"#).unwrap();
+        db.create_table_from_logs(&[log.clone()], 100).unwrap();
+        db.insert_logs(&[log.clone(), log]).unwrap();
+
+        assert_eq!(db.table_name(), "api_logs");
+        assert_eq!(db.count_logs().unwrap(), 2);
+        assert_eq!(db.query_logs(Some("level = 30")).unwrap().len(), 2);
+
+        let ids: Vec<i64> = db
+            .conn
+            .prepare("SELECT _row_id FROM api_logs ORDER BY _row_id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|id| id.unwrap())
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        assert!(
+            LogDatabase::new_in_memory()
+                .unwrap()
+                .with_table_name("")
+                .is_err()
+        );
+        assert!(
+            LogDatabase::new_in_memory()
+                .unwrap()
+                .with_table_name("logs; DROP TABLE x")
+                .is_err()
+        );
+        assert!(
+            LogDatabase::new_in_memory()
+                .unwrap()
+                .with_table_name("1logs")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reopen_database_file() {
+        let path =
+            std::env::temp_dir().join(format!("log-viewer-reopen-{}.duckdb", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        let parse = |line: &str| crate::ingestion::parse_json_line(line).unwrap();
+
+        let mut db = LogDatabase::new_with_file(path_str).unwrap();
+        assert!(!db.table_exists().unwrap());
+        let first = vec![parse(r#"{"msg":"a","level":30,"pid":1,"latency":1.5}"#)];
+        db.create_table_from_logs(&first, 100).unwrap();
+        db.insert_logs(&first).unwrap();
+        drop(db);
+
+        // Columns come from the existing table, so logs insert without creating it
+        let mut db = LogDatabase::new_with_file(path_str).unwrap();
+        assert!(db.table_exists().unwrap());
+        db.insert_logs(&first).unwrap();
+        assert_eq!(db.count_logs().unwrap(), 2);
+
+        // Logs that fit (missing columns, integers for a float) reuse the table
+        let fitting = vec![parse(r#"{"msg":"b","level":40,"latency":2}"#)];
+        db.create_table_from_logs(&fitting, 100).unwrap();
+        db.insert_logs(&fitting).unwrap();
+        assert_eq!(db.count_logs().unwrap(), 3);
+        let schema: HashMap<String, FieldType> = db.get_schema().unwrap().into_iter().collect();
+        assert_eq!(schema["latency"], FieldType::Float);
+        assert_eq!(db.query_logs(Some("latency = 2")).unwrap().len(), 1);
+
+        // A new column or a type the column can't hold needs a rebuild
+        let other = vec![parse(r#"{"msg":"c","level":"warn","host":"x"}"#)];
+        let err = db.create_table_from_logs(&other, 100).unwrap_err();
+        let message = format!("{:?}", err);
+        assert!(message.contains("no 'host' column"), "{}", message);
+        assert!(message.contains("'level' as BIGINT"), "{}", message);
+        assert!(message.contains("--rebuild-db"), "{}", message);
+
+        db.drop_table().unwrap();
+        db.create_table_from_logs(&other, 100).unwrap();
+        db.insert_logs(&other).unwrap();
+        assert_eq!(db.count_logs().unwrap(), 1);
+        assert_eq!(db.query_logs(Some("host = 'x'")).unwrap().len(), 1);
+        drop(db);
+
+        std::fs::remove_file(&path).unwrap();
+        let _ = std::fs::remove_file(path.with_extension("duckdb.wal"));
+    }
+
+    #[test]
+    fn test_table_without_row_id_needs_a_rebuild() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.conn
+            .execute_batch("CREATE TABLE logs (id INTEGER, message VARCHAR)")
+            .unwrap();
+
+        let logs = vec![crate::ingestion::parse_json_line(r#"{"msg":"a"}"#).unwrap()];
+        let err = db.create_table_from_logs(&logs, 100).unwrap_err();
+        let message = format!("{:?}", err);
+        assert!(message.contains("no '_row_id' column"), "{}", message);
+    }
+
+    #[test]
+    fn test_null_and_empty_string_stay_distinct() {
+        let parse = |line: &str| crate::ingestion::parse_json_line(line).unwrap();
+        let logs = vec![
+            parse(r#"{"msg":"empty","note":""}"#),
+            parse(r#"{"msg":"null","note":null}"#),
+            parse(r#"{"msg":"missing"}"#),
+        ];
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let stored = db.query_logs(None).unwrap();
+        assert_eq!(stored[0].get_field("note"), Some(&json!("")));
+        assert_eq!(stored[1].get_field("note"), Some(&Value::Null));
+        assert_eq!(stored[2].get_field("note"), Some(&Value::Null));
+
+        assert_eq!(db.query_logs(Some("note = ''")).unwrap().len(), 1);
+        assert_eq!(db.query_logs(Some("note IS NULL")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_array_contains() {
+        let parse = |line: &str| crate::ingestion::parse_json_line(line).unwrap();
+        let logs = vec![
+            parse(r#"{"msg":"a","tags":["urgent","db"],"codes":[1,2]}"#),
+            parse(r#"{"msg":"b","tags":["db"],"codes":[3]}"#),
+            parse(r#"{"msg":"c","tags":[],"codes":[]}"#),
+        ];
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let schema = db.get_schema().unwrap();
+        assert!(schema.contains(&("tags".to_string(), FieldType::Array)));
+
+        let matching = |clause: String| db.query_logs(Some(&clause)).unwrap().len();
+        assert_eq!(matching(array_contains_clause("tags", "urgent")), 1);
+        assert_eq!(matching(array_contains_clause("tags", "db")), 2);
+        assert_eq!(matching(array_contains_clause("codes", "3")), 1);
+    }
+
+    #[test]
+    fn test_array_contains_skips_non_array_values() {
+        let parse = |line: &str| crate::ingestion::parse_json_line(line).unwrap();
+        let logs = vec![
+            parse(r#"{"msg":"a","tags":["urgent"]}"#),
+            parse(r#"{"msg":"b","tags":"urgent"}"#),
+            parse(r#"{"msg":"c","tags":{"urgent":true}}"#),
+        ];
+        // Only the first log is sampled, so the others don't widen the column
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, 1).unwrap();
+        db.insert_logs(&logs).unwrap();
+        assert!(
+            db.get_schema()
+                .unwrap()
+                .contains(&("tags".to_string(), FieldType::Array))
+        );
+
+        let matching = db
+            .query_logs(Some(&array_contains_clause("tags", "urgent")))
+            .unwrap();
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].get_message(), Some("a"));
+    }
+
+    #[test]
+    fn test_schema_sample_size() {
+        let logs = vec![
+            crate::ingestion::parse_json_line(r#"{"msg":"a","code":1}"#).unwrap(),
+            crate::ingestion::parse_json_line(r#"{"msg":"b","code":"E_TIMEOUT"}"#).unwrap(),
+        ];
+
+        // Sampling only the first log types `code` as an integer, so the string fails
+        let mut sampled = LogDatabase::new_in_memory().unwrap();
+        sampled.create_table_from_logs(&logs, 1).unwrap();
+        assert!(sampled.insert_logs(&logs).is_err());
+
+        // Analyzing every log (`--two-pass`) merges the types to TEXT
+        let mut full = LogDatabase::new_in_memory().unwrap();
+        full.create_table_from_logs(&logs, logs.len()).unwrap();
+        assert_eq!(full.insert_logs(&logs).unwrap(), 2);
+        assert_eq!(
+            full.query_logs(Some("code = 'E_TIMEOUT'")).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_string_coercion_insert() {
+        let logs = vec![
+            crate::ingestion::parse_json_line(r#"{"msg":"a","cached":"true","retries":""}"#)
+                .unwrap(),
+            crate::ingestion::parse_json_line(r#"{"msg":"b","cached":"false","retries":2}"#)
+                .unwrap(),
+        ];
+
+        let mut raw = LogDatabase::new_in_memory().unwrap();
+        raw.create_table_from_logs(&logs, 100).unwrap();
+        raw.insert_logs(&logs).unwrap();
+        assert_eq!(raw.query_logs(Some("cached = 'true'")).unwrap().len(), 1);
+
+        let mut coerced = LogDatabase::new_in_memory()
+            .unwrap()
+            .with_string_coercion(true);
+        coerced.create_table_from_logs(&logs, 100).unwrap();
+        coerced.insert_logs(&logs).unwrap();
+        assert_eq!(coerced.query_logs(Some("cached")).unwrap().len(), 1);
+        assert_eq!(coerced.query_logs(Some("retries > 1")).unwrap().len(), 1);
+        assert_eq!(
+            coerced.query_logs(Some("retries IS NULL")).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_level_normalization_insert() {
+        let logs = vec![
+            crate::ingestion::parse_json_line(r#"{"msg":"a","level":50}"#).unwrap(),
+            crate::ingestion::parse_json_line(r#"{"msg":"b","severity":"warning"}"#).unwrap(),
+            crate::ingestion::parse_json_line(r#"{"msg":"c","level":"info"}"#).unwrap(),
+            crate::ingestion::parse_json_line(r#"{"msg":"d"}"#).unwrap(),
+        ];
+
+        let mut db = LogDatabase::new_in_memory()
+            .unwrap()
+            .with_level_normalization(true);
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let high = db.query_logs(Some("level_num >= 40")).unwrap();
+        let messages: Vec<_> = high.iter().filter_map(|log| log.get_message()).collect();
+        assert_eq!(messages, ["a", "b"]);
+        assert_eq!(db.query_logs(Some("level_num = 30")).unwrap().len(), 1);
+        assert_eq!(db.query_logs(Some("level_num IS NULL")).unwrap().len(), 1);
+        // The original level and severity are still stored as written
+        assert_eq!(db.query_logs(Some("level = 'info'")).unwrap().len(), 1);
+        assert_eq!(
+            db.query_logs(Some("severity = 'warning'")).unwrap().len(),
+            1
+        );
+    }
+
     #[test]
     fn test_batch_insert() {
         let mut db = LogDatabase::new_in_memory().unwrap();
@@ -398,6 +1479,111 @@ mod tests {
         assert_eq!(db.count_logs().unwrap(), 10);
     }
 
+    #[test]
+    fn test_insert_batches() {
+        let mut db = LogDatabase::new_in_memory().unwrap().with_insert_batch(3);
+
+        let mut logs: Vec<JsonLog> = (0..10)
+            .map(|i| {
+                let mut fields = HashMap::new();
+                fields.insert("n".to_string(), json!(i));
+                JsonLog::new(fields)
+            })
+            .collect();
+        // A placeholder doesn't take a row in a batch
+        logs.insert(4, JsonLog::unparsed(5, "not json".to_string()));
+        db.create_table_from_logs(&logs, 100).unwrap();
+
+        // 10 rows in batches of 3: three full commits and a partial one
+        assert_eq!(db.insert_logs(&logs).unwrap(), 10);
+        assert_eq!(db.count_logs().unwrap(), 10);
+        assert_eq!(db.max_id().unwrap(), 10);
+        let numbers: Vec<i64> = db
+            .query_logs(None)
+            .unwrap()
+            .iter()
+            .filter_map(|log| log.get_field("n").and_then(|n| n.as_i64()))
+            .collect();
+        assert_eq!(numbers, (0..10).collect::<Vec<_>>());
+
+        // A batch of exactly the row count commits once
+        let mut db = db.with_insert_batch(10);
+        assert_eq!(db.insert_logs(&logs).unwrap(), 10);
+        assert_eq!(db.count_logs().unwrap(), 20);
+    }
+
+    #[test]
+    fn test_query_page() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+
+        let logs: Vec<JsonLog> = (0..10)
+            .map(|i| {
+                let mut fields = HashMap::new();
+                fields.insert("msg".to_string(), json!(format!("message {}", i)));
+                JsonLog::new(fields)
+            })
+            .collect();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let page = db.query_page(None, 4, 3).unwrap();
+        let messages: Vec<&str> = page.iter().filter_map(|log| log.get_message()).collect();
+        assert_eq!(
+            messages,
+            vec!["message 3", "message 4", "message 5", "message 6"]
+        );
+        assert_eq!(db.query_page(None, 4, 8).unwrap().len(), 2);
+
+        // Offsets count matching logs only
+        let filter = "message IN ('message 2', 'message 5', 'message 7')";
+        assert_eq!(db.count_matching(Some(filter)).unwrap(), 3);
+        let page = db.query_page(Some(filter), 2, 1).unwrap();
+        let messages: Vec<&str> = page.iter().filter_map(|log| log.get_message()).collect();
+        assert_eq!(messages, vec!["message 5", "message 7"]);
+    }
+
+    #[test]
+    fn test_query_logs_in_insertion_order_across_batches() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        let batch = |range: std::ops::Range<usize>| -> Vec<JsonLog> {
+            range
+                .map(|i| {
+                    let mut fields = HashMap::new();
+                    fields.insert("msg".to_string(), json!(format!("message {}", i)));
+                    fields.insert("n".to_string(), json!(i));
+                    JsonLog::new(fields)
+                })
+                .collect()
+        };
+
+        let first = batch(0..1000);
+        db.create_table_from_logs(&first, 100).unwrap();
+        db.insert_logs(&first).unwrap();
+        // Follow mode inserts many small batches after the initial load
+        for start in (1000..5000).step_by(250) {
+            db.insert_logs(&batch(start..start + 250)).unwrap();
+        }
+
+        let numbers = |logs: Vec<JsonLog>| -> Vec<u64> {
+            logs.iter()
+                .map(|log| log.get_field("n").and_then(|n| n.as_u64()).unwrap())
+                .collect()
+        };
+        assert_eq!(
+            numbers(db.query_logs(None).unwrap()),
+            (0..5000).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            numbers(db.query_logs(Some("n % 7 = 0")).unwrap()),
+            (0..5000).step_by(7).collect::<Vec<u64>>()
+        );
+        assert_eq!(db.max_id().unwrap(), 5000);
+        assert_eq!(
+            db.query_ids("n % 1000 = 999").unwrap(),
+            vec![1000, 2000, 3000, 4000, 5000]
+        );
+    }
+
     #[test]
     fn test_field_normalization() {
         let mut db = LogDatabase::new_in_memory().unwrap();
@@ -419,4 +1605,285 @@ mod tests {
 
         assert_eq!(db.count_logs().unwrap(), 2);
     }
+
+    #[test]
+    fn test_top_values() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+
+        let mut logs = Vec::new();
+        for (i, host) in ["a", "b", "a", "c", "a", "b"].iter().enumerate() {
+            let level = if i % 2 == 0 { 30 } else { 50 };
+            let mut fields = HashMap::new();
+            fields.insert("msg".to_string(), json!(format!("message {}", i)));
+            fields.insert("level".to_string(), json!(level));
+            fields.insert("hostname".to_string(), json!(host));
+            logs.push(JsonLog::new(fields));
+        }
+
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let top = db.top_values("hostname", 2, None).unwrap();
+        assert_eq!(top.values, vec![(json!("a"), 3), (json!("b"), 2)]);
+        assert!(top.has_more);
+
+        let top = db.top_values("hostname", 10, Some("level >= 50")).unwrap();
+        assert_eq!(top.values, vec![(json!("b"), 2), (json!("c"), 1)]);
+        assert!(!top.has_more);
+
+        assert!(db.top_values("missing", 10, None).is_err());
+    }
+
+    #[test]
+    fn test_log_volume() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        let minute = 60_000;
+        let logs: Vec<JsonLog> = [0, 10, 30_000, 2 * minute, 9 * minute]
+            .iter()
+            .map(|offset| {
+                let mut fields = HashMap::new();
+                fields.insert("time".to_string(), json!(1_531_171_074_000_i64 + offset));
+                fields.insert(
+                    "level".to_string(),
+                    json!(if *offset < minute { 30 } else { 50 }),
+                );
+                JsonLog::new(fields)
+            })
+            .collect();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let time = crate::storage::sql::epoch_ms_expr("time", &FieldType::Integer);
+        let volume = db.log_volume(&time, 20, None).unwrap();
+        assert_eq!(volume.interval_ms, minute);
+        assert_eq!(volume.counts, [3, 0, 1, 0, 0, 0, 0, 0, 0, 1]);
+
+        // Too many minutes for the width: the next wider bucket
+        let volume = db.log_volume(&time, 5, None).unwrap();
+        assert_eq!(volume.interval_ms, 5 * minute);
+        assert_eq!(volume.counts, [4, 1]);
+
+        let volume = db.log_volume(&time, 20, Some("level >= 50")).unwrap();
+        assert_eq!(volume.counts, [1, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(
+            db.log_volume(&time, 20, Some("level > 90")).unwrap(),
+            LogVolume::default()
+        );
+    }
+
+    #[test]
+    fn test_time_clauses_scale_epoch_units() {
+        use crate::storage::sql::{before_clause, since_clause};
+
+        // The same instant in milli-, micro- and nanoseconds, plus an earlier log
+        let ms = 1_531_161_474_000_i64;
+        let parse = |line: String| crate::ingestion::parse_json_line(&line).unwrap();
+        let logs: Vec<JsonLog> = [ms, ms * 1_000, ms * 1_000_000, ms - 60_000]
+            .iter()
+            .map(|time| parse(format!(r#"{{"time":{}}}"#, time)))
+            .collect();
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let instant = jiff::Timestamp::from_millisecond(ms).unwrap();
+        let since = since_clause("time", &FieldType::Integer, instant);
+        assert_eq!(db.query_logs(Some(&since)).unwrap().len(), 3);
+        let before = before_clause("time", &FieldType::Integer, instant);
+        assert_eq!(db.query_logs(Some(&before)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_export_parquet() {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+
+        let mut fields1 = HashMap::new();
+        fields1.insert("msg".to_string(), json!("first"));
+        fields1.insert("level".to_string(), json!(30));
+        fields1.insert("metadata".to_string(), json!({"foo": "bar"}));
+
+        let mut fields2 = HashMap::new();
+        fields2.insert("msg".to_string(), json!("second"));
+        fields2.insert("level".to_string(), json!(50));
+        fields2.insert("metadata".to_string(), json!([1, 2]));
+
+        let logs = vec![JsonLog::new(fields1), JsonLog::new(fields2)];
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("log-viewer-export-{}.parquet", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+
+        assert_eq!(db.export_parquet(&path).unwrap(), 2);
+
+        // Read the file back with DuckDB
+        let mut stmt = db
+            .conn
+            .prepare(&format!(
+                "SELECT message, level, metadata FROM read_parquet({})",
+                quote_literal(&path)
+            ))
+            .unwrap();
+        let rows: Vec<(String, i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("first".to_string(), 30, r#"{"foo":"bar"}"#.to_string()),
+                ("second".to_string(), 50, "[1,2]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_format_from_path() {
+        assert_eq!(
+            ExternalFormat::from_path("logs.parquet"),
+            Some(ExternalFormat::Parquet)
+        );
+        assert_eq!(
+            ExternalFormat::from_path("logs.CSV"),
+            Some(ExternalFormat::Csv)
+        );
+        assert_eq!(ExternalFormat::from_path("app.log"), None);
+        assert_eq!(ExternalFormat::from_path("noext"), None);
+    }
+
+    #[test]
+    fn test_create_table_from_csv() {
+        let path = std::env::temp_dir().join(format!("log-viewer-load-{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "level,time,message,hostname\n30,1000,hello,web-01\n50,2000,boom,web-02\n",
+        )
+        .unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        let count = db
+            .create_table_from_file(&path, ExternalFormat::Csv)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(db.field_names(), &["level", "time", "message", "hostname"]);
+
+        let logs = db.query_logs(Some("level >= 50")).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].get_message(), Some("boom"));
+        assert_eq!(logs[0].get_timestamp_ms(), Some(2000));
+
+        let schema = db.get_schema().unwrap();
+        assert!(schema.contains(&("level".to_string(), FieldType::Integer)));
+        assert!(schema.contains(&("message".to_string(), FieldType::Text)));
+    }
+
+    #[test]
+    fn test_logged_id_field_is_kept() {
+        // A file's own `id` column is data; rows still come back in file order
+        let path =
+            std::env::temp_dir().join(format!("log-viewer-load-id-{}.csv", std::process::id()));
+        std::fs::write(&path, "id,message\n7,first\n3,second\n").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_file(&path, ExternalFormat::Csv)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(db.field_names(), &["id", "message"]);
+        let logs = db.query_logs(None).unwrap();
+        assert_eq!(logs[0].get_field("id"), Some(&json!(7)));
+        assert_eq!(logs[1].get_message(), Some("second"));
+        assert_eq!(db.query_ids("id = 3").unwrap(), vec![2]);
+        assert_eq!(db.max_id().unwrap(), 2);
+
+        // So is a logged `id` field
+        let logs: Vec<JsonLog> = [r#"{"id":"b","msg":"one"}"#, r#"{"id":"a","msg":"two"}"#]
+            .iter()
+            .map(|line| crate::ingestion::parse_json_line(line).unwrap())
+            .collect();
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, 100).unwrap();
+        db.insert_logs(&logs).unwrap();
+        let stored = db.query_logs(None).unwrap();
+        assert_eq!(stored[0].get_field("id"), Some(&json!("b")));
+        assert_eq!(stored[1].get_field("id"), Some(&json!("a")));
+        assert_eq!(db.query_ids("id = 'a'").unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_create_table_from_parquet_roundtrip() {
+        let mut source = LogDatabase::new_in_memory().unwrap();
+        let mut fields = HashMap::new();
+        fields.insert("msg".to_string(), json!("from parquet"));
+        fields.insert("level".to_string(), json!(40));
+        let logs = vec![JsonLog::new(fields)];
+        source.create_table_from_logs(&logs, 100).unwrap();
+        source.insert_logs(&logs).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("log-viewer-load-{}.parquet", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        source.export_parquet(&path).unwrap();
+
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        let count = db
+            .create_table_from_file(&path, ExternalFormat::Parquet)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(count, 1);
+        let loaded = db.query_logs(None).unwrap();
+        assert_eq!(loaded[0].get_message(), Some("from parquet"));
+        assert_eq!(loaded[0].get_level_raw(), Some(40));
+    }
+
+    #[test]
+    fn test_query_logs_non_json_types() {
+        let db = LogDatabase::new_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                "CREATE TABLE logs (_row_id BIGINT, ts TIMESTAMP, day DATE, amount DECIMAL(10, 2), raw BLOB);
+                 INSERT INTO logs VALUES
+                    (1, TIMESTAMP '2024-01-15 10:30:00', DATE '2024-01-15', 12.50, 'abc'::BLOB);",
+            )
+            .unwrap();
+
+        let logs = db.query_logs(None).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].get_field("ts"),
+            Some(&json!("2024-01-15T10:30:00Z"))
+        );
+        assert_eq!(logs[0].get_field("day"), Some(&json!("2024-01-15")));
+        assert_eq!(logs[0].get_field("amount"), Some(&json!(12.5)));
+        assert_eq!(logs[0].get_field("raw"), Some(&json!("abc")));
+    }
+
+    #[test]
+    fn test_query_logs_nested_types() {
+        let db = LogDatabase::new_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                "CREATE TABLE logs (_row_id BIGINT, tags VARCHAR[], point STRUCT(x INTEGER, y INTEGER),
+                                    attrs MAP(VARCHAR, INTEGER), at TIME, wait INTERVAL);
+                 INSERT INTO logs VALUES
+                    (1, ['a', 'b'], {'x': 1, 'y': 2}, MAP {'k': 3}, TIME '10:30:00', INTERVAL 2 DAYS);",
+            )
+            .unwrap();
+
+        let logs = db.query_logs(None).unwrap();
+        assert_eq!(logs[0].get_field("tags"), Some(&json!(["a", "b"])));
+        assert_eq!(logs[0].get_field("point"), Some(&json!({"x": 1, "y": 2})));
+        assert_eq!(logs[0].get_field("attrs"), Some(&json!({"k": 3})));
+        assert_eq!(logs[0].get_field("at"), Some(&json!("10:30:00")));
+        assert_eq!(logs[0].get_field("wait"), Some(&json!("P2D")));
+    }
 }
@@ -1,5 +1,9 @@
 pub mod database;
 pub mod schema;
+pub mod sql;
 
-pub use database::LogDatabase;
+pub use database::{
+    DEFAULT_INSERT_BATCH, DEFAULT_SCHEMA_SAMPLE, ExternalFormat, LEVEL_NUM_FIELD, LogDatabase,
+    LogVolume, TopValues,
+};
 pub use schema::{FieldType, SchemaBuilder};
@@ -1,6 +1,7 @@
 use crate::ingestion::JsonLog;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FieldType {
@@ -9,6 +10,8 @@ pub enum FieldType {
     Float,
     Boolean,
     Json,
+    /// JSON array, stored as JSON text; filtered with `sql::array_contains_clause`
+    Array,
 }
 
 impl FieldType {
@@ -19,6 +22,33 @@ impl FieldType {
             FieldType::Float => "DOUBLE",
             FieldType::Boolean => "BOOLEAN",
             FieldType::Json => "TEXT",
+            FieldType::Array => "TEXT",
+        }
+    }
+
+    /// Name shown for the type in the filter panel and schema summary
+    pub fn label(&self) -> &'static str {
+        match self {
+            FieldType::Text => "TEXT",
+            FieldType::Integer => "INTEGER",
+            FieldType::Float => "FLOAT",
+            FieldType::Boolean => "BOOLEAN",
+            FieldType::Json => "JSON",
+            FieldType::Array => "ARRAY",
+        }
+    }
+
+    /// Map a DuckDB column type name (as reported by `PRAGMA table_info`) to a field type
+    pub fn from_sql(type_str: &str) -> FieldType {
+        let upper = type_str.to_ascii_uppercase();
+        match upper.as_str() {
+            "BIGINT" | "INTEGER" | "SMALLINT" | "TINYINT" | "HUGEINT" | "UBIGINT" | "UINTEGER"
+            | "USMALLINT" | "UTINYINT" => FieldType::Integer,
+            "DOUBLE" | "FLOAT" | "REAL" => FieldType::Float,
+            "BOOLEAN" => FieldType::Boolean,
+            "JSON" => FieldType::Json,
+            _ if upper.starts_with("DECIMAL") => FieldType::Float,
+            _ => FieldType::Text,
         }
     }
 
@@ -52,7 +82,26 @@ pub fn detect_field_type(value: &Value) -> FieldType {
             }
         }
         Value::String(_) => FieldType::Text,
-        Value::Array(_) | Value::Object(_) => FieldType::Json, // Complex types stored as JSON
+        Value::Array(_) => FieldType::Array,
+        Value::Object(_) => FieldType::Json, // Complex types stored as JSON
+    }
+}
+
+/// Interpret boolean-like and null-like strings (`--coerce-strings`)
+/// `"true"`/`"false"` become booleans and `"null"`/`""` become null (case-insensitive);
+/// everything else is returned unchanged
+pub fn coerce_value(value: &Value) -> Cow<'_, Value> {
+    let Value::String(s) = value else {
+        return Cow::Borrowed(value);
+    };
+    if s.eq_ignore_ascii_case("true") {
+        Cow::Owned(Value::Bool(true))
+    } else if s.eq_ignore_ascii_case("false") {
+        Cow::Owned(Value::Bool(false))
+    } else if s.is_empty() || s.eq_ignore_ascii_case("null") {
+        Cow::Owned(Value::Null)
+    } else {
+        Cow::Borrowed(value)
     }
 }
 
@@ -66,21 +115,72 @@ pub fn normalize_field_name(field: &str) -> &str {
     }
 }
 
+/// Column numbering rows in insertion order, kept apart from a logged `id` field
+pub const ROW_ID_COLUMN: &str = "_row_id";
+
 pub struct SchemaBuilder {
     field_types: HashMap<String, FieldType>,
+    coerce_strings: bool,
+    /// Fields seen only as null in coercion mode, typed TEXT until a real value shows up
+    null_only: HashSet<String>,
+    /// Logs analyzed so far
+    log_count: usize,
+    /// Per field: the value it first had and how many logs had it unchanged,
+    /// or `None` once it has differed or been missing from a log
+    constant_values: HashMap<String, Option<(Value, usize)>>,
 }
 
 impl SchemaBuilder {
     pub fn new() -> Self {
         Self {
             field_types: HashMap::new(),
+            coerce_strings: false,
+            null_only: HashSet::new(),
+            log_count: 0,
+            constant_values: HashMap::new(),
         }
     }
 
+    /// Apply `coerce_value` before detecting types; nulls then no longer force TEXT
+    pub fn with_string_coercion(mut self, coerce_strings: bool) -> Self {
+        self.coerce_strings = coerce_strings;
+        self
+    }
+
     /// Analyze a log entry and update field type information
     pub fn analyze_log(&mut self, log: &JsonLog) {
+        self.track_constant_values(log);
         for (field_name, value) in &log.fields {
             let normalized_name = normalize_field_name(field_name).to_string();
+            if normalized_name == ROW_ID_COLUMN {
+                continue;
+            }
+
+            if self.coerce_strings {
+                let value = coerce_value(value);
+                if value.is_null() {
+                    if !self.field_types.contains_key(&normalized_name) {
+                        self.field_types
+                            .insert(normalized_name.clone(), FieldType::Text);
+                        self.null_only.insert(normalized_name);
+                    }
+                    continue;
+                }
+                if self.null_only.remove(&normalized_name) {
+                    self.field_types
+                        .insert(normalized_name, detect_field_type(&value));
+                    continue;
+                }
+                let detected_type = detect_field_type(&value);
+                self.field_types
+                    .entry(normalized_name)
+                    .and_modify(|existing_type| {
+                        *existing_type = existing_type.merge(&detected_type);
+                    })
+                    .or_insert(detected_type);
+                continue;
+            }
+
             let detected_type = detect_field_type(value);
 
             self.field_types
@@ -92,6 +192,64 @@ impl SchemaBuilder {
         }
     }
 
+    /// Keep each field's value while every log so far had the same one
+    fn track_constant_values(&mut self, log: &JsonLog) {
+        let previous_count = self.log_count;
+        self.log_count += 1;
+        for (field_name, value) in &log.fields {
+            let normalized_name = normalize_field_name(field_name).to_string();
+            // A field missing from earlier logs is not constant
+            let state = self
+                .constant_values
+                .entry(normalized_name)
+                .or_insert_with(|| (previous_count == 0).then(|| (value.clone(), 0)));
+            if let Some((first, count)) = state {
+                *count += usize::from(first == value);
+            }
+        }
+        // Anything not seen in this log (or with a new value) is out
+        let log_count = self.log_count;
+        for state in self.constant_values.values_mut() {
+            if state.as_ref().is_some_and(|(_, count)| *count != log_count) {
+                *state = None;
+            }
+        }
+    }
+
+    /// Fields with the same value in every analyzed log, like Bunyan's `"v": 0`;
+    /// empty until at least two logs were analyzed. Sorted by name
+    pub fn constant_fields(&self) -> Vec<String> {
+        if self.log_count < 2 {
+            return Vec::new();
+        }
+        let mut fields: Vec<String> = self
+            .constant_values
+            .iter()
+            .filter(|(_, state)| state.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        fields.sort_unstable();
+        fields
+    }
+
+    /// Fields holding only JSON arrays, sorted by name
+    pub fn array_fields(&self) -> Vec<String> {
+        let mut fields: Vec<String> = self
+            .field_types
+            .iter()
+            .filter(|(_, field_type)| **field_type == FieldType::Array)
+            .map(|(name, _)| name.clone())
+            .collect();
+        fields.sort_unstable();
+        fields
+    }
+
+    /// Use `field_type` for `field` whatever the logs hold, adding the column if needed
+    pub fn set_field_type(&mut self, field: &str, field_type: FieldType) {
+        self.null_only.remove(field);
+        self.field_types.insert(field.to_string(), field_type);
+    }
+
     /// Analyze multiple logs to build the schema
     pub fn analyze_logs(&mut self, logs: &[JsonLog]) {
         for log in logs {
@@ -100,28 +258,27 @@ impl SchemaBuilder {
     }
 
     /// Generate CREATE TABLE SQL statement
+    /// With no detected fields the table has only the `ROW_ID_COLUMN`
     pub fn generate_create_table_sql(&self, table_name: &str) -> String {
         let mut sql = format!("CREATE SEQUENCE IF NOT EXISTS seq_{}_id START 1;\n", table_name);
         sql.push_str(&format!("CREATE TABLE {} (\n", table_name));
-        sql.push_str("    id INTEGER PRIMARY KEY DEFAULT nextval('seq_");
-        sql.push_str(table_name);
-        sql.push_str("_id'),\n");
+
+        let mut columns = vec![format!(
+            "    {} INTEGER PRIMARY KEY DEFAULT nextval('seq_{}_id')",
+            ROW_ID_COLUMN, table_name
+        )];
 
         // Sort fields for consistent output
         let mut fields: Vec<_> = self.field_types.iter().collect();
         fields.sort_by_key(|(name, _)| *name);
+        columns.extend(
+            fields.iter().map(|(field_name, field_type)| {
+                format!("    {} {}", field_name, field_type.to_sql())
+            }),
+        );
 
-        for (i, (field_name, field_type)) in fields.iter().enumerate() {
-            let sql_type = field_type.to_sql();
-            sql.push_str(&format!("    {} {}", field_name, sql_type));
-
-            if i < fields.len() - 1 {
-                sql.push(',');
-            }
-            sql.push('\n');
-        }
-
-        sql.push_str(")");
+        sql.push_str(&columns.join(",\n"));
+        sql.push_str("\n)");
         sql
     }
 
@@ -156,7 +313,7 @@ mod tests {
         assert_eq!(detect_field_type(&json!("hello")), FieldType::Text);
         assert_eq!(detect_field_type(&json!(true)), FieldType::Boolean);
         assert_eq!(detect_field_type(&json!(null)), FieldType::Text);
-        assert_eq!(detect_field_type(&json!([])), FieldType::Json);
+        assert_eq!(detect_field_type(&json!([])), FieldType::Array);
         assert_eq!(detect_field_type(&json!({})), FieldType::Json);
     }
 
@@ -180,6 +337,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_field_type_from_sql() {
+        assert_eq!(FieldType::from_sql("BIGINT"), FieldType::Integer);
+        assert_eq!(FieldType::from_sql("integer"), FieldType::Integer);
+        assert_eq!(FieldType::from_sql("DOUBLE"), FieldType::Float);
+        assert_eq!(FieldType::from_sql("DECIMAL(18,3)"), FieldType::Float);
+        assert_eq!(FieldType::from_sql("BOOLEAN"), FieldType::Boolean);
+        assert_eq!(FieldType::from_sql("VARCHAR"), FieldType::Text);
+        assert_eq!(FieldType::from_sql("TIMESTAMP"), FieldType::Text);
+    }
+
     #[test]
     fn test_normalize_field_name() {
         assert_eq!(normalize_field_name("msg"), "message");
@@ -216,6 +384,28 @@ mod tests {
         assert_eq!(field_types.get("time"), Some(&FieldType::Integer));
     }
 
+    #[test]
+    fn test_constant_fields() {
+        let logs: Vec<JsonLog> = [
+            r#"{"v":0,"host":"a","msg":"one","pid":1}"#,
+            r#"{"v":0,"host":"a","message":"two"}"#,
+            r#"{"v":0,"host":"b","msg":"three","pid":1}"#,
+        ]
+        .iter()
+        .map(|line| crate::ingestion::parse_json_line(line).unwrap())
+        .collect();
+
+        let mut builder = SchemaBuilder::new();
+        builder.analyze_log(&logs[0]);
+        // One log says nothing about what varies
+        assert!(builder.constant_fields().is_empty());
+        builder.analyze_log(&logs[1]);
+        assert_eq!(builder.constant_fields(), ["host", "v"]);
+        // `pid` came back but was missing in between; `host` changed
+        builder.analyze_log(&logs[2]);
+        assert_eq!(builder.constant_fields(), ["v"]);
+    }
+
     #[test]
     fn test_generate_create_table_sql() {
         let mut builder = SchemaBuilder::new();
@@ -232,6 +422,48 @@ mod tests {
         insta::assert_snapshot!(sql);
     }
 
+    #[test]
+    fn test_coerce_value() {
+        assert_eq!(coerce_value(&json!("true")).into_owned(), json!(true));
+        assert_eq!(coerce_value(&json!("FALSE")).into_owned(), json!(false));
+        assert_eq!(coerce_value(&json!("null")).into_owned(), json!(null));
+        assert_eq!(coerce_value(&json!("")).into_owned(), json!(null));
+        assert_eq!(coerce_value(&json!("yes")).into_owned(), json!("yes"));
+        assert_eq!(coerce_value(&json!(1)).into_owned(), json!(1));
+    }
+
+    #[test]
+    fn test_string_coercion_types() {
+        let logs: Vec<JsonLog> = [
+            r#"{"ok":"true","count":""}"#,
+            r#"{"ok":"false","count":5,"note":"null"}"#,
+        ]
+        .iter()
+        .map(|line| crate::ingestion::parse_json_line(line).unwrap())
+        .collect();
+
+        // Raw: the strings are text, and the empty string makes `count` TEXT too
+        let mut raw = SchemaBuilder::new();
+        raw.analyze_logs(&logs);
+        assert_eq!(raw.field_types()["ok"], FieldType::Text);
+        assert_eq!(raw.field_types()["count"], FieldType::Text);
+
+        // Coerced: booleans are booleans, nulls don't decide the type
+        let mut coerced = SchemaBuilder::new().with_string_coercion(true);
+        coerced.analyze_logs(&logs);
+        assert_eq!(coerced.field_types()["ok"], FieldType::Boolean);
+        assert_eq!(coerced.field_types()["count"], FieldType::Integer);
+        assert_eq!(coerced.field_types()["note"], FieldType::Text);
+    }
+
+    #[test]
+    fn test_generate_create_table_sql_no_fields() {
+        let builder = SchemaBuilder::new();
+
+        let sql = builder.generate_create_table_sql("logs");
+        insta::assert_snapshot!(sql);
+    }
+
     #[test]
     fn test_generate_create_table_sql_complex() {
         let mut builder = SchemaBuilder::new();
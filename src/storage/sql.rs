@@ -0,0 +1,340 @@
+use crate::ingestion::models::{MICROSECOND_THRESHOLD, NANOSECOND_THRESHOLD};
+use crate::storage::FieldType;
+use serde_json::Value;
+
+/// Quote a string as a SQL string literal, escaping embedded quotes
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quote a column or table name as a SQL identifier
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Render a JSON value as a SQL literal matching how it is stored
+/// Arrays and objects are stored as JSON text, so they compare as strings
+pub fn value_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote_literal(s),
+        Value::Array(_) | Value::Object(_) => quote_literal(&value.to_string()),
+    }
+}
+
+/// Build a WHERE clause matching rows where `field` equals `value`
+pub fn equality_clause(field: &str, value: &Value) -> String {
+    match value {
+        Value::Null => format!("{} IS NULL", quote_identifier(field)),
+        _ => format!("{} = {}", quote_identifier(field), value_literal(value)),
+    }
+}
+
+/// Build a WHERE clause matching rows where the text at the dotted `path` inside
+/// the JSON `column` equals `value`, e.g. `trace_id` in `dd`
+pub fn json_path_equality_clause(column: &str, path: &str, value: &str) -> String {
+    let json_path: String = path
+        .split('.')
+        .map(|key| format!(".{}", quote_identifier(key)))
+        .collect();
+    format!(
+        "json_extract_string({}, {}) = {}",
+        quote_identifier(column),
+        quote_literal(&format!("${}", json_path)),
+        quote_literal(value)
+    )
+}
+
+/// Build a WHERE clause matching rows where `field`, as text, contains `substring`
+/// `%`, `_` and `\` in the substring match themselves
+pub fn contains_clause(field: &str, substring: &str) -> String {
+    format!(
+        "CAST({} AS VARCHAR) LIKE {} ESCAPE '\\'",
+        quote_identifier(field),
+        quote_literal(&like_pattern(substring))
+    )
+}
+
+/// Like [`contains_clause`], ignoring case
+pub fn contains_ignore_case_clause(field: &str, substring: &str) -> String {
+    format!(
+        "lower(CAST({} AS VARCHAR)) LIKE lower({}) ESCAPE '\\'",
+        quote_identifier(field),
+        quote_literal(&like_pattern(substring))
+    )
+}
+
+/// Build a WHERE clause matching rows where the JSON array in `field` has an element
+/// equal to `value`. Elements are compared as text, so `'1'` finds the number 1 too
+pub fn array_contains_clause(field: &str, value: &str) -> String {
+    let column = quote_identifier(field);
+    when_array(
+        &column,
+        &format!(
+            "list_contains(from_json({}, '[\"VARCHAR\"]'), {})",
+            column,
+            quote_literal(value)
+        ),
+    )
+}
+
+/// `condition` for rows whose `column` holds a JSON array, NULL for the rest, so a
+/// value outside the sampled schema (text, an object) doesn't fail `from_json`
+fn when_array(column: &str, condition: &str) -> String {
+    format!(
+        "CASE WHEN json_valid({0}) AND json_type({0}) = 'ARRAY' THEN {1} END",
+        column, condition
+    )
+}
+
+/// `%substring%` with the LIKE wildcards and the escape character escaped
+fn like_pattern(substring: &str) -> String {
+    let escaped = substring
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Build a WHERE clause matching rows whose `field` time is at or after `since`
+/// Numbers are epoch milliseconds, or micro/nanoseconds when too large to be
+/// milliseconds (as in `JsonLog::timestamp`); text (RFC 3339 with any UTC offset)
+/// is cast to TIMESTAMPTZ, so `+02:00`, `-05:00` and `Z` times compare as instants
+pub fn since_clause(field: &str, field_type: &FieldType, since: jiff::Timestamp) -> String {
+    match field_type {
+        FieldType::Text => format!(
+            "TRY_CAST({} AS TIMESTAMPTZ) >= TIMESTAMPTZ {}",
+            quote_identifier(field),
+            quote_literal(&since.to_string())
+        ),
+        _ => format!(
+            "{} >= {}",
+            epoch_ms_expr(field, field_type),
+            since.as_millisecond()
+        ),
+    }
+}
+
+/// Build a WHERE clause matching rows whose `field` time is before `before`,
+/// reading times like `since_clause`
+pub fn before_clause(field: &str, field_type: &FieldType, before: jiff::Timestamp) -> String {
+    match field_type {
+        FieldType::Text => format!(
+            "TRY_CAST({} AS TIMESTAMPTZ) < TIMESTAMPTZ {}",
+            quote_identifier(field),
+            quote_literal(&before.to_string())
+        ),
+        _ => format!(
+            "{} < {}",
+            epoch_ms_expr(field, field_type),
+            before.as_millisecond()
+        ),
+    }
+}
+
+/// Expression for `field` as epoch milliseconds, reading times like `since_clause`
+/// Text that isn't a valid time becomes NULL
+pub fn epoch_ms_expr(field: &str, field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Text => format!(
+            "epoch_ms(TRY_CAST({} AS TIMESTAMPTZ))",
+            quote_identifier(field)
+        ),
+        _ => {
+            let value = format!("TRY_CAST({} AS BIGINT)", quote_identifier(field));
+            format!(
+                "CASE WHEN abs({value}) >= {ns} THEN {value} // 1000000 \
+                 WHEN abs({value}) >= {us} THEN {value} // 1000 ELSE {value} END",
+                value = value,
+                ns = NANOSECOND_THRESHOLD,
+                us = MICROSECOND_THRESHOLD
+            )
+        }
+    }
+}
+
+/// Start of a comparison on `field` suited to its type, for the filter input to
+/// complete, with how many characters from the end the value goes
+/// The name is always quoted, as fields like `order` or `from` are SQL keywords
+pub fn comparison_template(field: &str, field_type: &FieldType) -> (String, usize) {
+    let column = quote_identifier(field);
+    match field_type {
+        FieldType::Text => (format!("{} = ''", column), 1),
+        FieldType::Integer | FieldType::Float => (format!("{} >= ", column), 0),
+        FieldType::Boolean => (format!("{} = true", column), 0),
+        FieldType::Json => (format!("{} LIKE '%%'", column), 2),
+        FieldType::Array => (
+            when_array(
+                &column,
+                &format!("list_contains(from_json({}, '[\"VARCHAR\"]'), '')", column),
+            ),
+            6,
+        ),
+    }
+}
+
+/// Join WHERE clauses with OR, each parenthesized so its own ANDs/ORs stay grouped
+pub fn any_clause(clauses: &[String]) -> String {
+    let clauses: Vec<String> = clauses
+        .iter()
+        .map(|clause| format!("({})", clause.trim()))
+        .collect();
+    clauses.join(" OR ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("web-01"), "'web-01'");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("hostname"), "\"hostname\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_equality_clause() {
+        assert_eq!(equality_clause("level", &json!(50)), "\"level\" = 50");
+        assert_eq!(
+            equality_clause("host", &json!("o'neil")),
+            "\"host\" = 'o''neil'"
+        );
+        assert_eq!(equality_clause("ok", &json!(true)), "\"ok\" = true");
+        assert_eq!(equality_clause("user", &json!(null)), "\"user\" IS NULL");
+    }
+
+    #[test]
+    fn test_json_path_equality_clause() {
+        assert_eq!(
+            json_path_equality_clause("dd", "trace_id", "o'neil"),
+            "json_extract_string(\"dd\", '$.\"trace_id\"') = 'o''neil'"
+        );
+        assert_eq!(
+            json_path_equality_clause("otel", "span.trace", "1"),
+            "json_extract_string(\"otel\", '$.\"span\".\"trace\"') = '1'"
+        );
+    }
+
+    #[test]
+    fn test_contains_clause() {
+        assert_eq!(
+            contains_clause("url", "/api"),
+            "CAST(\"url\" AS VARCHAR) LIKE '%/api%' ESCAPE '\\'"
+        );
+        assert_eq!(
+            contains_clause("note", "50%_o'k"),
+            "CAST(\"note\" AS VARCHAR) LIKE '%50\\%\\_o''k%' ESCAPE '\\'"
+        );
+    }
+
+    #[test]
+    fn test_contains_ignore_case_clause() {
+        assert_eq!(
+            contains_ignore_case_clause("message", "Disk 100%"),
+            "lower(CAST(\"message\" AS VARCHAR)) LIKE lower('%Disk 100\\%%') ESCAPE '\\'"
+        );
+    }
+
+    #[test]
+    fn test_any_clause() {
+        assert_eq!(any_clause(&["level >= 50".to_string()]), "(level >= 50)");
+        assert_eq!(
+            any_clause(&["level >= 50".to_string(), " a = 1 AND b = 2 ".to_string()]),
+            "(level >= 50) OR (a = 1 AND b = 2)"
+        );
+    }
+
+    #[test]
+    fn test_since_clause() {
+        let since: jiff::Timestamp = "2018-07-09T18:37:54Z".parse().unwrap();
+        assert_eq!(
+            since_clause("time", &FieldType::Integer, since),
+            format!(
+                "{} >= 1531161474000",
+                epoch_ms_expr("time", &FieldType::Integer)
+            )
+        );
+        assert_eq!(
+            since_clause("time", &FieldType::Text, since),
+            "TRY_CAST(\"time\" AS TIMESTAMPTZ) >= TIMESTAMPTZ '2018-07-09T18:37:54Z'"
+        );
+    }
+
+    #[test]
+    fn test_before_clause() {
+        let before: jiff::Timestamp = "2018-07-09T18:37:54Z".parse().unwrap();
+        assert_eq!(
+            before_clause("time", &FieldType::Integer, before),
+            format!(
+                "{} < 1531161474000",
+                epoch_ms_expr("time", &FieldType::Integer)
+            )
+        );
+        assert_eq!(
+            before_clause("time", &FieldType::Text, before),
+            "TRY_CAST(\"time\" AS TIMESTAMPTZ) < TIMESTAMPTZ '2018-07-09T18:37:54Z'"
+        );
+    }
+
+    #[test]
+    fn test_comparison_template() {
+        assert_eq!(
+            comparison_template("hostname", &FieldType::Text),
+            ("\"hostname\" = ''".to_string(), 1)
+        );
+        assert_eq!(
+            comparison_template("level", &FieldType::Integer),
+            ("\"level\" >= ".to_string(), 0)
+        );
+        assert_eq!(
+            comparison_template("order", &FieldType::Boolean),
+            ("\"order\" = true".to_string(), 0)
+        );
+        assert_eq!(
+            comparison_template("req-id", &FieldType::Json),
+            ("\"req-id\" LIKE '%%'".to_string(), 2)
+        );
+        assert_eq!(
+            comparison_template("tags", &FieldType::Array),
+            (
+                "CASE WHEN json_valid(\"tags\") AND json_type(\"tags\") = 'ARRAY' \
+                 THEN list_contains(from_json(\"tags\", '[\"VARCHAR\"]'), '') END"
+                    .to_string(),
+                6
+            )
+        );
+    }
+
+    #[test]
+    fn test_array_contains_clause() {
+        assert_eq!(
+            array_contains_clause("tags", "it's"),
+            "CASE WHEN json_valid(\"tags\") AND json_type(\"tags\") = 'ARRAY' \
+             THEN list_contains(from_json(\"tags\", '[\"VARCHAR\"]'), 'it''s') END"
+        );
+    }
+
+    #[test]
+    fn test_epoch_ms_expr() {
+        assert_eq!(
+            epoch_ms_expr("time", &FieldType::Integer),
+            "CASE WHEN abs(TRY_CAST(\"time\" AS BIGINT)) >= 100000000000000000 \
+             THEN TRY_CAST(\"time\" AS BIGINT) // 1000000 \
+             WHEN abs(TRY_CAST(\"time\" AS BIGINT)) >= 100000000000000 \
+             THEN TRY_CAST(\"time\" AS BIGINT) // 1000 ELSE TRY_CAST(\"time\" AS BIGINT) END"
+        );
+        assert_eq!(
+            epoch_ms_expr("time", &FieldType::Text),
+            "epoch_ms(TRY_CAST(\"time\" AS TIMESTAMPTZ))"
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Headless UI tests: build an `App`, drive it with key events and assert on
+//! the buffer rendered to a `TestBackend`
+//!
+//! New tests follow the same shape: `app_from_lines`, `press`/`type_text`,
+//! then `draw` and look at `rows`.
+
+use crate::render_ui;
+use crate::storage::{DEFAULT_SCHEMA_SAMPLE, LogDatabase};
+use crate::ui::{App, DisplayOptions, handle_key_event};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 12;
+
+/// App over an in-memory database holding one log per JSON line
+fn app_from_lines(lines: &[&str]) -> App {
+    let logs: Vec<_> = lines
+        .iter()
+        .map(|line| crate::ingestion::parse_json_line(line).unwrap())
+        .collect();
+    let mut db = LogDatabase::new_in_memory().unwrap();
+    db.create_table_from_logs(&logs, DEFAULT_SCHEMA_SAMPLE)
+        .unwrap();
+    db.insert_logs(&logs).unwrap();
+    App::new(db, logs, DisplayOptions::default()).unwrap()
+}
+
+/// `count` info logs with messages `log 0`, `log 1`, ...
+fn numbered_app(count: usize) -> App {
+    let lines: Vec<String> = (0..count)
+        .map(|i| format!(r#"{{"level":30,"msg":"log {}"}}"#, i))
+        .collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    app_from_lines(&lines)
+}
+
+/// Render a frame the way the event loop does
+fn draw(app: &mut App) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| render_ui(frame, app)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+fn press_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let page_height = app.page_height;
+    handle_key_event(app, KeyEvent::new(code, modifiers), page_height).unwrap();
+}
+
+fn press(app: &mut App, code: KeyCode) {
+    press_key(app, code, KeyModifiers::NONE);
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        press(app, KeyCode::Char(c));
+    }
+}
+
+fn rows(buf: &Buffer) -> Vec<String> {
+    (0..buf.area.height)
+        .map(|y| {
+            (0..buf.area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .collect()
+}
+
+/// The list row drawn with the highlight symbol
+fn selected_row(buf: &Buffer) -> String {
+    rows(buf)
+        .into_iter()
+        .find(|row| row.starts_with("│> "))
+        .expect("no selected row")
+}
+
+#[test]
+fn test_j_and_k_move_the_selected_row() {
+    let mut app = numbered_app(5);
+    assert!(selected_row(&draw(&mut app)).contains("log 0"));
+
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Down);
+    assert!(selected_row(&draw(&mut app)).contains("log 2"));
+
+    press(&mut app, KeyCode::Char('k'));
+    assert!(selected_row(&draw(&mut app)).contains("log 1"));
+}
+
+#[test]
+fn test_page_down_moves_by_the_rendered_height() {
+    let mut app = numbered_app(50);
+    draw(&mut app);
+    // 12 rows minus the list border
+    assert_eq!(app.page_height, 10);
+
+    press_key(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL);
+    let buf = draw(&mut app);
+    assert!(selected_row(&buf).contains("log 10"));
+    // The new selection is the bottom row inside the border
+    assert_eq!(rows(&buf)[HEIGHT as usize - 2], selected_row(&buf));
+}
+
+#[test]
+fn test_filter_input_applies_the_filter() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"msg":"started"}"#,
+        r#"{"level":50,"msg":"disk full"}"#,
+        r#"{"level":30,"msg":"request done"}"#,
+    ]);
+
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "level >= 50");
+    press(&mut app, KeyCode::Enter);
+
+    let rows = rows(&draw(&mut app));
+    assert!(rows[0].contains("Log Viewer - 1 logs (Filtered: level >= 50)"));
+    assert!(rows.iter().any(|row| row.contains("disk full")));
+    assert!(!rows.iter().any(|row| row.contains("started")));
+}
+
+#[test]
+fn test_message_fields_on_filtered_rows() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"context":"db","msg":"query failed"}"#,
+        r#"{"level":30,"context":"http","msg":"request done"}"#,
+    ]);
+    app.display.message_fields = vec!["context".to_string(), "msg".to_string()];
+
+    // Filtered rows come back from the database with `msg` stored as `message`
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "context = 'db'");
+    press(&mut app, KeyCode::Enter);
+
+    assert!(selected_row(&draw(&mut app)).contains("db | query failed"));
+}
+
+#[test]
+fn test_time_zone_toggle_changes_times_and_label() {
+    let mut app = app_from_lines(&[r#"{"level":30,"time":1700000000000,"msg":"started"}"#]);
+    app.display.time_zone = jiff::tz::TimeZone::fixed(jiff::tz::offset(9));
+
+    // The zone is named in the list's bottom border
+    let buf = draw(&mut app);
+    assert!(selected_row(&buf).contains("[07:13:20]"));
+    assert!(rows(&buf)[HEIGHT as usize - 1].contains(" +09:00 "));
+
+    press(&mut app, KeyCode::Char('Z'));
+    let buf = draw(&mut app);
+    assert!(selected_row(&buf).contains("[22:13:20]"));
+    assert!(rows(&buf)[HEIGHT as usize - 1].contains(" UTC "));
+
+    // Toggling again returns to the configured zone, not the system one
+    press(&mut app, KeyCode::Char('Z'));
+    let buf = draw(&mut app);
+    assert!(selected_row(&buf).contains("[07:13:20]"));
+    assert!(rows(&buf)[HEIGHT as usize - 1].contains(" +09:00 "));
+}
+
+#[test]
+fn test_numeric_filter_prompt_builds_the_filter() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"msg":"fast","latency":12}"#,
+        r#"{"level":30,"msg":"slow","latency":950}"#,
+    ]);
+
+    press(&mut app, KeyCode::Char('>'));
+    // Fields are listed with their column type; pick latency
+    assert!(
+        rows(&draw(&mut app))
+            .iter()
+            .any(|row| row.contains("latency (BIGINT)"))
+    );
+    while app.numeric_filter.as_ref().unwrap().field_name() != "latency" {
+        press(&mut app, KeyCode::Down);
+    }
+    press(&mut app, KeyCode::Enter);
+    // `>` is the first operator
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "500");
+    assert!(
+        rows(&draw(&mut app))
+            .iter()
+            .any(|row| row.contains("latency > 500"))
+    );
+
+    press(&mut app, KeyCode::Enter);
+    let rows = rows(&draw(&mut app));
+    assert!(rows[0].contains("(Filtered: \"latency\" > 500)"));
+    assert!(rows.iter().any(|row| row.contains("slow")));
+    assert!(!rows.iter().any(|row| row.contains("fast")));
+}
+
+#[test]
+fn test_multi_line_filter_editor() {
+    let mut app = numbered_app(5);
+
+    press(&mut app, KeyCode::Char('/'));
+    press_key(&mut app, KeyCode::Char('e'), KeyModifiers::CONTROL);
+    type_text(&mut app, "msg = 'log 1'");
+    // Enter starts a new line instead of applying
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "OR msg = 'log 3'");
+    assert_eq!(app.filter_input.lines().len(), 2);
+    assert!(app.active_filter.is_none());
+
+    press_key(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    let rows = rows(&draw(&mut app));
+    assert!(rows[0].contains("Log Viewer - 2 logs (Filtered: msg = 'log 1' OR msg = 'log 3')"));
+}
+
+#[test]
+fn test_filter_apply_keeping_panel_open() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"msg":"started"}"#,
+        r#"{"level":40,"msg":"slow request"}"#,
+        r#"{"level":50,"msg":"disk full"}"#,
+    ]);
+
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "level >= 50");
+    press_key(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert!(app.show_filter_panel);
+    assert_eq!(app.active_filter.as_deref(), Some("level >= 50"));
+    assert_eq!(app.current_logs().len(), 1);
+
+    // The input keeps the clause to refine and apply again
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    type_text(&mut app, "40");
+    press_key(&mut app, KeyCode::Enter, KeyModifiers::CONTROL);
+    assert_eq!(app.active_filter.as_deref(), Some("level >= 40"));
+    assert_eq!(app.current_logs().len(), 2);
+
+    // Plain Enter applies and closes
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.show_filter_panel);
+    assert_eq!(app.active_filter.as_deref(), Some("level >= 40"));
+}
+
+#[test]
+fn test_filter_panel_fits_short_terminal() {
+    let mut app = numbered_app(5);
+
+    // The panel needs more rows than the test terminal has; the input stays
+    // visible with its border, and the field table is dropped
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "level >=");
+    let screen = rows(&draw(&mut app));
+    let input = screen
+        .iter()
+        .position(|row| row.contains("level >="))
+        .unwrap();
+    assert!(input > 0 && input < HEIGHT as usize - 1);
+    assert!(screen.iter().any(|row| row.contains("Esc: Cancel")));
+    assert!(!screen.iter().any(|row| row.contains("Available Fields")));
+
+    // An error shows along with the input, pushing out the less important sections
+    press_key(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert!(app.filter_error.is_some());
+    let screen = rows(&draw(&mut app));
+    assert!(screen.iter().any(|row| row.contains("level >=")));
+    assert!(screen.iter().any(|row| row.contains("Error")));
+    assert!(screen.iter().any(|row| row.contains("Esc: Cancel")));
+}
+
+#[test]
+fn test_filter_panel_field_table_inserts_a_comparison() {
+    let mut app = numbered_app(5);
+
+    press(&mut app, KeyCode::Char('f'));
+    let message = app
+        .field_schema
+        .iter()
+        .position(|(name, _)| name == "message")
+        .unwrap();
+    for _ in 0..message {
+        press(&mut app, KeyCode::Down);
+    }
+    // Enter starts `"message" = ''` with the cursor between the quotes
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "log 3");
+    assert_eq!(app.filter_input.lines(), [r#""message" = 'log 3'"#]);
+
+    press(&mut app, KeyCode::Enter);
+    let rows = rows(&draw(&mut app));
+    assert!(rows[0].contains(r#"Log Viewer - 1 logs (Filtered: "message" = 'log 3')"#));
+}
+
+#[test]
+fn test_message_quick_filter() {
+    let mut app = numbered_app(5);
+
+    press(&mut app, KeyCode::Char('m'));
+    type_text(&mut app, "LOG 3");
+    let rows_typing = rows(&draw(&mut app));
+    assert!(rows_typing[HEIGHT as usize - 1].contains("Message contains: LOG 3_"));
+
+    press(&mut app, KeyCode::Enter);
+    let rows_filtered = rows(&draw(&mut app));
+    assert!(rows_filtered[0].contains("Log Viewer - 1 logs (Message contains: LOG 3)"));
+
+    // Submitting an empty term clears the filter
+    press(&mut app, KeyCode::Char('m'));
+    for _ in 0.."LOG 3".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    press(&mut app, KeyCode::Enter);
+    assert!(app.active_filter.is_none());
+}
+
+#[test]
+fn test_filter_panel_array_field_inserts_contains() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"msg":"a","tags":["urgent","db"]}"#,
+        r#"{"level":30,"msg":"b","tags":["db"]}"#,
+    ]);
+
+    press(&mut app, KeyCode::Char('f'));
+    let tags = app
+        .field_schema
+        .iter()
+        .position(|(name, _)| name == "tags")
+        .unwrap();
+    for _ in 0..tags {
+        press(&mut app, KeyCode::Down);
+    }
+    // Enter starts a membership test with the cursor on the value
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "urgent");
+    assert_eq!(
+        app.filter_input.lines(),
+        [concat!(
+            r#"CASE WHEN json_valid("tags") AND json_type("tags") = 'ARRAY' "#,
+            r#"THEN list_contains(from_json("tags", '["VARCHAR"]'), 'urgent') END"#
+        )]
+    );
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.current_logs().len(), 1);
+}
+
+#[test]
+fn test_pinned_detail_stays_while_selection_moves() {
+    let mut app = numbered_app(5);
+
+    press(&mut app, KeyCode::Char('p'));
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Char('j'));
+    let pinned = rows(&draw(&mut app));
+    assert!(
+        pinned
+            .iter()
+            .any(|row| row.contains("Log Details (pinned, P to unpin)"))
+    );
+    assert!(pinned.iter().any(|row| row.contains("\"msg\": \"log 0\"")));
+
+    press(&mut app, KeyCode::Char('P'));
+    let unpinned = rows(&draw(&mut app));
+    assert!(
+        unpinned
+            .iter()
+            .any(|row| row.contains("Log Details (3 of 5)"))
+    );
+}
+
+#[test]
+fn test_schema_summary_overlay() {
+    let mut app = app_from_lines(&[
+        r#"{"level":30,"msg":"a","user":"ann"}"#,
+        r#"{"level":30,"msg":"b"}"#,
+    ]);
+
+    press(&mut app, KeyCode::Char('i'));
+    let open = rows(&draw(&mut app));
+    assert!(
+        open.iter()
+            .any(|row| row.contains("Schema: 3 fields over 2 logs"))
+    );
+    let user = open.iter().find(|row| row.contains("user")).unwrap();
+    assert!(user.contains("50%") && user.contains("ann"));
+
+    // Keys go to the overlay until it is closed
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.selected_index, 0);
+    press(&mut app, KeyCode::Esc);
+    assert!(app.schema_summary.is_none());
+}
+
+#[test]
+fn test_out_of_range_timestamp_shown_raw() {
+    let mut app = app_from_lines(&[r#"{"level":30,"time":1e30,"msg":"bogus"}"#]);
+
+    // Marked raw value instead of a formatted time, or none at all
+    assert!(selected_row(&draw(&mut app)).contains("[!1e30] INFO"));
+}
+
+#[test]
+fn test_age_tags() {
+    let now = jiff::Timestamp::now().as_millisecond();
+    let mut app = app_from_lines(&[
+        &format!(
+            r#"{{"level":30,"time":{},"msg":"old"}}"#,
+            now - 2 * 60 * 60_000
+        ),
+        &format!(r#"{{"level":30,"time":{},"msg":"fresh"}}"#, now),
+    ]);
+
+    press(&mut app, KeyCode::Char('A'));
+    let rows = rows(&draw(&mut app));
+    assert!(rows.iter().any(|row| row.contains(">1h INFO  old")));
+    assert!(rows.iter().any(|row| row.contains("<1m INFO  fresh")));
+
+    // 4 in the filter panel keeps the last minute
+    press(&mut app, KeyCode::Char('f'));
+    press(&mut app, KeyCode::Char('4'));
+    assert_eq!(app.current_logs().len(), 1);
+}
+
+#[test]
+fn test_help_opens_action_search() {
+    let mut app = numbered_app(3);
+
+    press(&mut app, KeyCode::Char('?'));
+    assert!(app.show_help);
+
+    // `:` leaves the help for the command palette, which searches the same actions
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "help");
+    assert!(!app.show_help);
+    assert!(app.show_command_palette);
+    let rows = rows(&draw(&mut app));
+    assert!(rows.iter().any(|row| row.contains("Toggle help")));
+}
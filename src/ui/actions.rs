@@ -0,0 +1,495 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Everything that can be triggered from the log list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    MoveDown,
+    MoveUp,
+    JumpToFirst,
+    JumpToLast,
+    NextError,
+    PrevError,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    ToggleDetailPanel,
+    FocusDetail,
+    OpenDetailModal,
+    PinLog,
+    UnpinLog,
+    ToggleDiffPrevious,
+    ToggleCompactJson,
+    ToggleSingleLineJson,
+    ToggleDetailLineNumbers,
+    ToggleHexBinary,
+    ToggleFieldTypes,
+    ToggleExtraFieldsOnly,
+    GrowList,
+    ShrinkList,
+    ToggleNewestFirst,
+    ToggleGroup,
+    ToggleGroupedView,
+    ToggleWideMode,
+    ScrollColumnsLeft,
+    ScrollColumnsRight,
+    FilterByLogger,
+    FilterByTrace,
+    ToggleLoggerName,
+    ToggleSourceFile,
+    ToggleTimeZone,
+    ToggleAgeTags,
+    CopyAsCurl,
+    ExportNdjson,
+    ExportCsv,
+    CopyAsTable,
+    ToggleHiddenFields,
+    ToggleFilterPanel,
+    ToggleFacets,
+    ToggleVolume,
+    FocusFilter,
+    ClearFilter,
+    ResetView,
+    LoadMoreResults,
+    OpenNumericFilter,
+    MessageSearch,
+    ToggleSchemaSummary,
+    ToggleHelp,
+    ToggleDebugLogs,
+    OpenCommandPalette,
+}
+
+/// A key that triggers an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    const fn key(code: KeyCode) -> Self {
+        Self { code, ctrl: false }
+    }
+
+    const fn char(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            ctrl: true,
+        }
+    }
+
+    /// Whether a key event triggers this binding
+    /// Shift is part of the character (`G`), so only Ctrl is compared
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.code == self.code && key.modifiers.contains(KeyModifiers::CONTROL) == self.ctrl
+    }
+
+    /// Human-readable key, e.g. `Ctrl-d` or `PgDn`
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            other => format!("{:?}", other),
+        };
+        if self.ctrl {
+            format!("Ctrl-{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+/// An action with its display name and key bindings
+#[derive(Debug)]
+pub struct ActionInfo {
+    pub action: Action,
+    pub name: &'static str,
+    pub keys: &'static [KeyBinding],
+}
+
+impl ActionInfo {
+    /// All bindings joined for display, e.g. `j / Down`
+    pub fn key_labels(&self) -> String {
+        self.keys
+            .iter()
+            .map(KeyBinding::label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+/// Log list key map, also listed by the command palette
+pub const ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::Quit,
+        name: "Quit",
+        keys: &[KeyBinding::char('q'), KeyBinding::key(KeyCode::Esc)],
+    },
+    ActionInfo {
+        action: Action::MoveDown,
+        name: "Move down",
+        keys: &[KeyBinding::char('j'), KeyBinding::key(KeyCode::Down)],
+    },
+    ActionInfo {
+        action: Action::MoveUp,
+        name: "Move up",
+        keys: &[KeyBinding::char('k'), KeyBinding::key(KeyCode::Up)],
+    },
+    ActionInfo {
+        action: Action::JumpToFirst,
+        name: "Jump to first log",
+        keys: &[KeyBinding::char('g')],
+    },
+    ActionInfo {
+        action: Action::JumpToLast,
+        name: "Jump to last log",
+        keys: &[KeyBinding::char('G')],
+    },
+    ActionInfo {
+        action: Action::NextError,
+        name: "Next error",
+        keys: &[KeyBinding::char(']')],
+    },
+    ActionInfo {
+        action: Action::PrevError,
+        name: "Previous error",
+        keys: &[KeyBinding::char('[')],
+    },
+    ActionInfo {
+        action: Action::HalfPageDown,
+        name: "Half page down",
+        keys: &[KeyBinding::ctrl('d')],
+    },
+    ActionInfo {
+        action: Action::HalfPageUp,
+        name: "Half page up",
+        keys: &[KeyBinding::ctrl('u')],
+    },
+    ActionInfo {
+        action: Action::PageDown,
+        name: "Page down",
+        keys: &[KeyBinding::ctrl('f'), KeyBinding::key(KeyCode::PageDown)],
+    },
+    ActionInfo {
+        action: Action::PageUp,
+        name: "Page up",
+        keys: &[KeyBinding::ctrl('b'), KeyBinding::key(KeyCode::PageUp)],
+    },
+    ActionInfo {
+        action: Action::ToggleDetailPanel,
+        name: "Toggle detail panel",
+        keys: &[KeyBinding::char('d')],
+    },
+    ActionInfo {
+        action: Action::FocusDetail,
+        name: "Select a detail field to copy",
+        keys: &[KeyBinding::key(KeyCode::Tab)],
+    },
+    ActionInfo {
+        action: Action::OpenDetailModal,
+        name: "Open the log detail full screen",
+        keys: &[KeyBinding::char('v')],
+    },
+    ActionInfo {
+        action: Action::PinLog,
+        name: "Pin the detail panel to the selected log",
+        keys: &[KeyBinding::char('p')],
+    },
+    ActionInfo {
+        action: Action::UnpinLog,
+        name: "Unpin the detail panel",
+        keys: &[KeyBinding::char('P')],
+    },
+    ActionInfo {
+        action: Action::ToggleDiffPrevious,
+        name: "Toggle diff with previous log",
+        keys: &[KeyBinding::char('D')],
+    },
+    ActionInfo {
+        action: Action::ToggleCompactJson,
+        name: "Toggle compact detail JSON",
+        keys: &[KeyBinding::char('J')],
+    },
+    ActionInfo {
+        action: Action::ToggleSingleLineJson,
+        name: "Toggle one-line detail JSON",
+        keys: &[KeyBinding::char('I')],
+    },
+    ActionInfo {
+        action: Action::ToggleDetailLineNumbers,
+        name: "Toggle line numbers in detail JSON",
+        keys: &[KeyBinding::char('#')],
+    },
+    ActionInfo {
+        action: Action::ToggleHexBinary,
+        name: "Toggle hex for binary detail values",
+        keys: &[KeyBinding::char('B')],
+    },
+    ActionInfo {
+        action: Action::ToggleFieldTypes,
+        name: "Show/hide column types in detail",
+        keys: &[KeyBinding::char('K')],
+    },
+    ActionInfo {
+        action: Action::ToggleExtraFieldsOnly,
+        name: "Toggle time/level/message in detail",
+        keys: &[KeyBinding::char('X')],
+    },
+    ActionInfo {
+        action: Action::GrowList,
+        name: "Grow list, shrink detail panel",
+        keys: &[KeyBinding::char('+')],
+    },
+    ActionInfo {
+        action: Action::ShrinkList,
+        name: "Shrink list, grow detail panel",
+        keys: &[KeyBinding::char('-')],
+    },
+    ActionInfo {
+        action: Action::ToggleNewestFirst,
+        name: "Toggle newest-first order",
+        keys: &[KeyBinding::char('o')],
+    },
+    ActionInfo {
+        action: Action::ToggleGroup,
+        name: "Expand/collapse group",
+        keys: &[KeyBinding::key(KeyCode::Enter)],
+    },
+    ActionInfo {
+        action: Action::ToggleGroupedView,
+        name: "Toggle grouped view",
+        keys: &[KeyBinding::char('T')],
+    },
+    ActionInfo {
+        action: Action::ToggleWideMode,
+        name: "Toggle wide mode",
+        keys: &[KeyBinding::char('w')],
+    },
+    ActionInfo {
+        action: Action::ScrollColumnsLeft,
+        name: "Scroll wide mode columns left",
+        keys: &[KeyBinding::char('h'), KeyBinding::key(KeyCode::Left)],
+    },
+    ActionInfo {
+        action: Action::ScrollColumnsRight,
+        name: "Scroll wide mode columns right",
+        keys: &[KeyBinding::char('l'), KeyBinding::key(KeyCode::Right)],
+    },
+    ActionInfo {
+        action: Action::FilterByLogger,
+        name: "Filter by selected logger",
+        keys: &[KeyBinding::char('n')],
+    },
+    ActionInfo {
+        action: Action::FilterByTrace,
+        name: "Show all logs in this trace",
+        keys: &[KeyBinding::char('t')],
+    },
+    ActionInfo {
+        action: Action::ToggleLoggerName,
+        name: "Show/hide logger name",
+        keys: &[KeyBinding::char('N')],
+    },
+    ActionInfo {
+        action: Action::ToggleSourceFile,
+        name: "Show/hide source file column",
+        keys: &[KeyBinding::char('S')],
+    },
+    ActionInfo {
+        action: Action::ToggleTimeZone,
+        name: "Toggle UTC/configured time zone",
+        keys: &[KeyBinding::char('Z')],
+    },
+    ActionInfo {
+        action: Action::ToggleAgeTags,
+        name: "Toggle age tags (<1m, <5m, <1h, >1h)",
+        keys: &[KeyBinding::char('A')],
+    },
+    ActionInfo {
+        action: Action::CopyAsCurl,
+        name: "Copy HTTP request as curl",
+        keys: &[KeyBinding::char('C')],
+    },
+    ActionInfo {
+        action: Action::ExportNdjson,
+        name: "Export visible logs as NDJSON",
+        keys: &[KeyBinding::char('E')],
+    },
+    ActionInfo {
+        action: Action::ExportCsv,
+        name: "Export visible logs as CSV",
+        keys: &[],
+    },
+    ActionInfo {
+        action: Action::CopyAsTable,
+        name: "Copy visible logs as a text table",
+        keys: &[KeyBinding::char('Y')],
+    },
+    ActionInfo {
+        action: Action::ToggleHiddenFields,
+        name: "Show/hide hidden fields",
+        keys: &[KeyBinding::char('H')],
+    },
+    ActionInfo {
+        action: Action::ToggleFilterPanel,
+        name: "Toggle filter panel",
+        keys: &[KeyBinding::char('f')],
+    },
+    ActionInfo {
+        action: Action::ToggleFacets,
+        name: "Toggle facets sidebar",
+        keys: &[KeyBinding::char('F')],
+    },
+    ActionInfo {
+        action: Action::ToggleVolume,
+        name: "Toggle log volume sparkline",
+        keys: &[KeyBinding::char('V')],
+    },
+    ActionInfo {
+        action: Action::FocusFilter,
+        name: "Focus filter input",
+        keys: &[KeyBinding::char('/')],
+    },
+    ActionInfo {
+        action: Action::ClearFilter,
+        name: "Clear filter",
+        keys: &[KeyBinding::char('c')],
+    },
+    ActionInfo {
+        action: Action::ResetView,
+        name: "Reset view (filter, order, search, selection)",
+        keys: &[KeyBinding::char('R')],
+    },
+    ActionInfo {
+        action: Action::LoadMoreResults,
+        name: "Load more filter results (--result-limit)",
+        keys: &[KeyBinding::char('M')],
+    },
+    ActionInfo {
+        action: Action::OpenNumericFilter,
+        name: "Numeric filter (field, operator, value)",
+        keys: &[KeyBinding::char('>')],
+    },
+    ActionInfo {
+        action: Action::MessageSearch,
+        name: "Filter by message text",
+        keys: &[KeyBinding::char('m')],
+    },
+    ActionInfo {
+        action: Action::ToggleSchemaSummary,
+        name: "Show field types, presence and samples",
+        keys: &[KeyBinding::char('i')],
+    },
+    ActionInfo {
+        action: Action::ToggleHelp,
+        name: "Toggle help",
+        keys: &[KeyBinding::char('?')],
+    },
+    ActionInfo {
+        action: Action::ToggleDebugLogs,
+        name: "Toggle debug logs",
+        keys: &[KeyBinding::char('L')],
+    },
+    ActionInfo {
+        action: Action::OpenCommandPalette,
+        name: "Command palette",
+        keys: &[KeyBinding::char(':'), KeyBinding::ctrl('p')],
+    },
+];
+
+/// Look up the action bound to a key in the log list
+pub fn action_for_key(key: &KeyEvent) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|info| info.keys.iter().any(|binding| binding.matches(key)))
+        .map(|info| info.action)
+}
+
+/// Actions whose name contains every word of `query` (case-insensitive)
+pub fn filter_actions(query: &str) -> Vec<&'static ActionInfo> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    ACTIONS
+        .iter()
+        .filter(|info| {
+            let name = info.name.to_lowercase();
+            words.iter().all(|word| name.contains(word.as_str()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_action_for_key() {
+        assert_eq!(
+            action_for_key(&key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            action_for_key(&key(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Action::JumpToLast)
+        );
+        // Ctrl distinguishes page scrolling from the plain-letter actions
+        assert_eq!(
+            action_for_key(&key(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            Some(Action::HalfPageDown)
+        );
+        assert_eq!(
+            action_for_key(&key(KeyCode::Char('d'), KeyModifiers::NONE)),
+            Some(Action::ToggleDetailPanel)
+        );
+        assert_eq!(
+            action_for_key(&key(KeyCode::Char('z'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bindings_are_unique() {
+        let bindings: Vec<KeyBinding> = ACTIONS
+            .iter()
+            .flat_map(|info| info.keys.iter().copied())
+            .collect();
+        for (i, binding) in bindings.iter().enumerate() {
+            assert!(
+                !bindings[i + 1..].contains(binding),
+                "{} is bound twice",
+                binding.label()
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_actions() {
+        let names: Vec<&str> = filter_actions("toggle PANEL")
+            .iter()
+            .map(|info| info.name)
+            .collect();
+        assert_eq!(names, vec!["Toggle detail panel", "Toggle filter panel"]);
+        assert_eq!(filter_actions("").len(), ACTIONS.len());
+    }
+
+    #[test]
+    fn test_key_labels() {
+        let info = ACTIONS
+            .iter()
+            .find(|info| info.action == Action::PageDown)
+            .unwrap();
+        assert_eq!(info.key_labels(), "Ctrl-f / PgDn");
+    }
+}
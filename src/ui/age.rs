@@ -0,0 +1,115 @@
+use crate::error::{LogViewerError, Result};
+use ratatui::style::Color;
+use rootcause::prelude::Report;
+
+/// Age window of a log relative to the reference time, from newest to oldest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeBucket {
+    Minute,
+    FiveMinutes,
+    Hour,
+    Older,
+}
+
+impl AgeBucket {
+    /// Oldest age (inclusive) in the window; `None` for `Older`, which starts past an hour
+    pub fn window_ms(&self) -> Option<i64> {
+        match self {
+            AgeBucket::Minute => Some(60 * 1000),
+            AgeBucket::FiveMinutes => Some(5 * 60 * 1000),
+            AgeBucket::Hour => Some(60 * 60 * 1000),
+            AgeBucket::Older => None,
+        }
+    }
+
+    /// Age at the window's edge: the oldest it holds, or for `Older` the hour it starts after
+    pub fn edge_ms(&self) -> i64 {
+        self.window_ms().unwrap_or(60 * 60 * 1000)
+    }
+
+    /// Smallest window holding a log with this timestamp; logs after the
+    /// reference count as the newest
+    pub fn of(timestamp: jiff::Timestamp, reference: jiff::Timestamp) -> Self {
+        let age_ms = reference.as_millisecond() - timestamp.as_millisecond();
+        [AgeBucket::Minute, AgeBucket::FiveMinutes, AgeBucket::Hour]
+            .into_iter()
+            .find(|bucket| bucket.window_ms().is_some_and(|window| age_ms <= window))
+            .unwrap_or(AgeBucket::Older)
+    }
+
+    /// Tag shown in the log list
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgeBucket::Minute => "<1m",
+            AgeBucket::FiveMinutes => "<5m",
+            AgeBucket::Hour => "<1h",
+            AgeBucket::Older => ">1h",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            AgeBucket::Minute => Color::Green,
+            AgeBucket::FiveMinutes => Color::Cyan,
+            AgeBucket::Hour => Color::Blue,
+            AgeBucket::Older => Color::DarkGray,
+        }
+    }
+}
+
+/// Time log ages are measured from (`--age-reference`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgeReference {
+    /// The current time, for live logs
+    #[default]
+    Now,
+    /// The newest log's time, for files from the past
+    Newest,
+}
+
+impl std::str::FromStr for AgeReference {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "now" => Ok(AgeReference::Now),
+            "newest" => Ok(AgeReference::Newest),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown age reference '{}' (expected now or newest)",
+                s
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_bucket_of() {
+        let reference = jiff::Timestamp::from_millisecond(10_000_000).unwrap();
+        let bucket = |age_ms: i64| {
+            let timestamp = jiff::Timestamp::from_millisecond(10_000_000 - age_ms).unwrap();
+            AgeBucket::of(timestamp, reference)
+        };
+
+        assert_eq!(bucket(-5_000), AgeBucket::Minute);
+        assert_eq!(bucket(60_000), AgeBucket::Minute);
+        assert_eq!(bucket(60_001), AgeBucket::FiveMinutes);
+        assert_eq!(bucket(30 * 60_000), AgeBucket::Hour);
+        // Window edges are inclusive, like the `time >=` filters
+        assert_eq!(bucket(60 * 60_000), AgeBucket::Hour);
+        assert_eq!(bucket(60 * 60_000 + 1), AgeBucket::Older);
+    }
+
+    #[test]
+    fn test_parse_age_reference() {
+        assert_eq!("now".parse::<AgeReference>().unwrap(), AgeReference::Now);
+        assert_eq!(
+            "newest".parse::<AgeReference>().unwrap(),
+            AgeReference::Newest
+        );
+        assert!("oldest".parse::<AgeReference>().is_err());
+    }
+}
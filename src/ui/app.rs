@@ -1,8 +1,25 @@
 use crate::error::Result;
-use crate::ingestion::JsonLog;
-use crate::storage::{LogDatabase, FieldType};
+use crate::export::{ExportFormat, export_logs, format_curl, format_table};
+use crate::ingestion::{JsonLog, LogLevel};
+use crate::storage::schema::normalize_field_name;
+use crate::storage::sql::{
+    before_clause, comparison_template, contains_clause, contains_ignore_case_clause,
+    epoch_ms_expr, equality_clause, json_path_equality_clause, since_clause,
+};
+use crate::storage::{FieldType, LogDatabase, LogVolume, TopValues};
+use crate::ui::actions::{Action, ActionInfo, filter_actions};
+use crate::ui::age::AgeBucket;
+use crate::ui::clipboard::copy_to_clipboard;
+use crate::ui::components::log_detail::{search_match_field, search_match_line};
+use crate::ui::components::schema_summary::{FieldSummary, summarize_fields};
+use crate::ui::components::volume::VOLUME_LABEL_WIDTH;
+use crate::ui::display::DisplayOptions;
+use crate::ui::groups::{ListRow, group_key, group_rows};
+use crate::ui::numeric_filter::NumericFilter;
 use rootcause::prelude::ResultExt;
-use tui_textarea::TextArea;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use tui_textarea::{CursorMove, TextArea};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode {
@@ -15,63 +32,463 @@ pub enum Focus {
     LogList,
     FilterInput,
     FilterPresets,  // When filter panel is shown but input is not focused
+    Facets,
+    Detail,
+    /// Typing a search term for the detail panel
+    DetailSearch,
+    CommandPalette,
+    /// Building a comparison in the numeric filter prompt
+    NumericFilter,
+    /// Typing a term for the message quick filter
+    MessageSearch,
+}
+
+/// Number of distinct values requested for the facets sidebar
+const FACET_LIMIT: usize = 20;
+
+/// Bounds and step (in percent of the height) for the list/detail split
+const MIN_LIST_PERCENT: u16 = 20;
+const MAX_LIST_PERCENT: u16 = 80;
+const LIST_PERCENT_STEP: u16 = 10;
+
+/// Where the logs held in memory for a view sit among all the logs it matches
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LogWindow {
+    /// Position of the first log in memory among the view's logs, in insertion order
+    pub start: usize,
+    /// Logs the view matches in the database
+    pub total: usize,
+}
+
+impl LogWindow {
+    /// Window holding every one of `total` logs
+    fn full(total: usize) -> Self {
+        Self { start: 0, total }
+    }
+}
+
+/// State of the value-distribution ("facets") sidebar
+#[derive(Debug, Default)]
+pub struct FacetState {
+    /// Index into `field_schema` of the field being inspected
+    pub field_index: usize,
+    /// Selected row in the values list
+    pub selected: usize,
+    pub values: TopValues,
+    pub error: Option<String>,
+    /// Field index, filter and log count `values` was queried for
+    pub key: Option<(usize, Option<String>, usize)>,
+}
+
+/// Log volume over time shown as a sparkline under the list
+#[derive(Debug, Default)]
+pub struct VolumeState {
+    pub volume: LogVolume,
+    pub error: Option<String>,
+    /// Filter, log count and bucket count `volume` was queried for
+    pub key: Option<(Option<String>, usize, usize)>,
+    /// Buckets the sparkline has room for, one per column of the terminal
+    /// right of the label (`App::resize_volume`)
+    pub buckets: usize,
 }
 
 pub struct App {
     // Data
     pub db: LogDatabase,
+    /// Logs held in memory; with a memory limit, the part of the logs table in `all_window`
     pub all_logs: Vec<JsonLog>,
+    pub all_window: LogWindow,
+    /// Logs matching the active filter held in memory, the part in `filtered_window`
     pub filtered_logs: Vec<JsonLog>,
+    pub filtered_window: LogWindow,
+    /// Most logs a view keeps in memory (`--memory-limit`); the rest are paged
+    /// in from the database with `LogDatabase::query_page`
+    pub memory_limit: Option<usize>,
+    /// Most matches a filter loads at first (`--result-limit`); `load_more_results`
+    /// loads the next batch. Unused with `memory_limit`, which pages results anyway
+    pub result_limit: Option<usize>,
     pub field_schema: Vec<(String, FieldType)>,
 
     // View State
     pub selected_index: usize,
     pub scroll_offset: usize,
+    /// Log rows the list showed in the last frame, used as the page size
+    pub page_height: usize,
     pub view_mode: ViewMode,
     pub show_detail_panel: bool,
+    /// Share of the height given to the list when the detail panel is open
+    pub list_percent: u16,
+    /// Field cursor in the detail panel, an index into `DisplayOptions::visible_field_names()`
+    pub detail_field: usize,
+    /// Text highlighted in the detail panel, typed after `/` with the panel focused
+    pub detail_search: String,
+    /// Log the detail panel stays on while the selection moves (`p`, `P` unpins)
+    pub pinned_log: Option<JsonLog>,
+    pub display: DisplayOptions,
+    /// Grouped list rows built by `list_rows`, cleared when the logs or grouping change
+    row_cache: OnceCell<Vec<ListRow>>,
+    /// New lines are being read from the file (`--follow`)
+    pub following: bool,
+    /// The `--socket` connection was lost and is being retried
+    pub disconnected: bool,
+    /// Fields replaced with `"[REDACTED]"` on export (`--redact`)
+    pub redacted_fields: Vec<String>,
 
     // Filter State
     pub active_filter: Option<String>,
     pub filter_input: TextArea<'static>,
     pub show_filter_panel: bool,
+    /// Multi-line filter editor: Enter inserts a newline, Ctrl-Enter/Ctrl-s applies
+    pub filter_editor: bool,
     pub filter_error: Option<String>,
+    /// Row of the filter panel's field table, selected with Up/Down from the presets
+    pub filter_field: usize,
+    /// Term of the active message quick filter (`m`), shown in the title
+    pub message_search: Option<String>,
+    /// Term being typed in the message quick filter prompt
+    pub message_search_input: String,
+
+    // Facets State
+    pub show_facets: bool,
+    pub facets: FacetState,
+    pub show_volume: bool,
+    pub volume: VolumeState,
+
+    // Command Palette State
+    pub show_command_palette: bool,
+    pub palette_input: TextArea<'static>,
+    /// Index into the actions matching `palette_input`
+    pub palette_selected: usize,
+
+    /// Numeric filter prompt, while it is open
+    pub numeric_filter: Option<NumericFilter>,
 
     // UI State
     pub show_help: bool,
+    /// Lines scrolled past at the top of the help popup
+    pub help_scroll: u16,
+    /// Schema summary overlay (`i`), computed from `all_logs` when opened
+    pub schema_summary: Option<Vec<FieldSummary>>,
+    /// Fields scrolled past at the top of the schema summary
+    pub schema_summary_scroll: u16,
+    /// Selected log's detail shown over the whole screen
+    pub show_detail_modal: bool,
+    /// Lines scrolled past at the top of the detail modal
+    pub detail_modal_scroll: u16,
     pub show_debug_logs: bool,
     pub focus: Focus,
     pub should_quit: bool,
+    /// Transient feedback shown in the list's bottom border until the next key press
+    pub status_message: Option<String>,
 }
 
 impl App {
-    pub fn new(db: LogDatabase, all_logs: Vec<JsonLog>) -> Result<Self> {
+    pub fn new(
+        db: LogDatabase,
+        all_logs: Vec<JsonLog>,
+        mut display: DisplayOptions,
+    ) -> Result<Self> {
         let field_schema = db
             .get_schema()
             .attach("Failed to get database schema")?;
+        display.newest_time = all_logs.iter().filter_map(JsonLog::timestamp).max();
 
         let mut filter_input = TextArea::default();
         filter_input.set_placeholder_text("Enter SQL WHERE clause (e.g., level >= 40)");
 
         Ok(Self {
             db,
+            all_window: LogWindow::full(all_logs.len()),
             all_logs,
             filtered_logs: Vec::new(),
+            filtered_window: LogWindow::default(),
+            memory_limit: None,
+            result_limit: None,
             field_schema,
             selected_index: 0,
             scroll_offset: 0,
+            page_height: 0,
             view_mode: ViewMode::AllLogs,
             show_detail_panel: false,
+            list_percent: 50,
+            detail_field: 0,
+            detail_search: String::new(),
+            pinned_log: None,
+            display,
+            row_cache: OnceCell::new(),
+            following: false,
+            disconnected: false,
+            redacted_fields: Vec::new(),
             active_filter: None,
             filter_input,
             show_filter_panel: false,
+            filter_editor: false,
+            filter_field: 0,
             filter_error: None,
+            message_search: None,
+            message_search_input: String::new(),
+            show_facets: false,
+            facets: FacetState::default(),
+            show_volume: false,
+            volume: VolumeState::default(),
+            show_command_palette: false,
+            palette_input: TextArea::default(),
+            palette_selected: 0,
+            numeric_filter: None,
             show_help: false,
+            help_scroll: 0,
+            schema_summary: None,
+            schema_summary_scroll: 0,
+            show_detail_modal: false,
+            detail_modal_scroll: 0,
             show_debug_logs: false,
             focus: Focus::LogList,
             should_quit: false,
+            status_message: None,
         })
     }
 
+    /// Store logs read in follow mode and show them in the list
+    /// A selection on the newest log moves along with new logs, like `tail -f`
+    /// With an active filter, only the new rows are tested against it and the
+    /// matches are added to the filtered view
+    /// A failed insert is reported in the status bar; the rows committed before
+    /// the failure are still shown
+    pub fn append_logs(&mut self, logs: Vec<JsonLog>) -> Result<()> {
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let last_id = self.db.max_id()?;
+        let logs = match self.db.insert_logs(&logs) {
+            Ok(_) => logs,
+            Err(e) => {
+                tracing::warn!("Failed to insert followed logs: {:?}", e);
+                self.set_status(format!("Failed to store followed logs: {}", e));
+                self.db.query_logs_after("TRUE", last_id)?
+            }
+        };
+        if logs.is_empty() {
+            return Ok(());
+        }
+        self.refresh_schema()?;
+        let newest = logs.iter().filter_map(JsonLog::timestamp).max();
+        self.display.newest_time = self.display.newest_time.max(newest);
+
+        let matches = match &self.active_filter {
+            Some(filter) => match self.db.query_logs_after(filter, last_id) {
+                Ok(matches) => matches,
+                Err(e) => {
+                    tracing::warn!("Failed to filter followed logs: {:?}", e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+
+        self.extend_view(ViewMode::AllLogs, logs);
+        if self.active_filter.is_some() {
+            self.extend_view(ViewMode::Filtered, matches);
+        }
+        self.evict_oldest();
+        self.clamp_selection();
+        self.refresh_volume();
+
+        Ok(())
+    }
+
+    /// Pick up columns the database widened to TEXT for values that didn't fit their type
+    fn refresh_schema(&mut self) -> Result<()> {
+        let schema = self.db.get_schema()?;
+        let widened: Vec<&str> = schema
+            .iter()
+            .filter(|field| !self.field_schema.contains(field))
+            .filter(|(name, _)| self.field_schema.iter().any(|(old, _)| old == name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !widened.is_empty() {
+            self.set_status(format!(
+                "Stored {} as text: followed values didn't fit the column type",
+                widened.join(", ")
+            ));
+            self.field_schema = schema;
+        }
+        Ok(())
+    }
+
+    /// Logs held in memory for a view and its window
+    fn view_mut(&mut self, view: ViewMode) -> (&mut Vec<JsonLog>, &mut LogWindow) {
+        self.invalidate_rows();
+        match view {
+            ViewMode::AllLogs => (&mut self.all_logs, &mut self.all_window),
+            ViewMode::Filtered => (&mut self.filtered_logs, &mut self.filtered_window),
+        }
+    }
+
+    /// Add new logs at the end of a view, moving the selection along if it is shown
+    /// When paged back to older logs, new ones stay in the database until paged in
+    fn extend_view(&mut self, view: ViewMode, logs: Vec<JsonLog>) {
+        if logs.is_empty() {
+            return;
+        }
+        let count = logs.len();
+        let (held, window) = self.view_mut(view);
+        let held_count = held.len();
+        let at_tail = window.start + held_count == window.total;
+        window.total += count;
+        if !at_tail {
+            return;
+        }
+        if self.view_mode != view {
+            self.view_mut(view).0.extend(logs);
+            return;
+        }
+
+        // The newest log is the last row, or the first one when newest-first
+        let selected = self.selected_log_index();
+        let on_newest = if self.display.newest_first {
+            self.selected_index == 0
+        } else {
+            self.selected_index + 1 >= held_count
+        };
+        self.view_mut(view).0.extend(logs);
+
+        if self.display.group_field().is_some() {
+            // New logs may join any group; stay on the same log
+            if let Some(index) = selected {
+                self.select_log(index);
+            }
+        } else if self.display.newest_first {
+            // New rows are inserted above; keep the same log selected unless tailing
+            if !on_newest {
+                self.selected_index += count;
+            }
+        } else if on_newest {
+            self.jump_to_last();
+        }
+    }
+
+    /// Cap the logs in memory at the memory limit, keeping the selection on the same log
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = Some(limit);
+        self.evict_oldest();
+        self.clamp_selection();
+    }
+
+    /// Drop the oldest logs beyond `memory_limit` from memory; they stay in the database
+    fn evict_oldest(&mut self) {
+        let Some(limit) = self.memory_limit else {
+            return;
+        };
+        for view in [ViewMode::AllLogs, ViewMode::Filtered] {
+            let excess = self.view_mut(view).0.len().saturating_sub(limit);
+            if excess == 0 {
+                continue;
+            }
+
+            // Only the shown view has a selection to keep
+            let selected = if self.view_mode == view {
+                self.selected_log_index()
+            } else {
+                None
+            };
+            let (held, window) = self.view_mut(view);
+            held.drain(..excess);
+            window.start += excess;
+            if let Some(index) = selected {
+                self.select_log(index.saturating_sub(excess));
+            }
+            tracing::debug!("Evicted {} logs from memory", excess);
+        }
+    }
+
+    /// Whether navigation pages logs in from the database: a memory limit is set
+    /// and the list is flat (groups are built from the logs in memory)
+    fn is_windowed(&self) -> bool {
+        self.memory_limit.is_some() && self.display.group_field().is_none()
+    }
+
+    /// Window of the current view
+    pub fn current_window(&self) -> LogWindow {
+        match self.view_mode {
+            ViewMode::AllLogs => self.all_window,
+            ViewMode::Filtered => self.filtered_window,
+        }
+    }
+
+    /// Move the selection by `delta` rows
+    /// In windowed mode, moving past either end of `all_logs` pages logs in
+    fn move_selection(&mut self, delta: isize) {
+        let rows = self.row_count() as isize;
+        let target = self.selected_index as isize + delta;
+        if (target < 0 || target >= rows) && self.is_windowed() {
+            if let Some(index) = self.selected_log_index() {
+                let log_delta = if self.display.newest_first {
+                    -delta
+                } else {
+                    delta
+                };
+                let window = self.current_window();
+                let position = (window.start + index) as isize + log_delta;
+                let last = window.total.saturating_sub(1) as isize;
+                self.select_stored_log(position.clamp(0, last) as usize);
+                return;
+            }
+        }
+        self.selected_index = target.clamp(0, (rows - 1).max(0)) as usize;
+    }
+
+    /// Select the log at `position` among the current view's logs, paging in
+    /// the logs around it when it is not in memory
+    fn select_stored_log(&mut self, position: usize) {
+        let window = self.current_window();
+        let window_end = window.start + self.current_logs().len();
+        if !(window.start..window_end).contains(&position) {
+            let limit = self.memory_limit.unwrap_or(window.total).max(1);
+            let start = position
+                .saturating_sub(limit / 2)
+                .min(window.total.saturating_sub(limit));
+            if let Err(e) = self.load_page(start, limit) {
+                self.set_status(format!("Failed to load logs: {}", e));
+                return;
+            }
+        }
+        self.select_log(position.saturating_sub(self.current_window().start));
+        self.clamp_selection();
+    }
+
+    /// Replace the current view's logs in memory with `limit` logs from `start`
+    fn load_page(&mut self, start: usize, limit: usize) -> Result<()> {
+        let filter = match self.view_mode {
+            ViewMode::AllLogs => None,
+            ViewMode::Filtered => self.active_filter.as_deref(),
+        };
+        let logs = self
+            .db
+            .query_page(filter, limit, start)
+            .attach("Failed to page in logs")?;
+        tracing::debug!("Paged in {} logs from position {}", logs.len(), start);
+
+        let (held, window) = self.view_mut(self.view_mode);
+        *held = logs;
+        window.start = start;
+        Ok(())
+    }
+
+    /// Logs in the current view, counting the ones only in the database
+    pub fn total_log_count(&self) -> usize {
+        self.current_window().total
+    }
+
+    /// Keep `selected_index` on a row of `current_logs()`, or 0 when it is empty
+    /// Called after anything that changes which logs are visible, so navigation
+    /// never works off a row that no longer exists
+    pub fn clamp_selection(&mut self) {
+        let len = self.row_count();
+        self.selected_index = self.selected_index.min(len.saturating_sub(1));
+        self.clamp_detail_field();
+    }
+
     /// Toggle debug logs panel
     pub fn toggle_debug_logs(&mut self) {
         self.show_debug_logs = !self.show_debug_logs;
@@ -87,63 +504,310 @@ impl App {
 
     /// Get the currently selected log
     pub fn selected_log(&self) -> Option<&JsonLog> {
-        self.current_logs().get(self.selected_index)
+        self.current_logs().get(self.selected_log_index()?)
+    }
+
+    /// The log before the selected one in file order, used by the diff view
+    pub fn previous_log(&self) -> Option<&JsonLog> {
+        let index = self.selected_log_index()?.checked_sub(1)?;
+        self.current_logs().get(index)
+    }
+
+    /// Log shown in the detail panel: the pinned one, otherwise the selected one
+    pub fn detail_log(&self) -> Option<&JsonLog> {
+        self.pinned_log.as_ref().or_else(|| self.selected_log())
+    }
+
+    /// Log the detail panel diffs against; a pinned log has none
+    pub fn detail_previous_log(&self) -> Option<&JsonLog> {
+        if self.pinned_log.is_some() {
+            None
+        } else {
+            self.previous_log()
+        }
+    }
+
+    /// Keep the detail panel on the selected log while the selection moves
+    pub fn pin_selected_log(&mut self) {
+        let Some(log) = self.selected_log().cloned() else {
+            return;
+        };
+        self.pinned_log = Some(log);
+        self.show_detail_panel = true;
+        self.clamp_detail_field();
+        self.set_status("Detail pinned to this log (P to unpin)");
+    }
+
+    /// Let the detail panel follow the selection again
+    pub fn unpin_log(&mut self) {
+        if self.pinned_log.take().is_some() {
+            self.clamp_detail_field();
+            self.set_status("Detail follows the selection");
+        }
+    }
+
+    /// Rows of the grouped list, `None` when the list is not grouped
+    /// Built once and kept until `invalidate_rows`
+    pub fn list_rows(&self) -> Option<&[ListRow]> {
+        let field = self.display.group_field()?;
+        let rows = self.row_cache.get_or_init(|| {
+            group_rows(
+                self.current_logs(),
+                field,
+                &self.display.expanded_groups,
+                self.display.newest_first,
+            )
+        });
+        Some(rows)
+    }
+
+    /// Drop the cached list rows after the logs, the view or the grouping changed
+    fn invalidate_rows(&mut self) {
+        self.row_cache.take();
+    }
+
+    /// Number of rows in the list: one per log, or per group header and expanded log
+    pub fn row_count(&self) -> usize {
+        match self.list_rows() {
+            Some(rows) => rows.len(),
+            None => self.current_logs().len(),
+        }
+    }
+
+    /// Select the row showing the log at `index` into `current_logs()`
+    /// In a collapsed group that is the group header
+    fn select_log(&mut self, index: usize) {
+        if let Some(rows) = self.list_rows() {
+            let key = self
+                .display
+                .group_field()
+                .and_then(|field| group_key(self.current_logs().get(index)?, field));
+            let row = rows
+                .iter()
+                .position(|row| match row {
+                    ListRow::Log { index: i, .. } => *i == index,
+                    ListRow::Group {
+                        key: k,
+                        expanded: false,
+                        ..
+                    } => key.as_ref() == Some(k),
+                    ListRow::Group { .. } => false,
+                })
+                .unwrap_or(0);
+            self.selected_index = row;
+        } else if self.display.newest_first {
+            self.selected_index = self.current_logs().len().saturating_sub(index + 1);
+        } else {
+            self.selected_index = index;
+        }
+    }
+
+    /// Index into `current_logs()` of the selected row
+    fn selected_log_index(&self) -> Option<usize> {
+        let row = self.selected_index;
+        if let Some(rows) = self.list_rows() {
+            return rows.get(row).map(ListRow::log_index);
+        }
+        if self.display.newest_first {
+            self.current_logs().len().checked_sub(row + 1)
+        } else {
+            Some(row)
+        }
+    }
+
+    /// Toggle highlighting of the fields that changed since the previous log
+    pub fn toggle_diff_previous(&mut self) {
+        self.display.diff_previous = !self.display.diff_previous;
+    }
+
+    /// Switch the detail panel between pretty and compact JSON
+    pub fn toggle_compact_json(&mut self) {
+        self.display.compact_json = !self.display.compact_json;
+    }
+
+    /// Switch the detail panel between multi-line and one-line JSON
+    pub fn toggle_single_line_json(&mut self) {
+        self.display.single_line_json = !self.display.single_line_json;
+    }
+
+    /// Number the lines of the detail JSON, for pointing at a line
+    pub fn toggle_detail_line_numbers(&mut self) {
+        self.display.detail_line_numbers = !self.display.detail_line_numbers;
+    }
+
+    /// Show binary-looking strings in the detail panel as hex
+    pub fn toggle_hex_binary(&mut self) {
+        self.display.hex_binary = !self.display.hex_binary;
+    }
+
+    /// Show each field's database column type in the detail panel
+    pub fn toggle_field_types(&mut self) {
+        self.display.show_field_types = !self.display.show_field_types;
+    }
+
+    /// Show only the extra fields in the detail panel, without time, level and message
+    pub fn toggle_extra_fields_only(&mut self) {
+        self.display.extra_fields_only = !self.display.extra_fields_only;
+    }
+
+    /// Reverse the list order, keeping the same log selected
+    /// `selected_index` is a row on screen, so it is mirrored
+    pub fn toggle_newest_first(&mut self) {
+        let selected = self.selected_log_index();
+        self.display.newest_first = !self.display.newest_first;
+        self.invalidate_rows();
+        if let Some(index) = selected {
+            self.select_log(index);
+        }
+        self.clamp_selection();
+    }
+
+    /// Switch between the grouped and the flat list, keeping the same log selected
+    pub fn toggle_grouped(&mut self) {
+        if self.display.group_by.is_none() {
+            self.set_status("Start with --group-by <field> to group logs");
+            return;
+        }
+        let selected = self.selected_log_index();
+        self.display.grouped = !self.display.grouped;
+        self.invalidate_rows();
+        if let Some(index) = selected {
+            self.select_log(index);
+        }
+        self.clamp_selection();
+    }
+
+    /// Expand or collapse the group under the selection
+    /// On a log inside an expanded group, collapse that group
+    pub fn toggle_group(&mut self) {
+        let Some(row) = self
+            .list_rows()
+            .and_then(|rows| rows.get(self.selected_index).cloned())
+        else {
+            return;
+        };
+        match row {
+            ListRow::Group { key, expanded, .. } => {
+                if expanded {
+                    self.display.expanded_groups.remove(&key);
+                } else {
+                    self.display.expanded_groups.insert(key);
+                }
+                self.invalidate_rows();
+            }
+            ListRow::Log {
+                index,
+                nested: true,
+            } => {
+                let key = self
+                    .display
+                    .group_field()
+                    .and_then(|field| group_key(&self.current_logs()[index], field));
+                if let Some(key) = key {
+                    self.display.expanded_groups.remove(&key);
+                    self.invalidate_rows();
+                    self.select_log(index);
+                }
+            }
+            ListRow::Log { .. } => {}
+        }
     }
 
     /// Move selection down
     pub fn move_down(&mut self) {
-        let logs_len = self.current_logs().len();
-        if logs_len > 0 {
-            self.selected_index = (self.selected_index + 1).min(logs_len - 1);
-        }
+        self.move_selection(1);
     }
 
     /// Move selection up
     pub fn move_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
-        }
+        self.move_selection(-1);
     }
 
     /// Jump to first log
     pub fn jump_to_first(&mut self) {
+        let total = self.current_window().total;
+        if self.is_windowed() && total > 0 {
+            let first = if self.display.newest_first {
+                total - 1
+            } else {
+                0
+            };
+            self.select_stored_log(first);
+            return;
+        }
         self.selected_index = 0;
     }
 
     /// Jump to last log
     pub fn jump_to_last(&mut self) {
-        let logs_len = self.current_logs().len();
+        let total = self.current_window().total;
+        if self.is_windowed() && total > 0 {
+            let last = if self.display.newest_first {
+                0
+            } else {
+                total - 1
+            };
+            self.select_stored_log(last);
+            return;
+        }
+        let logs_len = self.row_count();
         if logs_len > 0 {
             self.selected_index = logs_len - 1;
         }
     }
 
+    /// Select the next ERROR/FATAL log below the selection
+    pub fn next_error(&mut self) {
+        self.jump_to_error(true);
+    }
+
+    /// Select the previous ERROR/FATAL log above the selection
+    pub fn prev_error(&mut self) {
+        self.jump_to_error(false);
+    }
+
+    /// Scan the logs in memory from the selection, stopping at the ends
+    /// `down` is the direction on screen, so it runs backwards with newest-first
+    fn jump_to_error(&mut self, down: bool) {
+        let Some(selected) = self.selected_log_index() else {
+            return;
+        };
+        let is_error = |log: &JsonLog| {
+            self.display
+                .log_level(log)
+                .is_some_and(|level| level >= LogLevel::Error)
+        };
+        let logs = self.current_logs();
+        let found = if down != self.display.newest_first {
+            (selected + 1..logs.len()).find(|&i| is_error(&logs[i]))
+        } else {
+            (0..selected).rev().find(|&i| is_error(&logs[i]))
+        };
+        match found {
+            Some(index) => self.select_log(index),
+            None if down => self.set_status("No more errors below"),
+            None => self.set_status("No more errors above"),
+        }
+    }
+
     /// Scroll down half page
     pub fn scroll_down_half_page(&mut self, page_height: usize) {
-        let logs_len = self.current_logs().len();
-        if logs_len > 0 {
-            let half_page = page_height / 2;
-            self.selected_index = (self.selected_index + half_page).min(logs_len - 1);
-        }
+        self.move_selection((page_height / 2) as isize);
     }
 
     /// Scroll up half page
     pub fn scroll_up_half_page(&mut self, page_height: usize) {
-        let half_page = page_height / 2;
-        self.selected_index = self.selected_index.saturating_sub(half_page);
+        self.move_selection(-((page_height / 2) as isize));
     }
 
     /// Scroll down full page
     pub fn scroll_down_page(&mut self, page_height: usize) {
-        let logs_len = self.current_logs().len();
-        if logs_len > 0 {
-            self.selected_index = (self.selected_index + page_height).min(logs_len - 1);
-        }
+        self.move_selection(page_height as isize);
     }
 
     /// Scroll up full page
     pub fn scroll_up_page(&mut self, page_height: usize) {
-        self.selected_index = self.selected_index.saturating_sub(page_height);
+        self.move_selection(-(page_height as isize));
     }
 
     /// Toggle detail panel
@@ -151,6 +815,235 @@ impl App {
         self.show_detail_panel = !self.show_detail_panel;
     }
 
+    /// Move keys to the detail panel's field cursor, opening the panel if needed
+    pub fn focus_detail(&mut self) {
+        self.show_detail_panel = true;
+        self.focus = Focus::Detail;
+        self.clamp_detail_field();
+    }
+
+    /// Return keys to the log list, dropping the detail search
+    pub fn unfocus_detail(&mut self) {
+        self.focus = Focus::LogList;
+        self.detail_search.clear();
+    }
+
+    /// Start typing a new detail search; matches are highlighted as you type
+    pub fn start_detail_search(&mut self) {
+        self.detail_search.clear();
+        self.focus = Focus::DetailSearch;
+    }
+
+    /// Keep the search term and move the field cursor to the first matching field
+    pub fn confirm_detail_search(&mut self) {
+        self.focus = Focus::Detail;
+        let Some(log) = self.detail_log() else {
+            return;
+        };
+        if self.show_detail_modal {
+            let line = search_match_line(log, &self.display, &self.detail_search);
+            // A few lines of context above the match, like the panel's auto-scroll
+            self.detail_modal_scroll = line.map_or(0, |line| line.saturating_sub(2) as u16);
+        }
+        match search_match_field(log, &self.display, &self.detail_search) {
+            Some(field) => self.detail_field = field,
+            None if !self.detail_search.is_empty() => {
+                let message = format!("No match for '{}'", self.detail_search);
+                self.set_status(message);
+            }
+            None => {}
+        }
+    }
+
+    /// Drop the search term and return to the field cursor
+    pub fn cancel_detail_search(&mut self) {
+        self.detail_search.clear();
+        self.focus = Focus::Detail;
+    }
+
+    pub fn detail_move_down(&mut self) {
+        self.detail_field += 1;
+        self.clamp_detail_field();
+    }
+
+    pub fn detail_move_up(&mut self) {
+        self.detail_field = self.detail_field.saturating_sub(1);
+    }
+
+    /// Keep the field cursor within the detail log's fields
+    fn clamp_detail_field(&mut self) {
+        let count = self
+            .detail_log()
+            .map_or(0, |log| self.display.visible_field_names(log).len());
+        self.detail_field = self.detail_field.min(count.saturating_sub(1));
+    }
+
+    /// Copy the value under the detail field cursor: strings as-is, anything else as JSON
+    pub fn copy_selected_field(&mut self) {
+        self.clamp_detail_field();
+        let Some(log) = self.detail_log() else {
+            return;
+        };
+        let Some(name) = self
+            .display
+            .visible_field_names(log)
+            .get(self.detail_field)
+            .copied()
+        else {
+            return;
+        };
+        let Some(value) = log.get_field(name) else {
+            return;
+        };
+
+        let text = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let name = name.to_string();
+        match copy_to_clipboard(&text) {
+            Ok(()) => self.set_status(format!("Copied {} to clipboard", name)),
+            Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+        }
+    }
+
+    /// Filter all logs by the value of the field under the detail cursor
+    ///
+    /// Values must be equal, or with `substring` the field must contain the detail
+    /// search term (the whole value without a search). Fields are matched by their
+    /// database column, so `msg` filters on `message`.
+    pub fn filter_by_selected_field(&mut self, substring: bool) -> Result<()> {
+        self.clamp_detail_field();
+        let Some(log) = self.detail_log() else {
+            return Ok(());
+        };
+        let Some(name) = self
+            .display
+            .visible_field_names(log)
+            .get(self.detail_field)
+            .copied()
+        else {
+            return Ok(());
+        };
+        let Some(value) = log.get_field(name) else {
+            return Ok(());
+        };
+
+        let column = normalize_field_name(name);
+        let clause = if !substring {
+            equality_clause(column, value)
+        } else if !self.detail_search.is_empty() {
+            contains_clause(column, &self.detail_search)
+        } else {
+            match value {
+                serde_json::Value::String(s) => contains_clause(column, s),
+                other => contains_clause(column, &other.to_string()),
+            }
+        };
+        self.detail_search.clear();
+        self.apply_preset_filter(&clause)
+    }
+
+    /// Give the list more height at the expense of the detail panel
+    pub fn grow_list(&mut self) {
+        self.list_percent = (self.list_percent + LIST_PERCENT_STEP).min(MAX_LIST_PERCENT);
+    }
+
+    /// Give the detail panel more height at the expense of the list
+    pub fn shrink_list(&mut self) {
+        self.list_percent = self
+            .list_percent
+            .saturating_sub(LIST_PERCENT_STEP)
+            .max(MIN_LIST_PERCENT);
+    }
+
+    /// Toggle whether hidden fields are shown
+    pub fn toggle_hidden_fields(&mut self) {
+        self.display.show_hidden_fields = !self.display.show_hidden_fields;
+    }
+
+    /// Toggle wide mode (inline key=value columns in the list)
+    pub fn toggle_wide_mode(&mut self) {
+        self.display.wide_mode = !self.display.wide_mode;
+    }
+
+    /// Scroll wide mode columns so those cut off at the right edge come into view
+    pub fn scroll_columns_right(&mut self) {
+        self.scroll_columns(true);
+    }
+
+    /// Scroll wide mode columns back towards the first one
+    pub fn scroll_columns_left(&mut self) {
+        self.scroll_columns(false);
+    }
+
+    /// Move `table_col_offset` by one column, keeping at least the last column visible
+    fn scroll_columns(&mut self, right: bool) {
+        let columns = self.display.visible_columns.len();
+        if !self.display.wide_mode || columns == 0 {
+            self.set_status("No columns to scroll (wide mode with --columns)");
+            return;
+        }
+        let offset = &mut self.display.table_col_offset;
+        *offset = if right {
+            (*offset + 1).min(columns - 1)
+        } else {
+            offset.saturating_sub(1)
+        };
+    }
+
+    /// Toggle timestamps between the configured time zone and UTC
+    pub fn toggle_time_zone(&mut self) {
+        self.display.toggle_time_zone();
+    }
+
+    /// Toggle the age tags (`<1m`, `<5m`, `<1h`, `>1h`) in the list
+    pub fn toggle_age_tags(&mut self) {
+        self.display.show_age = !self.display.show_age;
+    }
+
+    /// Toggle the logger name column in the list
+    pub fn toggle_logger_name(&mut self) {
+        self.display.show_logger = !self.display.show_logger;
+    }
+
+    /// Show/hide the source file column
+    pub fn toggle_source_file(&mut self) {
+        self.display.show_source_file = !self.display.show_source_file;
+    }
+
+    /// Filter to every log in the selected log's trace
+    /// A dotted trace field is matched inside its JSON column unless a key has that name
+    pub fn filter_by_trace(&mut self) -> Result<()> {
+        let Some(log) = self.selected_log() else {
+            return Ok(());
+        };
+        let Some((field, trace_id)) = log.get_trace_id(&self.display.trace_fields) else {
+            return Ok(());
+        };
+
+        let clause = match field.split_once('.') {
+            Some((column, path)) if !log.fields.contains_key(field) => {
+                json_path_equality_clause(column, path, &trace_id)
+            }
+            _ => equality_clause(field, &serde_json::Value::String(trace_id)),
+        };
+        self.apply_preset_filter(&clause)
+    }
+
+    /// Filter to logs from the same logger as the selected log
+    pub fn filter_by_logger(&mut self) -> Result<()> {
+        let Some((field, name)) = self
+            .selected_log()
+            .and_then(|log| log.get_logger_name_entry())
+        else {
+            return Ok(());
+        };
+
+        let clause = equality_clause(field, &serde_json::Value::String(name.to_string()));
+        self.apply_preset_filter(&clause)
+    }
+
     /// Toggle filter panel
     pub fn toggle_filter_panel(&mut self) {
         self.show_filter_panel = !self.show_filter_panel;
@@ -161,14 +1054,130 @@ impl App {
         }
     }
 
+    /// Select the next field in the filter panel's field table
+    pub fn filter_field_down(&mut self) {
+        let last = self.field_schema.len().saturating_sub(1);
+        self.filter_field = (self.filter_field + 1).min(last);
+    }
+
+    /// Select the previous field in the filter panel's field table
+    pub fn filter_field_up(&mut self) {
+        self.filter_field = self.filter_field.saturating_sub(1);
+    }
+
+    /// Add a comparison on the selected field to the filter input, after an
+    /// `AND` if it already has a clause, and start typing its value
+    pub fn insert_filter_field(&mut self) {
+        let Some((name, field_type)) = self.field_schema.get(self.filter_field) else {
+            return;
+        };
+        let (template, value_offset) = comparison_template(name, field_type);
+
+        self.filter_input.move_cursor(CursorMove::Bottom);
+        self.filter_input.move_cursor(CursorMove::End);
+        let has_clause = self
+            .filter_input
+            .lines()
+            .iter()
+            .any(|line| !line.trim().is_empty());
+        if has_clause {
+            self.filter_input.insert_str(" AND ");
+        }
+        self.filter_input.insert_str(&template);
+        // Put the cursor where the value goes, e.g. between the quotes
+        for _ in 0..value_offset {
+            self.filter_input.move_cursor(CursorMove::Back);
+        }
+        self.focus = Focus::FilterInput;
+    }
+
+    /// Switch the filter input between one line and the multi-line editor
+    pub fn toggle_filter_editor(&mut self) {
+        self.filter_editor = !self.filter_editor;
+    }
+
     /// Toggle help menu
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
+        self.help_scroll = 0;
+    }
+
+    /// Scroll the help popup down; clamped to the content when rendered
+    pub fn help_scroll_down(&mut self, lines: u16) {
+        self.help_scroll = self.help_scroll.saturating_add(lines);
+    }
+
+    /// Scroll the help popup up
+    pub fn help_scroll_up(&mut self, lines: u16) {
+        self.help_scroll = self.help_scroll.saturating_sub(lines);
+    }
+
+    /// Show the schema with each field's presence and a sample value, or close it
+    /// Presence counts the logs in memory, all of them unless `--memory-limit` is set
+    pub fn toggle_schema_summary(&mut self) {
+        self.schema_summary_scroll = 0;
+        self.schema_summary = match self.schema_summary {
+            Some(_) => None,
+            None => Some(summarize_fields(&self.field_schema, &self.all_logs)),
+        };
+    }
+
+    /// Scroll the schema summary down; clamped to the content when rendered
+    pub fn schema_summary_scroll_down(&mut self, lines: u16) {
+        self.schema_summary_scroll = self.schema_summary_scroll.saturating_add(lines);
+    }
+
+    /// Scroll the schema summary up
+    pub fn schema_summary_scroll_up(&mut self, lines: u16) {
+        self.schema_summary_scroll = self.schema_summary_scroll.saturating_sub(lines);
+    }
+
+    /// Open the detail log (pinned or selected) full screen, or close it
+    pub fn toggle_detail_modal(&mut self) {
+        if self.show_detail_modal {
+            self.show_detail_modal = false;
+            self.unfocus_detail();
+        } else if self.detail_log().is_some() {
+            self.show_detail_modal = true;
+            self.detail_modal_scroll = 0;
+            self.detail_search.clear();
+        } else {
+            self.set_status("No log selected");
+        }
+    }
+
+    /// Scroll the detail modal down; clamped to the content when rendered
+    pub fn detail_modal_scroll_down(&mut self, lines: u16) {
+        self.detail_modal_scroll = self.detail_modal_scroll.saturating_add(lines);
+    }
+
+    /// Scroll the detail modal up
+    pub fn detail_modal_scroll_up(&mut self, lines: u16) {
+        self.detail_modal_scroll = self.detail_modal_scroll.saturating_sub(lines);
     }
 
-    /// Apply the current filter from the input
+    /// Apply the current filter from the input and close the filter panel
     pub fn apply_filter(&mut self) -> Result<()> {
-        let filter_text = self.filter_input.lines().join("");
+        self.apply_filter_input(true)
+    }
+
+    /// Apply the current filter from the input, keeping the filter panel open to
+    /// refine it while the results show behind it
+    pub fn apply_filter_keep_open(&mut self) -> Result<()> {
+        self.apply_filter_input(false)
+    }
+
+    fn apply_filter_input(&mut self, close_panel: bool) -> Result<()> {
+        // Line breaks separate tokens, e.g. `level >= 40` / `AND pid = 1`;
+        // indentation and blank lines don't end up in the active filter
+        let filter_text = self
+            .filter_input
+            .lines()
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
         let trimmed = filter_text.trim();
 
         if trimmed.is_empty() {
@@ -176,15 +1185,22 @@ impl App {
             return Ok(());
         }
 
-        match self.db.query_logs(Some(trimmed)) {
-            Ok(logs) => {
+        match self.query_filtered(trimmed) {
+            Ok((logs, window)) => {
                 self.filtered_logs = logs;
+                self.filtered_window = window;
+                self.invalidate_rows();
                 self.active_filter = Some(trimmed.to_string());
+                self.message_search = None;
                 self.view_mode = ViewMode::Filtered;
+                self.refresh_volume();
                 self.selected_index = 0;
+                self.clamp_selection();
                 self.filter_error = None;
-                self.show_filter_panel = false;
-                self.focus = Focus::LogList;
+                if close_panel {
+                    self.show_filter_panel = false;
+                    self.focus = Focus::LogList;
+                }
                 Ok(())
             }
             Err(e) => {
@@ -194,22 +1210,356 @@ impl App {
         }
     }
 
-    /// Clear the active filter and return to all logs
-    pub fn clear_filter(&mut self) {
-        self.active_filter = None;
-        self.view_mode = ViewMode::AllLogs;
-        self.selected_index = 0;
-        self.filter_error = None;
-        self.filter_input = TextArea::default();
-        self.filter_input.set_placeholder_text("Enter SQL WHERE clause (e.g., level >= 40)");
+    /// Logs matching `filter` to hold in memory: all of them, or the first page
+    /// with a memory or result limit
+    fn query_filtered(&self, filter: &str) -> Result<(Vec<JsonLog>, LogWindow)> {
+        let Some(limit) = self.memory_limit.or(self.result_limit) else {
+            let logs = self.db.query_logs(Some(filter))?;
+            let window = LogWindow::full(logs.len());
+            return Ok((logs, window));
+        };
+        let total = self.db.count_matching(Some(filter))?;
+        let logs = self.db.query_page(Some(filter), limit, 0)?;
+        Ok((logs, LogWindow { start: 0, total }))
     }
 
-    /// Apply a preset filter
-    pub fn apply_preset_filter(&mut self, filter: &str) -> Result<()> {
+    /// Whether the filtered view holds only part of its matches because of
+    /// `result_limit`, with more to load
+    pub fn is_result_limited(&self) -> bool {
+        self.memory_limit.is_none()
+            && self.result_limit.is_some()
+            && self.view_mode == ViewMode::Filtered
+            && self.filtered_logs.len() < self.filtered_window.total
+    }
+
+    /// Load the next `result_limit` matches of a limited filter
+    pub fn load_more_results(&mut self) {
+        let (Some(limit), Some(filter)) = (self.result_limit, self.active_filter.clone()) else {
+            self.set_status("No limited filter results to load");
+            return;
+        };
+        if !self.is_result_limited() {
+            let message = format!("All {} matches loaded", self.filtered_window.total);
+            self.set_status(message);
+            return;
+        }
+
+        let selected = self.selected_log_index();
+        let loaded = self.filtered_logs.len();
+        match self.db.query_page(Some(&filter), limit, loaded) {
+            Ok(logs) => {
+                self.filtered_logs.extend(logs);
+                self.invalidate_rows();
+                // Newest-first rows shift as logs are added; stay on the same log
+                if let Some(index) = selected {
+                    self.select_log(index);
+                }
+                let message = format!(
+                    "Showing {} of {} matches",
+                    self.filtered_logs.len(),
+                    self.filtered_window.total
+                );
+                self.set_status(message);
+            }
+            Err(e) => self.set_status(format!("Failed to load more results: {}", e)),
+        }
+    }
+
+    /// Clear the active filter and return to all logs
+    pub fn clear_filter(&mut self) {
+        self.active_filter = None;
+        self.message_search = None;
+        self.view_mode = ViewMode::AllLogs;
+        self.invalidate_rows();
+        self.refresh_volume();
+        self.selected_index = 0;
+        self.clamp_selection();
+        self.filter_error = None;
+        self.filter_input = TextArea::default();
+        self.filter_input.set_placeholder_text("Enter SQL WHERE clause (e.g., level >= 40)");
+    }
+
+    /// Return to the unfiltered list at the first log, oldest first: clears the
+    /// filter, the detail search, field cursor and pinned log, expanded groups and
+    /// scrolled columns. Panels (detail, facets, debug logs) and display toggles stay as they are
+    pub fn reset_view(&mut self) {
+        self.clear_filter();
+        self.show_filter_panel = false;
+        self.numeric_filter = None;
+        self.focus = Focus::LogList;
+        self.detail_search.clear();
+        self.detail_field = 0;
+        self.pinned_log = None;
+        self.display.newest_first = false;
+        self.display.expanded_groups.clear();
+        self.invalidate_rows();
+        self.display.table_col_offset = 0;
+        self.jump_to_first();
+        self.scroll_offset = 0;
+        self.set_status("View reset");
+    }
+
+    /// Apply a preset filter
+    pub fn apply_preset_filter(&mut self, filter: &str) -> Result<()> {
         self.filter_input = TextArea::from([filter]);
         self.apply_filter()
     }
 
+    /// Open the message quick filter prompt, starting from the active term
+    pub fn start_message_search(&mut self) {
+        self.message_search_input = self.message_search.clone().unwrap_or_default();
+        self.focus = Focus::MessageSearch;
+    }
+
+    /// Filter to logs whose message contains the typed term, ignoring case
+    /// An empty term clears the filter
+    pub fn confirm_message_search(&mut self) -> Result<()> {
+        self.focus = Focus::LogList;
+        let term = std::mem::take(&mut self.message_search_input);
+        let term = term.trim();
+        if term.is_empty() {
+            self.clear_filter();
+            return Ok(());
+        }
+        if !self.field_schema.iter().any(|(name, _)| name == "message") {
+            self.set_status("No message field to search");
+            return Ok(());
+        }
+
+        self.apply_preset_filter(&contains_ignore_case_clause("message", term))?;
+        self.message_search = Some(term.to_string());
+        Ok(())
+    }
+
+    /// Close the message quick filter prompt, keeping the current filter
+    pub fn cancel_message_search(&mut self) {
+        self.message_search_input.clear();
+        self.focus = Focus::LogList;
+    }
+
+    /// Filter to logs from the last hour
+    /// String times are compared as instants, whatever their UTC offset
+    pub fn filter_last_hour(&mut self) -> Result<()> {
+        let now = jiff::Timestamp::now().as_millisecond();
+        let Ok(since) = jiff::Timestamp::from_millisecond(now - 60 * 60 * 1000) else {
+            return Ok(());
+        };
+        let field_type = self.time_field_type();
+        self.apply_preset_filter(&since_clause("time", &field_type, since))
+    }
+
+    /// Filter to logs in an age window of the reference time (`--age-reference`):
+    /// the last minute, 5 minutes or hour, or older than an hour
+    pub fn filter_by_age(&mut self, bucket: AgeBucket) -> Result<()> {
+        let Some(reference) = self.display.age_reference_time() else {
+            self.set_status("No log times to measure ages from");
+            return Ok(());
+        };
+        let Ok(edge) =
+            jiff::Timestamp::from_millisecond(reference.as_millisecond() - bucket.edge_ms())
+        else {
+            return Ok(());
+        };
+        let field_type = self.time_field_type();
+        let clause = match bucket {
+            AgeBucket::Older => before_clause("time", &field_type, edge),
+            _ => since_clause("time", &field_type, edge),
+        };
+        self.apply_preset_filter(&clause)
+    }
+
+    /// Column type of the `time` field, integer (epoch milliseconds) if there is none
+    fn time_field_type(&self) -> FieldType {
+        self.field_schema
+            .iter()
+            .find(|(name, _)| name == "time")
+            .map_or(FieldType::Integer, |(_, field_type)| field_type.clone())
+    }
+
+    /// Toggle the facets sidebar
+    pub fn toggle_facets(&mut self) {
+        self.show_facets = !self.show_facets;
+        if self.show_facets {
+            self.focus = Focus::Facets;
+            self.load_facets();
+        } else {
+            self.focus = Focus::LogList;
+        }
+    }
+
+    /// Toggle the log volume sparkline; needs a `time` field
+    pub fn toggle_volume(&mut self) {
+        if !self.show_volume && !self.field_schema.iter().any(|(name, _)| name == "time") {
+            self.set_status("No time field to chart");
+            return;
+        }
+        self.show_volume = !self.show_volume;
+        self.refresh_volume();
+    }
+
+    /// Fit the volume sparkline to a terminal `width` columns wide, at startup
+    /// and on resize, querying again if the bucket count changed
+    pub fn resize_volume(&mut self, width: u16) {
+        let buckets = width.saturating_sub(VOLUME_LABEL_WIDTH) as usize;
+        if buckets != self.volume.buckets {
+            self.volume.buckets = buckets;
+            self.refresh_volume();
+        }
+    }
+
+    /// Query the log volume of the current view while the sparkline is shown
+    /// Called when the filter, the logs or the width change; only queries if
+    /// one of them differs from the last query
+    pub fn refresh_volume(&mut self) {
+        let max_buckets = self.volume.buckets;
+        if !self.show_volume || max_buckets == 0 {
+            return;
+        }
+        let filter = match self.view_mode {
+            ViewMode::AllLogs => None,
+            ViewMode::Filtered => self.active_filter.clone(),
+        };
+        let key = (filter, self.total_log_count(), max_buckets);
+        if self.volume.key.as_ref() == Some(&key) {
+            return;
+        }
+
+        let time_expr = epoch_ms_expr("time", &self.time_field_type());
+        match self
+            .db
+            .log_volume(&time_expr, max_buckets, key.0.as_deref())
+        {
+            Ok(volume) => {
+                self.volume.volume = volume;
+                self.volume.error = None;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to query log volume: {:?}", e);
+                self.volume.volume = LogVolume::default();
+                self.volume.error = Some("Failed to query log volume".to_string());
+            }
+        }
+        self.volume.key = Some(key);
+    }
+
+    /// Name of the field currently shown in the facets sidebar
+    pub fn facet_field(&self) -> Option<&str> {
+        self.field_schema
+            .get(self.facets.field_index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Field index, filter and log count the facets sidebar should be showing
+    fn facets_key(&self) -> (usize, Option<String>, usize) {
+        (
+            self.facets.field_index,
+            self.active_filter.clone(),
+            self.total_log_count(),
+        )
+    }
+
+    /// Query the facets again if the filter or the logs changed since they were
+    /// loaded, keeping the selected row when the field is the same
+    /// Called on every render, so it only queries when the counts are stale
+    pub fn refresh_facets(&mut self) {
+        let key = self.facets_key();
+        let Some(old_key) = &self.facets.key else {
+            self.load_facets();
+            return;
+        };
+        if *old_key == key {
+            return;
+        }
+
+        let same_field = old_key.0 == key.0;
+        let selected = self.facets.selected;
+        self.load_facets();
+        if same_field {
+            let len = self.facets.values.values.len();
+            self.facets.selected = selected.min(len.saturating_sub(1));
+        }
+    }
+
+    /// Query the top values of the current facet field within the active filter
+    fn load_facets(&mut self) {
+        self.facets.selected = 0;
+        self.facets.key = Some(self.facets_key());
+
+        let Some(field) = self.facet_field() else {
+            self.facets.values = TopValues::default();
+            self.facets.error = Some("No fields available".to_string());
+            return;
+        };
+
+        match self
+            .db
+            .top_values(field, FACET_LIMIT, self.active_filter.as_deref())
+        {
+            Ok(values) => {
+                self.facets.values = values;
+                self.facets.error = None;
+            }
+            Err(e) => {
+                self.facets.values = TopValues::default();
+                self.facets.error = Some(format!("{}", e));
+            }
+        }
+    }
+
+    /// Show the next field in the facets sidebar
+    pub fn next_facet_field(&mut self) {
+        if !self.field_schema.is_empty() {
+            self.facets.field_index = (self.facets.field_index + 1) % self.field_schema.len();
+            self.load_facets();
+        }
+    }
+
+    /// Show the previous field in the facets sidebar
+    pub fn prev_facet_field(&mut self) {
+        if !self.field_schema.is_empty() {
+            let len = self.field_schema.len();
+            self.facets.field_index = (self.facets.field_index + len - 1) % len;
+            self.load_facets();
+        }
+    }
+
+    /// Move the facet value selection down
+    pub fn facet_move_down(&mut self) {
+        let len = self.facets.values.values.len();
+        if len > 0 {
+            self.facets.selected = (self.facets.selected + 1).min(len - 1);
+        }
+    }
+
+    /// Move the facet value selection up
+    pub fn facet_move_up(&mut self) {
+        self.facets.selected = self.facets.selected.saturating_sub(1);
+    }
+
+    /// Filter to logs where the facet field equals the selected value,
+    /// narrowing the active filter if there is one
+    pub fn apply_facet_value(&mut self) -> Result<()> {
+        let Some(field) = self.facet_field() else {
+            return Ok(());
+        };
+        let Some((value, _)) = self.facets.values.values.get(self.facets.selected) else {
+            return Ok(());
+        };
+
+        let clause = equality_clause(field, value);
+        let filter = match &self.active_filter {
+            Some(active) => format!("({}) AND {}", active, clause),
+            None => clause,
+        };
+
+        self.apply_preset_filter(&filter)?;
+
+        // Keep the sidebar open on the narrowed view
+        self.focus = Focus::Facets;
+        self.load_facets();
+        Ok(())
+    }
+
     /// Focus on filter input
     pub fn focus_filter(&mut self) {
         self.focus = Focus::FilterInput;
@@ -222,8 +1572,939 @@ impl App {
         self.show_filter_panel = false;
     }
 
+    /// Open the command palette with an empty search
+    pub fn open_command_palette(&mut self) {
+        self.show_command_palette = true;
+        self.palette_input = TextArea::default();
+        self.palette_input
+            .set_placeholder_text("Type to search actions");
+        self.palette_selected = 0;
+        self.focus = Focus::CommandPalette;
+    }
+
+    /// Close the command palette and return to the log list
+    pub fn close_command_palette(&mut self) {
+        self.show_command_palette = false;
+        self.focus = Focus::LogList;
+    }
+
+    /// Actions matching the palette search text
+    pub fn palette_matches(&self) -> Vec<&'static ActionInfo> {
+        filter_actions(&self.palette_input.lines().join(" "))
+    }
+
+    /// Action under the palette selection
+    pub fn selected_palette_action(&self) -> Option<Action> {
+        self.palette_matches()
+            .get(self.palette_selected)
+            .map(|info| info.action)
+    }
+
+    pub fn palette_move_down(&mut self) {
+        let len = self.palette_matches().len();
+        if len > 0 {
+            self.palette_selected = (self.palette_selected + 1).min(len - 1);
+        }
+    }
+
+    pub fn palette_move_up(&mut self) {
+        self.palette_selected = self.palette_selected.saturating_sub(1);
+    }
+
+    /// Open the numeric filter prompt on the first numeric field
+    pub fn open_numeric_filter(&mut self) {
+        match NumericFilter::new(&self.field_schema) {
+            Some(prompt) => {
+                self.numeric_filter = Some(prompt);
+                self.focus = Focus::NumericFilter;
+            }
+            None => self.set_status("No numeric fields to filter on"),
+        }
+    }
+
+    /// Close the numeric filter prompt and return to the log list
+    pub fn close_numeric_filter(&mut self) {
+        self.numeric_filter = None;
+        self.focus = Focus::LogList;
+    }
+
+    /// Confirm the prompt's current step, applying the filter after the value
+    pub fn confirm_numeric_filter(&mut self) -> Result<()> {
+        let Some(prompt) = self.numeric_filter.as_mut() else {
+            return Ok(());
+        };
+        match prompt.confirm() {
+            Ok(Some(clause)) => {
+                self.close_numeric_filter();
+                self.apply_preset_filter(&clause)
+            }
+            Ok(None) => Ok(()),
+            Err(e) => {
+                self.set_status(format!("{}", e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Show a transient status message
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+    }
+
+    /// Copy the selected HTTP request log as a curl command
+    pub fn copy_selected_as_curl(&mut self) {
+        let Some(log) = self.selected_log() else {
+            return;
+        };
+
+        match format_curl(log) {
+            Some(curl) => match copy_to_clipboard(&curl) {
+                Ok(()) => self.set_status("Copied curl command to clipboard"),
+                Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+            },
+            None => self.set_status("Selected log has no req.url to build a curl command"),
+        }
+    }
+
+    /// Write the visible logs to `log-viewer-export-<time>.<ext>` in the working directory
+    pub fn export_current_logs(&mut self, format: ExportFormat) {
+        let path = format!(
+            "log-viewer-export-{}.{}",
+            jiff::Timestamp::now().strftime("%Y%m%d-%H%M%S"),
+            format.extension()
+        );
+
+        let logs = self.source_logs();
+        match export_logs(&logs, &path, format, &self.redacted_fields) {
+            Ok(count) => self.set_status(format!("Exported {} logs to {}", count, path)),
+            Err(e) => self.set_status(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// The visible logs as parsed, with their original field names and order
+    ///
+    /// Filtered logs are rebuilt from database rows, which use normalized names
+    /// (`msg` → `message`) and the table's column order. Row id `n` is the `n`-th
+    /// parsed log in `all_logs` (placeholders for unparsable lines are not
+    /// stored), so the logs are looked up by row id. With `--memory-limit` only a
+    /// page is in memory and the rows are used as is.
+    fn source_logs(&self) -> Cow<'_, [JsonLog]> {
+        let filter = match (&self.view_mode, &self.active_filter) {
+            (ViewMode::Filtered, Some(filter)) if self.memory_limit.is_none() => filter,
+            _ => return Cow::Borrowed(self.current_logs()),
+        };
+        let stored: Vec<&JsonLog> = self
+            .all_logs
+            .iter()
+            .filter(|log| !log.is_unparsed())
+            .collect();
+        let originals: Option<Vec<JsonLog>> = self.db.query_ids(filter).ok().and_then(|ids| {
+            ids.into_iter()
+                .map(|id| {
+                    let index = usize::try_from(id).ok()?.checked_sub(1)?;
+                    stored.get(index).map(|log| (*log).clone())
+                })
+                .collect()
+        });
+        match originals {
+            Some(logs) if logs.len() == self.filtered_logs.len() => Cow::Owned(logs),
+            _ => Cow::Borrowed(self.current_logs()),
+        }
+    }
+
+    /// Copy the visible (filtered) logs to the clipboard as an aligned text table
+    pub fn copy_current_logs_as_table(&mut self) {
+        let count = self.current_logs().len();
+        if count == 0 {
+            return;
+        }
+        let table = format_table(self.current_logs(), &self.display, &self.redacted_fields);
+        match copy_to_clipboard(&table) {
+            Ok(()) => self.set_status(format!("Copied {} logs as a table to clipboard", count)),
+            Err(e) => self.set_status(format!("Clipboard error: {}", e)),
+        }
+    }
+
     /// Quit the application
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+    use crate::storage::DEFAULT_SCHEMA_SAMPLE;
+    use crate::ui::age::AgeReference;
+
+    fn logs(range: std::ops::Range<usize>) -> Vec<JsonLog> {
+        range
+            .map(|i| {
+                parse_json_line(&format!(r#"{{"level":30,"msg":"log {}","n":{}}}"#, i, i)).unwrap()
+            })
+            .collect()
+    }
+
+    fn app_with_logs(count: usize) -> App {
+        app_from(logs(0..count))
+    }
+
+    fn app_from(logs: Vec<JsonLog>) -> App {
+        let mut db = LogDatabase::new_in_memory().unwrap();
+        db.create_table_from_logs(&logs, DEFAULT_SCHEMA_SAMPLE)
+            .unwrap();
+        db.insert_logs(&logs).unwrap();
+        App::new(db, logs, DisplayOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_clamp_selection_on_shrink() {
+        let mut app = app_with_logs(10);
+        app.selected_index = 8;
+
+        app.filtered_logs = app.all_logs[..3].to_vec();
+        app.view_mode = ViewMode::Filtered;
+        app.clamp_selection();
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 2"));
+
+        app.filtered_logs.clear();
+        app.clamp_selection();
+        assert_eq!(app.selected_index, 0);
+        assert!(app.selected_log().is_none());
+
+        // A filter matching nothing leaves a valid (empty) selection too
+        app.filter_input = TextArea::from(["n > 100"]);
+        app.apply_filter().unwrap();
+        assert_eq!(app.selected_index, 0);
+        app.move_down();
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_selection_on_grow() {
+        let mut app = app_with_logs(5);
+
+        // Tailing: a selection on the last log follows new logs
+        app.jump_to_last();
+        app.append_logs(logs(5..8)).unwrap();
+        assert_eq!(app.selected_index, 7);
+
+        // Elsewhere it stays on the same log
+        app.selected_index = 2;
+        app.append_logs(logs(8..10)).unwrap();
+        assert_eq!(app.selected_index, 2);
+
+        // Newest first: rows are inserted above, so the index moves with the log
+        app.toggle_newest_first();
+        let selected = app
+            .selected_log()
+            .unwrap()
+            .get_message()
+            .map(str::to_string);
+        app.append_logs(logs(10..12)).unwrap();
+        assert_eq!(
+            app.selected_log()
+                .unwrap()
+                .get_message()
+                .map(str::to_string),
+            selected
+        );
+    }
+
+    #[test]
+    fn test_grouped_navigation() {
+        let logs = ["a", "b", "a", "c", "a"]
+            .iter()
+            .enumerate()
+            .map(|(i, req)| {
+                parse_json_line(&format!(r#"{{"msg":"log {}","reqId":"{}"}}"#, i, req)).unwrap()
+            })
+            .collect();
+        let mut app = app_from(logs);
+        app.display.group_by = Some("reqId".to_string());
+        app.toggle_grouped();
+
+        // Three collapsed groups; the header stands for the group's first log
+        assert_eq!(app.row_count(), 3);
+        app.jump_to_last();
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 3"));
+
+        // Expanding "a" lists its logs under the header
+        app.jump_to_first();
+        app.toggle_group();
+        assert_eq!(app.row_count(), 6);
+        app.move_down();
+        app.move_down();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 2"));
+
+        // Enter on a nested log collapses its group and selects the header
+        app.toggle_group();
+        assert_eq!(app.row_count(), 3);
+        assert_eq!(app.selected_index, 0);
+
+        // Back to the flat list on the same log
+        app.move_down();
+        app.toggle_grouped();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 1"));
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_grouped_rows_follow_appends_and_filters() {
+        let log = |i: usize, req: &str| {
+            parse_json_line(&format!(r#"{{"msg":"log {}","reqId":"{}"}}"#, i, req)).unwrap()
+        };
+        let mut app = app_from(vec![log(0, "a"), log(1, "b")]);
+        app.display.group_by = Some("reqId".to_string());
+        app.toggle_grouped();
+        assert_eq!(app.row_count(), 2);
+
+        // Rows are cached, but a new group shows up once appended
+        app.append_logs(vec![log(2, "c")]).unwrap();
+        assert_eq!(app.row_count(), 3);
+
+        app.apply_preset_filter(r#""reqId" = 'a'"#).unwrap();
+        assert_eq!(app.row_count(), 1);
+        app.clear_filter();
+        assert_eq!(app.row_count(), 3);
+    }
+
+    #[test]
+    fn test_detail_search_moves_field_cursor() {
+        let mut app = app_with_logs(3);
+        app.focus_detail();
+
+        app.start_detail_search();
+        app.detail_search.push_str("LOG 1");
+        assert_eq!(app.focus, Focus::DetailSearch);
+        app.confirm_detail_search();
+        // Fields are level, msg, n; the selected log is "log 0"
+        assert_eq!(app.focus, Focus::Detail);
+        assert_eq!(app.detail_field, 0);
+        assert_eq!(app.status_message.as_deref(), Some("No match for 'LOG 1'"));
+
+        app.start_detail_search();
+        app.detail_search.push_str("LOG 0");
+        app.confirm_detail_search();
+        assert_eq!(app.detail_field, 1);
+
+        app.unfocus_detail();
+        assert!(app.detail_search.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_log() {
+        let mut app = app_with_logs(5);
+        app.selected_index = 1;
+        app.pin_selected_log();
+        assert!(app.show_detail_panel);
+
+        // The detail stays on the pinned log while the selection moves
+        app.move_down();
+        app.move_down();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 3"));
+        assert_eq!(app.detail_log().unwrap().get_message(), Some("log 1"));
+        assert!(app.detail_previous_log().is_none());
+
+        app.unpin_log();
+        assert_eq!(app.detail_log().unwrap().get_message(), Some("log 3"));
+        assert_eq!(
+            app.detail_previous_log().unwrap().get_message(),
+            Some("log 2")
+        );
+    }
+
+    #[test]
+    fn test_detail_modal() {
+        let mut app = app_with_logs(3);
+        app.toggle_detail_modal();
+        assert!(app.show_detail_modal);
+
+        app.detail_modal_scroll_down(3);
+        app.detail_modal_scroll_up(1);
+        assert_eq!(app.detail_modal_scroll, 2);
+
+        // Confirming a search scrolls to the line of the match
+        app.start_detail_search();
+        app.detail_search.push_str("\"n\"");
+        app.confirm_detail_search();
+        // Lines are {, level, msg, n, }, with two lines of context kept above
+        assert_eq!(app.detail_modal_scroll, 1);
+        assert_eq!(app.detail_field, 2);
+
+        app.toggle_detail_modal();
+        assert!(!app.show_detail_modal);
+        assert_eq!(app.focus, Focus::LogList);
+        assert!(app.detail_search.is_empty());
+    }
+
+    #[test]
+    fn test_volume_follows_filter() {
+        let mut app = app_with_logs(3);
+        app.toggle_volume();
+        assert!(!app.show_volume);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("No time field to chart")
+        );
+
+        let mut app = app_from(
+            (0..4)
+                .map(|i| {
+                    parse_json_line(&format!(r#"{{"time":{},"n":{}}}"#, i * 60_000, i)).unwrap()
+                })
+                .collect(),
+        );
+        app.resize_volume(VOLUME_LABEL_WIDTH + 10);
+        app.toggle_volume();
+        assert!(app.show_volume);
+        assert_eq!(app.volume.volume.counts, [1, 1, 1, 1]);
+
+        // Applying a filter or appending logs queries again, without a render
+        app.apply_preset_filter("n >= 2").unwrap();
+        assert_eq!(app.volume.volume.counts, [1, 1]);
+        let log = parse_json_line(r#"{"time":240000,"n":4}"#).unwrap();
+        app.append_logs(vec![log]).unwrap();
+        assert_eq!(app.volume.volume.counts, [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_facets_follow_filter() {
+        let mut app = app_from(
+            ["INFO", "INFO", "WARN", "ERROR"]
+                .iter()
+                .enumerate()
+                .map(|(i, level)| {
+                    parse_json_line(&format!(r#"{{"n":{},"lvl":"{}"}}"#, i, level)).unwrap()
+                })
+                .collect(),
+        );
+        app.toggle_facets();
+        while app.facet_field() != Some("lvl") {
+            app.next_facet_field();
+        }
+        assert_eq!(app.facets.values.values.len(), 3);
+        app.facet_move_down();
+
+        // A filter applied with the sidebar open marks the counts stale
+        app.apply_preset_filter("n >= 1").unwrap();
+        assert_eq!(app.facets.values.values[0].1, 2);
+        app.refresh_facets();
+        assert_eq!(app.facets.values.values.len(), 3);
+        assert_eq!(app.facets.values.values[0].1, 1);
+        assert_eq!(app.facets.selected, 1);
+
+        app.clear_filter();
+        app.refresh_facets();
+        assert_eq!(
+            app.facets.values.values[0],
+            (serde_json::Value::from("INFO"), 2)
+        );
+    }
+
+    #[test]
+    fn test_memory_limit_pages_logs() {
+        let mut app = app_with_logs(10);
+        let selected = |app: &App| {
+            app.selected_log()
+                .unwrap()
+                .get_message()
+                .unwrap()
+                .to_string()
+        };
+
+        app.set_memory_limit(4);
+        assert_eq!(app.all_logs.len(), 4);
+        assert_eq!(app.all_window.start, 6);
+        assert_eq!(selected(&app), "log 6");
+
+        // Moving above the window reads the logs around the target back in
+        app.move_up();
+        assert_eq!(app.all_window.start, 3);
+        assert_eq!(selected(&app), "log 5");
+        app.jump_to_first();
+        assert_eq!(app.all_window.start, 0);
+        assert_eq!(selected(&app), "log 0");
+
+        // New logs only join the window when it is at the tail
+        app.append_logs(logs(10..11)).unwrap();
+        assert_eq!(app.all_window.total, 11);
+        assert_eq!(app.all_logs.len(), 4);
+        assert_eq!(selected(&app), "log 0");
+
+        app.jump_to_last();
+        assert_eq!(selected(&app), "log 10");
+        app.append_logs(logs(11..13)).unwrap();
+        assert_eq!(app.all_window.start, 9);
+        assert_eq!(selected(&app), "log 12");
+        assert_eq!(app.total_log_count(), 13);
+    }
+
+    #[test]
+    fn test_memory_limit_pages_filtered_logs() {
+        let mut app = app_with_logs(10);
+        let selected = |app: &App| {
+            app.selected_log()
+                .unwrap()
+                .get_message()
+                .unwrap()
+                .to_string()
+        };
+        app.set_memory_limit(2);
+
+        app.apply_preset_filter("n >= 3").unwrap();
+        assert_eq!(app.total_log_count(), 7);
+        assert_eq!(app.filtered_logs.len(), 2);
+        assert_eq!(selected(&app), "log 3");
+
+        app.jump_to_last();
+        assert_eq!(app.filtered_window, LogWindow { start: 5, total: 7 });
+        assert_eq!(selected(&app), "log 9");
+        app.scroll_up_page(3);
+        assert_eq!(selected(&app), "log 6");
+    }
+
+    #[test]
+    fn test_result_limit_loads_more() {
+        let mut app = app_with_logs(10);
+        app.result_limit = Some(3);
+
+        app.apply_preset_filter("n >= 2").unwrap();
+        assert_eq!(app.total_log_count(), 8);
+        assert_eq!(app.filtered_logs.len(), 3);
+        assert!(app.is_result_limited());
+
+        app.jump_to_last();
+        app.load_more_results();
+        assert_eq!(app.filtered_logs.len(), 6);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Showing 6 of 8 matches")
+        );
+        // The selection stays on the log it was on
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 4"));
+
+        app.load_more_results();
+        assert!(!app.is_result_limited());
+        app.load_more_results();
+        assert_eq!(app.status_message.as_deref(), Some("All 8 matches loaded"));
+
+        // Without a filter every log is in memory, as before
+        app.clear_filter();
+        assert_eq!(app.current_logs().len(), 10);
+    }
+
+    #[test]
+    fn test_append_type_mismatched_log() {
+        let mut app = app_with_logs(3);
+
+        // `n` was sampled as an integer column; a string there widens it to text
+        let mismatched = parse_json_line(r#"{"level":30,"msg":"log 3","n":"n/a"}"#).unwrap();
+        app.append_logs(vec![mismatched]).unwrap();
+        app.append_logs(logs(4..5)).unwrap();
+
+        assert_eq!(app.all_logs.len(), 5);
+        assert_eq!(app.db.count_logs().unwrap(), 5);
+        let stored = app.db.query_logs(Some("message = 'log 3'")).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].get_field("n"), app.all_logs[3].get_field("n"));
+        let stored = app.db.query_logs(Some("message = 'log 4'")).unwrap();
+        assert_eq!(stored[0].get_field("n"), Some(&serde_json::json!("4")));
+        assert!(
+            app.field_schema
+                .contains(&("n".to_string(), FieldType::Text))
+        );
+        assert!(
+            app.status_message
+                .as_ref()
+                .unwrap()
+                .contains("Stored n as text")
+        );
+    }
+
+    #[test]
+    fn test_followed_logs_join_filtered_view() {
+        let mut app = app_with_logs(5);
+        let selected = |app: &App| {
+            app.selected_log()
+                .unwrap()
+                .get_message()
+                .unwrap()
+                .to_string()
+        };
+        app.apply_preset_filter("n >= 3").unwrap();
+
+        let mut new_logs = logs(5..7);
+        new_logs.extend(logs(0..1));
+        app.append_logs(new_logs).unwrap();
+        let messages: Vec<&str> = app
+            .filtered_logs
+            .iter()
+            .filter_map(JsonLog::get_message)
+            .collect();
+        assert_eq!(messages, vec!["log 3", "log 4", "log 5", "log 6"]);
+        assert_eq!(app.total_log_count(), 4);
+        assert_eq!(app.all_logs.len(), 8);
+        assert_eq!(selected(&app), "log 3");
+
+        // A selection on the newest match keeps tailing
+        app.jump_to_last();
+        app.append_logs(logs(7..8)).unwrap();
+        assert_eq!(selected(&app), "log 7");
+    }
+
+    #[test]
+    fn test_filter_by_trace() {
+        let logs = ["abc", "def", "abc"]
+            .iter()
+            .enumerate()
+            .map(|(i, trace)| {
+                parse_json_line(&format!(
+                    r#"{{"level":30,"msg":"log {}","traceId":"{}"}}"#,
+                    i, trace
+                ))
+                .unwrap()
+            })
+            .collect();
+        let mut app = app_from(logs);
+
+        app.filter_by_trace().unwrap();
+        assert_eq!(app.active_filter.as_deref(), Some("\"traceId\" = 'abc'"));
+        let messages: Vec<_> = app
+            .current_logs()
+            .iter()
+            .filter_map(JsonLog::get_message)
+            .collect();
+        assert_eq!(messages, vec!["log 0", "log 2"]);
+
+        // Dotted trace fields look inside the JSON column
+        let logs = ["abc", "def", "abc"]
+            .iter()
+            .enumerate()
+            .map(|(i, trace)| {
+                parse_json_line(&format!(
+                    r#"{{"level":30,"msg":"log {}","dd":{{"trace_id":"{}"}}}}"#,
+                    i, trace
+                ))
+                .unwrap()
+            })
+            .collect();
+        let mut app = app_from(logs);
+        app.display.trace_fields = vec!["dd.trace_id".to_string()];
+
+        app.filter_by_trace().unwrap();
+        let messages: Vec<_> = app
+            .current_logs()
+            .iter()
+            .filter_map(JsonLog::get_message)
+            .collect();
+        assert_eq!(messages, vec!["log 0", "log 2"]);
+    }
+
+    #[test]
+    fn test_numeric_filter_prompt() {
+        let mut app = app_with_logs(10);
+        app.open_numeric_filter();
+        assert_eq!(app.focus, Focus::NumericFilter);
+        // `level` comes before `n` in the schema; pick `n`, then `>=`
+        let prompt = app.numeric_filter.as_mut().unwrap();
+        let n = prompt.fields.iter().position(|(f, _)| f == "n").unwrap();
+        prompt.field = n;
+        app.confirm_numeric_filter().unwrap();
+        app.numeric_filter.as_mut().unwrap().move_down();
+        app.confirm_numeric_filter().unwrap();
+
+        app.numeric_filter.as_mut().unwrap().value = "7".to_string();
+        app.confirm_numeric_filter().unwrap();
+        assert_eq!(app.focus, Focus::LogList);
+        assert!(app.numeric_filter.is_none());
+        assert_eq!(app.active_filter.as_deref(), Some("\"n\" >= 7"));
+        assert_eq!(app.current_logs().len(), 3);
+    }
+
+    #[test]
+    fn test_message_search() {
+        let mut app = app_with_logs(12);
+        app.start_message_search();
+        assert_eq!(app.focus, Focus::MessageSearch);
+        app.message_search_input = "LOG 1".to_string();
+        app.confirm_message_search().unwrap();
+        assert_eq!(app.focus, Focus::LogList);
+        assert_eq!(app.message_search.as_deref(), Some("LOG 1"));
+        // log 1, log 10 and log 11, whatever the case
+        assert_eq!(app.current_logs().len(), 3);
+
+        // Reopening starts from the active term; an empty term clears
+        app.start_message_search();
+        assert_eq!(app.message_search_input, "LOG 1");
+        app.message_search_input.clear();
+        app.confirm_message_search().unwrap();
+        assert_eq!(app.view_mode, ViewMode::AllLogs);
+        assert!(app.message_search.is_none());
+
+        // Another filter replaces the term
+        app.message_search_input = "log".to_string();
+        app.confirm_message_search().unwrap();
+        app.apply_preset_filter("level >= 30").unwrap();
+        assert!(app.message_search.is_none());
+    }
+
+    #[test]
+    fn test_follow_batches_match_database_order() {
+        let mut app = app_with_logs(10);
+        for start in (10..100).step_by(15) {
+            app.append_logs(logs(start..start + 15)).unwrap();
+        }
+
+        let messages = |logs: &[JsonLog]| -> Vec<String> {
+            logs.iter()
+                .filter_map(|log| log.get_message().map(str::to_string))
+                .collect()
+        };
+        let stored = app.db.query_logs(None).unwrap();
+        assert_eq!(messages(&app.all_logs), messages(&stored));
+        assert_eq!(messages(&stored), messages(&logs(0..100)));
+        assert_eq!(app.db.max_id().unwrap(), 100);
+    }
+
+    #[test]
+    fn test_invalid_preset_filter_keeps_all_logs() {
+        let mut app = app_with_logs(5);
+        assert!(app.apply_preset_filter("no_such_column = 1").is_err());
+        assert_eq!(app.active_filter, None);
+        assert_eq!(app.current_logs().len(), 5);
+        // The clause stays in the input for fixing
+        assert_eq!(app.filter_input.lines(), ["no_such_column = 1"]);
+        assert!(app.filter_error.is_some());
+    }
+
+    #[test]
+    fn test_multi_line_filter() {
+        let mut app = app_with_logs(5);
+        app.filter_input = TextArea::from(["n >= 2", "AND n <= 3"]);
+        app.apply_filter().unwrap();
+
+        assert_eq!(app.active_filter.as_deref(), Some("n >= 2 AND n <= 3"));
+        assert_eq!(app.current_logs().len(), 2);
+    }
+
+    #[test]
+    fn test_multi_line_filter_trims_lines() {
+        let mut app = app_with_logs(5);
+        app.filter_input = TextArea::from(["level >= 30", "", "    AND n = 4  "]);
+        app.apply_filter().unwrap();
+
+        assert_eq!(app.active_filter.as_deref(), Some("level >= 30 AND n = 4"));
+        assert_eq!(app.current_logs().len(), 1);
+    }
+
+    #[test]
+    fn test_next_and_prev_error() {
+        let mut app = app_from(
+            [30, 50, 30, 60, 30]
+                .iter()
+                .enumerate()
+                .map(|(i, level)| {
+                    parse_json_line(&format!(r#"{{"level":{},"msg":"log {}"}}"#, level, i)).unwrap()
+                })
+                .collect(),
+        );
+
+        app.next_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 1"));
+        app.next_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 3"));
+        app.next_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 3"));
+        assert_eq!(app.status_message.as_deref(), Some("No more errors below"));
+
+        app.prev_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 1"));
+
+        // Newest first: down on screen is towards older logs
+        app.toggle_newest_first();
+        app.prev_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 3"));
+        app.next_error();
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 1"));
+    }
+
+    #[test]
+    fn test_reset_view() {
+        let mut app = app_with_logs(10);
+        app.show_detail_panel = true;
+        app.apply_preset_filter("n >= 5").unwrap();
+        app.toggle_newest_first();
+        app.move_down();
+        app.detail_search = "log".to_string();
+        app.detail_field = 2;
+
+        app.reset_view();
+        assert_eq!(app.active_filter, None);
+        assert_eq!(app.current_logs().len(), 10);
+        assert!(!app.display.newest_first);
+        assert_eq!(app.selected_log().unwrap().get_message(), Some("log 0"));
+        assert!(app.detail_search.is_empty());
+        assert_eq!(app.detail_field, 0);
+        assert_eq!(app.focus, Focus::LogList);
+        // Panels are left alone
+        assert!(app.show_detail_panel);
+    }
+
+    #[test]
+    fn test_scroll_columns() {
+        let mut app = app_with_logs(3);
+        app.scroll_columns_right();
+        assert_eq!(app.display.table_col_offset, 0);
+        assert!(app.status_message.is_some());
+
+        app.display.visible_columns = vec!["n".to_string(), "pid".to_string()];
+        app.toggle_wide_mode();
+        app.scroll_columns_right();
+        app.scroll_columns_right();
+        // The last column stays visible
+        assert_eq!(app.display.table_col_offset, 1);
+        app.scroll_columns_left();
+        app.scroll_columns_left();
+        assert_eq!(app.display.table_col_offset, 0);
+    }
+
+    #[test]
+    fn test_source_logs_keep_original_fields() {
+        let mut app = app_from(
+            (0..4)
+                .map(|i| {
+                    parse_json_line(&format!(
+                        r#"{{"zone":"eu","msg":"log {}","level":30,"n":{}}}"#,
+                        i, i
+                    ))
+                    .unwrap()
+                })
+                .collect(),
+        );
+        app.filter_input = TextArea::from(["n % 2 = 1"]);
+        app.apply_filter().unwrap();
+        // The database rows use the normalized `message` column
+        assert!(app.current_logs()[0].get_field("msg").is_none());
+
+        let logs = app.source_logs();
+        assert_eq!(logs.len(), 2);
+        let names: Vec<&str> = logs[0].fields.keys().map(String::as_str).collect();
+        assert_eq!(names, ["zone", "msg", "level", "n"]);
+        assert_eq!(logs[1].get_message(), Some("log 3"));
+    }
+
+    #[test]
+    fn test_unparsed_placeholders_stay_out_of_the_database() {
+        let mut logs = logs(0..4);
+        logs.insert(1, JsonLog::unparsed(2, "not json".to_string()));
+        let mut app = app_from(logs);
+        assert_eq!(app.all_logs.len(), 5);
+        assert_eq!(app.db.count_logs().unwrap(), 4);
+
+        // A filter shows stored logs only; exports still map back by id
+        app.filter_input = TextArea::from(["n >= 2"]);
+        app.apply_filter().unwrap();
+        assert_eq!(app.current_logs().len(), 2);
+        let messages: Vec<_> = app
+            .source_logs()
+            .iter()
+            .map(|log| log.get_message().unwrap().to_string())
+            .collect();
+        assert_eq!(messages, ["log 2", "log 3"]);
+    }
+
+    #[test]
+    fn test_filter_by_selected_field() {
+        let mut app = app_with_logs(12);
+        app.move_down();
+        app.focus_detail();
+        // Fields are level, msg, n
+        app.detail_field = 1;
+
+        app.filter_by_selected_field(false).unwrap();
+        assert_eq!(app.active_filter.as_deref(), Some("\"message\" = 'log 1'"));
+        assert_eq!(app.current_logs().len(), 1);
+        assert_eq!(app.focus, Focus::LogList);
+
+        // The detail search narrows a substring match
+        app.clear_filter();
+        app.move_down();
+        app.focus_detail();
+        app.detail_field = 1;
+        app.detail_search = "1".to_string();
+        app.filter_by_selected_field(true).unwrap();
+        let messages: Vec<_> = app
+            .current_logs()
+            .iter()
+            .map(|log| log.get_message().unwrap().to_string())
+            .collect();
+        assert_eq!(messages, ["log 1", "log 10", "log 11"]);
+        assert!(app.detail_search.is_empty());
+    }
+
+    #[test]
+    fn test_filter_last_hour_with_offsets() {
+        let now = jiff::Timestamp::now();
+        let at = |minutes_ago: i64, offset_hours: i8| {
+            let time = now
+                .checked_sub(jiff::Span::new().minutes(minutes_ago))
+                .unwrap()
+                .to_zoned(jiff::tz::TimeZone::fixed(jiff::tz::offset(offset_hours)));
+            let time = time.strftime("%Y-%m-%dT%H:%M:%S%:z").to_string();
+            parse_json_line(&format!(
+                r#"{{"time":"{}","msg":"{}m ago"}}"#,
+                time, minutes_ago
+            ))
+            .unwrap()
+        };
+        let mut app = app_from(vec![at(90, 2), at(30, 2), at(20, -5), at(10, 0)]);
+
+        app.filter_last_hour().unwrap();
+        let messages: Vec<_> = app
+            .current_logs()
+            .iter()
+            .map(|log| log.get_message().unwrap().to_string())
+            .collect();
+        assert_eq!(messages, ["30m ago", "20m ago", "10m ago"]);
+    }
+
+    #[test]
+    fn test_filter_by_age_from_newest() {
+        let newest = 10_000_000;
+        let logs = [2 * 60 * 60_000, 30 * 60_000, 3 * 60_000, 30_000, 0]
+            .iter()
+            .map(|age| parse_json_line(&format!(r#"{{"time":{},"age":{}}}"#, newest - age, age)))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let mut app = app_from(logs);
+        app.display.age_reference = AgeReference::Newest;
+        assert_eq!(
+            app.display.newest_time,
+            jiff::Timestamp::from_millisecond(newest).ok()
+        );
+
+        let counts: Vec<usize> = [
+            AgeBucket::Minute,
+            AgeBucket::FiveMinutes,
+            AgeBucket::Hour,
+            AgeBucket::Older,
+        ]
+        .into_iter()
+        .map(|bucket| {
+            app.filter_by_age(bucket).unwrap();
+            app.current_logs().len()
+        })
+        .collect();
+        assert_eq!(counts, [2, 3, 4, 1]);
+
+        // Followed logs move the reference along
+        let later = parse_json_line(&format!(r#"{{"time":{}}}"#, newest + 60 * 60_000)).unwrap();
+        app.append_logs(vec![later]).unwrap();
+        app.filter_by_age(AgeBucket::Minute).unwrap();
+        assert_eq!(app.current_logs().len(), 1);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::{LogViewerError, Result};
+use ratatui::style::Color;
+use rootcause::prelude::Report;
+use serde_json::Value;
+
+/// A field rendered as a colored `field:value` badge in the log list
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgeRule {
+    pub field: String,
+    pub colors: BadgeColors,
+}
+
+/// How a badge value maps to a color
+#[derive(Debug, Clone, PartialEq)]
+pub enum BadgeColors {
+    /// `true` green, `false` red, HTTP status codes by class (2xx green, 3xx cyan,
+    /// 4xx yellow, 5xx red); other values get no badge
+    Auto,
+    /// Explicit value → color pairs; unlisted values get no badge
+    Values(Vec<(String, Color)>),
+}
+
+impl BadgeRule {
+    /// Badge color for a field value, `None` when the rule doesn't cover it
+    pub fn color_for(&self, value: &Value) -> Option<Color> {
+        match &self.colors {
+            BadgeColors::Auto => auto_color(value),
+            BadgeColors::Values(pairs) => {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                pairs
+                    .iter()
+                    .find(|(expected, _)| *expected == text)
+                    .map(|(_, color)| *color)
+            }
+        }
+    }
+}
+
+fn auto_color(value: &Value) -> Option<Color> {
+    match value {
+        Value::Bool(true) => Some(Color::Green),
+        Value::Bool(false) => Some(Color::Red),
+        Value::Number(n) => match n.as_u64()? {
+            200..=299 => Some(Color::Green),
+            300..=399 => Some(Color::Cyan),
+            400..=499 => Some(Color::Yellow),
+            500..=599 => Some(Color::Red),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parse `field` (automatic colors) or `field=value:color,value:color`
+impl std::str::FromStr for BadgeRule {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: String| {
+            Report::new(LogViewerError::InvalidArgument(format!(
+                "Invalid badge '{}': {}",
+                s, reason
+            )))
+        };
+
+        let (field, mapping) = match s.split_once('=') {
+            Some((field, mapping)) => (field.trim(), Some(mapping)),
+            None => (s.trim(), None),
+        };
+        if field.is_empty() {
+            return Err(invalid("missing field name".to_string()));
+        }
+
+        let colors = match mapping {
+            None => BadgeColors::Auto,
+            Some(mapping) => {
+                let mut pairs = Vec::new();
+                for pair in mapping.split(',').filter(|p| !p.trim().is_empty()) {
+                    let (value, color) = pair
+                        .rsplit_once(':')
+                        .ok_or_else(|| invalid(format!("expected value:color, got '{}'", pair)))?;
+                    let color = color
+                        .trim()
+                        .parse::<Color>()
+                        .map_err(|_| invalid(format!("unknown color '{}'", color.trim())))?;
+                    pairs.push((value.trim().to_string(), color));
+                }
+                if pairs.is_empty() {
+                    return Err(invalid("no value:color pairs".to_string()));
+                }
+                BadgeColors::Values(pairs)
+            }
+        };
+
+        Ok(BadgeRule {
+            field: field.to_string(),
+            colors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_auto_badge_colors() {
+        let rule: BadgeRule = "statusCode".parse().unwrap();
+        assert_eq!(rule.colors, BadgeColors::Auto);
+        assert_eq!(rule.color_for(&json!(204)), Some(Color::Green));
+        assert_eq!(rule.color_for(&json!(404)), Some(Color::Yellow));
+        assert_eq!(rule.color_for(&json!(503)), Some(Color::Red));
+        assert_eq!(rule.color_for(&json!(42)), None);
+        assert_eq!(rule.color_for(&json!(true)), Some(Color::Green));
+        assert_eq!(rule.color_for(&json!(false)), Some(Color::Red));
+        assert_eq!(rule.color_for(&json!("ok")), None);
+    }
+
+    #[test]
+    fn test_value_badge_colors() {
+        let rule: BadgeRule = "env=prod:red, staging:yellow".parse().unwrap();
+        assert_eq!(rule.field, "env");
+        assert_eq!(rule.color_for(&json!("prod")), Some(Color::Red));
+        assert_eq!(rule.color_for(&json!("staging")), Some(Color::Yellow));
+        assert_eq!(rule.color_for(&json!("dev")), None);
+
+        let rule: BadgeRule = "cached=true:blue".parse().unwrap();
+        assert_eq!(rule.color_for(&json!(true)), Some(Color::Blue));
+    }
+
+    #[test]
+    fn test_invalid_badges() {
+        assert!("".parse::<BadgeRule>().is_err());
+        assert!("env=prod".parse::<BadgeRule>().is_err());
+        assert!("env=prod:notacolor".parse::<BadgeRule>().is_err());
+        assert!("env=".parse::<BadgeRule>().is_err());
+    }
+}
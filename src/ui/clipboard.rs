@@ -0,0 +1,18 @@
+use crate::error::{LogViewerError, Result};
+use base64::Engine;
+use rootcause::prelude::ResultExt;
+use std::io::Write;
+
+/// Copy text to the system clipboard using the OSC 52 terminal escape sequence
+/// Works over SSH and inside tmux (with `set-clipboard on`) without a native clipboard library.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)
+        .and_then(|_| stdout.flush())
+        .map_err(LogViewerError::from)
+        .attach("Failed to write OSC 52 clipboard sequence")?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use std::ffi::OsStr;
+
+/// Whether the environment asks for no color: `NO_COLOR` set to anything
+/// non-empty (https://no-color.org), or a `TERM=dumb` terminal
+pub fn color_disabled_by_env() -> bool {
+    env_disables_color(
+        std::env::var_os("NO_COLOR").as_deref(),
+        std::env::var_os("TERM").as_deref(),
+    )
+}
+
+fn env_disables_color(no_color: Option<&OsStr>, term: Option<&OsStr>) -> bool {
+    no_color.is_some_and(|value| !value.is_empty()) || term.is_some_and(|term| term == "dumb")
+}
+
+/// Drop the colors of everything drawn in `buf`, keeping bold, dim and the like
+/// Cells with a background (the selection, search matches) get reverse video
+/// instead, so highlights stay visible without color
+pub fn strip_colors(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_env_disables_color() {
+        let os = |s: &'static str| Some(OsStr::new(s));
+        assert!(!env_disables_color(None, os("xterm-256color")));
+        assert!(env_disables_color(os("1"), os("xterm-256color")));
+        // An empty NO_COLOR doesn't count
+        assert!(!env_disables_color(os(""), None));
+        assert!(env_disables_color(None, os("dumb")));
+    }
+
+    #[test]
+    fn test_strip_colors() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf[(0, 0)].set_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        buf[(1, 0)].set_style(Style::default().bg(Color::DarkGray));
+
+        strip_colors(&mut buf);
+        assert_eq!(buf[(0, 0)].fg, Color::Reset);
+        assert_eq!(buf[(0, 0)].modifier, Modifier::BOLD);
+        assert_eq!(buf[(1, 0)].bg, Color::Reset);
+        assert_eq!(buf[(1, 0)].modifier, Modifier::REVERSED);
+        assert_eq!(buf[(2, 0)].modifier, Modifier::empty());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::ui::actions::ActionInfo;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+use tui_textarea::TextArea;
+
+pub struct CommandPaletteWidget<'a> {
+    input: &'a TextArea<'a>,
+    matches: &'a [&'static ActionInfo],
+    selected: usize,
+}
+
+impl<'a> CommandPaletteWidget<'a> {
+    pub fn new(
+        input: &'a TextArea<'a>,
+        matches: &'a [&'static ActionInfo],
+        selected: usize,
+    ) -> Self {
+        Self {
+            input,
+            matches,
+            selected,
+        }
+    }
+}
+
+impl<'a> Widget for CommandPaletteWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Centered near the top, like an editor's palette
+        let popup_width = 60.min(area.width);
+        let popup_height = 20.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - popup_width) / 2,
+            y: area.y + (area.height - popup_height) / 4,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .title("Command Palette")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let chunks = Layout::vertical([
+            Constraint::Length(1), // Search input
+            Constraint::Min(1),    // Matching actions
+            Constraint::Length(1), // Help text
+        ])
+        .split(inner);
+
+        Widget::render(self.input, chunks[0], buf);
+
+        let key_width = self
+            .matches
+            .iter()
+            .map(|info| info.key_labels().len())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|info| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", info.key_labels(), width = key_width),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(info.name),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+
+        let mut state = ListState::default();
+        if !self.matches.is_empty() {
+            state.select(Some(self.selected));
+        }
+        StatefulWidget::render(list, chunks[1], buf, &mut state);
+
+        Paragraph::new("Enter: Run  Up/Down: Select  Esc: Close")
+            .style(Style::default().fg(Color::DarkGray))
+            .render(chunks[2], buf);
+    }
+}
+
+/// Render the command palette as a centered overlay
+pub fn render_command_palette(
+    input: &TextArea,
+    matches: &[&'static ActionInfo],
+    selected: usize,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let widget = CommandPaletteWidget::new(input, matches, selected);
+    widget.render(area, buf);
+}
@@ -0,0 +1,124 @@
+use crate::ui::app::FacetState;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
+};
+use serde_json::Value;
+
+pub struct FacetsWidget<'a> {
+    field: Option<&'a str>,
+    state: &'a FacetState,
+    focused: bool,
+}
+
+impl<'a> FacetsWidget<'a> {
+    pub fn new(field: Option<&'a str>, state: &'a FacetState, focused: bool) -> Self {
+        Self {
+            field,
+            state,
+            focused,
+        }
+    }
+
+    fn format_value(value: &Value) -> String {
+        match value {
+            Value::Null => "(null)".to_string(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl<'a> Widget for FacetsWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let border_color = if self.focused {
+            Color::Cyan
+        } else {
+            Color::White
+        };
+        let title = match self.field {
+            Some(field) => format!("Facets: {} (h/l to switch)", field),
+            None => "Facets".to_string(),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+
+        if let Some(error) = &self.state.error {
+            Paragraph::new(error.as_str())
+                .style(Style::default().fg(Color::Red))
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let count_width = self
+            .state
+            .values
+            .values
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        let items: Vec<ListItem> = self
+            .state
+            .values
+            .values
+            .iter()
+            .map(|(value, count)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>width$} ", count, width = count_width),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(Self::format_value(value)),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+
+        let mut state = ListState::default();
+        if !self.state.values.values.is_empty() {
+            state.select(Some(self.state.selected));
+        }
+        StatefulWidget::render(list, chunks[0], buf, &mut state);
+
+        let footer = if self.state.values.has_more {
+            "+more  Enter: Filter  Esc: Close"
+        } else {
+            "Enter: Filter  Esc: Close"
+        };
+        Paragraph::new(footer)
+            .style(Style::default().fg(Color::DarkGray))
+            .render(chunks[1], buf);
+    }
+}
+
+/// Render the facets sidebar
+pub fn render_facets(
+    field: Option<&str>,
+    state: &FacetState,
+    focused: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let widget = FacetsWidget::new(field, state, focused);
+    widget.render(area, buf);
+}
@@ -4,7 +4,7 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Row, Table, Widget},
+    widgets::{Block, Borders, Clear, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
 };
 use tui_textarea::TextArea;
 
@@ -12,8 +12,24 @@ pub struct FilterPanelWidget<'a> {
     field_schema: &'a [(String, FieldType)],
     filter_input: &'a TextArea<'a>,
     filter_error: Option<&'a str>,
+    editor: bool,
+    /// Highlighted row of the field table, while the presets have focus
+    selected_field: Option<usize>,
 }
 
+/// Height of the filter input in the multi-line editor
+const EDITOR_HEIGHT: u16 = 10;
+
+/// Height of the one-line filter input, and the least the editor shrinks to
+const INPUT_HEIGHT: u16 = 3;
+
+/// Height of the error box, and the least it shrinks to (one line inside the border)
+const ERROR_HEIGHT: u16 = 5;
+const MIN_ERROR_HEIGHT: u16 = 3;
+
+/// Least height of the field table: borders, the header and its margin, and one row
+const MIN_FIELDS_HEIGHT: u16 = 5;
+
 impl<'a> FilterPanelWidget<'a> {
     pub fn new(
         field_schema: &'a [(String, FieldType)],
@@ -24,35 +40,56 @@ impl<'a> FilterPanelWidget<'a> {
             field_schema,
             filter_input,
             filter_error,
+            editor: false,
+            selected_field: None,
         }
     }
+
+    /// Give the input `EDITOR_HEIGHT` rows for a multi-line filter
+    pub fn with_editor(mut self, editor: bool) -> Self {
+        self.editor = editor;
+        self
+    }
+
+    /// Highlight a row of the field table, for Enter to insert
+    pub fn with_selected_field(mut self, selected_field: Option<usize>) -> Self {
+        self.selected_field = selected_field;
+        self
+    }
 }
 
 impl<'a> Widget for FilterPanelWidget<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        // Split the area into sections
+        // Blank out the dimmed log list behind the panel
+        Clear.render(area, buf);
+
+        // Split the area into sections, sized to fit short terminals
+        let [title, fields, presets, input, error, help] = self.section_heights(area.height);
         let chunks = Layout::vertical([
-            Constraint::Length(3), // Title
-            Constraint::Min(8),    // Field schema table
-            Constraint::Length(3), // Preset buttons
-            Constraint::Length(3), // Input
-            Constraint::Length(5), // Error message
-            Constraint::Length(2), // Help text
+            Constraint::Length(title),
+            Constraint::Length(fields),
+            Constraint::Length(presets),
+            Constraint::Length(input),
+            Constraint::Length(error),
+            Constraint::Length(help),
         ])
         .split(area);
 
         // Title
-        let title = Paragraph::new("Filter Panel")
+        let title_para = Paragraph::new("Filter Panel")
             .block(Block::default().borders(Borders::ALL))
             .style(
                 Style::default()
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             );
-        title.render(chunks[0], buf);
+        title_para.render(chunks[0], buf);
 
-        // Field schema table
-        self.render_field_schema(chunks[1], buf);
+        // Field schema table, scrolled to the selected field; dropped when too short
+        // to show a row
+        if fields >= MIN_FIELDS_HEIGHT {
+            self.render_field_schema(chunks[1], buf);
+        }
 
         // Preset filters
         self.render_presets(chunks[2], buf);
@@ -69,13 +106,66 @@ impl<'a> Widget for FilterPanelWidget<'a> {
         }
 
         // Help text
-        let help = Paragraph::new("Enter: Apply  Esc: Cancel  1-4: Preset Filters")
-            .style(Style::default().fg(Color::DarkGray));
+        let help_text = if self.editor {
+            "Enter: New line  Ctrl-Enter/Ctrl-s: Apply, keep open  Ctrl-e: One line\n\
+             Esc: Cancel"
+        } else {
+            "Enter: Apply  Ctrl-Enter/Ctrl-s: Apply, keep open  Ctrl-e: Multi-line editor\n\
+             Esc: Cancel  1-7: Preset Filters"
+        };
+        let help = Paragraph::new(help_text).style(Style::default().fg(Color::DarkGray));
         help.render(chunks[5], buf);
     }
 }
 
 impl<'a> FilterPanelWidget<'a> {
+    /// Rows of the title, field table, presets, input, error and help within
+    /// `height`. Sections are given room most important first: the input, then
+    /// any error, the help, the presets, a few rows of the field table and the
+    /// title. Sections that don't fit get no rows; the editor's extra rows and
+    /// the space kept for an error come next, and the field table takes the rest
+    fn section_heights(&self, height: u16) -> [u16; 6] {
+        let mut remaining = height;
+        // Take `want` rows if they fit, otherwise none
+        let mut take = |want: u16| {
+            if want <= remaining {
+                remaining -= want;
+                want
+            } else {
+                0
+            }
+        };
+
+        let mut input = take(INPUT_HEIGHT);
+        let mut error = match self.filter_error {
+            Some(_) => match take(ERROR_HEIGHT) {
+                0 => take(MIN_ERROR_HEIGHT),
+                rows => rows,
+            },
+            None => 0,
+        };
+        let help = take(2);
+        let presets = take(4);
+        let mut fields = take(MIN_FIELDS_HEIGHT);
+        let title = take(3);
+
+        if self.editor {
+            let extra = (EDITOR_HEIGHT - INPUT_HEIGHT).min(remaining);
+            input += extra;
+            remaining -= extra;
+        }
+        // Keep the error box's place so the input doesn't move when one appears
+        if self.filter_error.is_none() && fields > 0 && remaining >= ERROR_HEIGHT {
+            error = ERROR_HEIGHT;
+            remaining -= ERROR_HEIGHT;
+        }
+        if fields > 0 {
+            fields += remaining;
+        }
+
+        [title, fields, presets, input, error, help]
+    }
+
     fn render_field_schema(&self, area: Rect, buf: &mut Buffer) {
         let header = Row::new(vec!["Field", "Type", "Example"])
             .style(
@@ -89,25 +179,19 @@ impl<'a> FilterPanelWidget<'a> {
             .field_schema
             .iter()
             .map(|(name, field_type)| {
-                let type_str = match field_type {
-                    FieldType::Text => "TEXT",
-                    FieldType::Integer => "INTEGER",
-                    FieldType::Float => "FLOAT",
-                    FieldType::Boolean => "BOOLEAN",
-                    FieldType::Json => "JSON",
-                };
-
                 let example = match field_type {
                     FieldType::Text => "\"text\"",
                     FieldType::Integer => "12345",
                     FieldType::Float => "123.45",
                     FieldType::Boolean => "true",
                     FieldType::Json => "{}",
+                    // Enter inserts a `list_contains` membership test
+                    FieldType::Array => "contains 'x'",
                 };
 
                 Row::new(vec![
                     name.clone(),
-                    type_str.to_string(),
+                    field_type.label().to_string(),
                     example.to_string(),
                 ])
             })
@@ -124,12 +208,19 @@ impl<'a> FilterPanelWidget<'a> {
         .header(header)
         .block(
             Block::default()
-                .title("Available Fields")
+                .title("Available Fields (Up/Down, Enter: Insert)")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
 
-        Widget::render(table, area, buf);
+        let mut state = TableState::default().with_selected(self.selected_field);
+        StatefulWidget::render(table, area, buf, &mut state);
     }
 
     fn render_presets(&self, area: Rect, buf: &mut Buffer) {
@@ -140,11 +231,22 @@ impl<'a> FilterPanelWidget<'a> {
             Span::raw("Warnings+  "),
             Span::styled("[3] ", Style::default().fg(Color::Yellow)),
             Span::raw("Last Hour  "),
+            Span::raw("Other keys: Custom"),
+        ];
+        // Windows of the age reference (`--age-reference`), like the list's age tags
+        let age_presets = vec![
+            Span::raw("Age: "),
             Span::styled("[4] ", Style::default().fg(Color::Yellow)),
-            Span::raw("Custom"),
+            Span::raw("<1m  "),
+            Span::styled("[5] ", Style::default().fg(Color::Yellow)),
+            Span::raw("<5m  "),
+            Span::styled("[6] ", Style::default().fg(Color::Yellow)),
+            Span::raw("<1h  "),
+            Span::styled("[7] ", Style::default().fg(Color::Yellow)),
+            Span::raw(">1h"),
         ];
 
-        let para = Paragraph::new(Line::from(presets)).block(
+        let para = Paragraph::new(vec![Line::from(presets), Line::from(age_presets)]).block(
             Block::default()
                 .title("Preset Filters")
                 .borders(Borders::ALL),
@@ -164,9 +266,13 @@ pub fn render_filter_panel(
     field_schema: &[(String, FieldType)],
     filter_input: &TextArea,
     filter_error: Option<&str>,
+    editor: bool,
+    selected_field: Option<usize>,
     area: Rect,
     buf: &mut Buffer,
 ) {
-    let widget = FilterPanelWidget::new(field_schema, filter_input, filter_error);
+    let widget = FilterPanelWidget::new(field_schema, filter_input, filter_error)
+        .with_editor(editor)
+        .with_selected_field(selected_field);
     widget.render(area, buf);
 }
@@ -1,3 +1,4 @@
+use crate::ui::actions::ACTIONS;
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
@@ -6,51 +7,75 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
 
-pub struct HelpMenuWidget;
+/// Width of the help popup
+const POPUP_WIDTH: u16 = 70;
+
+pub struct HelpMenuWidget {
+    scroll: u16,
+}
 
 impl HelpMenuWidget {
     fn create_help_content() -> Vec<Line<'static>> {
-        vec![
+        let mut lines = vec![
             Line::from(vec![Span::styled(
                 "Log Viewer - Help",
                 Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(vec![Span::styled(
-                "Navigation:",
+                "Log List:",
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
             )]),
-            Line::from("  j / ↓       - Move down one log"),
-            Line::from("  k / ↑       - Move up one log"),
-            Line::from("  g           - Jump to first log"),
-            Line::from("  G           - Jump to last log"),
-            Line::from("  Ctrl+d      - Scroll down half page"),
-            Line::from("  Ctrl+u      - Scroll up half page"),
-            Line::from("  Ctrl+f      - Scroll down full page"),
-            Line::from("  Ctrl+b      - Scroll up full page"),
+        ];
+
+        // Generated from the key map so the help never drifts from the bindings
+        for info in ACTIONS {
+            let keys = if info.keys.is_empty() {
+                "(palette)".to_string()
+            } else {
+                info.key_labels()
+            };
+            lines.push(Line::from(format!("  {:<14}- {}", keys, info.name)));
+        }
+
+        lines.extend([
             Line::from(""),
             Line::from(vec![Span::styled(
-                "Actions:",
+                "Filter Panel:",
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
             )]),
-            Line::from("  d           - Toggle detail panel"),
-            Line::from("  f           - Toggle filter panel"),
-            Line::from("  /           - Focus filter input"),
-            Line::from("  c           - Clear active filter"),
-            Line::from("  L           - Toggle debug logs panel"),
-            Line::from("  ?           - Toggle this help menu"),
-            Line::from("  q / Esc     - Quit application"),
+            Line::from("  1             - Apply \"Errors Only\" filter (level >= 50)"),
+            Line::from("  2             - Apply \"Warnings+\" filter (level >= 40)"),
+            Line::from("  3             - Apply \"Last Hour\" filter"),
+            Line::from("  4 / 5 / 6 / 7 - Logs from the last 1m / 5m / 1h, or older than 1h"),
+            Line::from("  Up / Down     - Select a field"),
+            Line::from("  Enter         - Compare on the field / apply the filter"),
+            Line::from("  Any key       - Start typing custom SQL filter"),
+            Line::from("  Ctrl-Enter/s  - Apply and keep the panel open"),
+            Line::from("  Esc           - Back to presets / Close panel"),
             Line::from(""),
             Line::from(vec![Span::styled(
-                "Filter Panel:",
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                "Facets Sidebar:",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  h / l         - Previous / next field"),
+            Line::from("  j / k         - Move through values"),
+            Line::from("  Enter         - Filter to the selected value"),
+            Line::from("  Esc / F       - Close sidebar"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Full-Screen Detail (v):",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
             )]),
-            Line::from("  1           - Apply \"Errors Only\" filter (level >= 50)"),
-            Line::from("  2           - Apply \"Warnings+\" filter (level >= 40)"),
-            Line::from("  3           - Apply \"Last Hour\" filter"),
-            Line::from("  Any key     - Start typing custom SQL filter"),
-            Line::from("  Enter       - Apply current filter"),
-            Line::from("  Esc         - Back to presets / Close panel"),
+            Line::from("  j / k         - Scroll down / up"),
+            Line::from("  Ctrl-d / u    - Scroll half a page"),
+            Line::from("  g / G         - Top / bottom"),
+            Line::from("  /             - Search, Enter scrolls to the first match"),
+            Line::from("  Esc / q / v   - Close"),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "SQL Filter Examples:",
@@ -62,18 +87,21 @@ impl HelpMenuWidget {
             Line::from("  time >= 1531171074000"),
             Line::from(""),
             Line::from(vec![Span::styled(
-                "Press ? or Esc to close this help menu",
+                "j/k to scroll, : to search actions, ? or Esc to close",
                 Style::default().fg(Color::DarkGray),
             )]),
-        ]
+        ]);
+        lines
     }
 }
 
 impl Widget for HelpMenuWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        // Calculate centered position for the help modal
-        let popup_width = 70;
-        let popup_height = 38;
+        let content = Self::create_help_content();
+
+        // Calculate centered position for the help modal, sized to its content
+        let popup_width = POPUP_WIDTH;
+        let popup_height = content.len() as u16 + 2;
 
         let x = (area.width.saturating_sub(popup_width)) / 2;
         let y = (area.height.saturating_sub(popup_height)) / 2;
@@ -89,7 +117,6 @@ impl Widget for HelpMenuWidget {
         Clear.render(popup_area, buf);
 
         // Render the help content
-        let content = Self::create_help_content();
         let paragraph = Paragraph::new(content)
             .block(
                 Block::default()
@@ -97,14 +124,57 @@ impl Widget for HelpMenuWidget {
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::Cyan)),
             )
-            .alignment(Alignment::Left);
+            .alignment(Alignment::Left)
+            .scroll((self.scroll.min(max_help_scroll(area)), 0));
 
         paragraph.render(popup_area, buf);
     }
 }
 
-/// Render the help menu as a centered modal
-pub fn render_help_menu(area: Rect, buf: &mut Buffer) {
-    let widget = HelpMenuWidget;
+/// Largest useful scroll offset when the help is shown in `area`
+pub fn max_help_scroll(area: Rect) -> u16 {
+    let lines = HelpMenuWidget::create_help_content().len() as u16;
+    // Two rows go to the popup border
+    lines.saturating_sub(area.height.saturating_sub(2))
+}
+
+/// Render the help menu as a centered modal, scrolled down `scroll` lines
+pub fn render_help_menu(scroll: u16, area: Rect, buf: &mut Buffer) {
+    let widget = HelpMenuWidget { scroll };
     widget.render(area, buf);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_text() -> Vec<String> {
+        HelpMenuWidget::create_help_content()
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_help_lists_every_action() {
+        let text = content_text();
+        for info in ACTIONS {
+            assert!(
+                text.iter().any(|line| line.ends_with(info.name)),
+                "{} missing from help",
+                info.name
+            );
+        }
+        assert!(text.contains(&"  Ctrl-f / PgDn - Page down".to_string()));
+    }
+
+    #[test]
+    fn test_max_help_scroll() {
+        let lines = content_text().len() as u16;
+        let tall = Rect::new(0, 0, 80, lines + 2);
+        assert_eq!(max_help_scroll(tall), 0);
+
+        let short = Rect::new(0, 0, 80, 12);
+        assert_eq!(max_help_scroll(short), lines - 10);
+    }
+}
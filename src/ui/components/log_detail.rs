@@ -1,48 +1,290 @@
 use crate::ingestion::JsonLog;
+use crate::ingestion::models::ErrorObject;
+use crate::storage::FieldType;
+use crate::storage::schema::normalize_field_name;
+use crate::ui::display::DisplayOptions;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Widget},
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
 };
 
+/// String values longer than this are cut in the detail panel
+const MAX_STRING_BYTES: usize = 4 * 1024;
+
+/// Strings with at least this share of non-printable characters count as binary
+const BINARY_RATIO: f64 = 0.1;
+
+/// Bytes of a binary string shown in its hex preview
+const HEX_PREVIEW_BYTES: usize = 32;
+
+/// Lines of pretty-printed JSON turned into styled lines
+const MAX_DETAIL_LINES: usize = 1000;
+
+/// Stack lines shown in the error block above the JSON
+const MAX_STACK_LINES: usize = 50;
+
+/// Objects and arrays whose one-line JSON fits in this many bytes stay inline in compact mode
+const COMPACT_INLINE_WIDTH: usize = 40;
+
+/// Line style marking the field under the detail panel cursor
+const SELECTED_FIELD_STYLE: Style = Style::new().bg(Color::DarkGray);
+
+/// Style patched onto text matching the detail search
+const SEARCH_MATCH_STYLE: Style = Style::new().fg(Color::Black).bg(Color::Yellow);
+
+/// Ends the line number gutter, e.g. ` 12 │ `
+const GUTTER_SEPARATOR: &str = "│ ";
+
+/// Position shown in the detail panel title, and the field cursor when the panel has focus
+pub struct LogDetailState {
+    log_index: usize,
+    total_logs: usize,
+    /// Index into `DisplayOptions::visible_field_names()`
+    selected_field: Option<usize>,
+    search: Option<String>,
+    scroll: Option<u16>,
+    pinned: bool,
+}
+
+impl LogDetailState {
+    pub fn new(log_index: usize, total_logs: usize) -> Self {
+        Self {
+            log_index,
+            total_logs,
+            selected_field: None,
+            search: None,
+            scroll: None,
+            pinned: false,
+        }
+    }
+
+    /// The log is pinned rather than the selected one; the title says so
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Highlight a top-level field
+    pub fn with_selected_field(mut self, selected_field: Option<usize>) -> Self {
+        self.selected_field = selected_field;
+        self
+    }
+
+    /// Highlight text matching `search` (case-insensitive) and scroll to the first match
+    pub fn with_search(mut self, search: Option<String>) -> Self {
+        self.search = search.filter(|term| !term.is_empty());
+        self
+    }
+
+    /// Scroll down this many lines instead of following the cursor or search
+    pub fn with_scroll(mut self, scroll: Option<u16>) -> Self {
+        self.scroll = scroll;
+        self
+    }
+}
+
 pub struct LogDetailWidget<'a> {
     log: Option<&'a JsonLog>,
+    /// Log before `log`, compared against when `diff_previous` is on
+    previous: Option<&'a JsonLog>,
     log_index: usize,
     total_logs: usize,
+    selected_field: Option<usize>,
+    search: Option<&'a str>,
+    /// Fixed scroll offset (the full-screen modal); `None` follows the cursor or search
+    scroll: Option<u16>,
+    /// Showing a pinned log instead of the selection; replaces the position in the title
+    pinned: bool,
+    /// Database column types, shown after each top-level field with `show_field_types`
+    field_types: &'a [(String, FieldType)],
+    display: &'a DisplayOptions,
 }
 
 impl<'a> LogDetailWidget<'a> {
-    pub fn new(log: Option<&'a JsonLog>, log_index: usize, total_logs: usize) -> Self {
+    pub fn new(
+        log: Option<&'a JsonLog>,
+        log_index: usize,
+        total_logs: usize,
+        display: &'a DisplayOptions,
+    ) -> Self {
         Self {
             log,
+            previous: None,
             log_index,
             total_logs,
+            selected_field: None,
+            search: None,
+            scroll: None,
+            pinned: false,
+            field_types: &[],
+            display,
         }
     }
 
-    fn format_log_details(log: &JsonLog) -> Vec<Line<'static>> {
+    /// Log to diff against in `diff_previous` mode; `None` for the first log
+    pub fn with_previous(mut self, previous: Option<&'a JsonLog>) -> Self {
+        self.previous = previous;
+        self
+    }
+
+    /// Highlight the top-level field at this index of `DisplayOptions::visible_field_names()`
+    pub fn with_selected_field(mut self, selected_field: Option<usize>) -> Self {
+        self.selected_field = selected_field;
+        self
+    }
+
+    /// Highlight text matching this term, ignoring ASCII case
+    pub fn with_search(mut self, search: Option<&'a str>) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Scroll down this many lines, clamped to the content
+    pub fn with_scroll(mut self, scroll: Option<u16>) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    /// Title the log as pinned instead of showing its position in the list
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Column types (`LogDatabase::get_schema`) to annotate fields with
+    pub fn with_field_types(mut self, field_types: &'a [(String, FieldType)]) -> Self {
+        self.field_types = field_types;
+        self
+    }
+
+    fn format_log_details(
+        log: &JsonLog,
+        previous: Option<&JsonLog>,
+        display: &DisplayOptions,
+        selected_field: Option<usize>,
+        search: Option<&str>,
+    ) -> Vec<Line<'static>> {
+        Self::new(Some(log), 0, 0, display)
+            .with_previous(previous)
+            .with_selected_field(selected_field)
+            .with_search(search)
+            .format_lines(log)
+    }
+
+    /// Styled lines for `log`; with `show_field_types`, each top-level field is
+    /// annotated with its column type from `with_field_types`
+    fn format_lines(&self, log: &JsonLog) -> Vec<Line<'static>> {
+        let display = self.display;
+        let search = self.search;
+        let field_types = Some(self.field_types).filter(|_| display.show_field_types);
         let mut lines = Vec::new();
+        let diff_against = self.previous.filter(|_| display.diff_previous);
+        // Whether the top-level field being printed differs from the previous log
+        let mut changed: Option<bool> = None;
+        // Index of the top-level field being printed; keys are sorted like
+        // `visible_field_names()` since serde_json maps are ordered, whatever
+        // the source order kept in `JsonLog::fields`
+        let mut field_index: Option<usize> = None;
+        let mut fields_seen = 0;
+        let field_names = display.visible_field_names(log);
 
-        // Pretty-print the JSON
-        let json_value = serde_json::to_value(&log.fields).unwrap_or(serde_json::Value::Null);
-        let pretty_json = serde_json::to_string_pretty(&json_value).unwrap_or_default();
+        // Error object first: type and message, then the stack as a block
+        if !display.single_line_json {
+            let error = log
+                .get_error_object(&display.error_fields)
+                .filter(|error| !display.is_hidden(&error.field));
+            if let Some(error) = error {
+                lines.extend(
+                    format_error_block(&error)
+                        .into_iter()
+                        .map(|line| match search {
+                            Some(term) => highlight_matches(line, term),
+                            None => line,
+                        }),
+                );
+            }
+        }
+
+        // Pretty-print the JSON, leaving out hidden fields and cutting huge strings
+        let visible_fields: serde_json::Map<String, serde_json::Value> = log
+            .fields
+            .iter()
+            .filter(|(k, _)| !display.is_hidden(k))
+            .map(|(k, v)| {
+                // Hex before truncating, so the preview reports the full length
+                let hexed;
+                let v = if display.hex_binary {
+                    hexed = hex_binary_strings(v);
+                    &hexed
+                } else {
+                    v
+                };
+                (k.clone(), truncate_large_strings(v))
+            })
+            .collect();
+        let pretty_json = if display.single_line_json {
+            serde_json::to_string(&visible_fields).unwrap_or_default()
+        } else if display.compact_json {
+            to_compact_json(&serde_json::Value::Object(visible_fields))
+        } else {
+            serde_json::to_string_pretty(&visible_fields).unwrap_or_default()
+        };
+        // Indentation of the top-level keys
+        let field_indent = if display.compact_json { 1 } else { 2 };
+        let json_start = lines.len();
 
         // Add syntax highlighting for JSON
-        for line in pretty_json.lines() {
+        for line in pretty_json.lines().take(MAX_DETAIL_LINES) {
             let trimmed = line.trim_start();
             let indent_level = line.len() - trimmed.len();
             let indent = " ".repeat(indent_level);
 
-            if trimmed.starts_with('"') && trimmed.contains(':') {
+            if indent_level == field_indent && trimmed.starts_with('"') {
+                field_index = Some(fields_seen);
+                fields_seen += 1;
+            } else if indent_level < field_indent {
+                field_index = None;
+            }
+
+            // Top-level key starting on this line
+            let field_name = field_index
+                .filter(|_| indent_level == field_indent && trimmed.starts_with('"'))
+                .and_then(|index| field_names.get(index).copied());
+
+            // Diff mode: dim fields equal to the previous log's, highlight changed keys
+            if let Some(previous) = diff_against {
+                if let Some(key) = field_name {
+                    changed = Some(log.fields.get(key) != previous.fields.get(key));
+                } else if indent_level < field_indent {
+                    changed = None;
+                }
+            }
+
+            let selected = field_index.is_some() && field_index == self.selected_field;
+
+            let styled = if changed == Some(false) {
+                // Gray rather than dark gray so the text stays readable on the selection
+                let dim = if selected {
+                    Color::Gray
+                } else {
+                    Color::DarkGray
+                };
+                Line::from(Span::styled(line.to_string(), Style::default().fg(dim)))
+            } else if trimmed.starts_with('"') && trimmed.contains(':') {
                 // JSON key
                 let parts: Vec<&str> = trimmed.splitn(2, ':').collect();
+                let key_style = if changed == Some(true) && indent_level == field_indent {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
                 let mut spans = vec![Span::raw(indent)];
-                spans.push(Span::styled(
-                    parts[0].to_string(),
-                    Style::default().fg(Color::Cyan),
-                ));
+                spans.push(Span::styled(parts[0].to_string(), key_style));
                 if parts.len() > 1 {
                     spans.push(Span::styled(":", Style::default().fg(Color::White)));
                     spans.push(Span::styled(
@@ -50,17 +292,72 @@ impl<'a> LogDetailWidget<'a> {
                         Style::default().fg(Color::Green),
                     ));
                 }
-                lines.push(Line::from(spans));
+                Line::from(spans)
             } else if trimmed.starts_with('{') || trimmed.starts_with('}') {
                 // Braces
-                lines.push(Line::from(vec![
+                Line::from(vec![
                     Span::raw(indent),
                     Span::styled(trimmed.to_string(), Style::default().fg(Color::White)),
-                ]));
+                ])
             } else {
                 // Other content
-                lines.push(Line::from(line.to_string()));
+                Line::from(line.to_string())
+            };
+            let mut styled = match search {
+                Some(term) => highlight_matches(styled, term),
+                None => styled,
+            };
+
+            // Column type after the value, e.g. `"level": 30,  [BIGINT]`
+            if let (Some(field_types), Some(key)) = (field_types, field_name) {
+                let column = normalize_field_name(key);
+                if let Some((_, field_type)) = field_types.iter().find(|(name, _)| name == column) {
+                    styled.spans.push(Span::styled(
+                        format!("  [{}]", field_type.to_sql()),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
             }
+
+            if selected {
+                lines.push(styled.style(SELECTED_FIELD_STYLE));
+            } else {
+                lines.push(styled);
+            }
+        }
+        let json_end = lines.len();
+
+        // Fields the previous log had but this one lacks
+        if let Some(previous) = diff_against {
+            let mut removed: Vec<&str> = previous
+                .fields
+                .keys()
+                .filter(|k| !log.fields.contains_key(*k) && !display.is_hidden(k))
+                .map(String::as_str)
+                .collect();
+            if !removed.is_empty() {
+                removed.sort_unstable();
+                lines.push(Line::from(Span::styled(
+                    format!("removed: {}", removed.join(", ")),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+        }
+
+        let total_lines = pretty_json.lines().count();
+        if total_lines > MAX_DETAIL_LINES {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "... {} more lines not shown",
+                    total_lines - MAX_DETAIL_LINES
+                ),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        // The one-line JSON wraps under the gutter, so it only numbers multi-line JSON
+        if display.detail_line_numbers && !display.single_line_json {
+            add_line_numbers(&mut lines, json_start..json_end);
         }
 
         lines
@@ -70,36 +367,840 @@ impl<'a> LogDetailWidget<'a> {
 impl<'a> Widget for LogDetailWidget<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let title = if self.total_logs > 0 {
-            format!("Log Details ({} of {})", self.log_index + 1, self.total_logs)
+            let time = self
+                .log
+                .and_then(|log| log.timestamp())
+                .map(|ts| {
+                    format!(
+                        " - {}",
+                        self.display
+                            .format_timestamp(ts, self.display.detail_time_format())
+                    )
+                })
+                .unwrap_or_default();
+            let diff = match (self.display.diff_previous, self.previous) {
+                (false, _) => "",
+                (true, Some(_)) => " [diff vs previous]",
+                (true, None) => " [diff: no previous log]",
+            };
+            let position = if self.pinned {
+                "pinned, P to unpin".to_string()
+            } else {
+                format!("{} of {}", self.log_index + 1, self.total_logs)
+            };
+            format!("Log Details ({}){}{}", position, time, diff)
         } else {
             "Log Details (No logs)".to_string()
         };
 
         let content = if let Some(log) = self.log {
-            Self::format_log_details(log)
+            self.format_lines(log)
         } else {
             vec![Line::from("No log selected")]
         };
 
-        let paragraph = Paragraph::new(content).block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White)),
-        );
+        // Keep the selected field on screen, a few lines from the top; with a
+        // search, the first match (within the selected field if there is one)
+        let inner_height = area.height.saturating_sub(2) as usize;
+        let selected_line = content
+            .iter()
+            .position(|line| line.style == SELECTED_FIELD_STYLE);
+        let match_line = match selected_line {
+            Some(start) => content
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take_while(|(_, line)| line.style == SELECTED_FIELD_STYLE)
+                .find(|(_, line)| has_match(line))
+                .map(|(index, _)| index),
+            None => content.iter().position(has_match),
+        };
+        let scroll = match self.scroll {
+            Some(scroll) => (scroll as usize).min(content.len().saturating_sub(inner_height)),
+            None => match_line
+                .or(selected_line)
+                .filter(|&line| line >= inner_height)
+                .map_or(0, |line| line.saturating_sub(2)),
+        };
+
+        // Search term and match count in the bottom border
+        let search_title = self.search.map(|term| {
+            let count: usize = content
+                .iter()
+                .map(|line| {
+                    let text = line.to_string();
+                    match_ranges(strip_gutter(&text, self.display), term).len()
+                })
+                .sum();
+            let matches = match count {
+                0 => "no matches".to_string(),
+                1 => "1 match".to_string(),
+                n => format!("{} matches", n),
+            };
+            format!(" /{} ({}) ", term, matches)
+        });
+
+        // Border shows where keys go: cyan while the field cursor is active
+        let border_color = if self.selected_field.is_some() {
+            Color::Cyan
+        } else {
+            Color::White
+        };
+        let mut block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+        if let Some(search_title) = search_title {
+            block = block.title_bottom(search_title);
+        }
+        let mut paragraph = Paragraph::new(content)
+            .block(block)
+            .scroll((scroll as u16, 0));
+        // The one-line JSON wraps so the whole log stays visible
+        if self.display.single_line_json {
+            paragraph = paragraph.wrap(Wrap { trim: false });
+        }
 
         paragraph.render(area, buf);
     }
 }
 
+/// Error header (`Type: message`) and stack lines, followed by a blank line
+/// A stack starting with the header (V8 stacks do) doesn't repeat it
+fn format_error_block(error: &ErrorObject) -> Vec<Line<'static>> {
+    let header = match (&error.error_type, &error.message) {
+        (Some(error_type), Some(message)) => format!("{}: {}", error_type, message),
+        (Some(text), None) | (None, Some(text)) => text.clone(),
+        (None, None) => "Error".to_string(),
+    };
+    let mut lines = vec![Line::from(Span::styled(
+        header.clone(),
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    ))];
+
+    if let Some(stack) = &error.stack {
+        let mut stack_lines = stack.lines().peekable();
+        if stack_lines.peek().map(|line| line.trim()) == Some(header.as_str()) {
+            stack_lines.next();
+        }
+        let stack_lines: Vec<&str> = stack_lines.collect();
+        lines.extend(stack_lines.iter().take(MAX_STACK_LINES).map(|line| {
+            Line::from(Span::styled(
+                format!("  {}", line.trim_end()),
+                Style::default().fg(Color::Gray),
+            ))
+        }));
+        if stack_lines.len() > MAX_STACK_LINES {
+            lines.push(Line::from(Span::styled(
+                format!("  ... {} more frames", stack_lines.len() - MAX_STACK_LINES),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    lines.push(Line::default());
+    lines
+}
+
+/// Byte ranges of `term` in `text`, ignoring ASCII case
+fn match_ranges(text: &str, term: &str) -> Vec<std::ops::Range<usize>> {
+    if term.is_empty() {
+        return Vec::new();
+    }
+    // ASCII lowercasing keeps byte offsets, so ranges apply to the original text
+    text.to_ascii_lowercase()
+        .match_indices(&term.to_ascii_lowercase())
+        .map(|(start, found)| start..start + found.len())
+        .collect()
+}
+
+/// Split a line's spans at the matches of `term` and patch `SEARCH_MATCH_STYLE` onto them
+/// Matches may cross span boundaries, e.g. `"msg": "GET`
+fn highlight_matches(line: Line<'static>, term: &str) -> Line<'static> {
+    let text = line.to_string();
+    let ranges = match_ranges(&text, term);
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let end = offset + span.content.len();
+        let mut cuts: Vec<usize> = ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .filter(|&cut| cut > offset && cut < end)
+            .collect();
+        cuts.push(end);
+
+        let mut start = offset;
+        for cut in cuts {
+            let in_match = ranges.iter().any(|range| range.contains(&start));
+            let style = if in_match {
+                span.style.patch(SEARCH_MATCH_STYLE)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(
+                span.content[start - offset..cut - offset].to_string(),
+                style,
+            ));
+            start = cut;
+        }
+        offset = end;
+    }
+    Line { spans, ..line }
+}
+
+/// Prefix every line with a dim gutter: lines in `json` get their 1-based number
+/// within the JSON, the others (error block, notes) a blank one of the same width
+fn add_line_numbers(lines: &mut [Line<'static>], json: std::ops::Range<usize>) {
+    let width = json.len().to_string().len();
+    for (index, line) in lines.iter_mut().enumerate() {
+        let number = if json.contains(&index) {
+            (index - json.start + 1).to_string()
+        } else {
+            String::new()
+        };
+        line.spans.insert(
+            0,
+            Span::styled(
+                format!("{:>width$} {}", number, GUTTER_SEPARATOR),
+                Style::default().fg(Color::DarkGray),
+            ),
+        );
+    }
+}
+
+/// Text of a detail line without the line number gutter, if the gutter is on
+fn strip_gutter<'a>(text: &'a str, display: &DisplayOptions) -> &'a str {
+    if !display.detail_line_numbers || display.single_line_json {
+        return text;
+    }
+    text.split_once(GUTTER_SEPARATOR)
+        .map_or(text, |(_, rest)| rest)
+}
+
+fn has_match(line: &Line) -> bool {
+    line.spans
+        .iter()
+        .any(|span| span.style.bg == SEARCH_MATCH_STYLE.bg)
+}
+
+/// Line of the detail text holding the first match for `term`
+pub fn search_match_line(log: &JsonLog, display: &DisplayOptions, term: &str) -> Option<usize> {
+    let lines = LogDetailWidget::format_log_details(log, None, display, None, Some(term));
+    lines.iter().position(has_match)
+}
+
+/// Index into `DisplayOptions::visible_field_names()` of the first field matching `term`
+pub fn search_match_field(log: &JsonLog, display: &DisplayOptions, term: &str) -> Option<usize> {
+    let lines = LogDetailWidget::format_log_details(log, None, display, None, Some(term));
+    let first_match = lines.iter().position(has_match)?;
+    let field_indent = if display.compact_json { 1 } else { 2 };
+    // Count the top-level keys up to the match, like the field cursor does
+    let fields = lines[..=first_match]
+        .iter()
+        .map(|line| strip_gutter(&line.to_string(), display).to_string())
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            line.len() - trimmed.len() == field_indent && trimmed.starts_with('"')
+        })
+        .count();
+    fields.checked_sub(1)
+}
+
+/// Whether a string looks like binary data: control characters other than
+/// whitespace, and U+FFFD left by lossy decoding, make up `BINARY_RATIO` of it
+fn is_binary(s: &str) -> bool {
+    let total = s.chars().count();
+    let unprintable = s
+        .chars()
+        .filter(|&c| {
+            (c.is_control() && !matches!(c, '\n' | '\r' | '\t')) || c == char::REPLACEMENT_CHARACTER
+        })
+        .count();
+    unprintable > 0 && unprintable as f64 >= total as f64 * BINARY_RATIO
+}
+
+/// Length and the first `HEX_PREVIEW_BYTES` bytes in hex, e.g. `<binary, 3 bytes> 00 ff 41`
+fn hex_preview(s: &str) -> String {
+    let hex: Vec<String> = s
+        .bytes()
+        .take(HEX_PREVIEW_BYTES)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let more = if s.len() > HEX_PREVIEW_BYTES {
+        " ..."
+    } else {
+        ""
+    };
+    format!("<binary, {} bytes> {}{}", s.len(), hex.join(" "), more)
+}
+
+/// Copy a value, replacing binary-looking strings (`is_binary`) with a hex preview
+fn hex_binary_strings(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) if is_binary(s) => Value::String(hex_preview(s)),
+        Value::Array(items) => Value::Array(items.iter().map(hex_binary_strings).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), hex_binary_strings(v)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Copy a value, replacing strings over `MAX_STRING_BYTES` with their start and a marker
+fn truncate_large_strings(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) if s.len() > MAX_STRING_BYTES => {
+            let mut end = MAX_STRING_BYTES;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            Value::String(format!("{}... [truncated, {} bytes]", &s[..end], s.len()))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(truncate_large_strings).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), truncate_large_strings(v)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Pretty-print with one-space indentation, keeping small objects and arrays on one line
+fn to_compact_json(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_compact_json(value, 0, &mut out);
+    out
+}
+
+fn write_compact_json(value: &serde_json::Value, depth: usize, out: &mut String) {
+    use serde_json::Value;
+
+    let inline = value.to_string();
+    let items: Vec<(Option<&String>, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (Some(k), v)).collect(),
+        Value::Array(items) => items.iter().map(|v| (None, v)).collect(),
+        _ => Vec::new(),
+    };
+    // The top level always breaks so each field starts its own line
+    if items.is_empty() || (depth > 0 && inline.len() <= COMPACT_INLINE_WIDTH) {
+        out.push_str(&inline);
+        return;
+    }
+
+    let (open, close) = if value.is_object() {
+        ('{', '}')
+    } else {
+        ('[', ']')
+    };
+    out.push(open);
+    out.push('\n');
+    let count = items.len();
+    for (i, (key, item)) in items.into_iter().enumerate() {
+        out.push_str(&" ".repeat(depth + 1));
+        if let Some(key) = key {
+            out.push_str(&serde_json::Value::String(key.clone()).to_string());
+            out.push_str(": ");
+        }
+        write_compact_json(item, depth + 1, out);
+        if i + 1 < count {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(&" ".repeat(depth));
+    out.push(close);
+}
+
 /// Render the log detail panel
 pub fn render_log_detail(
     log: Option<&JsonLog>,
-    log_index: usize,
-    total_logs: usize,
+    previous: Option<&JsonLog>,
+    state: LogDetailState,
+    display: &DisplayOptions,
+    field_types: &[(String, FieldType)],
     area: Rect,
     buf: &mut Buffer,
 ) {
-    let widget = LogDetailWidget::new(log, log_index, total_logs);
+    let widget = LogDetailWidget::new(log, state.log_index, state.total_logs, display)
+        .with_previous(previous)
+        .with_field_types(field_types)
+        .with_selected_field(state.selected_field)
+        .with_search(state.search.as_deref())
+        .with_scroll(state.scroll)
+        .with_pinned(state.pinned);
     widget.render(area, buf);
 }
+
+/// Area of the full-screen detail modal: `area` less a one-cell margin
+pub fn detail_modal_area(area: Rect) -> Rect {
+    Rect {
+        x: area.x + 1.min(area.width),
+        y: area.y + 1.min(area.height),
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    }
+}
+
+/// Largest useful scroll offset for the detail modal shown over `area`
+pub fn max_detail_modal_scroll(
+    log: &JsonLog,
+    previous: Option<&JsonLog>,
+    display: &DisplayOptions,
+    area: Rect,
+) -> u16 {
+    let lines = LogDetailWidget::format_log_details(log, previous, display, None, None).len();
+    // Two rows go to the modal border
+    let visible = detail_modal_area(area).height.saturating_sub(2) as usize;
+    lines.saturating_sub(visible).min(u16::MAX as usize) as u16
+}
+
+/// Render the detail of `log` over most of `area`, hiding what is behind it
+pub fn render_detail_modal(
+    log: Option<&JsonLog>,
+    previous: Option<&JsonLog>,
+    state: LogDetailState,
+    display: &DisplayOptions,
+    field_types: &[(String, FieldType)],
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let modal_area = detail_modal_area(area);
+    Clear.render(modal_area, buf);
+    render_log_detail(log, previous, state, display, field_types, modal_area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_truncate_large_strings() {
+        let blob = "é".repeat(MAX_STRING_BYTES);
+        let value = json!({"small": "ok", "nested": [blob.clone()]});
+
+        let truncated = truncate_large_strings(&value);
+        assert_eq!(truncated["small"], json!("ok"));
+        let cut = truncated["nested"][0].as_str().unwrap();
+        assert!(cut.len() < MAX_STRING_BYTES + 64);
+        assert!(cut.ends_with(&format!("[truncated, {} bytes]", blob.len())));
+    }
+
+    #[test]
+    fn test_hex_binary_strings() {
+        assert!(!is_binary("plain text\twith\nwhitespace"));
+        assert!(!is_binary(&format!("{}\u{7}", "a".repeat(20))));
+        assert!(is_binary("\u{0}\u{1}PNG\u{fffd}"));
+
+        let value = json!({"text": "ok", "raw": ["\u{0}\u{ff}A"]});
+        let hexed = hex_binary_strings(&value);
+        assert_eq!(hexed["text"], json!("ok"));
+        assert_eq!(hexed["raw"][0], json!("<binary, 4 bytes> 00 c3 bf 41"));
+
+        let long = "\u{0}".repeat(40);
+        assert!(hex_preview(&long).starts_with("<binary, 40 bytes> 00 00"));
+        assert!(hex_preview(&long).ends_with("00 ..."));
+
+        // Only with the toggle on; otherwise serde_json escapes the control characters
+        let log = crate::ingestion::parse_json_line(r#"{"blob":"\u0000\u0001\u0002"}"#).unwrap();
+        let text = |display: &DisplayOptions| {
+            LogDetailWidget::format_log_details(&log, None, display, None, None)
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert!(text(&DisplayOptions::default()).contains(r#""\u0000\u0001\u0002""#));
+        let display = DisplayOptions {
+            hex_binary: true,
+            ..Default::default()
+        };
+        assert!(text(&display).contains("<binary, 3 bytes> 00 01 02"));
+    }
+
+    #[test]
+    fn test_format_log_details_stable_field_order() {
+        let render = |line: &str| -> Vec<String> {
+            let log = crate::ingestion::parse_json_line(line).unwrap();
+            LogDetailWidget::format_log_details(&log, None, &DisplayOptions::default(), None, None)
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        };
+        let first = render(r#"{"zone":"eu","msg":"hi","level":30,"a":1,"pid":7}"#);
+
+        assert_eq!(
+            first,
+            render(r#"{"zone":"eu","msg":"hi","level":30,"a":1,"pid":7}"#)
+        );
+        assert_eq!(
+            first,
+            render(r#"{"pid":7,"a":1,"level":30,"msg":"hi","zone":"eu"}"#)
+        );
+        let keys: Vec<&str> = first
+            .iter()
+            .filter_map(|line| line.trim_start().strip_prefix('"')?.split('"').next())
+            .collect();
+        assert_eq!(keys, ["a", "level", "msg", "pid", "zone"]);
+    }
+
+    #[test]
+    fn test_format_log_details_caps_lines() {
+        let items: Vec<u64> = (0..(MAX_DETAIL_LINES as u64) * 2).collect();
+        let mut fields = HashMap::new();
+        fields.insert("items".to_string(), json!(items));
+        let log = JsonLog::new(fields);
+
+        let lines =
+            LogDetailWidget::format_log_details(&log, None, &DisplayOptions::default(), None, None);
+        assert_eq!(lines.len(), MAX_DETAIL_LINES + 1);
+        assert!(
+            lines[MAX_DETAIL_LINES]
+                .to_string()
+                .contains("more lines not shown")
+        );
+    }
+
+    #[test]
+    fn test_format_log_details_diff_previous() {
+        let previous = crate::ingestion::parse_json_line(
+            r#"{"level":30,"msg":"GET /a","ctx":{"id":1},"old":true}"#,
+        )
+        .unwrap();
+        let log =
+            crate::ingestion::parse_json_line(r#"{"level":30,"msg":"GET /b","ctx":{"id":1}}"#)
+                .unwrap();
+        let display = DisplayOptions {
+            diff_previous: true,
+            ..Default::default()
+        };
+
+        let lines =
+            LogDetailWidget::format_log_details(&log, Some(&previous), &display, None, None);
+        let line_for = |text: &str| {
+            lines
+                .iter()
+                .find(|line| line.to_string().contains(text))
+                .unwrap()
+        };
+
+        // Unchanged fields, nested lines included, are dimmed
+        assert_eq!(
+            line_for("\"level\"").spans[0].style.fg,
+            Some(Color::DarkGray)
+        );
+        assert_eq!(line_for("\"id\"").spans[0].style.fg, Some(Color::DarkGray));
+        // Changed fields get a highlighted key
+        assert_eq!(line_for("\"msg\"").spans[1].style.fg, Some(Color::Yellow));
+        assert_eq!(line_for("removed:").to_string(), "removed: old");
+
+        // Without a previous log nothing is dimmed
+        let lines = LogDetailWidget::format_log_details(&log, None, &display, None, None);
+        assert!(
+            lines
+                .iter()
+                .flat_map(|line| &line.spans)
+                .all(|span| span.style.fg != Some(Color::DarkGray))
+        );
+    }
+
+    #[test]
+    fn test_format_log_details_selected_field() {
+        let log = crate::ingestion::parse_json_line(r#"{"b":{"x":1},"a":"first","c":3}"#).unwrap();
+        let display = DisplayOptions::default();
+        assert_eq!(display.visible_field_names(&log), vec!["a", "b", "c"]);
+
+        // The whole of the second field is highlighted, nested lines included
+        let lines = LogDetailWidget::format_log_details(&log, None, &display, Some(1), None);
+        let selected: Vec<String> = lines
+            .iter()
+            .filter(|line| line.style == SELECTED_FIELD_STYLE)
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(selected, vec!["  \"b\": {", "    \"x\": 1", "  },"]);
+    }
+
+    #[test]
+    fn test_format_log_details_field_types() {
+        let log = crate::ingestion::parse_json_line(
+            r#"{"msg":"hi","level":30,"req":{"id":1},"a:b":"x: y"}"#,
+        )
+        .unwrap();
+        let field_types = vec![
+            ("level".to_string(), FieldType::Integer),
+            ("message".to_string(), FieldType::Text),
+            ("a:b".to_string(), FieldType::Text),
+        ];
+        let display = DisplayOptions {
+            show_field_types: true,
+            ..Default::default()
+        };
+        let text: Vec<String> = LogDetailWidget::new(Some(&log), 0, 1, &display)
+            .with_field_types(&field_types)
+            .format_lines(&log)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert!(text.contains(&"  \"level\": 30,  [BIGINT]".to_string()));
+        // Looked up by the normalized column name
+        assert!(text.contains(&"  \"msg\": \"hi\",  [TEXT]".to_string()));
+        // Keys holding a colon are found too
+        assert!(text.contains(&"  \"a:b\": \"x: y\",  [TEXT]".to_string()));
+        // No column, no annotation; nested fields are never annotated
+        assert!(text.contains(&"  \"req\": {".to_string()));
+        assert!(text.contains(&"    \"id\": 1".to_string()));
+
+        // Only shown with `show_field_types`
+        let text: Vec<String> = LogDetailWidget::new(Some(&log), 0, 1, &DisplayOptions::default())
+            .with_field_types(&field_types)
+            .format_lines(&log)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert!(text.contains(&"  \"level\": 30,".to_string()));
+    }
+
+    #[test]
+    fn test_format_log_details_extra_fields_only() {
+        let log = crate::ingestion::parse_json_line(
+            r#"{"time":1700000000000,"level":30,"msg":"hi","pid":7,"host":"a"}"#,
+        )
+        .unwrap();
+        let display = DisplayOptions {
+            extra_fields_only: true,
+            ..Default::default()
+        };
+        assert_eq!(display.visible_field_names(&log), vec!["host", "pid"]);
+
+        let text: Vec<String> =
+            LogDetailWidget::format_log_details(&log, None, &display, None, None)
+                .iter()
+                .map(|line| line.to_string())
+                .collect();
+        assert_eq!(text, vec!["{", "  \"host\": \"a\",", "  \"pid\": 7", "}"]);
+        // Display only: the log keeps its fields
+        assert_eq!(log.get_message(), Some("hi"));
+    }
+
+    #[test]
+    fn test_highlight_matches_across_spans() {
+        let log = crate::ingestion::parse_json_line(r#"{"msg":"GET /users","n":1}"#).unwrap();
+        let display = DisplayOptions::default();
+
+        let lines =
+            LogDetailWidget::format_log_details(&log, None, &display, None, Some("MSG\": \"get"));
+        let msg_line = lines
+            .iter()
+            .find(|line| line.to_string().contains("msg"))
+            .unwrap();
+        let matched: String = msg_line
+            .spans
+            .iter()
+            .filter(|span| span.style.bg == Some(Color::Yellow))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(matched, "msg\": \"GET");
+        // Splitting keeps the text and the original colors outside the match
+        assert_eq!(msg_line.to_string(), "  \"msg\": \"GET /users\",");
+        assert_eq!(msg_line.spans.last().unwrap().content, " /users\",");
+        assert_eq!(msg_line.spans.last().unwrap().style.fg, Some(Color::Green));
+
+        assert_eq!(search_match_field(&log, &display, "users"), Some(0));
+        assert_eq!(search_match_field(&log, &display, "\"n\""), Some(1));
+        assert_eq!(search_match_field(&log, &display, "missing"), None);
+    }
+
+    #[test]
+    fn test_render_scrolls_to_search_match() {
+        let mut fields: HashMap<String, serde_json::Value> = (0..20)
+            .map(|i| (format!("field{:02}", i), json!(i)))
+            .collect();
+        fields.insert("zz".to_string(), json!("needle"));
+        let log = JsonLog::new(fields);
+        let display = DisplayOptions::default();
+
+        let area = Rect::new(0, 0, 40, 8);
+        let mut buf = Buffer::empty(area);
+        render_log_detail(
+            Some(&log),
+            None,
+            LogDetailState::new(0, 1).with_search(Some("NEEDLE".to_string())),
+            &display,
+            &[],
+            area,
+            &mut buf,
+        );
+        let row = |y: u16| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        };
+
+        assert!((1..7).any(|y| row(y).contains("\"zz\": \"needle\"")));
+        assert!(row(7).contains("/NEEDLE (1 match)"));
+    }
+
+    #[test]
+    fn test_render_detail_modal_scroll() {
+        let fields: HashMap<String, serde_json::Value> = (0..20)
+            .map(|i| (format!("field{:02}", i), json!(i)))
+            .collect();
+        let log = JsonLog::new(fields);
+        let display = DisplayOptions::default();
+
+        // 22 lines ({, 20 fields, }) with 6 visible inside the modal border
+        let area = Rect::new(0, 0, 40, 10);
+        assert_eq!(max_detail_modal_scroll(&log, None, &display, area), 16);
+
+        let mut buf = Buffer::empty(area);
+        render_detail_modal(
+            Some(&log),
+            None,
+            LogDetailState::new(0, 1).with_scroll(Some(5)),
+            &display,
+            &[],
+            area,
+            &mut buf,
+        );
+        let row = |y: u16| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        };
+
+        // One-cell margin around the modal, then the border
+        assert!(row(0).trim().is_empty());
+        assert!(row(1).contains("Log Details"));
+        assert!(row(2).contains("\"field04\": 4"));
+    }
+
+    #[test]
+    fn test_to_compact_json() {
+        let long = "x".repeat(COMPACT_INLINE_WIDTH);
+        let value = json!({
+            "ctx": {"id": 1, "tags": ["a", "b"]},
+            "empty": {},
+            "msg": "hi",
+            "req": {"body": long, "id": 2}
+        });
+
+        assert_eq!(
+            to_compact_json(&value),
+            format!(
+                "{{\n \"ctx\": {{\"id\":1,\"tags\":[\"a\",\"b\"]}},\n \"empty\": {{}},\n \"msg\": \"hi\",\n \"req\": {{\n  \"body\": \"{}\",\n  \"id\": 2\n }}\n}}",
+                long
+            )
+        );
+
+        // Top-level fields still get their own line, so the field cursor works
+        let log = crate::ingestion::parse_json_line(r#"{"a":{"x":1},"b":2}"#).unwrap();
+        let display = DisplayOptions {
+            compact_json: true,
+            ..Default::default()
+        };
+        let lines = LogDetailWidget::format_log_details(&log, None, &display, Some(0), None);
+        let selected: Vec<String> = lines
+            .iter()
+            .filter(|line| line.style == SELECTED_FIELD_STYLE)
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(selected, vec![" \"a\": {\"x\":1},"]);
+    }
+
+    #[test]
+    fn test_format_log_details_single_line() {
+        let log = crate::ingestion::parse_json_line(r#"{"msg":"hi","a":{"x":1},"v":0}"#).unwrap();
+        let display = DisplayOptions {
+            hidden_fields: ["v".to_string()].into_iter().collect(),
+            single_line_json: true,
+            compact_json: true,
+            ..Default::default()
+        };
+
+        let lines = LogDetailWidget::format_log_details(&log, None, &display, None, None);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].to_string(), r#"{"a":{"x":1},"msg":"hi"}"#);
+    }
+
+    #[test]
+    fn test_format_log_details_line_numbers() {
+        let log =
+            crate::ingestion::parse_json_line(r#"{"err":{"message":"boom"},"msg":"hi","n":1}"#)
+                .unwrap();
+        let display = DisplayOptions {
+            detail_line_numbers: true,
+            ..Default::default()
+        };
+
+        let lines: Vec<String> =
+            LogDetailWidget::format_log_details(&log, None, &display, None, None)
+                .iter()
+                .map(|line| line.to_string())
+                .collect();
+        // The error block gets a blank gutter; the JSON is numbered from its `{`
+        assert!(lines[0].starts_with("  │ "));
+        let json: Vec<&str> = lines
+            .iter()
+            .skip_while(|line| !line.starts_with("1 │"))
+            .map(String::as_str)
+            .collect();
+        assert_eq!(json[0], "1 │ {");
+        assert_eq!(json[4], "5 │   \"msg\": \"hi\",");
+        assert_eq!(json.last(), Some(&"7 │ }"));
+
+        // The gutter doesn't count as text: digits don't match, indentation still finds fields
+        assert_eq!(search_match_field(&log, &display, "hi"), Some(1));
+        assert_eq!(search_match_field(&log, &display, "7"), None);
+
+        // One-line JSON wraps, so it stays unnumbered
+        let display = DisplayOptions {
+            single_line_json: true,
+            ..display
+        };
+        let lines = LogDetailWidget::format_log_details(&log, None, &display, None, None);
+        assert!(lines[0].to_string().starts_with('{'));
+    }
+
+    #[test]
+    fn test_format_log_details_error_block() {
+        let log = crate::ingestion::parse_json_line(
+            r#"{"msg":"failed","err":{"type":"TypeError","message":"x is undefined","stack":"TypeError: x is undefined\n    at f (app.js:1:2)\n    at g (app.js:3:4)"}}"#,
+        )
+        .unwrap();
+        let lines: Vec<String> =
+            LogDetailWidget::format_log_details(&log, None, &DisplayOptions::default(), None, None)
+                .iter()
+                .map(|line| line.to_string())
+                .collect();
+        assert_eq!(
+            lines[..5],
+            [
+                "TypeError: x is undefined",
+                "      at f (app.js:1:2)",
+                "      at g (app.js:3:4)",
+                "",
+                "{",
+            ]
+        );
+
+        // Any other shape only gets the generic JSON
+        let log =
+            crate::ingestion::parse_json_line(r#"{"msg":"failed","error":"timeout"}"#).unwrap();
+        let lines =
+            LogDetailWidget::format_log_details(&log, None, &DisplayOptions::default(), None, None);
+        assert_eq!(lines[0].to_string(), "{");
+    }
+}
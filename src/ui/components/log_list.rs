@@ -1,44 +1,119 @@
+use crate::ingestion::models::{SOURCE_FIELD, UNPARSED_FIELD};
 use crate::ingestion::{JsonLog, LogLevel};
+use crate::storage::schema::normalize_field_name;
+use crate::ui::age::AgeBucket;
+use crate::ui::display::DisplayOptions;
+use crate::ui::groups::{ListRow, group_rows};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, StatefulWidget, Widget},
+    widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
 };
+use std::borrow::Cow;
+
+/// Width of the source file column, longer names are cut with `…`
+const SOURCE_COLUMN_WIDTH: usize = 12;
+
+/// Colors assigned to source files; a name always maps to the same one
+const SOURCE_COLORS: [Color; 6] = [
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightBlue,
+    Color::LightRed,
+];
 
 pub struct LogListWidget<'a> {
     logs: &'a [JsonLog],
     title: String,
+    status: Option<&'a str>,
+    display: &'a DisplayOptions,
 }
 
 impl<'a> LogListWidget<'a> {
-    pub fn new(logs: &'a [JsonLog], title: String) -> Self {
-        Self { logs, title }
+    pub fn new(logs: &'a [JsonLog], title: String, display: &'a DisplayOptions) -> Self {
+        Self {
+            logs,
+            title,
+            status: None,
+            display,
+        }
+    }
+
+    /// Show a status message in the bottom border
+    pub fn with_status(mut self, status: Option<&'a str>) -> Self {
+        self.status = status;
+        self
     }
 
     /// Format a log entry as a single line
-    fn format_log_line(log: &JsonLog) -> Line<'static> {
+    /// `width` is the number of columns available for the line
+    fn format_log_line(log: &JsonLog, display: &DisplayOptions, width: usize) -> Line<'static> {
         let mut spans = Vec::new();
 
+        // Source line number gutter
+        if display.line_numbers {
+            let line_number = log.line_number().map(|n| n.to_string()).unwrap_or_default();
+            spans.push(Span::styled(
+                format!("{:>6} ", line_number),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+
+        // Source file column, colored per file
+        if display.show_source_file {
+            if let Some(source) = log.get_field(SOURCE_FIELD).and_then(|v| v.as_str()) {
+                spans.push(Span::styled(
+                    format!("{} ", source_cell(source)),
+                    Style::default().fg(source_color(source)),
+                ));
+            }
+        }
+
+        // Placeholder for a line that failed to parse: no fields to format
+        if let Some(content) = log.get_field(UNPARSED_FIELD).and_then(|v| v.as_str()) {
+            let line_number = log.line_number().unwrap_or_default();
+            spans.push(Span::styled(
+                format!("<unparseable line {}: {}>", line_number, content),
+                Style::default().fg(Color::Red),
+            ));
+            return Line::from(spans);
+        }
+
         // Format timestamp
         if let Some(timestamp) = log.timestamp() {
-            let time_str = timestamp
-                .strftime("%H:%M:%S")
-                .to_string();
+            let time_str = display.format_timestamp(timestamp, display.list_time_format());
             spans.push(Span::styled(
                 format!("[{}] ", time_str),
                 Style::default().fg(Color::DarkGray),
             ));
-        } else if let Some(time_ms) = log.get_timestamp_ms() {
+
+            // Age tag, measured from now or the newest log
+            let reference = if display.show_age {
+                display.age_reference_time()
+            } else {
+                None
+            };
+            if let Some(reference) = reference {
+                let bucket = AgeBucket::of(timestamp, reference);
+                spans.push(Span::styled(
+                    format!("{} ", bucket.label()),
+                    Style::default().fg(bucket.color()),
+                ));
+            }
+        } else if let Some(time) = log.out_of_range_timestamp() {
+            // Raw value, marked so it isn't mistaken for a formatted time
             spans.push(Span::styled(
-                format!("[{}] ", time_ms),
-                Style::default().fg(Color::DarkGray),
+                format!("[!{}] ", time),
+                Style::default().fg(Color::Yellow),
             ));
         }
 
         // Format level with color
-        if let Some(level) = log.get_level() {
+        if let Some(level) = display.log_level(log) {
             let (level_str, color) = match level {
                 LogLevel::Trace => ("TRACE", Color::DarkGray),
                 LogLevel::Debug => ("DEBUG", Color::Blue),
@@ -53,8 +128,59 @@ impl<'a> LogListWidget<'a> {
             ));
         }
 
+        // Format badges
+        for rule in &display.badges {
+            let Some(value) = find_field(log, &rule.field) else {
+                continue;
+            };
+            if let Some(color) = rule.color_for(value) {
+                spans.push(Span::styled(
+                    format!(" {}:{} ", rule.field, value_text(value)),
+                    Style::default().fg(Color::Black).bg(color),
+                ));
+                spans.push(Span::raw(" "));
+            }
+        }
+
+        // Format logger name
+        if display.show_logger {
+            if let Some(name) = log.get_logger_name() {
+                spans.push(Span::styled(
+                    format!("[{}] ", name),
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+        }
+
+        // Format source location
+        if display.show_source {
+            if let Some(location) = log.get_source_location() {
+                spans.push(Span::styled(
+                    format!("{} ", location),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+
+        // Format trace/span ids
+        if let Some((_, trace_id)) = log.get_trace_id(&display.trace_fields) {
+            let ids = match log.get_span_id() {
+                Some(span_id) => format!("{}/{} ", short_id(&trace_id), short_id(span_id)),
+                None => format!("{} ", short_id(&trace_id)),
+            };
+            spans.push(Span::styled(ids, Style::default().fg(Color::Blue)));
+        }
+
+        // Mark logs carrying an error object
+        if log.get_error_object(&display.error_fields).is_some() {
+            spans.push(Span::styled(
+                "✗ ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+
         // Format message
-        if let Some(message) = log.get_message() {
+        if let Some(message) = display.log_message(log) {
             // Truncate long messages
             let truncated = if message.len() > 80 {
                 format!("{}...", &message[..77])
@@ -64,8 +190,37 @@ impl<'a> LogListWidget<'a> {
             spans.push(Span::raw(truncated));
         }
 
-        // Show field count
-        let field_count = log.fields.len();
+        // Wide mode: append the configured columns as key=value pairs, starting at
+        // the scrolled-to column; `…` marks columns hidden on either side
+        if display.wide_mode {
+            let columns = display
+                .visible_columns
+                .get(display.table_col_offset..)
+                .unwrap_or_default();
+            if display.table_col_offset > 0 && !columns.is_empty() {
+                spans.push(Span::styled(" …", Style::default().fg(Color::DarkGray)));
+            }
+            let used: usize = spans.iter().map(|span| span.width()).sum();
+            spans.extend(Self::format_inline_fields(
+                log,
+                columns,
+                width.saturating_sub(used),
+            ));
+        }
+
+        // Show the number of extra fields (standard, hidden, constant and message
+        // fields excluded)
+        let field_count = log
+            .extra_field_names()
+            .into_iter()
+            .filter(|k| !display.is_hidden(k) && !display.is_constant(k))
+            .filter(|k| {
+                !display
+                    .message_fields
+                    .iter()
+                    .any(|field| normalize_field_name(field) == normalize_field_name(k))
+            })
+            .count();
         if field_count > 0 {
             spans.push(Span::styled(
                 format!(" (+{})", field_count),
@@ -75,23 +230,181 @@ impl<'a> LogListWidget<'a> {
 
         Line::from(spans)
     }
+
+    /// Format a group header: expansion marker, `field=value` and the number of logs
+    fn format_group_header(field: &str, key: &str, count: usize, expanded: bool) -> Line<'static> {
+        let marker = if expanded { "▾" } else { "▸" };
+        let noun = if count == 1 { "log" } else { "logs" };
+        Line::from(vec![
+            Span::styled(format!("{} ", marker), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("{}={}", field, key),
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" ({} {})", count, noun),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    }
+
+    /// Format `key=value` spans for the given fields, truncated to `max_width` columns
+    fn format_inline_fields(
+        log: &JsonLog,
+        fields: &[String],
+        max_width: usize,
+    ) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        let mut remaining = max_width;
+
+        for field in fields {
+            let Some(value) = find_field(log, field) else {
+                continue;
+            };
+
+            let value_str = value_text(value);
+            let key_str = format!(" {}=", field);
+            let pair_width = key_str.chars().count() + value_str.chars().count();
+
+            if pair_width <= remaining {
+                remaining -= pair_width;
+                spans.push(Span::styled(key_str, Style::default().fg(Color::DarkGray)));
+                spans.push(Span::styled(value_str, Style::default().fg(Color::Green)));
+                continue;
+            }
+
+            // Doesn't fit: show as much as possible, then stop
+            let key_width = key_str.chars().count();
+            if remaining > key_width + 1 {
+                let visible: String = value_str.chars().take(remaining - key_width - 1).collect();
+                spans.push(Span::styled(key_str, Style::default().fg(Color::DarkGray)));
+                spans.push(Span::styled(
+                    format!("{}…", visible),
+                    Style::default().fg(Color::Green),
+                ));
+            } else if remaining > 0 {
+                spans.push(Span::styled("…", Style::default().fg(Color::DarkGray)));
+            }
+            break;
+        }
+
+        spans
+    }
 }
 
-impl<'a> Widget for LogListWidget<'a> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let items: Vec<ListItem> = self
-            .logs
+/// Look up a field by its original or normalized name
+fn find_field<'a>(log: &'a JsonLog, field: &str) -> Option<&'a serde_json::Value> {
+    log.get_field(field).or_else(|| {
+        log.fields
             .iter()
-            .map(|log| ListItem::new(Self::format_log_line(log)))
-            .collect();
+            .find(|(k, _)| normalize_field_name(k) == field)
+            .map(|(_, v)| v)
+    })
+}
 
-        let list = List::new(items)
-            .block(
-                Block::default()
-                    .title(self.title)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::White)),
+/// Strings without quotes, everything else as JSON
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Stable color for a source file name (a string hash, not `RandomState`)
+fn source_color(source: &str) -> Color {
+    let hash = source.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    SOURCE_COLORS[hash % SOURCE_COLORS.len()]
+}
+
+/// Source name padded or cut to `SOURCE_COLUMN_WIDTH` characters
+fn source_cell(source: &str) -> String {
+    if source.chars().count() > SOURCE_COLUMN_WIDTH {
+        let cut: String = source.chars().take(SOURCE_COLUMN_WIDTH - 1).collect();
+        format!("{}…", cut)
+    } else {
+        format!("{:<width$}", source, width = SOURCE_COLUMN_WIDTH)
+    }
+}
+
+/// First 8 characters of a trace or span id, enough to tell traces apart
+fn short_id(id: &str) -> &str {
+    id.char_indices().nth(8).map_or(id, |(end, _)| &id[..end])
+}
+
+/// Columns available for a list line inside the bordered block with the "> " highlight symbol
+fn line_width(area: Rect) -> usize {
+    area.width.saturating_sub(4) as usize
+}
+
+impl<'a> StatefulWidget for LogListWidget<'a> {
+    type State = LogListState<'a>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let to_item =
+            |log| ListItem::new(Self::format_log_line(log, self.display, line_width(area)));
+        // Newest-first only reverses the rows; `state.selected` is already a row index
+        let items: Vec<ListItem> = if let Some(field) = self.display.group_field() {
+            let rows = state.rows.map(Cow::Borrowed).unwrap_or_else(|| {
+                Cow::Owned(group_rows(
+                    self.logs,
+                    field,
+                    &self.display.expanded_groups,
+                    self.display.newest_first,
+                ))
+            });
+            rows.iter()
+                .map(|row| match row {
+                    ListRow::Group {
+                        key,
+                        count,
+                        expanded,
+                        ..
+                    } => ListItem::new(Self::format_group_header(field, key, *count, *expanded)),
+                    ListRow::Log { index, nested } => {
+                        let log = &self.logs[*index];
+                        if *nested {
+                            // Indent logs under their group header
+                            let line = Self::format_log_line(
+                                log,
+                                self.display,
+                                line_width(area).saturating_sub(2),
+                            );
+                            let mut spans = vec![Span::raw("  ")];
+                            spans.extend(line.spans);
+                            ListItem::new(Line::from(spans))
+                        } else {
+                            to_item(log)
+                        }
+                    }
+                })
+                .collect()
+        } else if self.display.newest_first {
+            self.logs.iter().rev().map(to_item).collect()
+        } else {
+            self.logs.iter().map(to_item).collect()
+        };
+
+        let mut block = Block::default()
+            .title(self.title)
+            .title_bottom(
+                Line::from(format!(" {} ", self.display.time_zone_label()))
+                    .style(Style::default().fg(Color::DarkGray))
+                    .right_aligned(),
             )
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White));
+        if let Some(status) = self.status {
+            block = block.title_bottom(
+                Line::from(format!(" {} ", status)).style(Style::default().fg(Color::Yellow)),
+            );
+        }
+
+        let list = List::new(items)
+            .block(block)
             .highlight_style(
                 Style::default()
                     .bg(Color::DarkGray)
@@ -99,54 +412,510 @@ impl<'a> Widget for LogListWidget<'a> {
             )
             .highlight_symbol("> ");
 
-        // Create a stateful widget and render with state
-        let mut state = ratatui::widgets::ListState::default();
-        state.select(Some(0)); // This will be overridden by the actual selected index
-        StatefulWidget::render(list, area, buf, &mut state);
+        let mut list_state = ListState::default().with_offset(state.offset);
+        if !self.logs.is_empty() {
+            list_state.select(Some(state.selected));
+        }
+
+        StatefulWidget::render(list, area, buf, &mut list_state);
+
+        // Keep the offset ratatui settled on
+        state.offset = list_state.offset();
     }
 }
 
-pub struct LogListState {
+/// Selection and first visible row of the log list
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogListState<'a> {
     pub selected: usize,
+    pub offset: usize,
+    /// Grouped rows already built by the app, grouped again from the logs when `None`
+    pub rows: Option<&'a [ListRow]>,
 }
 
-impl LogListState {
-    pub fn new(selected: usize) -> Self {
-        Self { selected }
+impl<'a> LogListState<'a> {
+    pub fn new(selected: usize, offset: usize) -> Self {
+        Self {
+            selected,
+            offset,
+            rows: None,
+        }
+    }
+
+    /// Use the app's cached grouped rows
+    pub fn with_rows(mut self, rows: Option<&'a [ListRow]>) -> Self {
+        self.rows = rows;
+        self
     }
 }
 
 /// Render the log list with proper state management
 pub fn render_log_list(
     logs: &[JsonLog],
-    selected_index: usize,
+    mut state: LogListState<'_>,
     title: String,
+    status: Option<&str>,
+    display: &DisplayOptions,
     area: Rect,
     buf: &mut Buffer,
 ) {
-    let items: Vec<ListItem> = logs
-        .iter()
-        .map(|log| ListItem::new(LogListWidget::format_log_line(log)))
-        .collect();
-
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White)),
+    let widget = LogListWidget::new(logs, title, display).with_status(status);
+    widget.render(area, buf, &mut state);
+}
+
+/// Number of log rows the list shows in `area`: everything inside the border
+/// (the status message is drawn on the border, not in a row of its own)
+pub fn visible_rows(area: Rect) -> usize {
+    area.height.saturating_sub(2) as usize
+}
+
+/// Compute the first visible row so the selection keeps `scrolloff` rows of context
+/// Starts from the previous offset and scrolls only as far as needed, like vim
+pub fn compute_scroll_offset(
+    current: usize,
+    selected: usize,
+    len: usize,
+    height: usize,
+    scrolloff: usize,
+) -> usize {
+    if height == 0 || len <= height {
+        return 0;
+    }
+
+    // More than half a page of padding would leave no valid offset
+    let scrolloff = scrolloff.min((height - 1) / 2);
+    let max_offset = len - height;
+    let mut offset = current.min(max_offset);
+
+    if selected < offset + scrolloff {
+        offset = selected.saturating_sub(scrolloff);
+    } else if selected + scrolloff >= offset + height {
+        offset = selected + scrolloff + 1 - height;
+    }
+
+    offset.min(max_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+    use crate::ui::display::TimePrecision;
+    use std::collections::HashSet;
+
+    fn highlighted_rows(buf: &Buffer, area: Rect) -> Vec<u16> {
+        (area.top() + 1..area.bottom() - 1)
+            .filter(|&y| buf[(area.left() + 1, y)].symbol() == ">")
+            .collect()
+    }
+
+    #[test]
+    fn test_render_highlights_selected_row() {
+        let logs: Vec<JsonLog> = (0..5)
+            .map(|i| parse_json_line(&format!(r#"{{"level":30,"msg":"log {}"}}"#, i)).unwrap())
+            .collect();
+        let display = DisplayOptions::default();
+        let area = Rect::new(0, 0, 40, 7);
+
+        let mut buf = Buffer::empty(area);
+        let mut state = LogListState::new(3, 0);
+        LogListWidget::new(&logs, "Logs".to_string(), &display).render(area, &mut buf, &mut state);
+
+        // Row 0 is the border, so log 3 is drawn on row 4
+        assert_eq!(highlighted_rows(&buf, area), vec![4]);
+        assert_eq!(buf[(1, 4)].bg, Color::DarkGray);
+    }
+
+    #[test]
+    fn test_visible_rows_match_render() {
+        let logs: Vec<JsonLog> = (0..50)
+            .map(|i| parse_json_line(&format!(r#"{{"level":30,"msg":"log {}"}}"#, i)).unwrap())
+            .collect();
+        let display = DisplayOptions::default();
+        let area = Rect::new(0, 0, 40, 10);
+        let rows = visible_rows(area);
+        let render = |selected: usize| {
+            let mut buf = Buffer::empty(area);
+            let mut state = LogListState::new(selected, 0);
+            LogListWidget::new(&logs, "Logs".to_string(), &display)
+                .with_status(Some("status"))
+                .render(area, &mut buf, &mut state);
+            (highlighted_rows(&buf, area), state.offset)
+        };
+
+        // The last visible row is the bottom row inside the border
+        assert_eq!(rows, 8);
+        assert_eq!(render(rows - 1), (vec![area.bottom() - 2], 0));
+        // A page down from the top lands just past it, scrolling by one row
+        assert_eq!(render(rows), (vec![area.bottom() - 2], 1));
+        assert_eq!(compute_scroll_offset(0, rows, logs.len(), rows, 0), 1);
+    }
+
+    #[test]
+    fn test_render_scrolls_to_selected_row() {
+        let logs: Vec<JsonLog> = (0..10)
+            .map(|i| parse_json_line(&format!(r#"{{"level":30,"msg":"log {}"}}"#, i)).unwrap())
+            .collect();
+        let display = DisplayOptions::default();
+        let area = Rect::new(0, 0, 40, 5);
+
+        let mut buf = Buffer::empty(area);
+        render_log_list(
+            &logs,
+            LogListState::new(8, 0),
+            "Logs".to_string(),
+            None,
+            &display,
+            area,
+            &mut buf,
+        );
+
+        // Three visible rows, selection scrolled to the last one
+        assert_eq!(highlighted_rows(&buf, area), vec![3]);
+    }
+
+    #[test]
+    fn test_render_grouped_rows() {
+        let logs: Vec<JsonLog> = ["a", "b", "a"]
+            .iter()
+            .map(|req| parse_json_line(&format!(r#"{{"msg":"hi","reqId":"{}"}}"#, req)).unwrap())
+            .collect();
+        let display = DisplayOptions {
+            group_by: Some("reqId".to_string()),
+            grouped: true,
+            expanded_groups: ["a".to_string()].into_iter().collect(),
+            ..Default::default()
+        };
+        let area = Rect::new(0, 0, 30, 6);
+
+        let mut buf = Buffer::empty(area);
+        let mut state = LogListState::new(0, 0);
+        LogListWidget::new(&logs, "Logs".to_string(), &display).render(area, &mut buf, &mut state);
+
+        let row = |y: u16| {
+            (1..area.width - 1)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(row(1), "> ▾ reqId=a (2 logs)");
+        assert_eq!(row(2), "    hi (+1)");
+        assert_eq!(row(3), "    hi (+1)");
+        assert_eq!(row(4), "  ▸ reqId=b (1 log)");
+    }
+
+    #[test]
+    fn test_format_log_line_badges() {
+        let log = parse_json_line(r#"{"msg":"GET /","statusCode":503,"cached":true}"#).unwrap();
+        let display = DisplayOptions {
+            badges: vec!["statusCode".parse().unwrap()],
+            ..Default::default()
+        };
+
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        let badge = line
+            .spans
+            .iter()
+            .find(|span| span.content == " statusCode:503 ")
+            .unwrap();
+        assert_eq!(badge.style.bg, Some(Color::Red));
+        assert!(
+            !line
+                .spans
+                .iter()
+                .any(|span| span.content.contains("cached"))
+        );
+    }
+
+    #[test]
+    fn test_format_log_line_lenient_level() {
+        let log = parse_json_line(r#"{"level":35,"msg":"custom"}"#).unwrap();
+        let level_span = |display: &DisplayOptions| {
+            LogListWidget::format_log_line(&log, display, 80)
+                .spans
+                .into_iter()
+                .find(|span| span.content.starts_with("INFO"))
+        };
+
+        assert!(level_span(&DisplayOptions::default()).is_none());
+        let display = DisplayOptions {
+            lenient_levels: true,
+            ..Default::default()
+        };
+        assert_eq!(level_span(&display).unwrap().style.fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn test_format_log_line_nested_level() {
+        let level_span = |line: &str, display: &DisplayOptions| {
+            let log = parse_json_line(line).unwrap();
+            LogListWidget::format_log_line(&log, display, 80)
+                .spans
+                .into_iter()
+                .find(|span| span.content.starts_with("ERROR") || span.content.starts_with("WARN"))
+        };
+        let nested = r#"{"severity":{"number":17,"text":"ERROR"},"msg":"failed"}"#;
+
+        assert!(level_span(nested, &DisplayOptions::default()).is_none());
+        let display = DisplayOptions {
+            level_field: Some("severity.number".to_string()),
+            ..Default::default()
+        };
+        let span = level_span(nested, &display).unwrap();
+        assert_eq!(span.content, "ERROR ");
+        assert_eq!(span.style.fg, Some(Color::Red));
+
+        // Names work too, and objects read back from the database are JSON text
+        let display = DisplayOptions {
+            level_field: Some("log.level".to_string()),
+            ..Default::default()
+        };
+        let span = level_span(r#"{"log":"{\"level\":\"warn\"}","msg":"slow"}"#, &display).unwrap();
+        assert_eq!(span.style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn test_format_log_line_message_fields() {
+        let log =
+            parse_json_line(r#"{"context":"db","msg":"query failed","detail":"timeout"}"#).unwrap();
+        let text = |display: &DisplayOptions| {
+            LogListWidget::format_log_line(&log, display, 80)
+                .spans
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+
+        assert!(text(&DisplayOptions::default()).ends_with("query failed (+2)"));
+        let display = DisplayOptions {
+            message_fields: vec!["context".into(), "msg".into(), "detail".into()],
+            ..Default::default()
+        };
+        // Joined fields aren't counted as extra fields
+        assert!(text(&display).ends_with("db | query failed | timeout"));
+        let display = DisplayOptions {
+            message_separator: ": ".to_string(),
+            ..display
+        };
+        assert!(text(&display).ends_with("db: query failed: timeout"));
+    }
+
+    #[test]
+    fn test_format_log_line_extra_field_count() {
+        let display = DisplayOptions {
+            hidden_fields: ["pid".to_string()].into_iter().collect(),
+            ..Default::default()
+        };
+        let count_span = |line: &Line| {
+            line.spans
+                .iter()
+                .find(|span| span.content.starts_with(" (+"))
+                .map(|span| span.content.to_string())
+        };
+
+        let log =
+            parse_json_line(r#"{"time":1,"level":30,"msg":"hi","pid":1,"host":"a","req":{}}"#)
+                .unwrap();
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(count_span(&line).as_deref(), Some(" (+2)"));
+
+        let log = parse_json_line(r#"{"timestamp":1,"lvl":30,"message":"hi"}"#).unwrap();
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(count_span(&line), None);
+
+        // `--hide-constant` leaves out fields with one value everywhere
+        let display = DisplayOptions {
+            constant_fields: ["host".to_string()].into_iter().collect(),
+            ..display
+        };
+        let log =
+            parse_json_line(r#"{"time":1,"level":30,"msg":"hi","pid":1,"host":"a","req":{}}"#)
+                .unwrap();
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(count_span(&line).as_deref(), Some(" (+1)"));
+    }
+
+    #[test]
+    fn test_format_log_line_time_precision() {
+        let log = parse_json_line(r#"{"time":1531171074631042,"msg":"hi"}"#).unwrap();
+        let time_span = |display: &DisplayOptions| {
+            LogListWidget::format_log_line(&log, display, 80).spans[0]
+                .content
+                .to_string()
+        };
+
+        assert_eq!(time_span(&DisplayOptions::default()), "[21:17:54] ");
+        let display = DisplayOptions {
+            time_precision: TimePrecision::Micros,
+            ..Default::default()
+        };
+        assert_eq!(time_span(&display), "[21:17:54.631042] ");
+    }
+
+    #[test]
+    fn test_format_log_line_bunyan() {
+        let log = parse_json_line(
+            r#"{"name":"myapp","hostname":"banana.local","pid":40161,"level":30,"msg":"hi","time":"2013-01-04T18:46:23.851Z","v":0,"src":{"file":"/srv/app/examples/src.js","line":20,"func":"Wuzzle.woos"}}"#,
         )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD),
+        .unwrap();
+        let display = DisplayOptions {
+            hidden_fields: ["v".to_string()].into_iter().collect(),
+            show_source: true,
+            ..Default::default()
+        };
+
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.to_string(), "[18:46:23] INFO  src.js:20 hi (+4)");
+        assert_eq!(line.spans[1].style.fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn test_format_log_line_wide_mode_columns() {
+        let log = parse_json_line(r#"{"level":30,"msg":"hi","a":"1","b":"2","c":"3"}"#).unwrap();
+        let mut display = DisplayOptions {
+            visible_columns: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            wide_mode: true,
+            ..Default::default()
+        };
+        let text = |display: &DisplayOptions, width| {
+            LogListWidget::format_log_line(&log, display, width).to_string()
+        };
+
+        assert!(text(&display, 100).contains("hi a=1 b=2 c=3"));
+        // Columns past the right edge are cut with `…`
+        let narrow = text(&display, 13);
+        assert!(narrow.contains("hi a=1…"));
+        assert!(!narrow.contains("b="));
+
+        display.table_col_offset = 1;
+        let scrolled = text(&display, 100);
+        assert!(scrolled.contains("hi … b=2 c=3"));
+        assert!(!scrolled.contains("a=1"));
+    }
+
+    #[test]
+    fn test_format_log_line_source_file() {
+        let log = parse_json_line(
+            r#"{"level":30,"msg":"hi","time":"2013-01-04T18:46:23Z","_source":"api.log"}"#,
         )
-        .highlight_symbol("> ");
+        .unwrap();
+        let mut display = DisplayOptions::default();
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.to_string(), "[18:46:23] INFO  hi");
+
+        display.show_source_file = true;
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.to_string(), "api.log      [18:46:23] INFO  hi");
+        assert_eq!(line.spans[0].style.fg, Some(source_color("api.log")));
+    }
+
+    #[test]
+    fn test_source_color_and_cell() {
+        assert_eq!(source_color("api.log"), source_color("api.log"));
+        let colors: HashSet<_> = ["api.log", "worker.log", "db.log", "web.log"]
+            .iter()
+            .map(|source| source_color(source))
+            .collect();
+        assert!(colors.len() > 1);
 
-    let mut state = ratatui::widgets::ListState::default();
-    if !logs.is_empty() {
-        state.select(Some(selected_index));
+        assert_eq!(source_cell("a-very-long-service.log"), "a-very-long…");
+        assert_eq!(
+            source_cell("a-very-long-service.log").chars().count(),
+            SOURCE_COLUMN_WIDTH
+        );
     }
 
-    StatefulWidget::render(list, area, buf, &mut state);
+    #[test]
+    fn test_format_log_line_unparsed() {
+        let log = JsonLog::unparsed(42, "plain text".to_string());
+        let line = LogListWidget::format_log_line(&log, &DisplayOptions::default(), 80);
+        assert_eq!(line.to_string(), "<unparseable line 42: plain text>");
+        assert_eq!(line.spans[0].style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn test_format_log_line_error_object() {
+        let log = parse_json_line(
+            r#"{"level":50,"msg":"failed","time":"2013-01-04T18:46:23Z","err":{"type":"Error","message":"boom"}}"#,
+        )
+        .unwrap();
+        let line = LogListWidget::format_log_line(&log, &DisplayOptions::default(), 80);
+        assert_eq!(line.to_string(), "[18:46:23] ERROR ✗ failed (+1)");
+
+        let display = DisplayOptions {
+            error_fields: vec!["failure".to_string()],
+            ..Default::default()
+        };
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.to_string(), "[18:46:23] ERROR failed (+1)");
+    }
+
+    #[test]
+    fn test_format_log_line_trace_ids() {
+        let log = parse_json_line(
+            r#"{"level":30,"msg":"hi","time":"2013-01-04T18:46:23Z","trace_id":"4bf92f3577b34da6a3ce929d0e0e4736","span_id":"00f067aa0ba902b7"}"#,
+        )
+        .unwrap();
+
+        let line = LogListWidget::format_log_line(&log, &DisplayOptions::default(), 80);
+        assert_eq!(
+            line.to_string(),
+            "[18:46:23] INFO  4bf92f35/00f067aa hi (+2)"
+        );
+        assert_eq!(line.spans[2].style.fg, Some(Color::Blue));
+
+        let display = DisplayOptions {
+            trace_fields: vec!["request_id".to_string()],
+            ..Default::default()
+        };
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.to_string(), "[18:46:23] INFO  hi (+2)");
+    }
+
+    #[test]
+    fn test_format_log_line_line_numbers() {
+        let mut log = parse_json_line(r#"{"msg":"hi","host":"a"}"#).unwrap();
+        log.set_line_number(42);
+
+        let line = LogListWidget::format_log_line(&log, &DisplayOptions::default(), 80);
+        assert!(!line.spans.iter().any(|span| span.content.contains("42")));
+
+        let display = DisplayOptions {
+            line_numbers: true,
+            ..Default::default()
+        };
+        let line = LogListWidget::format_log_line(&log, &display, 80);
+        assert_eq!(line.spans[0].content, "    42 ");
+        // The line number is not an extra field
+        assert!(line.spans.iter().any(|span| span.content == " (+1)"));
+    }
+
+    #[test]
+    fn test_scroll_offset_without_scrolloff() {
+        // Selection stays put until it reaches an edge
+        assert_eq!(compute_scroll_offset(0, 5, 100, 10, 0), 0);
+        assert_eq!(compute_scroll_offset(0, 10, 100, 10, 0), 1);
+        assert_eq!(compute_scroll_offset(20, 25, 100, 10, 0), 20);
+        assert_eq!(compute_scroll_offset(20, 19, 100, 10, 0), 19);
+    }
+
+    #[test]
+    fn test_scroll_offset_with_scrolloff() {
+        assert_eq!(compute_scroll_offset(0, 7, 100, 10, 3), 1);
+        assert_eq!(compute_scroll_offset(20, 22, 100, 10, 3), 19);
+        // Near the start and end the selection may reach the edge
+        assert_eq!(compute_scroll_offset(5, 1, 100, 10, 3), 0);
+        assert_eq!(compute_scroll_offset(80, 99, 100, 10, 3), 90);
+    }
+
+    #[test]
+    fn test_scroll_offset_clamps() {
+        // Everything fits
+        assert_eq!(compute_scroll_offset(5, 3, 8, 10, 3), 0);
+        // Oversized scrolloff centers the selection
+        assert_eq!(compute_scroll_offset(0, 50, 100, 11, 99), 45);
+        // Stale offset after the list shrank
+        assert_eq!(compute_scroll_offset(90, 10, 20, 10, 0), 10);
+    }
 }
@@ -1,7 +1,12 @@
+pub mod command_palette;
 pub mod debug_logs;
+pub mod facets;
 pub mod filter_panel;
 pub mod help_menu;
 pub mod log_detail;
 pub mod log_list;
+pub mod numeric_filter;
+pub mod schema_summary;
+pub mod volume;
 
 
@@ -0,0 +1,133 @@
+use crate::ui::numeric_filter::{NumericFilter, NumericFilterStep, OPERATORS};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+pub struct NumericFilterWidget<'a> {
+    prompt: &'a NumericFilter,
+}
+
+impl<'a> NumericFilterWidget<'a> {
+    pub fn new(prompt: &'a NumericFilter) -> Self {
+        Self { prompt }
+    }
+
+    /// The comparison so far, with the part being chosen highlighted
+    fn preview(&self) -> Line<'static> {
+        let active = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let style_for = |step| {
+            if self.prompt.step == step {
+                active
+            } else {
+                Style::default()
+            }
+        };
+        let value = if self.prompt.value.is_empty() {
+            "_".to_string()
+        } else {
+            self.prompt.value.clone()
+        };
+        Line::from(vec![
+            Span::styled(
+                self.prompt.field_name().to_string(),
+                style_for(NumericFilterStep::Field),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                self.prompt.operator(),
+                style_for(NumericFilterStep::Operator),
+            ),
+            Span::raw(" "),
+            Span::styled(value, style_for(NumericFilterStep::Value)),
+        ])
+    }
+}
+
+impl<'a> Widget for NumericFilterWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_width = 44.min(area.width);
+        let popup_height = 14.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - popup_width) / 2,
+            y: area.y + (area.height - popup_height) / 4,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::default()
+            .title("Numeric Filter")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        let inner = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let chunks = Layout::vertical([
+            Constraint::Length(2), // Comparison so far
+            Constraint::Min(1),    // Choices for the current step
+            Constraint::Length(1), // Help text
+        ])
+        .split(inner);
+
+        Paragraph::new(self.preview()).render(chunks[0], buf);
+
+        let (items, selected): (Vec<ListItem>, usize) = match self.prompt.step {
+            NumericFilterStep::Field => (
+                self.prompt
+                    .fields
+                    .iter()
+                    .map(|(name, field_type)| {
+                        ListItem::new(Line::from(vec![
+                            Span::raw(name.clone()),
+                            Span::styled(
+                                format!(" ({})", field_type.to_sql()),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]))
+                    })
+                    .collect(),
+                self.prompt.field,
+            ),
+            NumericFilterStep::Operator => (
+                OPERATORS.iter().map(|op| ListItem::new(*op)).collect(),
+                self.prompt.operator,
+            ),
+            NumericFilterStep::Value => {
+                Paragraph::new("Type a number")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .render(chunks[1], buf);
+                (Vec::new(), 0)
+            }
+        };
+
+        if !items.is_empty() {
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("> ");
+            let mut state = ListState::default().with_selected(Some(selected));
+            StatefulWidget::render(list, chunks[1], buf, &mut state);
+        }
+
+        Paragraph::new("Enter: Next  Up/Down: Select  Esc: Cancel")
+            .style(Style::default().fg(Color::DarkGray))
+            .render(chunks[2], buf);
+    }
+}
+
+/// Render the numeric filter prompt as a centered overlay
+pub fn render_numeric_filter(prompt: &NumericFilter, area: Rect, buf: &mut Buffer) {
+    NumericFilterWidget::new(prompt).render(area, buf);
+}
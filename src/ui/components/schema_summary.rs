@@ -0,0 +1,248 @@
+use crate::ingestion::JsonLog;
+use crate::storage::FieldType;
+use crate::storage::schema::normalize_field_name;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Row, Table, Widget},
+};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Width of the schema summary popup
+const POPUP_WIDTH: u16 = 90;
+
+/// Rows above the fields: the header and its margin
+const HEADER_ROWS: u16 = 2;
+
+/// One field of the schema with how often the loaded logs have it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSummary {
+    pub name: String,
+    pub field_type: FieldType,
+    /// Logs with a non-null value for the field
+    pub present: usize,
+    /// First non-null value, strings as-is and anything else as JSON
+    pub sample: Option<String>,
+}
+
+/// Summarize every schema field over `logs`; log fields are matched by their
+/// normalized name, so `msg` counts for `message`, at most once per log
+pub fn summarize_fields(schema: &[(String, FieldType)], logs: &[JsonLog]) -> Vec<FieldSummary> {
+    let mut summaries: Vec<FieldSummary> = schema
+        .iter()
+        .map(|(name, field_type)| FieldSummary {
+            name: name.clone(),
+            field_type: field_type.clone(),
+            present: 0,
+            sample: None,
+        })
+        .collect();
+    let index: HashMap<String, usize> = summaries
+        .iter()
+        .enumerate()
+        .map(|(i, summary)| (summary.name.clone(), i))
+        .collect();
+
+    let mut seen = HashSet::new();
+    for log in logs {
+        seen.clear();
+        for (key, value) in &log.fields {
+            if value.is_null() {
+                continue;
+            }
+            let Some(&i) = index.get(normalize_field_name(key)) else {
+                continue;
+            };
+            if !seen.insert(i) {
+                continue;
+            }
+            let summary = &mut summaries[i];
+            summary.present += 1;
+            if summary.sample.is_none() {
+                summary.sample = Some(match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                });
+            }
+        }
+    }
+
+    summaries
+}
+
+/// Overlay listing each field with its type, presence and a sample value
+pub struct SchemaSummaryWidget<'a> {
+    fields: &'a [FieldSummary],
+    total_logs: usize,
+    scroll: u16,
+}
+
+impl<'a> SchemaSummaryWidget<'a> {
+    pub fn new(fields: &'a [FieldSummary], total_logs: usize, scroll: u16) -> Self {
+        Self {
+            fields,
+            total_logs,
+            scroll,
+        }
+    }
+
+    fn presence(&self, present: usize) -> String {
+        if self.total_logs == 0 {
+            return "-".to_string();
+        }
+        let percent = present as f64 * 100.0 / self.total_logs as f64;
+        format!("{:.0}%", percent)
+    }
+}
+
+impl<'a> Widget for SchemaSummaryWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup_area = schema_summary_area(self.fields.len(), area);
+        Clear.render(popup_area, buf);
+
+        let header = Row::new(vec!["Field", "Type", "Present", "Sample"])
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .bottom_margin(1);
+
+        let scroll = self
+            .scroll
+            .min(max_schema_summary_scroll(self.fields.len(), area));
+        let rows: Vec<Row> = self
+            .fields
+            .iter()
+            .skip(scroll as usize)
+            .map(|field| {
+                let sample = field.sample.as_deref().unwrap_or("(none)");
+                Row::new(vec![
+                    field.name.clone(),
+                    field.field_type.label().to_string(),
+                    self.presence(field.present),
+                    // Samples can span lines; keep each row on one
+                    sample.replace('\n', " "),
+                ])
+            })
+            .collect();
+
+        let title = format!(
+            "Schema: {} fields over {} logs (j/k scroll, i/Esc close)",
+            self.fields.len(),
+            self.total_logs
+        );
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(30),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Min(0),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+        table.render(popup_area, buf);
+    }
+}
+
+/// Centered popup sized to `field_count` rows, within `area`
+fn schema_summary_area(field_count: usize, area: Rect) -> Rect {
+    let width = POPUP_WIDTH.min(area.width);
+    // Two rows go to the popup border
+    let height = (field_count as u16)
+        .saturating_add(HEADER_ROWS + 2)
+        .min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Largest useful scroll offset for `field_count` fields shown in `area`
+pub fn max_schema_summary_scroll(field_count: usize, area: Rect) -> u16 {
+    let visible = schema_summary_area(field_count, area)
+        .height
+        .saturating_sub(HEADER_ROWS + 2);
+    (field_count as u16).saturating_sub(visible)
+}
+
+/// Render the schema summary as a centered modal, scrolled down `scroll` fields
+pub fn render_schema_summary(
+    fields: &[FieldSummary],
+    total_logs: usize,
+    scroll: u16,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let widget = SchemaSummaryWidget::new(fields, total_logs, scroll);
+    widget.render(area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_fields() {
+        let logs: Vec<JsonLog> = [
+            r#"{"msg":"a","user":{"id":1}}"#,
+            r#"{"msg":"b","user":null}"#,
+            r#"{"msg":"c"}"#,
+            r#"{"message":"d"}"#,
+            r#"{"msg":"e","message":"f"}"#,
+        ]
+        .iter()
+        .map(|line| crate::ingestion::parse_json_line(line).unwrap())
+        .collect();
+        let schema = vec![
+            ("message".to_string(), FieldType::Text),
+            ("user".to_string(), FieldType::Json),
+            ("gone".to_string(), FieldType::Text),
+        ];
+
+        let summary = summarize_fields(&schema, &logs);
+        // A log with both `msg` and `message` counts once
+        assert_eq!(summary[0].present, 5);
+        assert_eq!(summary[0].sample.as_deref(), Some("a"));
+        // Nulls don't count as present
+        assert_eq!(summary[1].present, 1);
+        assert_eq!(summary[1].sample.as_deref(), Some(r#"{"id":1}"#));
+        assert_eq!(summary[2].present, 0);
+        assert_eq!(summary[2].sample, None);
+    }
+
+    #[test]
+    fn test_render_schema_summary() {
+        let fields = vec![FieldSummary {
+            name: "level".to_string(),
+            field_type: FieldType::Integer,
+            present: 3,
+            sample: Some("30".to_string()),
+        }];
+        let area = Rect::new(0, 0, 100, 10);
+        let mut buf = Buffer::empty(area);
+        render_schema_summary(&fields, 4, 0, area, &mut buf);
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        let row = rows.iter().find(|row| row.contains("level")).unwrap();
+        assert!(row.contains("INTEGER"));
+        assert!(row.contains("75%"));
+        assert!(row.contains("30"));
+        assert_eq!(max_schema_summary_scroll(fields.len(), area), 0);
+        assert_eq!(max_schema_summary_scroll(20, area), 14);
+    }
+}
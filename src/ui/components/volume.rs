@@ -0,0 +1,115 @@
+use crate::ui::app::VolumeState;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Paragraph, Sparkline, Widget},
+};
+
+/// Narrowest terminal the sparkline is shown on; below this the row goes to the list
+pub const MIN_VOLUME_WIDTH: u16 = 40;
+
+/// Columns in front of the sparkline for the peak count and bucket width
+pub const VOLUME_LABEL_WIDTH: u16 = 12;
+
+/// Bucket width as `5m`, `1h` or `1d`
+pub fn interval_label(interval_ms: i64) -> String {
+    const MINUTE: i64 = 60_000;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    if interval_ms >= DAY && interval_ms % DAY == 0 {
+        format!("{}d", interval_ms / DAY)
+    } else if interval_ms >= HOUR && interval_ms % HOUR == 0 {
+        format!("{}h", interval_ms / HOUR)
+    } else {
+        format!("{}m", interval_ms / MINUTE)
+    }
+}
+
+/// One-row sparkline of logs per time bucket, labeled with the busiest bucket
+pub struct VolumeWidget<'a> {
+    state: &'a VolumeState,
+}
+
+impl<'a> VolumeWidget<'a> {
+    pub fn new(state: &'a VolumeState) -> Self {
+        Self { state }
+    }
+}
+
+impl<'a> Widget for VolumeWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if let Some(error) = &self.state.error {
+            Paragraph::new(error.as_str())
+                .style(Style::default().fg(Color::Red))
+                .render(area, buf);
+            return;
+        }
+
+        let volume = &self.state.volume;
+        if volume.counts.is_empty() {
+            Paragraph::new("No log times to chart")
+                .style(Style::default().fg(Color::DarkGray))
+                .render(area, buf);
+            return;
+        }
+
+        let chunks =
+            Layout::horizontal([Constraint::Length(VOLUME_LABEL_WIDTH), Constraint::Min(0)])
+                .split(area);
+
+        // Peak per bucket, so the bar heights have a scale
+        let peak = volume.counts.iter().max().copied().unwrap_or(0);
+        Paragraph::new(format!("{}/{}", peak, interval_label(volume.interval_ms)))
+            .style(Style::default().fg(Color::DarkGray))
+            .render(chunks[0], buf);
+
+        Sparkline::default()
+            .data(&volume.counts)
+            .style(Style::default().fg(Color::Cyan))
+            .render(chunks[1], buf);
+    }
+}
+
+/// Render the log volume sparkline
+pub fn render_volume(state: &VolumeState, area: Rect, buf: &mut Buffer) {
+    let widget = VolumeWidget::new(state);
+    widget.render(area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LogVolume;
+
+    #[test]
+    fn test_interval_label() {
+        assert_eq!(interval_label(60_000), "1m");
+        assert_eq!(interval_label(15 * 60_000), "15m");
+        assert_eq!(interval_label(6 * 60 * 60_000), "6h");
+        assert_eq!(interval_label(7 * 24 * 60 * 60_000), "7d");
+    }
+
+    #[test]
+    fn test_render_volume() {
+        let state = VolumeState {
+            volume: LogVolume {
+                interval_ms: 60_000,
+                counts: vec![1, 0, 8],
+            },
+            ..Default::default()
+        };
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        render_volume(&state, area, &mut buf);
+
+        let row: String = (0..area.width).map(|x| buf[(x, 0)].symbol()).collect();
+        assert!(row.starts_with("8/1m "));
+        // The busiest bucket gets a full bar, an empty one none
+        let bars: Vec<&str> = (12..15).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(bars[1], " ");
+        assert_eq!(bars[2], "█");
+        assert_ne!(bars[0], " ");
+    }
+}
@@ -0,0 +1,319 @@
+use crate::error::{LogViewerError, Result};
+use crate::ingestion::models::{ERROR_FIELDS, STANDARD_FIELDS, TRACE_ID_FIELDS};
+use crate::ingestion::{JsonLog, LogLevel};
+use crate::storage::schema::normalize_field_name;
+use crate::ui::age::AgeReference;
+use crate::ui::badges::BadgeRule;
+use jiff::tz::TimeZone;
+use rootcause::prelude::Report;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Default separator between `--message-fields` in the list
+pub const DEFAULT_MESSAGE_SEPARATOR: &str = " | ";
+
+/// Default `strftime` pattern for the log list
+pub const DEFAULT_LIST_TIME_FORMAT: &str = "%H:%M:%S";
+
+/// Default `strftime` pattern for the detail panel title
+pub const DEFAULT_DETAIL_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
+
+/// Sub-second digits shown by the default time formats (`--time-precision`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimePrecision {
+    #[default]
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimePrecision {
+    /// Default `strftime` pattern for the log list at this precision
+    pub fn list_format(&self) -> &'static str {
+        match self {
+            TimePrecision::Seconds => DEFAULT_LIST_TIME_FORMAT,
+            TimePrecision::Millis => "%H:%M:%S%.3f",
+            TimePrecision::Micros => "%H:%M:%S%.6f",
+            TimePrecision::Nanos => "%H:%M:%S%.9f",
+        }
+    }
+
+    /// Default `strftime` pattern for the detail panel title at this precision
+    pub fn detail_format(&self) -> &'static str {
+        match self {
+            TimePrecision::Seconds => DEFAULT_DETAIL_TIME_FORMAT,
+            TimePrecision::Millis => "%Y-%m-%d %H:%M:%S%.3f %Z",
+            TimePrecision::Micros => "%Y-%m-%d %H:%M:%S%.6f %Z",
+            TimePrecision::Nanos => "%Y-%m-%d %H:%M:%S%.9f %Z",
+        }
+    }
+}
+
+impl std::str::FromStr for TimePrecision {
+    type Err = Report<LogViewerError>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "s" => Ok(TimePrecision::Seconds),
+            "ms" => Ok(TimePrecision::Millis),
+            "us" => Ok(TimePrecision::Micros),
+            "ns" => Ok(TimePrecision::Nanos),
+            _ => Err(Report::new(LogViewerError::InvalidArgument(format!(
+                "Unknown time precision '{}' (expected s, ms, us or ns)",
+                s
+            )))),
+        }
+    }
+}
+
+/// Display settings read by the render functions
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    /// Fields excluded from the detail panel and the list's field count
+    pub hidden_fields: HashSet<String>,
+    /// Show hidden fields anyway (runtime toggle)
+    pub show_hidden_fields: bool,
+    /// Time zone timestamps are converted to before formatting (`--timezone`)
+    pub time_zone: TimeZone,
+    /// Show the other zone of the `Z` toggle instead: UTC, or the system zone
+    /// when `time_zone` is UTC
+    pub time_zone_toggled: bool,
+    /// Custom `strftime` pattern used by both the list and the detail panel
+    pub time_format: Option<String>,
+    /// Sub-second digits of the default formats, ignored with `time_format`
+    pub time_precision: TimePrecision,
+    /// Fields shown as `key=value` after the message in wide mode
+    pub visible_columns: Vec<String>,
+    /// Append `visible_columns` to each list row (runtime toggle)
+    pub wide_mode: bool,
+    /// Leading `visible_columns` scrolled out of view in wide mode (`h`/`l`)
+    pub table_col_offset: usize,
+    /// Show the logger name (`name`/`logger`) before the message
+    pub show_logger: bool,
+    /// Show the source location (Bunyan `src`) before the message
+    pub show_source: bool,
+    /// Show the file each log came from (`source`) as a colored column (runtime toggle)
+    pub show_source_file: bool,
+    /// Fields holding the trace id, shortened before the message and used by `t`
+    pub trace_fields: Vec<String>,
+    /// Fields holding an error object (`{type, message, stack}`), rendered apart in the detail panel
+    pub error_fields: Vec<String>,
+    /// Rows kept visible above and below the selection (vim's `scrolloff`)
+    pub scrolloff: usize,
+    /// Fields rendered as colored `field:value` badges after the level
+    pub badges: Vec<BadgeRule>,
+    /// Show the list in reverse, newest log on top (runtime toggle)
+    pub newest_first: bool,
+    /// Prefix list rows with the source line number (`_line`)
+    pub line_numbers: bool,
+    /// Detail panel dims fields equal to the previous log's (runtime toggle)
+    pub diff_previous: bool,
+    /// Detail panel uses one-space indentation and inlines small values (runtime toggle)
+    pub compact_json: bool,
+    /// Detail panel shows the log as one line of JSON, wrapped (runtime toggle)
+    pub single_line_json: bool,
+    /// Detail panel shows binary-looking strings as a hex preview (runtime toggle)
+    pub hex_binary: bool,
+    /// Detail panel shows each field's database column type after its value (runtime toggle)
+    pub show_field_types: bool,
+    /// Detail panel prefixes each JSON line with its number in a gutter (runtime toggle)
+    pub detail_line_numbers: bool,
+    /// Detail panel leaves out the time, level and message fields (runtime toggle)
+    pub extra_fields_only: bool,
+    /// Map non-standard level numbers to the nearest level below (35 → INFO)
+    pub lenient_levels: bool,
+    /// Dotted path to a nested level (`--level-field`, e.g. `severity.number`)
+    pub level_field: Option<String>,
+    /// Fields joined into the list's message (`--message-fields`); empty shows `message`/`msg`
+    pub message_fields: Vec<String>,
+    /// Placed between `message_fields` (`--message-separator`)
+    pub message_separator: String,
+    /// Field the list groups logs by (`--group-by`)
+    pub group_by: Option<String>,
+    /// Show the list grouped by `group_by` (runtime toggle)
+    pub grouped: bool,
+    /// Group values whose logs are listed under their header
+    pub expanded_groups: HashSet<String>,
+    /// Fields left out of the list's field count for having one value in every
+    /// sampled log (`--hide-constant`); still shown in the detail panel
+    pub constant_fields: HashSet<String>,
+    /// Draw without colors (`--no-color`, `NO_COLOR`, `TERM=dumb`)
+    pub monochrome: bool,
+    /// Tag list rows with their `AgeBucket` (runtime toggle)
+    pub show_age: bool,
+    /// Time ages are measured from (`--age-reference`)
+    pub age_reference: AgeReference,
+    /// Latest log time, kept up to date by `App` for `AgeReference::Newest`
+    pub newest_time: Option<jiff::Timestamp>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            hidden_fields: HashSet::new(),
+            show_hidden_fields: false,
+            time_zone: TimeZone::UTC,
+            time_zone_toggled: false,
+            time_format: None,
+            time_precision: TimePrecision::Seconds,
+            visible_columns: Vec::new(),
+            wide_mode: false,
+            table_col_offset: 0,
+            show_logger: false,
+            show_source: false,
+            show_source_file: false,
+            trace_fields: TRACE_ID_FIELDS.iter().map(|f| f.to_string()).collect(),
+            error_fields: ERROR_FIELDS.iter().map(|f| f.to_string()).collect(),
+            scrolloff: 0,
+            badges: Vec::new(),
+            newest_first: false,
+            line_numbers: false,
+            diff_previous: false,
+            compact_json: false,
+            single_line_json: false,
+            detail_line_numbers: false,
+            hex_binary: false,
+            show_field_types: false,
+            extra_fields_only: false,
+            lenient_levels: false,
+            level_field: None,
+            message_fields: Vec::new(),
+            message_separator: DEFAULT_MESSAGE_SEPARATOR.to_string(),
+            group_by: None,
+            grouped: false,
+            expanded_groups: HashSet::new(),
+            constant_fields: HashSet::new(),
+            monochrome: false,
+            show_age: false,
+            age_reference: AgeReference::Now,
+            newest_time: None,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Format a timestamp in the display time zone
+    /// Falls back to RFC 3339 if the pattern cannot be formatted
+    pub fn format_timestamp(&self, timestamp: jiff::Timestamp, format: &str) -> String {
+        let zoned = timestamp.to_zoned(self.display_time_zone());
+        jiff::fmt::strftime::format(format, &zoned).unwrap_or_else(|_| zoned.to_string())
+    }
+
+    /// Time zone timestamps are shown in: the configured zone, or the other
+    /// side of the `Z` toggle
+    pub fn display_time_zone(&self) -> TimeZone {
+        if !self.time_zone_toggled {
+            self.time_zone.clone()
+        } else if self.time_zone.iana_name() == Some("UTC") {
+            TimeZone::system()
+        } else {
+            TimeZone::UTC
+        }
+    }
+
+    /// Switch between the configured time zone and UTC (the system zone when
+    /// UTC is the configured one)
+    pub fn toggle_time_zone(&mut self) {
+        self.time_zone_toggled = !self.time_zone_toggled;
+    }
+
+    /// Name of the display time zone, or its current offset (e.g. `+09:00`)
+    /// for fixed offsets and system zones without a name
+    pub fn time_zone_label(&self) -> String {
+        let zone = self.display_time_zone();
+        if let Some(name) = zone.iana_name() {
+            return name.to_string();
+        }
+        let now = jiff::Timestamp::now().to_zoned(zone);
+        jiff::fmt::strftime::format("%:z", &now).unwrap_or_else(|_| "local".to_string())
+    }
+
+    /// `strftime` pattern for timestamps in the log list
+    pub fn list_time_format(&self) -> &str {
+        self.time_format
+            .as_deref()
+            .unwrap_or(self.time_precision.list_format())
+    }
+
+    /// `strftime` pattern for the timestamp in the detail panel title
+    pub fn detail_time_format(&self) -> &str {
+        self.time_format
+            .as_deref()
+            .unwrap_or(self.time_precision.detail_format())
+    }
+
+    /// Time log ages are measured from, `None` with `AgeReference::Newest` and no timed logs
+    pub fn age_reference_time(&self) -> Option<jiff::Timestamp> {
+        match self.age_reference {
+            AgeReference::Now => Some(jiff::Timestamp::now()),
+            AgeReference::Newest => self.newest_time,
+        }
+    }
+
+    /// Level of `log`, read from `level_field` if set and mapped with `lenient_levels`
+    pub fn log_level(&self, log: &JsonLog) -> Option<LogLevel> {
+        let Some(path) = &self.level_field else {
+            return if self.lenient_levels {
+                log.get_level_lenient()
+            } else {
+                log.get_level()
+            };
+        };
+        let raw = log.get_level_raw_at(path)?;
+        if self.lenient_levels {
+            Some(LogLevel::from_u64_lenient(raw))
+        } else {
+            LogLevel::from_u64(raw)
+        }
+    }
+
+    /// Message shown in the list: `message_fields` joined if set, otherwise `message`/`msg`
+    /// The fields themselves stay separate in the detail panel and for filters
+    pub fn log_message<'a>(&self, log: &'a JsonLog) -> Option<Cow<'a, str>> {
+        if self.message_fields.is_empty() {
+            return log.get_message().map(Cow::Borrowed);
+        }
+        log.get_message_from(&self.message_fields, &self.message_separator).map(Cow::Owned)
+    }
+
+    /// Field the list is currently grouped by
+    pub fn group_field(&self) -> Option<&str> {
+        self.group_by.as_deref().filter(|_| self.grouped)
+    }
+
+    /// Top-level fields of `log` shown in the detail panel, in display (sorted) order
+    pub fn visible_field_names<'a>(&self, log: &'a JsonLog) -> Vec<&'a str> {
+        let mut names: Vec<&str> = log
+            .fields
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !self.is_hidden(name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Whether `field` (original or normalized name) is one of `constant_fields`
+    pub fn is_constant(&self, field: &str) -> bool {
+        self.constant_fields.contains(field)
+            || self.constant_fields.contains(normalize_field_name(field))
+    }
+
+    /// Whether a field should be left out of the rendered output
+    /// Matches both the original and the normalized field name
+    pub fn is_hidden(&self, field: &str) -> bool {
+        (self.extra_fields_only && STANDARD_FIELDS.contains(&field))
+            || (!self.show_hidden_fields
+                && (self.hidden_fields.contains(field)
+                    || self.hidden_fields.contains(normalize_field_name(field))))
+    }
+}
+
+/// Check that a `strftime` pattern can format a sample timestamp
+pub fn validate_time_format(format: &str) -> std::result::Result<(), String> {
+    let sample = jiff::Timestamp::UNIX_EPOCH.to_zoned(TimeZone::UTC);
+    jiff::fmt::strftime::format(format, &sample)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
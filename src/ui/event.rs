@@ -1,116 +1,322 @@
 use crate::error::Result;
+use crate::export::ExportFormat;
+use crate::ui::actions::{Action, action_for_key};
+use crate::ui::age::AgeBucket;
 use crate::ui::app::{App, Focus};
+use crate::ui::numeric_filter::NumericFilterStep;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// How often the event loop wakes up while active, to pick up followed logs
+const INPUT_TICK: Duration = Duration::from_millis(100);
+
+/// Time without input after which the event loop waits for the next event
+pub const DEFAULT_IDLE_AFTER: Duration = Duration::from_secs(5);
+
+/// Decides whether the event loop ticks or sleeps until the next event
+/// It goes idle after `idle_after` without input or when the terminal loses
+/// focus, unless logs are being followed; any key, resize or focus wakes it
+#[derive(Debug)]
+pub struct IdleTimer {
+    /// `None` never goes idle
+    idle_after: Option<Duration>,
+    last_input: Instant,
+    focused: bool,
+}
+
+impl IdleTimer {
+    pub fn new(idle_after: Option<Duration>, now: Instant) -> Self {
+        Self {
+            idle_after,
+            last_input: now,
+            focused: true,
+        }
+    }
+
+    /// How long to wait for an event, or `None` to wait until one arrives
+    pub fn timeout(&self, following: bool, now: Instant) -> Option<Duration> {
+        let Some(idle_after) = self.idle_after else {
+            return Some(INPUT_TICK);
+        };
+        let idle = !self.focused || now.duration_since(self.last_input) >= idle_after;
+        if idle && !following {
+            None
+        } else {
+            Some(INPUT_TICK)
+        }
+    }
+
+    /// Note an event: input wakes the timer, losing focus makes it idle
+    pub fn record(&mut self, event: &Event, now: Instant) {
+        match event {
+            Event::FocusLost => self.focused = false,
+            Event::FocusGained => {
+                self.focused = true;
+                self.last_input = now;
+            }
+            _ => self.last_input = now,
+        }
+    }
+}
 
 /// Handle keyboard events for the application
-pub fn handle_events(app: &mut App, page_height: usize) -> Result<()> {
-    // Poll for events with a timeout
-    if event::poll(Duration::from_millis(100))
-        .map_err(crate::error::LogViewerError::from)?
-    {
-        if let Event::Key(key) = event::read().map_err(crate::error::LogViewerError::from)? {
-            handle_key_event(app, key, page_height)?;
+/// Waits up to one tick for an event, or until one arrives once `idle` says so
+pub fn handle_events(
+    app: &mut App,
+    page_height: usize,
+    idle: &mut IdleTimer,
+    following: bool,
+) -> Result<()> {
+    let ready = match idle.timeout(following, Instant::now()) {
+        Some(timeout) => event::poll(timeout).map_err(crate::error::LogViewerError::from)?,
+        None => true,
+    };
+    if ready {
+        let event = event::read().map_err(crate::error::LogViewerError::from)?;
+        idle.record(&event, Instant::now());
+        match event {
+            Event::Key(key) => handle_key_event(app, key, page_height)?,
+            Event::Resize(width, _) => app.resize_volume(width),
+            _ => {}
         }
     }
     Ok(())
 }
 
 /// Handle a single key event
-fn handle_key_event(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
-    // Help menu has priority - if it's shown, only handle keys that close it
+pub fn handle_key_event(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
+    // Status messages last until the next key press
+    app.status_message = None;
+
+    // Help menu has priority - if it's shown, only handle keys that scroll or close it,
+    // or that move on to the command palette
     if app.show_help {
+        let half_page = (page_height / 2).max(1) as u16;
         match key.code {
             KeyCode::Char('?') | KeyCode::Esc => {
                 app.toggle_help();
             }
+            // Search the actions listed in the help with the command palette
+            KeyCode::Char(':') => {
+                app.toggle_help();
+                app.open_command_palette();
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.help_scroll_down(half_page);
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.help_scroll_up(half_page);
+            }
+            KeyCode::Char('j') | KeyCode::Down => app.help_scroll_down(1),
+            KeyCode::Char('k') | KeyCode::Up => app.help_scroll_up(1),
+            KeyCode::PageDown => app.help_scroll_down(half_page),
+            KeyCode::PageUp => app.help_scroll_up(half_page),
+            KeyCode::Char('g') => app.help_scroll = 0,
+            KeyCode::Char('G') => app.help_scroll = u16::MAX,
             _ => {}
         }
         return Ok(());
     }
 
+    // The detail modal takes every key until closed
+    if app.show_detail_modal {
+        return handle_detail_modal_keys(app, key, page_height);
+    }
+
+    // So does the schema summary
+    if app.schema_summary.is_some() {
+        return handle_schema_summary_keys(app, key, page_height);
+    }
+
     // Handle keys based on current focus
     match app.focus {
         Focus::LogList => handle_log_list_keys(app, key, page_height),
         Focus::FilterInput => handle_filter_input_keys(app, key),
         Focus::FilterPresets => handle_filter_presets_keys(app, key),
+        Focus::Facets => handle_facets_keys(app, key),
+        Focus::Detail => handle_detail_keys(app, key),
+        Focus::DetailSearch => handle_detail_search_keys(app, key),
+        Focus::CommandPalette => handle_command_palette_keys(app, key, page_height),
+        Focus::NumericFilter => handle_numeric_filter_keys(app, key),
+        Focus::MessageSearch => handle_message_search_keys(app, key),
     }
 }
 
 /// Handle keys when focus is on the log list (normal mode)
 fn handle_log_list_keys(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
-    match key.code {
-        // Quit
-        KeyCode::Char('q') | KeyCode::Esc => {
-            app.quit();
-        }
+    if let Some(action) = action_for_key(&key) {
+        run_action(app, action, page_height)?;
+    }
+
+    Ok(())
+}
+
+/// Perform a log list action, from its key binding or the command palette
+fn run_action(app: &mut App, action: Action, page_height: usize) -> Result<()> {
+    match action {
+        Action::Quit => app.quit(),
 
         // Navigation - vim style
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.move_down();
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.move_up();
-        }
+        Action::MoveDown => app.move_down(),
+        Action::MoveUp => app.move_up(),
+        Action::JumpToFirst => app.jump_to_first(),
+        Action::JumpToLast => app.jump_to_last(),
+        Action::NextError => app.next_error(),
+        Action::PrevError => app.prev_error(),
+
+        // Page scrolling
+        Action::HalfPageDown => app.scroll_down_half_page(page_height),
+        Action::HalfPageUp => app.scroll_up_half_page(page_height),
+        Action::PageDown => app.scroll_down_page(page_height),
+        Action::PageUp => app.scroll_up_page(page_height),
 
-        // Jump to first/last - vim style
-        KeyCode::Char('g') if matches!(key.modifiers, KeyModifiers::NONE) => {
-            app.jump_to_first();
+        // View toggles
+        Action::ToggleDetailPanel => app.toggle_detail_panel(),
+        Action::FocusDetail => app.focus_detail(),
+        Action::OpenDetailModal => app.toggle_detail_modal(),
+        Action::PinLog => app.pin_selected_log(),
+        Action::UnpinLog => app.unpin_log(),
+        Action::ToggleDiffPrevious => app.toggle_diff_previous(),
+        Action::ToggleCompactJson => app.toggle_compact_json(),
+        Action::ToggleSingleLineJson => app.toggle_single_line_json(),
+        Action::ToggleDetailLineNumbers => app.toggle_detail_line_numbers(),
+        Action::ToggleHexBinary => app.toggle_hex_binary(),
+        Action::ToggleFieldTypes => app.toggle_field_types(),
+        Action::ToggleExtraFieldsOnly => app.toggle_extra_fields_only(),
+        Action::GrowList => app.grow_list(),
+        Action::ShrinkList => app.shrink_list(),
+        Action::ToggleNewestFirst => app.toggle_newest_first(),
+        Action::ToggleGroup => app.toggle_group(),
+        Action::ToggleGroupedView => app.toggle_grouped(),
+        Action::ToggleWideMode => app.toggle_wide_mode(),
+        Action::ScrollColumnsLeft => app.scroll_columns_left(),
+        Action::ScrollColumnsRight => app.scroll_columns_right(),
+        Action::ToggleLoggerName => app.toggle_logger_name(),
+        Action::ToggleSourceFile => app.toggle_source_file(),
+        Action::ToggleTimeZone => app.toggle_time_zone(),
+        Action::ToggleAgeTags => app.toggle_age_tags(),
+        Action::ToggleHiddenFields => app.toggle_hidden_fields(),
+        Action::ToggleFacets => app.toggle_facets(),
+        Action::ToggleVolume => app.toggle_volume(),
+        Action::ToggleSchemaSummary => app.toggle_schema_summary(),
+        Action::ToggleHelp => app.toggle_help(),
+        Action::ToggleDebugLogs => app.toggle_debug_logs(),
+        Action::OpenCommandPalette => app.open_command_palette(),
+
+        // Filtering
+        Action::FilterByLogger => {
+            let _ = app.filter_by_logger();
         }
-        KeyCode::Char('G') => {
-            app.jump_to_last();
+        Action::FilterByTrace => {
+            let _ = app.filter_by_trace();
         }
+        Action::ToggleFilterPanel => app.toggle_filter_panel(),
+        Action::FocusFilter => app.focus_filter(),
+        Action::ClearFilter => app.clear_filter(),
+        Action::ResetView => app.reset_view(),
+        Action::LoadMoreResults => app.load_more_results(),
+        Action::OpenNumericFilter => app.open_numeric_filter(),
+        Action::MessageSearch => app.start_message_search(),
 
-        // Page scrolling
-        KeyCode::Char('d') if matches!(key.modifiers, KeyModifiers::CONTROL) => {
-            app.scroll_down_half_page(page_height);
+        // Copy selected HTTP request log as curl
+        Action::CopyAsCurl => app.copy_selected_as_curl(),
+
+        // Export the visible logs
+        Action::ExportNdjson => app.export_current_logs(ExportFormat::Ndjson),
+        Action::ExportCsv => app.export_current_logs(ExportFormat::Csv),
+        Action::CopyAsTable => app.copy_current_logs_as_table(),
+    }
+
+    Ok(())
+}
+
+/// Handle keys when the command palette is open
+fn handle_command_palette_keys(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.close_command_palette();
         }
-        KeyCode::Char('u') if matches!(key.modifiers, KeyModifiers::CONTROL) => {
-            app.scroll_up_half_page(page_height);
+
+        // Run the selected action
+        KeyCode::Enter => {
+            let action = app.selected_palette_action();
+            app.close_command_palette();
+            if let Some(action) = action {
+                run_action(app, action, page_height)?;
+            }
         }
-        KeyCode::Char('f') if matches!(key.modifiers, KeyModifiers::CONTROL) => {
-            app.scroll_down_page(page_height);
+
+        // Move through matches
+        KeyCode::Down => {
+            app.palette_move_down();
         }
-        KeyCode::Char('b') if matches!(key.modifiers, KeyModifiers::CONTROL) => {
-            app.scroll_up_page(page_height);
+        KeyCode::Up => {
+            app.palette_move_up();
         }
-        KeyCode::PageDown => {
-            app.scroll_down_page(page_height);
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.palette_move_down();
         }
-        KeyCode::PageUp => {
-            app.scroll_up_page(page_height);
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.palette_move_up();
         }
 
-        // Toggle detail panel
-        KeyCode::Char('d') if matches!(key.modifiers, KeyModifiers::NONE) => {
-            app.toggle_detail_panel();
+        // Everything else edits the search text
+        _ => {
+            if app.palette_input.input(key) {
+                app.palette_selected = 0;
+            }
         }
+    }
 
-        // Toggle filter panel
-        KeyCode::Char('f') if matches!(key.modifiers, KeyModifiers::NONE) => {
-            app.toggle_filter_panel();
-        }
+    Ok(())
+}
 
-        // Focus filter input
-        KeyCode::Char('/') => {
-            app.focus_filter();
-        }
+/// Handle keys in the numeric filter prompt
+fn handle_numeric_filter_keys(app: &mut App, key: KeyEvent) -> Result<()> {
+    let Some(prompt) = app.numeric_filter.as_mut() else {
+        app.focus_log_list();
+        return Ok(());
+    };
 
-        // Clear filter
-        KeyCode::Char('c') => {
-            app.clear_filter();
+    match key.code {
+        KeyCode::Esc => app.close_numeric_filter(),
+        KeyCode::Enter => {
+            let _ = app.confirm_numeric_filter();
         }
 
-        // Toggle help
-        KeyCode::Char('?') => {
-            app.toggle_help();
+        // The value is typed; backspace on an empty value goes back a step
+        KeyCode::Backspace if prompt.step == NumericFilterStep::Value => {
+            if prompt.value.pop().is_none() {
+                prompt.back();
+            }
         }
-
-        // Toggle debug logs
-        KeyCode::Char('L') => {
-            app.toggle_debug_logs();
+        KeyCode::Char(c) if prompt.step == NumericFilterStep::Value => prompt.value.push(c),
+        KeyCode::Backspace => {
+            prompt.back();
         }
 
+        // Fields and operators are picked from a list
+        KeyCode::Char('j') | KeyCode::Down => prompt.move_down(),
+        KeyCode::Char('k') | KeyCode::Up => prompt.move_up(),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handle keys while typing a message quick filter term
+fn handle_message_search_keys(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => app.cancel_message_search(),
+        KeyCode::Enter => {
+            let _ = app.confirm_message_search();
+        }
+        KeyCode::Backspace => {
+            app.message_search_input.pop();
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.message_search_input.push(c);
+        }
         _ => {}
     }
 
@@ -125,11 +331,26 @@ fn handle_filter_input_keys(app: &mut App, key: KeyEvent) -> Result<()> {
             app.focus = Focus::FilterPresets;
         }
 
-        // Apply filter
+        // Apply filter and keep the panel open; Ctrl-s stands in for Ctrl-Enter,
+        // which most terminals don't report
+        KeyCode::Enter if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let _ = app.apply_filter_keep_open();
+        }
+        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let _ = app.apply_filter_keep_open();
+        }
+        KeyCode::Enter if app.filter_editor => {
+            app.filter_input.insert_newline();
+        }
         KeyCode::Enter => {
             let _ = app.apply_filter();
         }
 
+        // Switch between one line and the multi-line editor
+        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_filter_editor();
+        }
+
         // Pass other keys to the text area widget
         _ => {
             app.filter_input.input(key);
@@ -155,15 +376,27 @@ fn handle_filter_presets_keys(app: &mut App, key: KeyEvent) -> Result<()> {
             let _ = app.apply_preset_filter("level >= 40");
         }
         KeyCode::Char('3') => {
-            // Last hour - calculate timestamp
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as i64;
-            let one_hour_ago = now - (60 * 60 * 1000);
-            let filter = format!("time >= {}", one_hour_ago);
-            let _ = app.apply_preset_filter(&filter);
+            let _ = app.filter_last_hour();
+        }
+
+        // Age windows of the reference time
+        KeyCode::Char('4') => {
+            let _ = app.filter_by_age(AgeBucket::Minute);
+        }
+        KeyCode::Char('5') => {
+            let _ = app.filter_by_age(AgeBucket::FiveMinutes);
+        }
+        KeyCode::Char('6') => {
+            let _ = app.filter_by_age(AgeBucket::Hour);
         }
+        KeyCode::Char('7') => {
+            let _ = app.filter_by_age(AgeBucket::Older);
+        }
+
+        // Pick a field from the table and start a comparison on it
+        KeyCode::Down => app.filter_field_down(),
+        KeyCode::Up => app.filter_field_up(),
+        KeyCode::Enter => app.insert_filter_field(),
 
         // Any printable character - switch to input mode and type it
         KeyCode::Char(_) => {
@@ -184,3 +417,184 @@ fn handle_filter_presets_keys(app: &mut App, key: KeyEvent) -> Result<()> {
 
     Ok(())
 }
+
+/// Handle keys when focus is on the facets sidebar
+fn handle_facets_keys(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        // Close facets sidebar
+        KeyCode::Esc | KeyCode::Char('F') | KeyCode::Char('q') => {
+            app.toggle_facets();
+        }
+
+        // Move through values
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.facet_move_down();
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.facet_move_up();
+        }
+
+        // Switch field
+        KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => {
+            app.next_facet_field();
+        }
+        KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => {
+            app.prev_facet_field();
+        }
+
+        // Filter to the selected value
+        KeyCode::Enter => {
+            let _ = app.apply_facet_value();
+        }
+
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handle keys when the detail panel's field cursor has focus
+fn handle_detail_keys(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        // Back to the log list
+        KeyCode::Esc | KeyCode::Tab | KeyCode::Char('q') => {
+            app.unfocus_detail();
+        }
+
+        // Move through fields
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.detail_move_down();
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.detail_move_up();
+        }
+
+        // Copy the selected field's value
+        KeyCode::Char('y') | KeyCode::Enter => {
+            app.copy_selected_field();
+        }
+
+        // Search within the detail panel
+        KeyCode::Char('/') => {
+            app.start_detail_search();
+        }
+
+        // Filter all logs by the selected field: equal value, or containing the search term
+        KeyCode::Char('=') => {
+            let _ = app.filter_by_selected_field(false);
+        }
+        KeyCode::Char('~') => {
+            let _ = app.filter_by_selected_field(true);
+        }
+
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handle keys while the schema summary is open: scroll, or close with i/Esc/q
+fn handle_schema_summary_keys(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
+    let half_page = (page_height / 2).max(1) as u16;
+    match key.code {
+        KeyCode::Char('i') | KeyCode::Char('q') | KeyCode::Esc => app.toggle_schema_summary(),
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.schema_summary_scroll_down(half_page);
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.schema_summary_scroll_up(half_page);
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.schema_summary_scroll_down(1),
+        KeyCode::Char('k') | KeyCode::Up => app.schema_summary_scroll_up(1),
+        KeyCode::PageDown => app.schema_summary_scroll_down(half_page),
+        KeyCode::PageUp => app.schema_summary_scroll_up(half_page),
+        KeyCode::Char('g') => app.schema_summary_scroll = 0,
+        KeyCode::Char('G') => app.schema_summary_scroll = u16::MAX,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handle keys while the full-screen detail modal is open
+fn handle_detail_modal_keys(app: &mut App, key: KeyEvent, page_height: usize) -> Result<()> {
+    // Typing a search term, as in the detail panel
+    if app.focus == Focus::DetailSearch {
+        return handle_detail_search_keys(app, key);
+    }
+
+    let half_page = (page_height / 2).max(1) as u16;
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => {
+            app.toggle_detail_modal();
+        }
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.detail_modal_scroll_down(half_page);
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.detail_modal_scroll_up(half_page);
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.detail_modal_scroll_down(1),
+        KeyCode::Char('k') | KeyCode::Up => app.detail_modal_scroll_up(1),
+        KeyCode::PageDown => app.detail_modal_scroll_down(half_page),
+        KeyCode::PageUp => app.detail_modal_scroll_up(half_page),
+        KeyCode::Char('g') => app.detail_modal_scroll = 0,
+        KeyCode::Char('G') => app.detail_modal_scroll = u16::MAX,
+        KeyCode::Char('/') => app.start_detail_search(),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handle keys while typing a detail panel search
+fn handle_detail_search_keys(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_detail_search();
+        }
+        KeyCode::Enter => {
+            app.confirm_detail_search();
+        }
+        KeyCode::Backspace => {
+            app.detail_search.pop();
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.detail_search.push(c);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_timer() {
+        let start = Instant::now();
+        let mut idle = IdleTimer::new(Some(Duration::from_secs(5)), start);
+        assert_eq!(idle.timeout(false, start), Some(INPUT_TICK));
+
+        // Idle after the delay, unless following logs
+        let later = start + Duration::from_secs(6);
+        assert_eq!(idle.timeout(false, later), None);
+        assert_eq!(idle.timeout(true, later), Some(INPUT_TICK));
+
+        // A resize counts as input
+        idle.record(&Event::Resize(80, 24), later);
+        assert_eq!(idle.timeout(false, later), Some(INPUT_TICK));
+
+        // Losing focus goes idle at once, regaining it wakes up
+        idle.record(&Event::FocusLost, later);
+        assert_eq!(idle.timeout(false, later), None);
+        idle.record(&Event::FocusGained, later);
+        assert_eq!(idle.timeout(false, later), Some(INPUT_TICK));
+
+        // Without a delay it never goes idle
+        let never = IdleTimer::new(None, start);
+        assert_eq!(never.timeout(false, later), Some(INPUT_TICK));
+    }
+}
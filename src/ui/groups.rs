@@ -0,0 +1,163 @@
+use crate::ingestion::JsonLog;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A row of the log list when grouping by a field (`--group-by`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListRow {
+    /// Header for the logs sharing a value; `first` is the log shown in the detail panel
+    Group {
+        key: String,
+        count: usize,
+        first: usize,
+        expanded: bool,
+    },
+    /// A log, by index into `current_logs()`; `nested` under an expanded group
+    Log { index: usize, nested: bool },
+}
+
+impl ListRow {
+    /// Index of the log this row stands for
+    pub fn log_index(&self) -> usize {
+        match self {
+            ListRow::Group { first, .. } => *first,
+            ListRow::Log { index, .. } => *index,
+        }
+    }
+}
+
+/// A group or an ungrouped log, in the order they first appear
+enum Entry {
+    Group(String),
+    Single(usize),
+}
+
+/// Group value of a log, `None` when it lacks the field
+pub fn group_key(log: &JsonLog, field: &str) -> Option<String> {
+    match log.get_field(field)? {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Rows for `logs` grouped by `field`, in list order (reversed when `newest_first`)
+///
+/// A group sits where its first log appears and lists its logs when its key is
+/// in `expanded`. Logs without the field are plain rows.
+pub fn group_rows(
+    logs: &[JsonLog],
+    field: &str,
+    expanded: &HashSet<String>,
+    newest_first: bool,
+) -> Vec<ListRow> {
+    let order: Vec<usize> = if newest_first {
+        (0..logs.len()).rev().collect()
+    } else {
+        (0..logs.len()).collect()
+    };
+
+    // Members of each group in list order, and the order groups first appear in
+    let mut members: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut entries = Vec::new();
+    for &index in &order {
+        match group_key(&logs[index], field) {
+            Some(key) => {
+                let group = members.entry(key.clone()).or_default();
+                if group.is_empty() {
+                    entries.push(Entry::Group(key));
+                }
+                group.push(index);
+            }
+            None => entries.push(Entry::Single(index)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for entry in entries {
+        match entry {
+            Entry::Group(key) => {
+                let group = &members[&key];
+                let is_expanded = expanded.contains(&key);
+                rows.push(ListRow::Group {
+                    key,
+                    count: group.len(),
+                    first: group[0],
+                    expanded: is_expanded,
+                });
+                if is_expanded {
+                    rows.extend(group.iter().map(|&index| ListRow::Log {
+                        index,
+                        nested: true,
+                    }));
+                }
+            }
+            Entry::Single(index) => rows.push(ListRow::Log {
+                index,
+                nested: false,
+            }),
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::parse_json_line;
+
+    fn sample_logs() -> Vec<JsonLog> {
+        [
+            r#"{"msg":"a1","reqId":"a"}"#,
+            r#"{"msg":"b1","reqId":"b"}"#,
+            r#"{"msg":"startup"}"#,
+            r#"{"msg":"a2","reqId":"a"}"#,
+        ]
+        .iter()
+        .map(|line| parse_json_line(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_group_rows_collapsed() {
+        let rows = group_rows(&sample_logs(), "reqId", &HashSet::new(), false);
+        assert_eq!(
+            rows,
+            vec![
+                ListRow::Group {
+                    key: "a".to_string(),
+                    count: 2,
+                    first: 0,
+                    expanded: false
+                },
+                ListRow::Group {
+                    key: "b".to_string(),
+                    count: 1,
+                    first: 1,
+                    expanded: false
+                },
+                ListRow::Log {
+                    index: 2,
+                    nested: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_rows_expanded_newest_first() {
+        let expanded: HashSet<String> = ["a".to_string()].into_iter().collect();
+        let rows = group_rows(&sample_logs(), "reqId", &expanded, true);
+        let indexes: Vec<usize> = rows.iter().map(ListRow::log_index).collect();
+
+        // Group "a" starts at its newest log and lists its logs newest first
+        assert_eq!(indexes, vec![3, 3, 0, 2, 1]);
+        assert_eq!(
+            rows[1],
+            ListRow::Log {
+                index: 3,
+                nested: true
+            }
+        );
+    }
+}
@@ -1,8 +1,19 @@
+pub mod actions;
+pub mod age;
 mod app;
+pub mod badges;
+pub mod clipboard;
+pub mod color;
 pub mod components;
+pub mod display;
 mod event;
+pub mod groups;
+pub mod numeric_filter;
 pub mod terminal;
 
-pub use app::App;
-pub use event::handle_events;
-pub use terminal::{cleanup_terminal, setup_terminal, Tui};
+pub use app::{App, FacetState, Focus};
+pub use display::DisplayOptions;
+pub use event::{DEFAULT_IDLE_AFTER, IdleTimer, handle_events};
+#[cfg(test)]
+pub use event::handle_key_event;
+pub use terminal::{cleanup_terminal, restore_title, save_title, set_title, setup_terminal, Tui};
@@ -0,0 +1,222 @@
+use crate::error::{LogViewerError, Result};
+use crate::storage::FieldType;
+use crate::storage::sql::quote_identifier;
+use rootcause::prelude::Report;
+
+/// Comparison operators offered by the prompt
+pub const OPERATORS: [&str; 6] = [">", ">=", "<", "<=", "=", "!="];
+
+/// Part of the comparison the prompt is asking for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericFilterStep {
+    Field,
+    Operator,
+    Value,
+}
+
+/// Guided prompt building a `field <op> number` filter on a numeric column
+#[derive(Debug, Clone)]
+pub struct NumericFilter {
+    /// Integer and float columns from the schema
+    pub fields: Vec<(String, FieldType)>,
+    /// Index into `fields`
+    pub field: usize,
+    /// Index into `OPERATORS`
+    pub operator: usize,
+    pub value: String,
+    pub step: NumericFilterStep,
+}
+
+impl NumericFilter {
+    /// Prompt over the numeric fields of `schema`, `None` if it has none
+    pub fn new(schema: &[(String, FieldType)]) -> Option<Self> {
+        let fields: Vec<_> = schema
+            .iter()
+            .filter(|(_, field_type)| is_numeric(field_type))
+            .cloned()
+            .collect();
+        if fields.is_empty() {
+            return None;
+        }
+        Some(Self {
+            fields,
+            field: 0,
+            operator: 0,
+            value: String::new(),
+            step: NumericFilterStep::Field,
+        })
+    }
+
+    /// Name of the selected field
+    pub fn field_name(&self) -> &str {
+        &self.fields[self.field].0
+    }
+
+    /// Selected operator
+    pub fn operator(&self) -> &'static str {
+        OPERATORS[self.operator]
+    }
+
+    /// Select the next field or operator, wrapping around
+    pub fn move_down(&mut self) {
+        match self.step {
+            NumericFilterStep::Field => self.field = (self.field + 1) % self.fields.len(),
+            NumericFilterStep::Operator => self.operator = (self.operator + 1) % OPERATORS.len(),
+            NumericFilterStep::Value => {}
+        }
+    }
+
+    /// Select the previous field or operator, wrapping around
+    pub fn move_up(&mut self) {
+        match self.step {
+            NumericFilterStep::Field => {
+                self.field = (self.field + self.fields.len() - 1) % self.fields.len()
+            }
+            NumericFilterStep::Operator => {
+                self.operator = (self.operator + OPERATORS.len() - 1) % OPERATORS.len()
+            }
+            NumericFilterStep::Value => {}
+        }
+    }
+
+    /// Go back to the previous step, `false` if already on the first
+    pub fn back(&mut self) -> bool {
+        self.step = match self.step {
+            NumericFilterStep::Field => return false,
+            NumericFilterStep::Operator => NumericFilterStep::Field,
+            NumericFilterStep::Value => NumericFilterStep::Operator,
+        };
+        true
+    }
+
+    /// Confirm the current step; returns the clause once the value is entered
+    pub fn confirm(&mut self) -> Result<Option<String>> {
+        match self.step {
+            NumericFilterStep::Field => self.step = NumericFilterStep::Operator,
+            NumericFilterStep::Operator => self.step = NumericFilterStep::Value,
+            NumericFilterStep::Value => {
+                let (field, field_type) = &self.fields[self.field];
+                return numeric_clause(field, field_type, self.operator(), &self.value).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn is_numeric(field_type: &FieldType) -> bool {
+    matches!(field_type, FieldType::Integer | FieldType::Float)
+}
+
+/// WHERE clause comparing a numeric field to a number, e.g. `"latency" >= 250`
+/// The value is re-printed from the parsed number, so it can't inject SQL
+pub fn numeric_clause(
+    field: &str,
+    field_type: &FieldType,
+    operator: &str,
+    value: &str,
+) -> Result<String> {
+    if !is_numeric(field_type) {
+        return Err(Report::new(LogViewerError::InvalidArgument(format!(
+            "'{}' is not a numeric field",
+            field
+        ))));
+    }
+    if !OPERATORS.contains(&operator) {
+        return Err(Report::new(LogViewerError::InvalidArgument(format!(
+            "Unknown operator '{}'",
+            operator
+        ))));
+    }
+
+    let value = value.trim();
+    let literal = match value.parse::<i64>() {
+        Ok(integer) => integer.to_string(),
+        Err(_) => match value.parse::<f64>() {
+            Ok(float) if float.is_finite() => float.to_string(),
+            _ => {
+                return Err(Report::new(LogViewerError::InvalidArgument(format!(
+                    "'{}' is not a number",
+                    value
+                ))));
+            }
+        },
+    };
+    Ok(format!(
+        "{} {} {}",
+        quote_identifier(field),
+        operator,
+        literal
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Vec<(String, FieldType)> {
+        vec![
+            ("hostname".to_string(), FieldType::Text),
+            ("level".to_string(), FieldType::Integer),
+            ("latency".to_string(), FieldType::Float),
+        ]
+    }
+
+    #[test]
+    fn test_numeric_clause() {
+        assert_eq!(
+            numeric_clause("level", &FieldType::Integer, ">=", "40").unwrap(),
+            "\"level\" >= 40"
+        );
+        assert_eq!(
+            numeric_clause("latency", &FieldType::Float, "<", " 2.5 ").unwrap(),
+            "\"latency\" < 2.5"
+        );
+        assert_eq!(
+            numeric_clause("latency", &FieldType::Float, "!=", "1e3").unwrap(),
+            "\"latency\" != 1000"
+        );
+    }
+
+    #[test]
+    fn test_numeric_clause_rejects_invalid_input() {
+        assert!(numeric_clause("hostname", &FieldType::Text, "=", "1").is_err());
+        assert!(numeric_clause("level", &FieldType::Integer, "LIKE", "1").is_err());
+        assert!(numeric_clause("level", &FieldType::Integer, "=", "1 OR 1=1").is_err());
+        assert!(numeric_clause("level", &FieldType::Integer, "=", "inf").is_err());
+        assert!(numeric_clause("level", &FieldType::Integer, "=", "").is_err());
+    }
+
+    #[test]
+    fn test_prompt_steps() {
+        let mut prompt = NumericFilter::new(&schema()).unwrap();
+        // Only numeric fields are offered
+        assert_eq!(prompt.fields.len(), 2);
+        assert_eq!(prompt.field_name(), "level");
+
+        prompt.move_up();
+        assert_eq!(prompt.field_name(), "latency");
+        assert_eq!(prompt.confirm().unwrap(), None);
+
+        prompt.move_down();
+        assert_eq!(prompt.operator(), ">=");
+        assert_eq!(prompt.confirm().unwrap(), None);
+        assert_eq!(prompt.step, NumericFilterStep::Value);
+
+        prompt.value = "abc".to_string();
+        assert!(prompt.confirm().is_err());
+        prompt.value = "250".to_string();
+        assert_eq!(
+            prompt.confirm().unwrap(),
+            Some("\"latency\" >= 250".to_string())
+        );
+
+        assert!(prompt.back());
+        assert!(prompt.back());
+        assert!(!prompt.back());
+    }
+
+    #[test]
+    fn test_prompt_needs_numeric_fields() {
+        assert!(NumericFilter::new(&[("msg".to_string(), FieldType::Text)]).is_none());
+    }
+}
@@ -1,12 +1,15 @@
 use crossterm::{
+    event::{DisableFocusChange, EnableFocusChange},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
 };
 use ratatui::{
     backend::CrosstermBackend,
     Terminal,
 };
-use std::io;
+use std::io::{self, Write};
 
 use crate::error::Result;
 
@@ -16,6 +19,8 @@ pub type Tui = Terminal<CrosstermBackend<io::Stdout>>;
 pub fn setup_terminal() -> Result<Tui> {
     enable_raw_mode().map_err(crate::error::LogViewerError::from)?;
     execute!(io::stdout(), EnterAlternateScreen).map_err(crate::error::LogViewerError::from)?;
+    // Focus events let the event loop go idle while the terminal is in the background
+    execute!(io::stdout(), EnableFocusChange).map_err(crate::error::LogViewerError::from)?;
 
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend).map_err(crate::error::LogViewerError::from)?;
@@ -26,7 +31,38 @@ pub fn setup_terminal() -> Result<Tui> {
 /// Restore terminal to normal mode
 pub fn cleanup_terminal() -> Result<()> {
     disable_raw_mode().map_err(crate::error::LogViewerError::from)?;
+    execute!(io::stdout(), DisableFocusChange).map_err(crate::error::LogViewerError::from)?;
     execute!(io::stdout(), LeaveAlternateScreen).map_err(crate::error::LogViewerError::from)?;
 
     Ok(())
 }
+
+/// Push the current window title onto the xterm title stack, for `restore_title`
+pub fn save_title() -> Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[22;0t")
+        .and_then(|_| stdout.flush())
+        .map_err(crate::error::LogViewerError::from)?;
+
+    Ok(())
+}
+
+/// Set the terminal window title; control characters are dropped
+pub fn set_title(title: &str) -> Result<()> {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    execute!(io::stdout(), SetTitle(title)).map_err(crate::error::LogViewerError::from)?;
+
+    Ok(())
+}
+
+/// Pop the title saved by `save_title`
+/// The title is cleared first, for terminals without a title stack
+pub fn restore_title() -> Result<()> {
+    set_title("")?;
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[23;0t")
+        .and_then(|_| stdout.flush())
+        .map_err(crate::error::LogViewerError::from)?;
+
+    Ok(())
+}